@@ -0,0 +1,219 @@
+//! ASP-signed (authenticated) V-PACKs.
+//!
+//! The CRC32 in the header only catches corruption. An ASP can additionally sign a V-PACK: with
+//! `FLAG_ASP_SIGNED` set, a 64-byte BIP-340 signature follows the payload:
+//!
+//! ```text
+//! Header (24) || Payload (payload_len) || Signature (64)
+//! ```
+//!
+//! The signed message is `tagged_hash("VPack/signature", header[0..20] || payload)`, where the
+//! header bytes already carry `FLAG_ASP_SIGNED` and the checksum field is excluded as for the
+//! CRC. `payload_len` and the checksum do not cover the signature, so unsigned readers can still
+//! parse a signed V-PACK.
+//!
+//! Checking the signature is cheap next to tree reconstruction; [`crate::verify_authenticated`]
+//! does it before parsing the payload.
+
+use alloc::vec::Vec;
+
+use crate::consensus::taproot::tagged_hash;
+use crate::error::VPackError;
+use crate::header::{Header, FLAG_ASP_SIGNED, HEADER_SIZE};
+use crate::pack::pack_from_payload;
+
+/// BIP-340 tag for the ASP signature message.
+pub const SIGNATURE_TAG: &[u8] = b"VPack/signature";
+
+/// Header, checksum-verified payload, and the ASP signature if present.
+type SplitVPack<'a> = (Header, &'a [u8], Option<&'a [u8; 64]>);
+
+fn split_vpack(vpack_bytes: &[u8]) -> Result<SplitVPack<'_>, VPackError> {
+    if vpack_bytes.len() < HEADER_SIZE {
+        return Err(VPackError::IncompleteData);
+    }
+    let header = Header::from_bytes(&vpack_bytes[..HEADER_SIZE])?;
+    let (payload, signature) = header.split_body(&vpack_bytes[HEADER_SIZE..])?;
+    header.verify_checksum(payload)?;
+    Ok((header, payload, signature))
+}
+
+/// Message the ASP signs. The same for a V-PACK before and after [`attach_signature`].
+pub fn signature_message(vpack_bytes: &[u8]) -> Result<[u8; 32], VPackError> {
+    let (header, payload, _) = split_vpack(vpack_bytes)?;
+    Ok(message_for(&header, payload))
+}
+
+fn message_for(header: &Header, payload: &[u8]) -> [u8; 32] {
+    let mut signed_header = *header;
+    signed_header.flags |= FLAG_ASP_SIGNED;
+    let mut msg = Vec::with_capacity(20 + payload.len());
+    msg.extend_from_slice(&signed_header.to_bytes()[..20]);
+    msg.extend_from_slice(payload);
+    tagged_hash(SIGNATURE_TAG, &msg)
+}
+
+/// Sets `FLAG_ASP_SIGNED`, recomputes the checksum and appends `signature` (produced externally
+/// over [`signature_message`]). An existing signature is replaced. The signature is not checked.
+pub fn attach_signature(vpack_bytes: &[u8], signature: &[u8; 64]) -> Result<Vec<u8>, VPackError> {
+    let (mut header, payload, _) = split_vpack(vpack_bytes)?;
+    header.flags |= FLAG_ASP_SIGNED;
+    let mut out = pack_from_payload(&header, payload)?;
+    out.extend_from_slice(signature);
+    Ok(out)
+}
+
+/// Signs a V-PACK with the ASP's secret key (BIP-340, `aux_rand` as in the BIP's signing
+/// algorithm) and returns the signed bytes.
+#[cfg(feature = "schnorr-verify")]
+pub fn sign_vpack(
+    vpack_bytes: &[u8],
+    secret_key: &[u8; 32],
+    aux_rand: &[u8; 32],
+) -> Result<Vec<u8>, VPackError> {
+    use k256::schnorr::SigningKey;
+
+    let msg = signature_message(vpack_bytes)?;
+    let signing_key = SigningKey::from_bytes(secret_key).map_err(|_| VPackError::EncodingError)?;
+    let signature = signing_key
+        .sign_prehash_with_aux_rand(&msg, aux_rand)
+        .map_err(|_| VPackError::EncodingError)?;
+    attach_signature(vpack_bytes, &signature.to_bytes())
+}
+
+/// Checks the ASP signature against the x-only `asp_pubkey`. Fails with
+/// [`VPackError::MissingAspSignature`] for unsigned V-PACKs.
+#[cfg(feature = "schnorr-verify")]
+pub fn verify_asp_signature(vpack_bytes: &[u8], asp_pubkey: &[u8; 32]) -> Result<(), VPackError> {
+    let (header, payload, signature) = split_vpack(vpack_bytes)?;
+    check_signature(&header, payload, signature, asp_pubkey)
+}
+
+/// [`verify_asp_signature`] over an already split, checksum-verified V-PACK.
+#[cfg(feature = "schnorr-verify")]
+pub(crate) fn check_signature(
+    header: &Header,
+    payload: &[u8],
+    signature: Option<&[u8; 64]>,
+    asp_pubkey: &[u8; 32],
+) -> Result<(), VPackError> {
+    let signature = signature.ok_or(VPackError::MissingAspSignature)?;
+    let msg = message_for(header, payload);
+    crate::consensus::taproot_sighash::verify_schnorr_bip340(asp_pubkey, &msg, signature)
+        .map_err(|_| VPackError::InvalidAspSignature)
+}
+
+#[cfg(all(test, feature = "schnorr-verify"))]
+mod tests {
+    use super::*;
+    use crate::header::{TxVariant, FLAG_PROOF_COMPACT};
+    use crate::pack::pack;
+    use crate::payload::tree::{VPackTree, VtxoLeaf};
+    use crate::types::hashes::Hash;
+    use crate::types::{OutPoint, Txid};
+
+    const SECRET: [u8; 32] = [0x42; 32];
+
+    fn sample_vpack() -> Vec<u8> {
+        let header = Header {
+            flags: FLAG_PROOF_COMPACT,
+            version: 1,
+            tx_variant: TxVariant::V3Plain,
+            tree_arity: 16,
+            tree_depth: 32,
+            node_count: 0,
+            asset_type: 0,
+            payload_len: 0,
+            checksum: 0,
+        };
+        let tree = VPackTree {
+            leaf: VtxoLeaf {
+                amount: 1000,
+                vout: 0,
+                sequence: 0,
+                expiry: 0,
+                exit_delta: 0,
+                script_pubkey: alloc::vec![0x51, 0x20, 0x00],
+            },
+            leaf_siblings: Vec::new(),
+            path: Vec::new(),
+            anchor: OutPoint {
+                txid: Txid::from_byte_array([0x42; 32]),
+                vout: 0,
+            },
+            asset_id: None,
+            fee_anchor_script: Vec::new(),
+            internal_key: [0xAA; 32],
+            asp_expiry_script: alloc::vec![0x51],
+        };
+        pack(&header, &tree).expect("pack")
+    }
+
+    fn asp_pubkey() -> [u8; 32] {
+        let key = k256::schnorr::SigningKey::from_bytes(&SECRET).expect("key");
+        key.verifying_key().to_bytes().into()
+    }
+
+    #[test]
+    fn test_sign_and_verify_round_trip() {
+        let unsigned = sample_vpack();
+        let signed = sign_vpack(&unsigned, &SECRET, &[0u8; 32]).expect("sign");
+        assert_eq!(signed.len(), unsigned.len() + 64);
+        let header = Header::from_bytes(&signed[..HEADER_SIZE]).expect("header");
+        assert!(header.is_signed());
+        assert_eq!(
+            signature_message(&signed),
+            signature_message(&unsigned),
+            "message is stable across signing"
+        );
+        assert_eq!(verify_asp_signature(&signed, &asp_pubkey()), Ok(()));
+
+        // Re-signing replaces the signature rather than appending a second one.
+        let resigned = sign_vpack(&signed, &SECRET, &[1u8; 32]).expect("re-sign");
+        assert_eq!(resigned.len(), signed.len());
+        assert_eq!(verify_asp_signature(&resigned, &asp_pubkey()), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_rejects_missing_wrong_or_tampered() {
+        let unsigned = sample_vpack();
+        assert_eq!(
+            verify_asp_signature(&unsigned, &asp_pubkey()),
+            Err(VPackError::MissingAspSignature)
+        );
+
+        let signed = sign_vpack(&unsigned, &SECRET, &[0u8; 32]).expect("sign");
+        let other = sign_vpack(&unsigned, &[0x43; 32], &[0u8; 32]).expect("sign other");
+        assert_eq!(
+            verify_asp_signature(&other, &asp_pubkey()),
+            Err(VPackError::InvalidAspSignature)
+        );
+
+        let mut bad_sig = signed.clone();
+        let last = bad_sig.len() - 1;
+        bad_sig[last] ^= 0x01;
+        assert_eq!(
+            verify_asp_signature(&bad_sig, &asp_pubkey()),
+            Err(VPackError::InvalidAspSignature)
+        );
+
+        // Dropping the signature but keeping the flag leaves a short payload.
+        assert_eq!(
+            verify_asp_signature(&signed[..signed.len() - 64], &asp_pubkey()),
+            Err(VPackError::IncompleteData)
+        );
+
+        let header = Header::from_bytes(&signed[..HEADER_SIZE]).expect("header");
+        assert_eq!(
+            pack(
+                &header,
+                &crate::payload::reader::BoundedReader::parse(
+                    &header,
+                    &signed[HEADER_SIZE..signed.len() - 64]
+                )
+                .expect("parse")
+            ),
+            Err(VPackError::EncodingError)
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Multi-VTXO bundle container (V-PACK archive).
+//!
+//! A wallet backup or transfer of many VTXOs packs their V-PACKs behind one small header and a
+//! table of contents keyed by [`VtxoId`]:
+//!
+//! ```text
+//! Header (16 bytes)
+//!   magic:       "VPB"   (3)
+//!   version:     u8      (1)
+//!   entry_count: u32 LE
+//!   data_len:    u32 LE  — total bytes of the data section
+//!   checksum:    u32 LE  — CRC32 over header[0..12] + TOC
+//! TOC (entry_count × 53 bytes, strictly ascending by key)
+//!   id_kind:      u8     — 0 = Raw, 1 = OutPoint
+//!   id_hash:      [u8;32] — raw hash, or txid (internal wire order)
+//!   id_vout:      u32 LE — 0 for Raw
+//!   anchor_value: u64 LE — value of the L1 anchor passed to `verify`
+//!   offset:       u32 LE — into the data section; entries are contiguous in TOC order
+//!   len:          u32 LE
+//! Data
+//!   N complete V-PACKs (each with its own 24-byte header and CRC32)
+//! ```
+//!
+//! The container checksum covers only the header and TOC, so one entry can be located and
+//! sliced out ([`BundleReader::get`]) without touching the other entries; each embedded V-PACK is
+//! still protected by its own checksum when verified.
+
+use alloc::vec::Vec;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::consensus::VtxoId;
+use crate::error::VPackError;
+use crate::header::{Network, HEADER_SIZE};
+use crate::payload::tree::VPackTree;
+
+/// Bundle magic bytes: "VPB".
+pub const BUNDLE_MAGIC: [u8; 3] = [0x56, 0x50, 0x42];
+/// Current bundle format version.
+pub const BUNDLE_VERSION: u8 = 1;
+/// Size of the bundle header.
+pub const BUNDLE_HEADER_SIZE: usize = 16;
+/// Size of one table-of-contents entry.
+pub const BUNDLE_TOC_ENTRY_SIZE: usize = 1 + 32 + 4 + 8 + 4 + 4;
+
+/// One VTXO to be packed into a bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleEntry {
+    /// Expected VTXO ID; the TOC key.
+    pub id: VtxoId,
+    /// Value (in satoshis) of the L1 UTXO anchoring the tree.
+    pub anchor_value: u64,
+    /// Complete V-PACK bytes (header + payload).
+    pub vpack: Vec<u8>,
+}
+
+/// Parsed table-of-contents entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TocEntry {
+    pub id: VtxoId,
+    pub anchor_value: u64,
+    /// Offset of the V-PACK within the data section.
+    pub offset: u32,
+    /// Length of the V-PACK in bytes.
+    pub len: u32,
+}
+
+/// Per-entry outcome of [`verify_bundle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleEntryResult {
+    pub id: VtxoId,
+    pub result: Result<VPackTree, VPackError>,
+}
+
+/// Packs entries into a bundle. The TOC is sorted by ID; duplicate IDs are rejected with
+/// [`VPackError::EncodingError`]. Entries are not verified here (see [`verify_bundle`]).
+pub fn pack_bundle(entries: &[BundleEntry]) -> Result<Vec<u8>, VPackError> {
+    let mut order: Vec<&BundleEntry> = entries.iter().collect();
+    order.sort_by_key(|e| e.id.key());
+    if order.windows(2).any(|w| w[0].id == w[1].id) {
+        return Err(VPackError::EncodingError);
+    }
+
+    let toc_len = order
+        .len()
+        .checked_mul(BUNDLE_TOC_ENTRY_SIZE)
+        .ok_or(VPackError::EncodingError)?;
+    let mut toc = Vec::with_capacity(toc_len);
+    let mut offset: u32 = 0;
+    for entry in &order {
+        if entry.vpack.len() < HEADER_SIZE {
+            return Err(VPackError::IncompleteData);
+        }
+        let len = u32::try_from(entry.vpack.len()).map_err(|_| VPackError::EncodingError)?;
+        let (kind, hash, vout) = entry.id.key();
+        let mut buf = [0u8; BUNDLE_TOC_ENTRY_SIZE];
+        buf[0] = kind;
+        buf[1..33].copy_from_slice(&hash);
+        LittleEndian::write_u32(&mut buf[33..37], vout);
+        LittleEndian::write_u64(&mut buf[37..45], entry.anchor_value);
+        LittleEndian::write_u32(&mut buf[45..49], offset);
+        LittleEndian::write_u32(&mut buf[49..53], len);
+        toc.extend_from_slice(&buf);
+        offset = offset.checked_add(len).ok_or(VPackError::EncodingError)?;
+    }
+
+    let entry_count = u32::try_from(order.len()).map_err(|_| VPackError::EncodingError)?;
+    let mut header = [0u8; BUNDLE_HEADER_SIZE];
+    header[0..3].copy_from_slice(&BUNDLE_MAGIC);
+    header[3] = BUNDLE_VERSION;
+    LittleEndian::write_u32(&mut header[4..8], entry_count);
+    LittleEndian::write_u32(&mut header[8..12], offset);
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&header[0..12]);
+    hasher.update(&toc);
+    LittleEndian::write_u32(&mut header[12..16], hasher.finalize());
+
+    let mut out = Vec::with_capacity(BUNDLE_HEADER_SIZE + toc.len() + offset as usize);
+    out.extend_from_slice(&header);
+    out.extend_from_slice(&toc);
+    for entry in order {
+        out.extend_from_slice(&entry.vpack);
+    }
+    Ok(out)
+}
+
+/// Random-access view over a bundle: parses the header and TOC only.
+#[derive(Debug, Clone)]
+pub struct BundleReader<'a> {
+    toc: Vec<TocEntry>,
+    data: &'a [u8],
+}
+
+impl<'a> BundleReader<'a> {
+    /// Validates the header, container checksum and TOC layout. Entry bytes are not inspected.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, VPackError> {
+        if bytes.len() < BUNDLE_HEADER_SIZE {
+            return Err(VPackError::IncompleteData);
+        }
+        if bytes[0..3] != BUNDLE_MAGIC {
+            return Err(VPackError::InvalidMagic);
+        }
+        if bytes[3] != BUNDLE_VERSION {
+            return Err(VPackError::UnsupportedVersion(bytes[3]));
+        }
+        let entry_count = LittleEndian::read_u32(&bytes[4..8]) as usize;
+        let data_len = LittleEndian::read_u32(&bytes[8..12]) as usize;
+        let checksum = LittleEndian::read_u32(&bytes[12..16]);
+
+        // Bound the TOC by the input before allocating for it.
+        let toc_len = entry_count
+            .checked_mul(BUNDLE_TOC_ENTRY_SIZE)
+            .ok_or(VPackError::IncompleteData)?;
+        let rest = &bytes[BUNDLE_HEADER_SIZE..];
+        if rest.len() < toc_len {
+            return Err(VPackError::IncompleteData);
+        }
+        let (toc_bytes, data) = rest.split_at(toc_len);
+
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&bytes[0..12]);
+        hasher.update(toc_bytes);
+        let found = hasher.finalize();
+        if found != checksum {
+            return Err(VPackError::ChecksumMismatch {
+                expected: checksum,
+                found,
+            });
+        }
+
+        if data.len() < data_len {
+            return Err(VPackError::IncompleteData);
+        }
+        if data.len() > data_len {
+            return Err(VPackError::TrailingData(data.len() - data_len));
+        }
+
+        let mut toc = Vec::with_capacity(entry_count);
+        let mut next_offset: usize = 0;
+        let mut prev_key = None;
+        for raw in toc_bytes.chunks_exact(BUNDLE_TOC_ENTRY_SIZE) {
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(&raw[1..33]);
+            let vout = LittleEndian::read_u32(&raw[33..37]);
+            let id = VtxoId::from_key((raw[0], hash, vout)).ok_or(VPackError::EncodingError)?;
+            let entry = TocEntry {
+                id,
+                anchor_value: LittleEndian::read_u64(&raw[37..45]),
+                offset: LittleEndian::read_u32(&raw[45..49]),
+                len: LittleEndian::read_u32(&raw[49..53]),
+            };
+
+            // Keys strictly ascending (sorted, no duplicates); entries contiguous in TOC order.
+            let key = entry.id.key();
+            if prev_key.is_some_and(|prev| prev >= key) {
+                return Err(VPackError::EncodingError);
+            }
+            prev_key = Some(key);
+            if entry.offset as usize != next_offset || (entry.len as usize) < HEADER_SIZE {
+                return Err(VPackError::EncodingError);
+            }
+            next_offset = next_offset
+                .checked_add(entry.len as usize)
+                .ok_or(VPackError::EncodingError)?;
+            toc.push(entry);
+        }
+        if next_offset != data_len {
+            return Err(VPackError::EncodingError);
+        }
+
+        Ok(Self { toc, data })
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.toc.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.toc.is_empty()
+    }
+
+    /// The table of contents, sorted by ID.
+    pub fn toc(&self) -> &[TocEntry] {
+        &self.toc
+    }
+
+    /// Looks up an entry by ID (binary search over the TOC) and returns its V-PACK bytes.
+    pub fn get(&self, id: &VtxoId) -> Option<(&TocEntry, &'a [u8])> {
+        let key = id.key();
+        let index = self.toc.binary_search_by_key(&key, |e| e.id.key()).ok()?;
+        self.entry(index)
+    }
+
+    /// Returns the `index`-th entry in TOC order and its V-PACK bytes.
+    pub fn entry(&self, index: usize) -> Option<(&TocEntry, &'a [u8])> {
+        let entry = self.toc.get(index)?;
+        let start = entry.offset as usize;
+        Some((entry, &self.data[start..start + entry.len as usize]))
+    }
+
+    /// Iterates over all entries in TOC order.
+    pub fn iter(&self) -> impl Iterator<Item = (&TocEntry, &'a [u8])> + '_ {
+        (0..self.toc.len()).filter_map(move |i| self.entry(i))
+    }
+}
+
+/// Unpacks every entry of a bundle into owned [`BundleEntry`] values (TOC order).
+pub fn unpack_bundle(bytes: &[u8]) -> Result<Vec<BundleEntry>, VPackError> {
+    let reader = BundleReader::parse(bytes)?;
+    Ok(reader
+        .iter()
+        .map(|(entry, vpack)| BundleEntry {
+            id: entry.id,
+            anchor_value: entry.anchor_value,
+            vpack: vpack.to_vec(),
+        })
+        .collect())
+}
+
+/// Runs [`crate::verify_batch`] over every entry against its TOC ID and anchor value, on
+/// `network`.
+///
+/// Container-level failures (magic, checksum, TOC layout) are returned as `Err`; per-entry
+/// failures are reported in the corresponding [`BundleEntryResult`] and do not stop the others.
+/// The TOC is self-asserted: callers that hold independent expectations (e.g. on-chain anchor
+/// values) should compare them against [`BundleReader::toc`].
+pub fn verify_bundle(bytes: &[u8], network: Network) -> Result<Vec<BundleEntryResult>, VPackError> {
+    let reader = BundleReader::parse(bytes)?;
+    let items: Vec<(&[u8], VtxoId, u64)> = reader
+        .iter()
+        .map(|(entry, vpack)| (vpack, entry.id, entry.anchor_value))
+        .collect();
+    Ok(crate::verify_batch(&items, network)
+        .into_iter()
+        .zip(&items)
+        .map(|(result, (_, id, _))| BundleEntryResult { id: *id, result })
+        .collect())
+}
@@ -9,23 +9,17 @@ use alloc::vec::Vec;
 
 use crate::types::{hashes::sha256d, hashes::Hash, OutPoint, Txid};
 
+use crate::consensus::fold::ChainState;
+#[cfg(feature = "schnorr-verify")]
+use crate::consensus::memo::check_hop_signature;
+use crate::consensus::memo::{hop_txid, HopMemo};
 use crate::consensus::taproot::{compute_balanced_merkle_root, tap_leaf_hash};
 use crate::consensus::{
-    tx_preimage, tx_signed_hex, ConsensusEngine, TxInPreimage, TxOutPreimage, VerificationOutput,
-    VtxoId,
+    enforce_conservation, tx_preimage, tx_signed_hex, ConsensusEngine, ExitLeaf, TxInPreimage,
+    TxOutPreimage, VerificationOutput, VtxoId,
 };
 use crate::error::VPackError;
-use crate::payload::tree::{SiblingNode, VPackTree};
-
-#[cfg(feature = "schnorr-verify")]
-use crate::consensus::taproot_sighash::{
-    extract_verify_key, taproot_sighash, verify_schnorr_bip340,
-};
-
-struct ReconstructedOutput {
-    value: u64,
-    script_pubkey: Vec<u8>,
-}
+use crate::payload::tree::{GenesisItemRef, SiblingNodeRef, VPackTree, VPackTreeRef, VtxoLeafRef};
 
 /// Ark Labs V3-Anchored consensus engine (Variant 0x04).
 ///
@@ -41,265 +35,284 @@ impl ConsensusEngine for ArkLabsV3 {
         tree: &VPackTree,
         anchor_value: Option<u64>,
     ) -> Result<VerificationOutput, VPackError> {
-        // If path is empty, this is a leaf node
-        if tree.path.is_empty() {
-            // Optional validation: V3-Anchored leaf must have anchor in data (leaf_siblings)
-            if tree.leaf_siblings.is_empty() && !tree.fee_anchor_script.is_empty() {
-                return Err(VPackError::FeeAnchorMissing);
-            }
-            return self.compute_leaf_vtxo_id(tree, anchor_value);
+        self.compute_vtxo_id_ref(&VPackTreeRef::from(tree), anchor_value)
+    }
+
+    fn exclusivity_merkle_root(&self, tree: &VPackTreeRef<'_>) -> Result<[u8; 32], VPackError> {
+        if tree.asp_expiry_script.is_empty() {
+            return Err(VPackError::MissingExclusivityData);
+        }
+        ark_labs_merkle_root_from_parts(tree.asp_expiry_script, tree.leaf.exit_delta)
+            .ok_or(VPackError::InvalidArkLabsScript)
+    }
+
+    fn tap_leaf_hashes(&self, tree: &VPackTree) -> Result<(Vec<[u8; 32]>, usize), VPackError> {
+        ark_labs_tap_leaf_hashes_for_merkle_path(tree)
+            .ok_or(VPackError::ControlBlockReconstructionFailed)
+    }
+
+    fn exit_leaf(&self, tree: &VPackTree) -> Result<ExitLeaf, VPackError> {
+        ark_labs_exit_leaf(tree).ok_or(VPackError::ControlBlockReconstructionFailed)
+    }
+
+    fn compute_vtxo_id_ref(
+        &self,
+        tree: &VPackTreeRef<'_>,
+        anchor_value: Option<u64>,
+    ) -> Result<VerificationOutput, VPackError> {
+        self.compute_vtxo_id_memoized(tree, anchor_value, &mut HopMemo::default())
+    }
+}
+
+impl ArkLabsV3 {
+    /// [`ConsensusEngine::compute_vtxo_id_ref`], reusing txids and verified signatures from
+    /// `memo` (see [`crate::verify_batch`]). The path signatures are checked as one batch when
+    /// the walk ends.
+    pub(crate) fn compute_vtxo_id_memoized(
+        &self,
+        tree: &VPackTreeRef<'_>,
+        anchor_value: Option<u64>,
+        memo: &mut HopMemo,
+    ) -> Result<VerificationOutput, VPackError> {
+        let walk = Self::walk_path(tree, anchor_value, memo);
+        memo.finish_tree(walk)
+    }
+
+    fn walk_path(
+        tree: &VPackTreeRef<'_>,
+        anchor_value: Option<u64>,
+        memo: &mut HopMemo,
+    ) -> Result<VerificationOutput, VPackError> {
+        // Optional validation: V3-Anchored leaf must have anchor in data (leaf_siblings)
+        if tree.path.is_empty()
+            && tree.leaf_siblings.is_empty()
+            && !tree.fee_anchor_script.is_empty()
+        {
+            return Err(VPackError::FeeAnchorMissing);
         }
 
         // Top-down chaining: start with on-chain anchor
-        let mut current_prevout = tree.anchor;
-        let mut last_txid_bytes = None;
-        let mut prev_outputs: Option<Vec<ReconstructedOutput>> = None;
-        let mut input_amount: Option<u64> = anchor_value;
+        let mut chain = ChainState::new(tree.anchor, anchor_value);
+        let mut prev_outputs: Option<Vec<TxOutPreimage<'_>>> = None;
         let mut signed_txs = Vec::with_capacity(tree.path.len() + 1);
 
-        // Iterate through path (top-down from root to leaf).
-        //
-        // When `child_script_pubkey` is non-empty, the Arkade / Bitcoin wire order may place the
-        // child at a non-zero `vout` (see `parent_index` of the **next** path step, or `leaf.vout`
-        // for the final hop). Outputs are built in consensus order: siblings before the child
-        // slot, then the child, then siblings after — matching multi-party round templates.
+        // Iterate through path (top-down from root to leaf). The child continues at the
+        // `parent_index` of the **next** path step, or `leaf.vout` for the final hop.
         for (i, genesis_item) in tree.path.iter().enumerate() {
-            let mut outputs = Vec::new();
-
-            if genesis_item.child_script_pubkey.is_empty() {
-                // Branch / internal template: only explicit sibling outputs (e.g. round root tx).
-                for sibling in &genesis_item.siblings {
-                    match sibling {
-                        SiblingNode::Compact { value, script, .. } => {
-                            outputs.push(TxOutPreimage {
-                                value: *value,
-                                script_pubkey: script.as_slice(),
-                            });
-                        }
-                        SiblingNode::Full(_) => return Err(VPackError::EncodingError),
-                    }
-                }
+            let child_vout = tree
+                .path
+                .get(i + 1)
+                .map_or(tree.leaf.vout, |next| next.parent_index);
+            let (outputs, signed_hex) = Self::apply_hop(
+                &mut chain,
+                genesis_item,
+                child_vout,
+                prev_outputs.as_deref(),
+                tree.leaf.script_pubkey,
+                Some(&mut *memo),
+            )?;
+            signed_txs.push(signed_hex);
+            prev_outputs = Some(outputs);
+        }
+
+        let last_hop = tree.path.last().zip(prev_outputs.as_deref());
+        let (id, leaf_signed_hex) =
+            Self::finish_chain(&chain, &tree.leaf, &tree.leaf_siblings, last_hop)?;
+        signed_txs.extend(leaf_signed_hex);
+        Ok(VerificationOutput { id, signed_txs })
+    }
+
+    /// Outputs of one path transaction.
+    ///
+    /// When `child_script_pubkey` is non-empty, the Arkade / Bitcoin wire order may place the
+    /// child at a non-zero `child_vout`. Outputs are built in consensus order: siblings before the
+    /// child slot, then the child, then siblings after — matching multi-party round templates.
+    /// An empty child script is a branch / internal template with only explicit sibling outputs
+    /// (e.g. round root tx).
+    pub(crate) fn hop_outputs<'a>(
+        genesis_item: &GenesisItemRef<'a>,
+        child_vout: u32,
+    ) -> Result<Vec<TxOutPreimage<'a>>, VPackError> {
+        let sibling_output = |sibling: &SiblingNodeRef<'a>| match *sibling {
+            SiblingNodeRef::Compact { value, script, .. } => Ok(TxOutPreimage {
+                value,
+                script_pubkey: script,
+            }),
+            SiblingNodeRef::Full { .. } => Err(VPackError::EncodingError),
+        };
+
+        if genesis_item.child_script_pubkey.is_empty() {
+            return genesis_item.siblings.iter().map(sibling_output).collect();
+        }
+
+        let insert_idx = child_vout as usize;
+        let num_out = 1 + genesis_item.siblings.len();
+        if insert_idx >= num_out {
+            return Err(VPackError::InvalidVout(child_vout));
+        }
+        let mut outputs = Vec::with_capacity(num_out);
+        let mut sib_iter = genesis_item.siblings.iter();
+        for j in 0..num_out {
+            if j == insert_idx {
+                outputs.push(TxOutPreimage {
+                    value: genesis_item.child_amount,
+                    script_pubkey: genesis_item.child_script_pubkey,
+                });
             } else {
-                let insert_idx = if i + 1 < tree.path.len() {
-                    tree.path[i + 1].parent_index as usize
-                } else {
-                    tree.leaf.vout as usize
-                };
-                let num_out = 1 + genesis_item.siblings.len();
-                if insert_idx >= num_out {
-                    return Err(VPackError::InvalidVout(insert_idx as u32));
-                }
-                outputs.reserve(num_out);
-                let mut sib_iter = genesis_item.siblings.iter();
-                for j in 0..num_out {
-                    if j == insert_idx {
-                        outputs.push(TxOutPreimage {
-                            value: genesis_item.child_amount,
-                            script_pubkey: genesis_item.child_script_pubkey.as_slice(),
-                        });
-                    } else {
-                        let sibling = sib_iter.next().ok_or(VPackError::EncodingError)?;
-                        match sibling {
-                            SiblingNode::Compact { value, script, .. } => {
-                                outputs.push(TxOutPreimage {
-                                    value: *value,
-                                    script_pubkey: script.as_slice(),
-                                });
-                            }
-                            SiblingNode::Full(_) => return Err(VPackError::EncodingError),
-                        }
-                    }
-                }
+                let sibling = sib_iter.next().ok_or(VPackError::EncodingError)?;
+                outputs.push(sibling_output(sibling)?);
             }
+        }
+        Ok(outputs)
+    }
 
-            if let Some(expected) = input_amount {
-                let sum = outputs
-                    .iter()
-                    .try_fold(0u64, |acc, o| acc.checked_add(o.value));
-                match sum {
-                    None => {
-                        return Err(crate::consensus::value_mismatch_for_output_sum(
-                            expected, &outputs,
-                        ));
-                    }
-                    Some(s) if s != expected => {
-                        return Err(VPackError::ValueMismatch {
-                            expected,
-                            actual: s,
-                        });
-                    }
-                    Some(_) => {}
-                }
-                input_amount = if genesis_item.child_script_pubkey.is_empty() {
-                    outputs.first().map(|o| o.value)
-                } else {
-                    let insert_idx = if i + 1 < tree.path.len() {
-                        tree.path[i + 1].parent_index as usize
-                    } else {
-                        tree.leaf.vout as usize
-                    };
-                    outputs.get(insert_idx).map(|o| o.value)
-                };
-            }
+    /// Applies one path step to `chain`: builds the transaction spending the current prevout,
+    /// enforces conservation of value and the step signature, and hands off to the continuation
+    /// output (`vout` 0 for branch templates, else `child_vout`).
+    ///
+    /// Returns the step's outputs (the next hop's `prev_outputs`) and its signed transaction.
+    pub(crate) fn apply_hop<'a>(
+        chain: &mut ChainState,
+        genesis_item: &GenesisItemRef<'a>,
+        child_vout: u32,
+        prev_outputs: Option<&[TxOutPreimage<'_>]>,
+        leaf_script: &[u8],
+        mut memo: Option<&mut HopMemo>,
+    ) -> Result<(Vec<TxOutPreimage<'a>>, Vec<u8>), VPackError> {
+        let outputs = Self::hop_outputs(genesis_item, child_vout)?;
+        let branch_template = genesis_item.child_script_pubkey.is_empty();
+        let continuation_vout = if branch_template { 0 } else { child_vout };
+
+        if chain.input_amount.is_some() {
+            enforce_conservation(chain.input_amount, &outputs)?;
+            chain.input_amount = outputs.get(continuation_vout as usize).map(|o| o.value);
+        }
 
-            // Build input spending current_prevout
-            let input = TxInPreimage {
-                prev_out_txid: current_prevout.txid.to_byte_array(),
-                prev_out_vout: current_prevout.vout,
-                sequence: genesis_item.sequence,
-            };
-
-            #[cfg(feature = "schnorr-verify")]
-            if let Some(sig) = genesis_item.signature {
-                if i > 0 {
-                    let verify_key = extract_verify_key(tree.leaf.script_pubkey.as_slice())
-                        .or_else(|| {
-                            if tree.leaf.script_pubkey.len() == 33 {
-                                tree.leaf.script_pubkey[1..33].try_into().ok()
-                            } else {
-                                None
-                            }
-                        });
-                    let verify_key = verify_key.ok_or(VPackError::InvalidSignature)?;
-                    let prev = prev_outputs.as_ref().ok_or(VPackError::EncodingError)?;
-                    let idx = current_prevout.vout as usize;
-                    if idx >= prev.len() {
-                        return Err(VPackError::InvalidVout(current_prevout.vout));
-                    }
-                    let parent_amount = prev[idx].value;
-                    let parent_script = prev[idx].script_pubkey.as_slice();
-                    let sighash =
-                        taproot_sighash(3, 0, &input, parent_amount, parent_script, &outputs, 0x00);
-                    verify_schnorr_bip340(&verify_key, &sighash, &sig)?;
-                }
+        // Build input spending current_prevout
+        let input = TxInPreimage {
+            prev_out_txid: chain.current_prevout.txid.to_byte_array(),
+            prev_out_vout: chain.current_prevout.vout,
+            sequence: genesis_item.sequence,
+        };
+
+        // Hash transaction → Raw Hash
+        let txid_bytes = hop_txid(memo.as_deref_mut(), &input, &outputs);
+
+        #[cfg(feature = "schnorr-verify")]
+        if let Some(sig) = genesis_item.signature {
+            if chain.hops > 0 {
+                check_hop_signature(
+                    memo,
+                    &txid_bytes,
+                    &input,
+                    &outputs,
+                    prev_outputs,
+                    leaf_script,
+                    sig,
+                )?;
             }
+        }
+        #[cfg(not(feature = "schnorr-verify"))]
+        let _ = (prev_outputs, leaf_script, memo);
 
-            let sig = [genesis_item.signature];
-            let signed_hex = tx_signed_hex(3, core::slice::from_ref(&input), &outputs, &sig, 0);
-            signed_txs.push(signed_hex);
+        let sig = [genesis_item.signature.copied()];
+        let signed_hex = tx_signed_hex(3, core::slice::from_ref(&input), &outputs, &sig, 0);
 
-            // Hash transaction → Raw Hash
-            let txid_bytes = Self::hash_node_bytes(3, &[input], &outputs, 0)?;
-            let txid = Txid::from_byte_array(txid_bytes);
-
-            // Store the last transaction's hash
-            last_txid_bytes = Some(txid_bytes);
-
-            prev_outputs = Some(
-                outputs
-                    .iter()
-                    .map(|o| ReconstructedOutput {
-                        value: o.value,
-                        script_pubkey: o.script_pubkey.to_vec(),
-                    })
-                    .collect(),
-            );
+        chain.last_txid = Some(txid_bytes);
+        chain.hops += 1;
 
-            // Hand-off: spend the child output (the continuation output for this branch).
-            current_prevout = if genesis_item.child_script_pubkey.is_empty() {
-                OutPoint { txid, vout: 0 }
-            } else {
-                let child_vout = if i + 1 < tree.path.len() {
-                    tree.path[i + 1].parent_index
-                } else {
-                    tree.leaf.vout
-                };
-                OutPoint {
-                    txid,
-                    vout: child_vout,
-                }
-            };
-        }
+        // Hand-off: spend the child output (the continuation output for this branch).
+        chain.current_prevout = OutPoint {
+            txid: Txid::from_byte_array(txid_bytes),
+            vout: continuation_vout,
+        };
+
+        Ok((outputs, signed_hex))
+    }
+
+    /// Final step after the path has been applied.
+    ///
+    /// `last_hop` is the final path step with its outputs (`None` for a path-less leaf, which is
+    /// spent directly from the anchor). Returns the VTXO ID and, when a follow-up leaf
+    /// transaction is built, its signed bytes.
+    pub(crate) fn finish_chain(
+        chain: &ChainState,
+        leaf: &VtxoLeafRef<'_>,
+        leaf_siblings: &[SiblingNodeRef<'_>],
+        last_hop: Option<(&GenesisItemRef<'_>, &[TxOutPreimage<'_>])>,
+    ) -> Result<(VtxoId, Option<Vec<u8>>), VPackError> {
+        let Some((last_step, last_outputs)) = last_hop else {
+            let (id, signed_hex) = Self::compute_leaf_vtxo_id_with_prevout(
+                leaf,
+                leaf_siblings,
+                chain.current_prevout,
+                chain.input_amount,
+            )?;
+            return Ok((id, Some(signed_hex)));
+        };
+        let last_txid = chain.last_txid.ok_or(VPackError::EncodingError)?;
 
-        // Final step: Build leaf transaction spending `current_prevout` when the path has not yet
+        // Build leaf transaction spending `current_prevout` only when the path has not yet
         // materialized the user's final VTXO output as a separable UTXO.
         //
         // Arkade / round templates often encode the last path step's `child` as the final P2TR
         // VTXO: `lineage.len() == path.len()` and there is no additional spend. In that case the
         // VTXO ID is the last **path** transaction hash (not a follow-up "leaf" transaction).
-        if tree.leaf.script_pubkey.is_empty() {
-            Ok(VerificationOutput {
-                id: VtxoId::Raw(last_txid_bytes.expect("path should have at least one item")),
-                signed_txs,
-            })
-        } else {
-            let idx = current_prevout.vout as usize;
-            let last_step = tree
-                .path
-                .last()
-                .expect("path should have at least one item");
-            // Round leaves: last path tx is `[user vtxo, fee anchor]` only (two outputs). Branch
-            // nodes include additional sibling VTXO outputs — those still use a follow-up leaf spend
-            // in V-PACK (see `round_branch_v3.json`).
-            let last_path_tx_output_count = 1 + last_step.siblings.len();
-            let already_final = prev_outputs
-                .as_ref()
-                .and_then(|prev| prev.get(idx))
-                .is_some_and(|o| {
-                    o.value == tree.leaf.amount && o.script_pubkey == tree.leaf.script_pubkey
-                });
-            if already_final && last_path_tx_output_count == 2 {
-                Ok(VerificationOutput {
-                    id: VtxoId::Raw(last_txid_bytes.expect("path should have at least one item")),
-                    signed_txs,
-                })
-            } else {
-                let (id, leaf_signed_hex) =
-                    self.compute_leaf_vtxo_id_with_prevout(tree, current_prevout, input_amount)?;
-                signed_txs.push(leaf_signed_hex);
-                Ok(VerificationOutput { id, signed_txs })
-            }
+        if leaf.script_pubkey.is_empty() {
+            return Ok((VtxoId::Raw(last_txid), None));
         }
-    }
-}
 
-impl ArkLabsV3 {
-    /// Compute VTXO ID for a leaf node (no path).
-    fn compute_leaf_vtxo_id(
-        &self,
-        tree: &VPackTree,
-        anchor_value: Option<u64>,
-    ) -> Result<VerificationOutput, VPackError> {
-        let (id, signed_hex) =
-            self.compute_leaf_vtxo_id_with_prevout(tree, tree.anchor, anchor_value)?;
-        Ok(VerificationOutput {
-            id,
-            signed_txs: vec![signed_hex],
-        })
+        // Round leaves: last path tx is `[user vtxo, fee anchor]` only (two outputs). Branch
+        // nodes include additional sibling VTXO outputs — those still use a follow-up leaf spend
+        // in V-PACK (see `round_branch_v3.json`).
+        let last_path_tx_output_count = 1 + last_step.siblings.len();
+        let already_final = last_outputs
+            .get(chain.current_prevout.vout as usize)
+            .is_some_and(|o| o.value == leaf.amount && o.script_pubkey == leaf.script_pubkey);
+        if already_final && last_path_tx_output_count == 2 {
+            return Ok((VtxoId::Raw(last_txid), None));
+        }
+
+        let (id, signed_hex) = Self::compute_leaf_vtxo_id_with_prevout(
+            leaf,
+            leaf_siblings,
+            chain.current_prevout,
+            chain.input_amount,
+        )?;
+        Ok((id, Some(signed_hex)))
     }
 
-    /// Compute VTXO ID for a leaf node with a custom prevout.
-    /// Used for the final leaf transaction in recursive path traversal.
+    /// Compute VTXO ID for the leaf transaction spending `prevout`.
     /// Returns (VtxoId, signed_tx_bytes). Leaf output is placed at index leaf.vout per V-PACK data.
     fn compute_leaf_vtxo_id_with_prevout(
-        &self,
-        tree: &VPackTree,
+        leaf: &VtxoLeafRef<'_>,
+        leaf_siblings: &[SiblingNodeRef<'_>],
         prevout: OutPoint,
         input_amount: Option<u64>,
     ) -> Result<(VtxoId, Vec<u8>), VPackError> {
-        let num_outputs = 1 + tree.leaf_siblings.len();
-        if tree.leaf.vout >= num_outputs as u32 {
-            return Err(VPackError::InvalidVout(tree.leaf.vout));
+        let num_outputs = 1 + leaf_siblings.len();
+        if leaf.vout >= num_outputs as u32 {
+            return Err(VPackError::InvalidVout(leaf.vout));
         }
         // Build outputs: leaf at index leaf.vout, siblings at other indices (matches reconstruct_link logic)
         let mut outputs = Vec::with_capacity(num_outputs);
-        let mut sibling_iter = tree.leaf_siblings.iter();
+        let mut sibling_iter = leaf_siblings.iter();
         for i in 0..num_outputs {
-            if i == tree.leaf.vout as usize {
+            if i == leaf.vout as usize {
                 outputs.push(TxOutPreimage {
-                    value: tree.leaf.amount,
-                    script_pubkey: tree.leaf.script_pubkey.as_slice(),
+                    value: leaf.amount,
+                    script_pubkey: leaf.script_pubkey,
                 });
             } else {
                 let sibling = sibling_iter.next().ok_or(VPackError::EncodingError)?;
-                match sibling {
-                    SiblingNode::Compact { value, script, .. } => {
+                match *sibling {
+                    SiblingNodeRef::Compact { value, script, .. } => {
                         outputs.push(TxOutPreimage {
-                            value: *value,
-                            script_pubkey: script.as_slice(),
+                            value,
+                            script_pubkey: script,
                         });
                     }
-                    SiblingNode::Full(_) => return Err(VPackError::EncodingError),
+                    SiblingNodeRef::Full { .. } => return Err(VPackError::EncodingError),
                 }
             }
         }
@@ -307,31 +320,13 @@ impl ArkLabsV3 {
             return Err(VPackError::EncodingError);
         }
 
-        if let Some(expected) = input_amount {
-            let sum = outputs
-                .iter()
-                .try_fold(0u64, |acc, o| acc.checked_add(o.value));
-            match sum {
-                None => {
-                    return Err(crate::consensus::value_mismatch_for_output_sum(
-                        expected, &outputs,
-                    ));
-                }
-                Some(s) if s != expected => {
-                    return Err(VPackError::ValueMismatch {
-                        expected,
-                        actual: s,
-                    });
-                }
-                Some(_) => {}
-            }
-        }
+        enforce_conservation(input_amount, &outputs)?;
 
         // Build input from prevout OutPoint
         let input = TxInPreimage {
             prev_out_txid: prevout.txid.to_byte_array(),
             prev_out_vout: prevout.vout,
-            sequence: tree.leaf.sequence,
+            sequence: leaf.sequence,
         };
 
         // Signed hex: leaf has no signature in schema, use empty witness
@@ -585,8 +580,8 @@ fn arkade_verbatim_closure_segments(script: &[u8]) -> Option<Vec<Vec<u8>>> {
     Some(out)
 }
 
-fn ark_labs_merkle_from_verbatim_segments(tree: &VPackTree) -> Option<[u8; 32]> {
-    let segs = arkade_verbatim_closure_segments(&tree.asp_expiry_script)?;
+fn ark_labs_merkle_from_verbatim_segments(asp_expiry_script: &[u8]) -> Option<[u8; 32]> {
+    let segs = arkade_verbatim_closure_segments(asp_expiry_script)?;
     let hashes: Vec<[u8; 32]> = segs.iter().map(|s| tap_leaf_hash(s)).collect();
     compute_balanced_merkle_root(&hashes)
 }
@@ -605,24 +600,32 @@ fn ark_labs_merkle_from_verbatim_segments(tree: &VPackTree) -> Option<[u8; 32]>
 /// Returns `None` if `asp_expiry_script` is empty or doesn't match a recognised
 /// Ark Labs template.
 pub fn compute_ark_labs_merkle_root(tree: &VPackTree) -> Option<[u8; 32]> {
-    if tree.asp_expiry_script.is_empty() {
+    ark_labs_merkle_root_from_parts(&tree.asp_expiry_script, tree.leaf.exit_delta)
+}
+
+/// [`compute_ark_labs_merkle_root`] from the two tree fields it depends on.
+pub(crate) fn ark_labs_merkle_root_from_parts(
+    asp_expiry_script: &[u8],
+    exit_delta: u16,
+) -> Option<[u8; 32]> {
+    if asp_expiry_script.is_empty() {
         return None;
     }
 
-    if let Some(root) = ark_labs_merkle_from_verbatim_segments(tree) {
+    if let Some(root) = ark_labs_merkle_from_verbatim_segments(asp_expiry_script) {
         return Some(root);
     }
 
-    let (asp_pk, user_pk) = parse_ark_labs_pubkeys(&tree.asp_expiry_script)?;
-    let forfeit_template = is_forfeit_template(&tree.asp_expiry_script)?;
+    let (asp_pk, user_pk) = parse_ark_labs_pubkeys(asp_expiry_script)?;
+    let forfeit_template = is_forfeit_template(asp_expiry_script)?;
 
     let (forfeit_script, exit_script) = if forfeit_template {
-        let csv_bytes = encode_exit_delta_csv(tree.leaf.exit_delta);
+        let csv_bytes = encode_exit_delta_csv(exit_delta);
         let exit = compile_exit_script(&asp_pk, &user_pk, &csv_bytes);
-        (tree.asp_expiry_script.clone(), exit)
+        (asp_expiry_script.to_vec(), exit)
     } else {
         let forfeit = compile_forfeit_script(&asp_pk, &user_pk);
-        (forfeit, tree.asp_expiry_script.clone())
+        (forfeit, asp_expiry_script.to_vec())
     };
 
     let leaf_hashes = [tap_leaf_hash(&forfeit_script), tap_leaf_hash(&exit_script)];
@@ -637,13 +640,31 @@ pub fn compute_ark_labs_merkle_root(tree: &VPackTree) -> Option<[u8; 32]> {
 pub fn ark_labs_tap_leaf_hashes_for_merkle_path(
     tree: &VPackTree,
 ) -> Option<(Vec<[u8; 32]>, usize)> {
+    let (scripts, asp_idx, _) = ark_labs_leaf_scripts(tree)?;
+    let hashes = scripts.iter().map(|s| tap_leaf_hash(s)).collect();
+    Some((hashes, asp_idx))
+}
+
+/// The exit (CSV) leaf of the Ark Labs script tree: the compiled exit script beside the forfeit
+/// leaf, or the first closure of a verbatim multi-closure `asp_expiry_script`.
+pub fn ark_labs_exit_leaf(tree: &VPackTree) -> Option<ExitLeaf> {
+    let (mut scripts, _, exit_idx) = ark_labs_leaf_scripts(tree)?;
+    let leaf_hashes = scripts.iter().map(|s| tap_leaf_hash(s)).collect();
+    Some(ExitLeaf {
+        script: scripts.swap_remove(exit_idx),
+        leaf_hashes,
+        index: exit_idx,
+    })
+}
+
+/// Leaf scripts in Merkle order, the index of `asp_expiry_script` and the index of the exit leaf.
+fn ark_labs_leaf_scripts(tree: &VPackTree) -> Option<(Vec<Vec<u8>>, usize, usize)> {
     if tree.asp_expiry_script.is_empty() {
         return None;
     }
 
     if let Some(segs) = arkade_verbatim_closure_segments(&tree.asp_expiry_script) {
-        let hashes: Vec<[u8; 32]> = segs.iter().map(|s| tap_leaf_hash(s)).collect();
-        return Some((hashes, 0));
+        return Some((segs, 0, 0));
     }
 
     let (asp_pk, user_pk) = parse_ark_labs_pubkeys(&tree.asp_expiry_script)?;
@@ -658,9 +679,8 @@ pub fn ark_labs_tap_leaf_hashes_for_merkle_path(
         (forfeit, tree.asp_expiry_script.clone())
     };
 
-    let hashes = vec![tap_leaf_hash(&forfeit_script), tap_leaf_hash(&exit_script)];
-    let leaf_idx = if forfeit_template { 0 } else { 1 };
-    Some((hashes, leaf_idx))
+    let asp_idx = if forfeit_template { 0 } else { 1 };
+    Some((vec![forfeit_script, exit_script], asp_idx, 1))
 }
 
 /// Encodes `exit_delta` as a minimal Bitcoin Script number for use with OP_CSV.
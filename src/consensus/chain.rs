@@ -0,0 +1,144 @@
+//! The exit chain as a list of transactions.
+//!
+//! [`reconstruct_chain`] builds the same transactions as
+//! [`ConsensusEngine::compute_vtxo_id_ref`](crate::consensus::ConsensusEngine::compute_vtxo_id_ref)
+//! but keeps each one instead of folding them into an ID, and enforces neither conservation of
+//! value nor signatures. Diagnostics (the verification report, mismatch localization, the WASM
+//! path details) run their checks over the result.
+
+use alloc::vec::Vec;
+
+use crate::consensus::fold::ChainState;
+use crate::consensus::{tx_preimage, ArkLabsV3, SecondTechV3, TxInPreimage, TxOutPreimage, VtxoId};
+use crate::error::VPackError;
+use crate::header::{BuiltinVariant, TxVariant};
+use crate::payload::tree::VPackTreeRef;
+use crate::types::hashes::{sha256d, Hash};
+use crate::types::{OutPoint, Txid};
+
+/// One reconstructed transaction (version 3, locktime 0, one input).
+#[derive(Debug, Clone)]
+pub struct ChainTx<'a> {
+    pub input: TxInPreimage,
+    pub outputs: Vec<TxOutPreimage<'a>>,
+    pub txid: [u8; 32],
+}
+
+impl ChainTx<'_> {
+    /// Unsigned consensus serialization; [`Self::txid`] is its double SHA-256.
+    pub fn preimage(&self) -> Vec<u8> {
+        tx_preimage(3, core::slice::from_ref(&self.input), &self.outputs, 0)
+    }
+}
+
+/// Transactions top-down: `path[i]` at index `i`, then the leaf transaction when the engine
+/// builds one. `result` is the VTXO ID, or the error that stopped the walk after `txs`.
+#[derive(Debug, Clone)]
+pub struct ChainWalk<'a> {
+    pub txs: Vec<ChainTx<'a>>,
+    pub result: Result<VtxoId, VPackError>,
+}
+
+/// Rebuilds the exit chain of `tree` as `variant` builds it. See the [module](self) docs.
+pub fn reconstruct_chain<'a>(tree: &VPackTreeRef<'a>, variant: TxVariant) -> ChainWalk<'a> {
+    let mut txs = Vec::with_capacity(tree.path.len() + 1);
+    let result = walk(tree, variant, &mut txs);
+    ChainWalk { txs, result }
+}
+
+fn walk<'a>(
+    tree: &VPackTreeRef<'a>,
+    variant: TxVariant,
+    txs: &mut Vec<ChainTx<'a>>,
+) -> Result<VtxoId, VPackError> {
+    if tree.path.is_empty() && tree.leaf_siblings.is_empty() && !tree.fee_anchor_script.is_empty() {
+        return Err(VPackError::FeeAnchorMissing);
+    }
+
+    let variant = variant.builtin()?;
+    let mut chain = ChainState::new(tree.anchor, None);
+    for (i, item) in tree.path.iter().enumerate() {
+        let child_vout = tree
+            .path
+            .get(i + 1)
+            .map_or(tree.leaf.vout, |next| next.parent_index);
+        let (outputs, continuation_vout) = match variant {
+            BuiltinVariant::V3Anchored => {
+                let vout = if item.child_script_pubkey.is_empty() {
+                    0
+                } else {
+                    child_vout
+                };
+                (ArkLabsV3::hop_outputs(item, child_vout)?, vout)
+            }
+            BuiltinVariant::V3Plain => (SecondTechV3::reconstruct_link_ref(item)?, child_vout),
+        };
+        let input = TxInPreimage {
+            prev_out_txid: chain.current_prevout.txid.to_byte_array(),
+            prev_out_vout: chain.current_prevout.vout,
+            sequence: item.sequence,
+        };
+        let txid = txid_of(&input, &outputs);
+
+        chain.last_txid = Some(txid);
+        chain.hops += 1;
+        chain.current_prevout = OutPoint {
+            txid: Txid::from_byte_array(txid),
+            vout: continuation_vout,
+        };
+        txs.push(ChainTx {
+            input,
+            outputs,
+            txid,
+        });
+    }
+
+    let (id, leaf_tx) = match variant {
+        BuiltinVariant::V3Anchored => {
+            let last_hop = tree
+                .path
+                .last()
+                .zip(txs.last().map(|tx| tx.outputs.as_slice()));
+            ArkLabsV3::finish_chain(&chain, &tree.leaf, &tree.leaf_siblings, last_hop)?
+        }
+        BuiltinVariant::V3Plain => {
+            SecondTechV3::finish_chain(&chain, &tree.leaf, &tree.leaf_siblings)?
+        }
+    };
+
+    if leaf_tx.is_some() {
+        // Leaf output at `leaf.vout`, siblings in order around it (as the engines lay it out).
+        let mut outputs = Vec::with_capacity(1 + tree.leaf_siblings.len());
+        let mut siblings = tree.leaf_siblings.iter();
+        for i in 0..=tree.leaf_siblings.len() {
+            if i == tree.leaf.vout as usize {
+                outputs.push(TxOutPreimage {
+                    value: tree.leaf.amount,
+                    script_pubkey: tree.leaf.script_pubkey,
+                });
+            } else if let Some(s) = siblings.next() {
+                outputs.push(TxOutPreimage {
+                    value: s.value(),
+                    script_pubkey: s.script(),
+                });
+            }
+        }
+        let input = TxInPreimage {
+            prev_out_txid: chain.current_prevout.txid.to_byte_array(),
+            prev_out_vout: chain.current_prevout.vout,
+            sequence: tree.leaf.sequence,
+        };
+        let txid = txid_of(&input, &outputs);
+        txs.push(ChainTx {
+            input,
+            outputs,
+            txid,
+        });
+    }
+
+    Ok(id)
+}
+
+fn txid_of(input: &TxInPreimage, outputs: &[TxOutPreimage<'_>]) -> [u8; 32] {
+    sha256d::Hash::hash(&tx_preimage(3, core::slice::from_ref(input), outputs, 0)).to_byte_array()
+}
@@ -8,6 +8,7 @@ use alloc::vec::Vec;
 use crate::consensus::taproot::{
     self, balanced_merkle_sibling_path, compute_balanced_merkle_root, TAPLEAF_VERSION,
 };
+use crate::consensus::ConsensusEngine;
 use crate::error::VPackError;
 use crate::header::TxVariant;
 use crate::payload::tree::VPackTree;
@@ -81,21 +82,20 @@ fn try_reconstruct_with_hashes(
 pub fn reconstruct_control_block(
     tree: &VPackTree,
     variant: TxVariant,
+) -> Result<Vec<u8>, VPackError> {
+    reconstruct_control_block_with(crate::consensus::builtin_engine(variant)?, tree)
+}
+
+/// [`reconstruct_control_block`] with the Taproot leaf rules of `engine`.
+pub(crate) fn reconstruct_control_block_with(
+    engine: &dyn ConsensusEngine,
+    tree: &VPackTree,
 ) -> Result<Vec<u8>, VPackError> {
     if tree.asp_expiry_script.is_empty() {
         return Err(VPackError::MissingExclusivityData);
     }
     let expected = p2tr_output_xonly(tree)?;
-
-    let (hashes, leaf_idx) = match variant {
-        TxVariant::V3Anchored => {
-            crate::consensus::ark_labs::ark_labs_tap_leaf_hashes_for_merkle_path(tree)
-                .ok_or(VPackError::ControlBlockReconstructionFailed)?
-        }
-        TxVariant::V3Plain => {
-            crate::consensus::second_tech::bark_tap_leaf_hashes_for_merkle_path(tree)?
-        }
-    };
+    let (hashes, leaf_idx) = engine.tap_leaf_hashes(tree)?;
 
     try_reconstruct_with_hashes(tree, &expected, &hashes, leaf_idx)
         .ok_or(VPackError::ControlBlockReconstructionFailed)
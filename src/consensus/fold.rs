@@ -0,0 +1,212 @@
+//! Streaming VTXO ID reconstruction.
+//!
+//! [`VtxoIdFold`] consumes [`StreamRecord`]s from a
+//! [`StreamingReader`](crate::payload::stream::StreamingReader) and runs the same per-hop logic
+//! as [`ConsensusEngine::compute_vtxo_id`](crate::consensus::ConsensusEngine::compute_vtxo_id)
+//! without materializing the whole tree: only the leaf, the previous path step (for the
+//! signature's prevout) and one step of lookahead (for the child's `vout`) are held.
+
+use alloc::vec::Vec;
+
+use crate::consensus::{ArkLabsV3, SecondTechV3, TxOutPreimage, VtxoId};
+use crate::error::VPackError;
+use crate::header::{BuiltinVariant, TxVariant};
+use crate::payload::stream::StreamRecord;
+use crate::payload::tree::{
+    GenesisItem, GenesisItemRef, SiblingNode, SiblingNodeRef, VtxoLeaf, VtxoLeafRef,
+};
+use crate::types::OutPoint;
+
+/// Running state of the top-down transaction chain, shared by the batch engines and the fold.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ChainState {
+    /// Outpoint spent by the next transaction (starts at the on-chain anchor).
+    pub(crate) current_prevout: OutPoint,
+    /// Value of `current_prevout` when conservation is enforced.
+    pub(crate) input_amount: Option<u64>,
+    /// TxID of the last path transaction.
+    pub(crate) last_txid: Option<[u8; 32]>,
+    /// Number of path steps applied.
+    pub(crate) hops: usize,
+}
+
+impl ChainState {
+    pub(crate) fn new(anchor: OutPoint, anchor_value: Option<u64>) -> Self {
+        Self {
+            current_prevout: anchor,
+            input_amount: anchor_value,
+            last_txid: None,
+            hops: 0,
+        }
+    }
+}
+
+/// Incremental VTXO ID computation over streamed records.
+///
+/// Push every record in order (the `Header` record is accepted and ignored), then call
+/// [`finish`](Self::finish) once the Trailer has been pushed. Out-of-order records are rejected
+/// with [`VPackError::EncodingError`]. The resulting ID is only trustworthy once the reader has
+/// emitted the Trailer (CRC verified).
+pub struct VtxoIdFold {
+    variant: TxVariant,
+    anchor_value: Option<u64>,
+    chain: Option<ChainState>,
+    has_fee_anchor: bool,
+    leaf: Option<(VtxoLeaf, Vec<SiblingNode>, u32)>,
+    /// Last applied step and the `vout` its child was placed at.
+    prev: Option<(GenesisItem, u32)>,
+    /// Step waiting for the next record to learn its child's `vout`.
+    pending: Option<GenesisItem>,
+    steps_seen: u32,
+    trailer_seen: bool,
+}
+
+impl VtxoIdFold {
+    /// Starts a fold for `variant`. `anchor_value` enables conservation of value as in
+    /// [`ConsensusEngine::compute_vtxo_id`](crate::consensus::ConsensusEngine::compute_vtxo_id).
+    pub fn new(variant: TxVariant, anchor_value: Option<u64>) -> Self {
+        Self {
+            variant,
+            anchor_value,
+            chain: None,
+            has_fee_anchor: false,
+            leaf: None,
+            prev: None,
+            pending: None,
+            steps_seen: 0,
+            trailer_seen: false,
+        }
+    }
+
+    /// Folds one record into the chain.
+    pub fn push(&mut self, record: &StreamRecord) -> Result<(), VPackError> {
+        if self.trailer_seen {
+            return Err(VPackError::EncodingError);
+        }
+        match record {
+            StreamRecord::Header(_) => {}
+            StreamRecord::Prefix {
+                anchor,
+                fee_anchor_script,
+                ..
+            } => {
+                if self.chain.is_some() {
+                    return Err(VPackError::EncodingError);
+                }
+                self.chain = Some(ChainState::new(*anchor, self.anchor_value));
+                self.has_fee_anchor = !fee_anchor_script.is_empty();
+            }
+            StreamRecord::Leaf {
+                leaf,
+                leaf_siblings,
+                path_len,
+            } => {
+                if self.chain.is_none() || self.leaf.is_some() {
+                    return Err(VPackError::EncodingError);
+                }
+                self.leaf = Some((leaf.clone(), leaf_siblings.clone(), *path_len));
+            }
+            StreamRecord::Step(item) => {
+                let path_len = self.leaf.as_ref().ok_or(VPackError::EncodingError)?.2;
+                if self.steps_seen >= path_len {
+                    return Err(VPackError::EncodingError);
+                }
+                self.steps_seen += 1;
+                if let Some(parent) = self.pending.replace(item.clone()) {
+                    self.apply(parent, item.parent_index)?;
+                }
+            }
+            StreamRecord::Trailer { .. } => {
+                if self.leaf.is_none() {
+                    return Err(VPackError::EncodingError);
+                }
+                self.trailer_seen = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the pending step and the leaf; returns the reconstructed VTXO ID.
+    ///
+    /// Fails with [`VPackError::IncompleteData`] unless every record through the Trailer was pushed.
+    pub fn finish(mut self) -> Result<VtxoId, VPackError> {
+        let (leaf, _, path_len) = self.leaf.as_ref().ok_or(VPackError::IncompleteData)?;
+        if !self.trailer_seen || self.steps_seen != *path_len {
+            return Err(VPackError::IncompleteData);
+        }
+        let leaf_vout = leaf.vout;
+        if let Some(last) = self.pending.take() {
+            self.apply(last, leaf_vout)?;
+        }
+
+        let chain = self.chain.ok_or(VPackError::IncompleteData)?;
+        let (leaf, leaf_siblings, _) = self.leaf.as_ref().ok_or(VPackError::IncompleteData)?;
+        if chain.hops == 0 && leaf_siblings.is_empty() && self.has_fee_anchor {
+            return Err(VPackError::FeeAnchorMissing);
+        }
+        let leaf = VtxoLeafRef::from(leaf);
+        let leaf_siblings: Vec<SiblingNodeRef<'_>> =
+            leaf_siblings.iter().map(SiblingNodeRef::from).collect();
+
+        let (id, _) = match self.variant.builtin()? {
+            BuiltinVariant::V3Anchored => {
+                let last = self
+                    .prev
+                    .as_ref()
+                    .map(|(item, vout)| (GenesisItemRef::from(item), *vout));
+                let outputs = match &last {
+                    Some((item, vout)) => Some(ArkLabsV3::hop_outputs(item, *vout)?),
+                    None => None,
+                };
+                let last_hop = last.as_ref().map(|(item, _)| item).zip(outputs.as_deref());
+                ArkLabsV3::finish_chain(&chain, &leaf, &leaf_siblings, last_hop)?
+            }
+            BuiltinVariant::V3Plain => SecondTechV3::finish_chain(&chain, &leaf, &leaf_siblings)?,
+        };
+        Ok(id)
+    }
+
+    /// Applies one path step now that its child's `vout` is known.
+    fn apply(&mut self, item: GenesisItem, child_vout: u32) -> Result<(), VPackError> {
+        let chain = self.chain.as_mut().ok_or(VPackError::EncodingError)?;
+        let leaf_script = self
+            .leaf
+            .as_ref()
+            .map(|(leaf, _, _)| leaf.script_pubkey.as_slice())
+            .ok_or(VPackError::EncodingError)?;
+        let item_ref = GenesisItemRef::from(&item);
+
+        let prev = self
+            .prev
+            .as_ref()
+            .map(|(parent, vout)| (GenesisItemRef::from(parent), *vout));
+        let prev_outputs: Option<Vec<TxOutPreimage<'_>>> = match &prev {
+            Some((parent, vout)) => Some(match self.variant.builtin()? {
+                BuiltinVariant::V3Anchored => ArkLabsV3::hop_outputs(parent, *vout)?,
+                BuiltinVariant::V3Plain => SecondTechV3::reconstruct_link_ref(parent)?,
+            }),
+            None => None,
+        };
+
+        match self.variant.builtin()? {
+            BuiltinVariant::V3Anchored => ArkLabsV3::apply_hop(
+                chain,
+                &item_ref,
+                child_vout,
+                prev_outputs.as_deref(),
+                leaf_script,
+                None,
+            )?,
+            BuiltinVariant::V3Plain => SecondTechV3::apply_hop(
+                chain,
+                &item_ref,
+                child_vout,
+                prev_outputs.as_deref(),
+                leaf_script,
+                None,
+            )?,
+        };
+        self.prev = Some((item, child_vout));
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+//! Work shared across the path steps of one tree, and across the trees of one
+//! [`crate::verify_batch`] call.
+//!
+//! VTXOs of one round reconstruct the same upper path transactions. [`HopMemo`] remembers the
+//! txid of every unsigned transaction and which hop signatures already verified, so shared
+//! levels are hashed and signature-checked once per batch instead of once per leaf. Signatures
+//! not seen before are collected while a tree is walked and checked as one
+//! [`SchnorrBatch`](crate::consensus::SchnorrBatch) at the end of the walk.
+//!
+//! A cached signature is keyed by the spending transaction's txid, the signature and the leaf
+//! script it was checked against. The txid commits to the spent outpoint, whose txid in turn
+//! commits to the prevout value and script, so a hit covers the same sighash message.
+
+use alloc::collections::BTreeMap;
+#[cfg(feature = "schnorr-verify")]
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+#[cfg(feature = "schnorr-verify")]
+use crate::consensus::SchnorrBatch;
+use crate::consensus::{tx_preimage, ArkLabsV3, SecondTechV3, TxInPreimage, TxOutPreimage, VtxoId};
+use crate::error::VPackError;
+use crate::header::{BuiltinVariant, TxVariant};
+use crate::payload::tree::VPackTreeRef;
+use crate::types::hashes::{sha256d, Hash};
+
+#[derive(Debug, Default)]
+pub(crate) struct HopMemo {
+    /// Unsigned preimage (version 3, locktime 0) → txid.
+    txids: BTreeMap<Vec<u8>, [u8; 32]>,
+    /// SHA-256 over (txid, signature, leaf script) of every signature that verified.
+    #[cfg(feature = "schnorr-verify")]
+    verified_signatures: BTreeSet<[u8; 32]>,
+    /// Signatures of the tree being walked, with their `verified_signatures` keys.
+    #[cfg(feature = "schnorr-verify")]
+    pending: SchnorrBatch,
+    #[cfg(feature = "schnorr-verify")]
+    pending_keys: Vec<[u8; 32]>,
+}
+
+impl HopMemo {
+    /// [`ConsensusEngine::verify_ref`](crate::consensus::ConsensusEngine::verify_ref) of the
+    /// built-in engine for `variant`, with this memo.
+    pub(crate) fn verify_tree(
+        &mut self,
+        variant: TxVariant,
+        tree: &VPackTreeRef<'_>,
+        expected: &VtxoId,
+        anchor_value: u64,
+    ) -> Result<(), VPackError> {
+        let computed = match variant.builtin()? {
+            BuiltinVariant::V3Anchored => {
+                ArkLabsV3.compute_vtxo_id_memoized(tree, Some(anchor_value), self)?
+            }
+            BuiltinVariant::V3Plain => {
+                SecondTechV3.compute_vtxo_id_memoized(tree, Some(anchor_value), self)?
+            }
+        };
+        crate::consensus::check_expected_id(&computed.id, expected)
+    }
+
+    /// Ends a tree walk: checks the signatures collected during it, then returns `walk`.
+    ///
+    /// Signatures are checked even when the walk failed, so that an invalid signature before
+    /// the failing step is reported as [`VPackError::InvalidSignature`], as a one-by-one check
+    /// would have.
+    pub(crate) fn finish_tree<T>(&mut self, walk: Result<T, VPackError>) -> Result<T, VPackError> {
+        #[cfg(feature = "schnorr-verify")]
+        {
+            let verified = self.pending.verify();
+            self.pending.clear();
+            let keys = core::mem::take(&mut self.pending_keys);
+            verified.map_err(|_| VPackError::InvalidSignature)?;
+            self.verified_signatures.extend(keys);
+        }
+        walk
+    }
+}
+
+/// Txid of the one-input path transaction, looked up in `memo` when there is one.
+pub(crate) fn hop_txid(
+    memo: Option<&mut HopMemo>,
+    input: &TxInPreimage,
+    outputs: &[TxOutPreimage<'_>],
+) -> [u8; 32] {
+    let preimage = tx_preimage(3, core::slice::from_ref(input), outputs, 0);
+    let Some(memo) = memo else {
+        return sha256d::Hash::hash(&preimage).to_byte_array();
+    };
+    if let Some(txid) = memo.txids.get(&preimage) {
+        return *txid;
+    }
+    let txid = sha256d::Hash::hash(&preimage).to_byte_array();
+    memo.txids.insert(preimage, txid);
+    txid
+}
+
+/// [`verify_hop_signature`](crate::consensus::verify_hop_signature), or with a `memo`: skipped
+/// when already verified for the transaction `txid`, else queued for [`HopMemo::finish_tree`].
+#[cfg(feature = "schnorr-verify")]
+pub(crate) fn check_hop_signature(
+    memo: Option<&mut HopMemo>,
+    txid: &[u8; 32],
+    input: &TxInPreimage,
+    outputs: &[TxOutPreimage<'_>],
+    prev_outputs: Option<&[TxOutPreimage<'_>]>,
+    leaf_script: &[u8],
+    sig: &[u8; 64],
+) -> Result<(), VPackError> {
+    let Some(memo) = memo else {
+        return crate::consensus::verify_hop_signature(
+            input,
+            outputs,
+            prev_outputs,
+            leaf_script,
+            sig,
+        );
+    };
+    let mut buf = Vec::with_capacity(32 + 64 + leaf_script.len());
+    buf.extend_from_slice(txid);
+    buf.extend_from_slice(sig);
+    buf.extend_from_slice(leaf_script);
+    let key = crate::types::hashes::sha256::Hash::hash(&buf).to_byte_array();
+    if memo.verified_signatures.contains(&key) {
+        return Ok(());
+    }
+    let (verify_key, sighash) =
+        crate::consensus::hop_signature_message(input, outputs, prev_outputs, leaf_script)?;
+    memo.pending.push(verify_key, sighash, *sig);
+    memo.pending_keys.push(key);
+    Ok(())
+}
@@ -0,0 +1,184 @@
+//! Localizing an [`IdMismatch`](crate::error::VPackError::IdMismatch).
+//!
+//! A mismatch only reports the final IDs. When an ASP changes its transaction template, the first
+//! transaction that differs is what matters. [`localize_mismatch`] compares the reconstructed
+//! chain against independently known transactions (a block explorer dump, the ASP's tree JSON)
+//! and returns the first step that differs, with a field-level diff when the raw transaction is
+//! known.
+//!
+//! Steps are numbered as in [`crate::report`]: `path[i]` is step `i`, the leaf transaction (when
+//! the engine builds one) is step `path.len()`.
+
+use alloc::vec::Vec;
+
+use crate::consensus::chain::{reconstruct_chain, ChainTx};
+use crate::consensus::tx_decode::ParsedTx;
+use crate::error::VPackError;
+use crate::header::TxVariant;
+use crate::payload::tree::{VPackTree, VPackTreeRef};
+
+/// Version and locktime of every reconstructed transaction.
+const RECONSTRUCTED_VERSION: u32 = 3;
+const RECONSTRUCTED_LOCKTIME: u32 = 0;
+
+/// An independently known transaction of the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KnownTx<'a> {
+    /// Only the txid (internal byte order).
+    Txid([u8; 32]),
+    /// Consensus-serialized transaction, with or without witness data.
+    Raw(&'a [u8]),
+}
+
+/// One differing field between the reconstructed and the known transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldDiff {
+    Version {
+        reconstructed: u32,
+        known: u32,
+    },
+    InputCount {
+        reconstructed: usize,
+        known: usize,
+    },
+    /// Spent outpoint of input `index` (txid in internal byte order).
+    Prevout {
+        index: usize,
+        reconstructed: ([u8; 32], u32),
+        known: ([u8; 32], u32),
+    },
+    Sequence {
+        index: usize,
+        reconstructed: u32,
+        known: u32,
+    },
+    OutputCount {
+        reconstructed: usize,
+        known: usize,
+    },
+    OutputValue {
+        index: usize,
+        reconstructed: u64,
+        known: u64,
+    },
+    OutputScript {
+        index: usize,
+        reconstructed: Vec<u8>,
+        known: Vec<u8>,
+    },
+    Locktime {
+        reconstructed: u32,
+        known: u32,
+    },
+}
+
+/// First step whose reconstructed transaction differs from the known one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub step: u32,
+    pub reconstructed_txid: [u8; 32],
+    pub known_txid: [u8; 32],
+    /// Field-level differences; empty for [`KnownTx::Txid`]. Output fields are compared index by
+    /// index over the common prefix.
+    pub fields: Vec<FieldDiff>,
+}
+
+/// Compares the chain rebuilt from `tree` with `known` (`known[i]` is step `i`; a shorter list
+/// checks a prefix) and returns the first divergence, or `None` when every known step matches.
+///
+/// Fails with the reconstruction error when the chain stops before the last known step, with
+/// [`VPackError::EncodingError`] when `known` has more steps than the chain, and with
+/// [`VPackError::IncompleteData`] / [`VPackError::TrailingData`] for an unparseable raw
+/// transaction.
+pub fn localize_mismatch(
+    tree: &VPackTree,
+    variant: TxVariant,
+    known: &[KnownTx<'_>],
+) -> Result<Option<Divergence>, VPackError> {
+    let tree = VPackTreeRef::from(tree);
+    let walk = reconstruct_chain(&tree, variant);
+    for (step, known_tx) in known.iter().enumerate() {
+        let Some(tx) = walk.txs.get(step) else {
+            // Either the walk failed here, or `known` is longer than the chain.
+            return Err(walk.result.err().unwrap_or(VPackError::EncodingError));
+        };
+        let (known_txid, parsed) = match *known_tx {
+            KnownTx::Txid(txid) => (txid, None),
+            KnownTx::Raw(raw) => {
+                let parsed = ParsedTx::parse(raw)?;
+                (parsed.txid, Some(parsed))
+            }
+        };
+        if known_txid != tx.txid {
+            return Ok(Some(Divergence {
+                step: step as u32,
+                reconstructed_txid: tx.txid,
+                known_txid,
+                fields: parsed.map(|p| diff_fields(tx, &p)).unwrap_or_default(),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+fn diff_fields(tx: &ChainTx<'_>, known: &ParsedTx<'_>) -> Vec<FieldDiff> {
+    let mut fields = Vec::new();
+    if known.version != RECONSTRUCTED_VERSION {
+        fields.push(FieldDiff::Version {
+            reconstructed: RECONSTRUCTED_VERSION,
+            known: known.version,
+        });
+    }
+    if known.inputs.len() != 1 {
+        fields.push(FieldDiff::InputCount {
+            reconstructed: 1,
+            known: known.inputs.len(),
+        });
+    }
+    if let Some(&(txid, vout, sequence)) = known.inputs.first() {
+        let reconstructed = (tx.input.prev_out_txid, tx.input.prev_out_vout);
+        if reconstructed != (txid, vout) {
+            fields.push(FieldDiff::Prevout {
+                index: 0,
+                reconstructed,
+                known: (txid, vout),
+            });
+        }
+        if tx.input.sequence != sequence {
+            fields.push(FieldDiff::Sequence {
+                index: 0,
+                reconstructed: tx.input.sequence,
+                known: sequence,
+            });
+        }
+    }
+    if known.outputs.len() != tx.outputs.len() {
+        fields.push(FieldDiff::OutputCount {
+            reconstructed: tx.outputs.len(),
+            known: known.outputs.len(),
+        });
+    }
+    for (index, (ours, &(value, script))) in tx.outputs.iter().zip(&known.outputs).enumerate() {
+        if ours.value != value {
+            fields.push(FieldDiff::OutputValue {
+                index,
+                reconstructed: ours.value,
+                known: value,
+            });
+        }
+        if ours.script_pubkey != script {
+            fields.push(FieldDiff::OutputScript {
+                index,
+                reconstructed: ours.script_pubkey.to_vec(),
+                known: script.to_vec(),
+            });
+        }
+    }
+    if known.locktime != RECONSTRUCTED_LOCKTIME {
+        fields.push(FieldDiff::Locktime {
+            reconstructed: RECONSTRUCTED_LOCKTIME,
+            known: known.locktime,
+        });
+    }
+    fields
+}
@@ -10,20 +10,41 @@ use core::str::FromStr;
 use crate::types::{hashes::sha256d, hashes::Hash, OutPoint, Txid};
 
 use crate::error::VPackError;
-use crate::payload::tree::VPackTree;
+use crate::payload::tree::{VPackTree, VPackTreeRef};
 
 pub mod ark_labs;
+pub mod chain;
 pub mod completeness;
+pub mod fold;
+pub(crate) mod memo;
+pub mod mismatch;
+pub mod registry;
+// Most opcodes are only matched by the tapscript evaluator.
+#[cfg_attr(not(feature = "schnorr-verify"), allow(dead_code))]
+pub(crate) mod script;
 pub mod second_tech;
 pub mod taproot;
 pub mod timelocks;
+pub(crate) mod tx_decode;
 pub mod tx_factory;
 
 pub use completeness::{validate_exit_ready_completeness, validate_tree_completeness};
+pub use fold::VtxoIdFold;
+pub use mismatch::{localize_mismatch, Divergence, FieldDiff, KnownTx};
+pub use registry::EngineRegistry;
 pub use timelocks::validate_timelocks;
 
+#[cfg(feature = "schnorr-verify")]
+pub mod schnorr_batch;
 #[cfg(feature = "schnorr-verify")]
 pub mod taproot_sighash;
+#[cfg(feature = "schnorr-verify")]
+pub mod tapscript;
+
+#[cfg(feature = "schnorr-verify")]
+pub use schnorr_batch::SchnorrBatch;
+#[cfg(feature = "schnorr-verify")]
+pub use tapscript::{eval_tapscript, SpendContext};
 
 #[cfg(feature = "bitcoin")]
 pub mod control_block;
@@ -36,7 +57,7 @@ pub use ark_labs::ArkLabsV3;
 pub use second_tech::compute_bark_merkle_root;
 pub use second_tech::compute_bark_vtxo_tapscript_root;
 pub use second_tech::SecondTechV3;
-pub use tx_factory::{tx_preimage, tx_signed_hex, TxInPreimage, TxOutPreimage};
+pub use tx_factory::{tx_preimage, tx_signed_hex, tx_with_witnesses, TxInPreimage, TxOutPreimage};
 
 /// Conservation-of-value failure with summed output totals for auditing.
 pub(crate) fn value_mismatch_for_output_sum(
@@ -56,6 +77,74 @@ pub(crate) fn value_mismatch_for_output_sum(
     VPackError::ValueMismatch { expected, actual }
 }
 
+/// Conservation of value: when `expected` is `Some`, the outputs must sum to it exactly.
+pub(crate) fn enforce_conservation(
+    expected: Option<u64>,
+    outputs: &[TxOutPreimage<'_>],
+) -> Result<(), VPackError> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+    match outputs
+        .iter()
+        .try_fold(0u64, |acc, o| acc.checked_add(o.value))
+    {
+        None => Err(value_mismatch_for_output_sum(expected, outputs)),
+        Some(s) if s != expected => Err(VPackError::ValueMismatch {
+            expected,
+            actual: s,
+        }),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Verifies a cosigned path step: BIP-340 over the key-path sighash of `input` spending
+/// `prev_outputs[input.prev_out_vout]`, keyed by the x-only key in the leaf script.
+#[cfg(feature = "schnorr-verify")]
+pub(crate) fn verify_hop_signature(
+    input: &TxInPreimage,
+    outputs: &[TxOutPreimage<'_>],
+    prev_outputs: Option<&[TxOutPreimage<'_>]>,
+    leaf_script: &[u8],
+    sig: &[u8; 64],
+) -> Result<(), VPackError> {
+    let (verify_key, sighash) = hop_signature_message(input, outputs, prev_outputs, leaf_script)?;
+    taproot_sighash::verify_schnorr_bip340(&verify_key, &sighash, sig)
+}
+
+/// Key and sighash a path step's signature is checked against (see [`verify_hop_signature`]).
+#[cfg(feature = "schnorr-verify")]
+pub(crate) fn hop_signature_message(
+    input: &TxInPreimage,
+    outputs: &[TxOutPreimage<'_>],
+    prev_outputs: Option<&[TxOutPreimage<'_>]>,
+    leaf_script: &[u8],
+) -> Result<([u8; 32], [u8; 32]), VPackError> {
+    let verify_key = taproot_sighash::extract_verify_key(leaf_script).or_else(|| {
+        if leaf_script.len() == 33 {
+            leaf_script[1..33].try_into().ok()
+        } else {
+            None
+        }
+    });
+    let verify_key = verify_key.ok_or(VPackError::InvalidSignature)?;
+    let prev = prev_outputs.ok_or(VPackError::EncodingError)?;
+    let idx = input.prev_out_vout as usize;
+    if idx >= prev.len() {
+        return Err(VPackError::InvalidVout(input.prev_out_vout));
+    }
+    let sighash = taproot_sighash::taproot_sighash(
+        3,
+        0,
+        input,
+        prev[idx].value,
+        prev[idx].script_pubkey,
+        outputs,
+        0x00,
+    )?;
+    Ok((verify_key, sighash))
+}
+
 // -----------------------------------------------------------------------------
 // VtxoId
 // -----------------------------------------------------------------------------
@@ -88,6 +177,31 @@ pub fn vtxo_id_mismatch_diagnostic_vout(id: &VtxoId) -> Option<u32> {
     }
 }
 
+/// Total-order key for a [`VtxoId`]: (kind, hash, vout) with kind 0 = Raw, 1 = OutPoint.
+/// Used where IDs key a sorted table (bundle TOC, dedup store).
+pub(crate) type VtxoIdKey = (u8, [u8; 32], u32);
+
+impl VtxoId {
+    pub(crate) fn key(&self) -> VtxoIdKey {
+        match self {
+            VtxoId::Raw(hash) => (0, *hash, 0),
+            VtxoId::OutPoint(op) => (1, op.txid.to_byte_array(), op.vout),
+        }
+    }
+
+    /// Inverse of [`Self::key`]; `None` for an unknown kind or a Raw key with non-zero vout.
+    pub(crate) fn from_key((kind, hash, vout): VtxoIdKey) -> Option<Self> {
+        match kind {
+            0 if vout == 0 => Some(VtxoId::Raw(hash)),
+            1 => Some(VtxoId::OutPoint(OutPoint {
+                txid: Txid::from_byte_array(hash),
+                vout,
+            })),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for VtxoId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -211,6 +325,18 @@ pub trait ConsensusEngine {
         anchor_value: Option<u64>,
     ) -> Result<VerificationOutput, VPackError>;
 
+    /// Compute the VTXO ID from a borrowed tree (zero-copy parse path).
+    ///
+    /// The default materializes an owned [`VPackTree`]; the built-in engines override this and
+    /// implement [`Self::compute_vtxo_id`] on top of it, so no script is copied.
+    fn compute_vtxo_id_ref(
+        &self,
+        tree: &VPackTreeRef<'_>,
+        anchor_value: Option<u64>,
+    ) -> Result<VerificationOutput, VPackError> {
+        self.compute_vtxo_id(&VPackTree::from(tree), anchor_value)
+    }
+
     /// Verify that the tree yields the expected VTXO ID with conservation of value.
     fn verify(
         &self,
@@ -219,16 +345,82 @@ pub trait ConsensusEngine {
         anchor_value: u64,
     ) -> Result<(), VPackError> {
         let computed = self.compute_vtxo_id(tree, Some(anchor_value))?;
-        if computed.id == *expected {
-            Ok(())
-        } else {
-            Err(VPackError::IdMismatch {
-                computed: vtxo_id_mismatch_diagnostic_bytes(&computed.id),
-                expected: vtxo_id_mismatch_diagnostic_bytes(expected),
-                computed_vout: vtxo_id_mismatch_diagnostic_vout(&computed.id),
-                expected_vout: vtxo_id_mismatch_diagnostic_vout(expected),
-            })
-        }
+        check_expected_id(&computed.id, expected)
+    }
+
+    /// [`Self::verify`] over a borrowed tree.
+    fn verify_ref(
+        &self,
+        tree: &VPackTreeRef<'_>,
+        expected: &VtxoId,
+        anchor_value: u64,
+    ) -> Result<(), VPackError> {
+        let computed = self.compute_vtxo_id_ref(tree, Some(anchor_value))?;
+        check_expected_id(&computed.id, expected)
+    }
+
+    /// Taproot Merkle root the leaf's P2TR output key must commit to, rebuilt from the tree's
+    /// spend scripts. Path exclusivity tweaks `internal_key` with it and compares the result to
+    /// the leaf key.
+    ///
+    /// The default knows no script tree and fails with [`VPackError::MissingExclusivityData`], so
+    /// an engine without exclusivity rules never passes the check.
+    fn exclusivity_merkle_root(&self, tree: &VPackTreeRef<'_>) -> Result<[u8; 32], VPackError> {
+        let _ = tree;
+        Err(VPackError::MissingExclusivityData)
+    }
+
+    /// TapLeaf hashes of the leaf's script tree, in the order the balanced Merkle tree is built,
+    /// and the index of the `asp_expiry_script` spend path. Used for control block reconstruction.
+    ///
+    /// The default fails with [`VPackError::ControlBlockReconstructionFailed`].
+    fn tap_leaf_hashes(&self, tree: &VPackTree) -> Result<(Vec<[u8; 32]>, usize), VPackError> {
+        let _ = tree;
+        Err(VPackError::ControlBlockReconstructionFailed)
+    }
+
+    /// The user's unilateral exit leaf: its tapscript, the TapLeaf hashes of the VTXO's script
+    /// tree in balanced Merkle order, and the exit leaf's index. Used to build the exit sweep.
+    ///
+    /// The default fails with [`VPackError::ControlBlockReconstructionFailed`].
+    fn exit_leaf(&self, tree: &VPackTree) -> Result<ExitLeaf, VPackError> {
+        let _ = tree;
+        Err(VPackError::ControlBlockReconstructionFailed)
+    }
+}
+
+/// The exit spend path of a VTXO, from [`ConsensusEngine::exit_leaf`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExitLeaf {
+    /// Tapscript of the exit leaf.
+    pub script: Vec<u8>,
+    /// TapLeaf hashes of every leaf of the script tree, in balanced Merkle order.
+    pub leaf_hashes: Vec<[u8; 32]>,
+    /// Index of the exit leaf in `leaf_hashes`.
+    pub index: usize,
+}
+
+/// Engine of a built-in variant; [`TxVariant::Custom`](crate::header::TxVariant::Custom) needs an
+/// [`EngineRegistry`].
+pub(crate) fn builtin_engine(
+    variant: crate::header::TxVariant,
+) -> Result<&'static dyn ConsensusEngine, VPackError> {
+    match variant.builtin()? {
+        crate::header::BuiltinVariant::V3Anchored => Ok(&ArkLabsV3),
+        crate::header::BuiltinVariant::V3Plain => Ok(&SecondTechV3),
+    }
+}
+
+pub(crate) fn check_expected_id(computed: &VtxoId, expected: &VtxoId) -> Result<(), VPackError> {
+    if computed == expected {
+        Ok(())
+    } else {
+        Err(VPackError::IdMismatch {
+            computed: vtxo_id_mismatch_diagnostic_bytes(computed),
+            expected: vtxo_id_mismatch_diagnostic_bytes(expected),
+            computed_vout: vtxo_id_mismatch_diagnostic_vout(computed),
+            expected_vout: vtxo_id_mismatch_diagnostic_vout(expected),
+        })
     }
 }
 
@@ -262,18 +454,26 @@ pub fn verify_path_exclusivity(
     tree: &VPackTree,
     variant: crate::header::TxVariant,
 ) -> Result<(), VPackError> {
-    if tree.asp_expiry_script.is_empty() {
-        return Err(VPackError::MissingExclusivityData);
-    }
+    verify_path_exclusivity_ref(&VPackTreeRef::from(tree), variant)
+}
 
-    let merkle_root = match variant {
-        crate::header::TxVariant::V3Anchored => {
-            compute_ark_labs_merkle_root(tree).ok_or(VPackError::InvalidArkLabsScript)?
-        }
-        crate::header::TxVariant::V3Plain => compute_bark_merkle_root(tree)?,
-    };
+/// [`verify_path_exclusivity`] over a borrowed tree.
+#[cfg(feature = "schnorr-verify")]
+pub fn verify_path_exclusivity_ref(
+    tree: &VPackTreeRef<'_>,
+    variant: crate::header::TxVariant,
+) -> Result<(), VPackError> {
+    verify_path_exclusivity_with(builtin_engine(variant)?, tree)
+}
 
-    let expected_from_script = p2tr_embedded_xonly_key(&tree.leaf.script_pubkey);
+/// Path exclusivity with the script-tree rules of `engine`.
+#[cfg(feature = "schnorr-verify")]
+pub(crate) fn verify_path_exclusivity_with(
+    engine: &dyn ConsensusEngine,
+    tree: &VPackTreeRef<'_>,
+) -> Result<(), VPackError> {
+    let merkle_root = engine.exclusivity_merkle_root(tree)?;
+    let expected_from_script = p2tr_embedded_xonly_key(tree.leaf.script_pubkey);
 
     let derived_key = match taproot::compute_taproot_tweak(tree.internal_key, merkle_root) {
         Some(k) => k,
@@ -285,7 +485,7 @@ pub fn verify_path_exclusivity(
         }
     };
 
-    let script = &tree.leaf.script_pubkey;
+    let script = tree.leaf.script_pubkey;
     if script.len() != 34 || script[..2] != P2TR_PREFIX {
         return Err(VPackError::PathExclusivityViolation {
             derived_key,
@@ -305,6 +505,16 @@ pub fn verify_path_exclusivity(
     Ok(())
 }
 
+/// Taproot Merkle root the leaf key must commit to: the variant's script tree rebuilt from
+/// `asp_expiry_script` (and, for V3-Plain, the leaf siblings' scripts).
+#[cfg(feature = "schnorr-verify")]
+pub(crate) fn exclusivity_merkle_root(
+    tree: &VPackTreeRef<'_>,
+    variant: crate::header::TxVariant,
+) -> Result<[u8; 32], VPackError> {
+    builtin_engine(variant)?.exclusivity_merkle_root(tree)
+}
+
 // -----------------------------------------------------------------------------
 // Tests: Verification Gate (VtxoId parsing)
 // -----------------------------------------------------------------------------
@@ -0,0 +1,177 @@
+//! Dispatch from the header's variant byte to a [`ConsensusEngine`].
+//!
+//! [`crate::verify`] runs through [`EngineRegistry::default`], which maps 0x03 to
+//! [`SecondTechV3`] and 0x04 to [`ArkLabsV3`]. An experimental Ark-style dialect registers its own
+//! engine under a private variant byte; the engine's
+//! [`exclusivity_merkle_root`](ConsensusEngine::exclusivity_merkle_root) and
+//! [`tap_leaf_hashes`](ConsensusEngine::tap_leaf_hashes) supply its path-exclusivity and control
+//! block rules.
+//!
+//! A V-PACK carrying a registered private byte parses as [`TxVariant::Custom`] through the
+//! registry only ([`EngineRegistry::verify`], [`EngineRegistry::verify_ref`],
+//! [`EngineRegistry::verify_with`]); [`Header::from_bytes`] keeps rejecting it. Its payload is read with the
+//! V3-Plain framing rules (no mandatory fee anchor).
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::consensus::{ArkLabsV3, ConsensusEngine, SecondTechV3, VtxoId};
+use crate::error::VPackError;
+use crate::header::{Header, Network, TxVariant};
+use crate::payload::tree::{VPackTree, VPackTreeRef};
+use crate::policy::VerificationPolicy;
+
+/// Consensus engines by variant byte. The default registers the two built-in dialects.
+pub struct EngineRegistry {
+    engines: Vec<(u8, Box<dyn ConsensusEngine>)>,
+}
+
+impl EngineRegistry {
+    /// Registry without any engine, not even the built-in ones.
+    pub fn new() -> Self {
+        Self {
+            engines: Vec::new(),
+        }
+    }
+
+    /// Registers `engine` for `variant`, returning the engine it replaces. Re-registering 0x03 or
+    /// 0x04 overrides a built-in dialect.
+    pub fn register(
+        &mut self,
+        variant: u8,
+        engine: impl ConsensusEngine + 'static,
+    ) -> Option<Box<dyn ConsensusEngine>> {
+        let engine: Box<dyn ConsensusEngine> = Box::new(engine);
+        match self.engines.iter_mut().find(|(byte, _)| *byte == variant) {
+            Some((_, slot)) => Some(core::mem::replace(slot, engine)),
+            None => {
+                self.engines.push((variant, engine));
+                None
+            }
+        }
+    }
+
+    /// Builder form of [`Self::register`].
+    pub fn with_engine(mut self, variant: u8, engine: impl ConsensusEngine + 'static) -> Self {
+        self.register(variant, engine);
+        self
+    }
+
+    /// Engine registered for `variant`, if any.
+    pub fn engine(&self, variant: TxVariant) -> Option<&dyn ConsensusEngine> {
+        let byte = variant.as_u8();
+        self.engines
+            .iter()
+            .find(|(b, _)| *b == byte)
+            .map(|(_, engine)| engine.as_ref())
+    }
+
+    /// Like [`Self::engine`], failing with [`VPackError::InvalidTxVariant`] for an unregistered
+    /// variant.
+    pub fn engine_for(&self, variant: TxVariant) -> Result<&dyn ConsensusEngine, VPackError> {
+        self.engine(variant)
+            .ok_or(VPackError::InvalidTxVariant(variant.as_u8()))
+    }
+
+    /// `true` when an engine is registered for the variant byte.
+    pub fn contains(&self, variant: u8) -> bool {
+        self.engines.iter().any(|(b, _)| *b == variant)
+    }
+
+    /// Parses a header, accepting every registered variant byte. Unregistered bytes fail with
+    /// [`VPackError::InvalidTxVariant`], built-in ones included when they were not registered.
+    pub fn parse_header(&self, bytes: &[u8]) -> Result<Header, VPackError> {
+        let header = Header::from_bytes_with_variants(bytes, |byte| self.contains(byte))?;
+        if !self.contains(header.tx_variant.as_u8()) {
+            return Err(VPackError::InvalidTxVariant(header.tx_variant.as_u8()));
+        }
+        Ok(header)
+    }
+
+    /// [`crate::verify`] dispatched through this registry.
+    pub fn verify(
+        &self,
+        vpack_bytes: &[u8],
+        expected_id: &VtxoId,
+        anchor_value: u64,
+        network: Network,
+    ) -> Result<VPackTree, VPackError> {
+        crate::verify_in(self, None, vpack_bytes, expected_id, anchor_value, network)
+    }
+
+    /// [`crate::verify_ref`] dispatched through this registry.
+    pub fn verify_ref<'a>(
+        &self,
+        vpack_bytes: &'a [u8],
+        expected_id: &VtxoId,
+        anchor_value: u64,
+        network: Network,
+    ) -> Result<VPackTreeRef<'a>, VPackError> {
+        crate::verify_ref_in(self, vpack_bytes, expected_id, anchor_value, network)
+    }
+
+    /// [`crate::verify_with`] dispatched through this registry.
+    pub fn verify_with(
+        &self,
+        vpack_bytes: &[u8],
+        expected_id: &VtxoId,
+        anchor_value: u64,
+        policy: &VerificationPolicy,
+    ) -> Result<VPackTree, VPackError> {
+        crate::verify_with_in(self, vpack_bytes, expected_id, anchor_value, policy)
+    }
+
+    /// [`ConsensusEngine::verify_ref`] with the engine registered for `variant`.
+    pub fn verify_tree_ref(
+        &self,
+        variant: TxVariant,
+        tree: &VPackTreeRef<'_>,
+        expected_id: &VtxoId,
+        anchor_value: u64,
+    ) -> Result<(), VPackError> {
+        self.engine_for(variant)?
+            .verify_ref(tree, expected_id, anchor_value)
+    }
+
+    /// [`crate::consensus::verify_path_exclusivity_ref`] with the script-tree rules of the engine
+    /// registered for `variant`.
+    #[cfg(feature = "schnorr-verify")]
+    pub fn verify_path_exclusivity_ref(
+        &self,
+        tree: &VPackTreeRef<'_>,
+        variant: TxVariant,
+    ) -> Result<(), VPackError> {
+        crate::consensus::verify_path_exclusivity_with(self.engine_for(variant)?, tree)
+    }
+
+    /// [`crate::consensus::reconstruct_control_block`] with the Taproot leaf rules of the engine
+    /// registered for `variant`.
+    #[cfg(feature = "bitcoin")]
+    pub fn reconstruct_control_block(
+        &self,
+        tree: &VPackTree,
+        variant: TxVariant,
+    ) -> Result<Vec<u8>, VPackError> {
+        crate::consensus::control_block::reconstruct_control_block_with(
+            self.engine_for(variant)?,
+            tree,
+        )
+    }
+}
+
+impl Default for EngineRegistry {
+    fn default() -> Self {
+        Self::new()
+            .with_engine(TxVariant::V3Plain.as_u8(), SecondTechV3)
+            .with_engine(TxVariant::V3Anchored.as_u8(), ArkLabsV3)
+    }
+}
+
+impl fmt::Debug for EngineRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.engines.iter().map(|(byte, _)| byte))
+            .finish()
+    }
+}
@@ -0,0 +1,148 @@
+//! Script opcodes and instruction decoding.
+//!
+//! [`Instructions`] splits a script into data pushes and opcodes. The tapscript evaluator, the
+//! exit cost estimate (signature opcodes of the sweep leaf) and PSBT finalization (keys a leaf
+//! checks) all read scripts through it.
+
+use crate::error::ScriptErrorReason;
+
+/// Pay-to-anchor scriptPubKey: `OP_1 <0x4e73>`; spent with an empty witness.
+pub const P2A_SCRIPT: [u8; 4] = [0x51, 0x02, 0x4e, 0x73];
+
+pub(crate) const OP_0: u8 = 0x00;
+pub(crate) const OP_PUSHDATA1: u8 = 0x4c;
+pub(crate) const OP_PUSHDATA2: u8 = 0x4d;
+pub(crate) const OP_PUSHDATA4: u8 = 0x4e;
+pub(crate) const OP_1NEGATE: u8 = 0x4f;
+pub(crate) const OP_1: u8 = 0x51;
+pub(crate) const OP_16: u8 = 0x60;
+pub(crate) const OP_VERIFY: u8 = 0x69;
+pub(crate) const OP_DROP: u8 = 0x75;
+pub(crate) const OP_EQUAL: u8 = 0x87;
+pub(crate) const OP_EQUALVERIFY: u8 = 0x88;
+pub(crate) const OP_NUMEQUAL: u8 = 0x9c;
+pub(crate) const OP_NUMEQUALVERIFY: u8 = 0x9d;
+pub(crate) const OP_HASH160: u8 = 0xa9;
+pub(crate) const OP_CHECKSIG: u8 = 0xac;
+pub(crate) const OP_CHECKSIGVERIFY: u8 = 0xad;
+pub(crate) const OP_CLTV: u8 = 0xb1;
+pub(crate) const OP_CSV: u8 = 0xb2;
+pub(crate) const OP_CHECKSIGADD: u8 = 0xba;
+
+/// One script instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Instruction<'s> {
+    /// Data of a `0x01..=0x4e` push opcode.
+    Push(&'s [u8]),
+    /// Any other opcode, `OP_0` included.
+    Op(u8),
+}
+
+/// Instructions of a script with the byte offset of each opcode.
+///
+/// A push running past the end of the script yields [`ScriptErrorReason::MalformedPush`] at the
+/// offset of its opcode and ends the iteration.
+pub(crate) struct Instructions<'s> {
+    script: &'s [u8],
+    pc: usize,
+}
+
+impl<'s> Instructions<'s> {
+    pub(crate) fn new(script: &'s [u8]) -> Self {
+        Self { script, pc: 0 }
+    }
+
+    /// Push data of the `0x01..=0x4e` opcode whose byte was just consumed.
+    fn read_push(&mut self, opcode: u8) -> Option<&'s [u8]> {
+        let len_bytes = match opcode {
+            OP_PUSHDATA1 => 1,
+            OP_PUSHDATA2 => 2,
+            OP_PUSHDATA4 => 4,
+            _ => 0,
+        };
+        let len = if len_bytes == 0 {
+            opcode as usize
+        } else {
+            let field = self.script.get(self.pc..self.pc + len_bytes)?;
+            self.pc += len_bytes;
+            field
+                .iter()
+                .rev()
+                .fold(0usize, |acc, &b| (acc << 8) | b as usize)
+        };
+        let end = self.pc.checked_add(len)?;
+        let data = self.script.get(self.pc..end)?;
+        self.pc = end;
+        Some(data)
+    }
+}
+
+impl<'s> Iterator for Instructions<'s> {
+    type Item = (usize, Result<Instruction<'s>, ScriptErrorReason>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.pc;
+        let opcode = *self.script.get(offset)?;
+        self.pc += 1;
+        let instruction = match opcode {
+            0x01..=OP_PUSHDATA4 => match self.read_push(opcode) {
+                Some(data) => Ok(Instruction::Push(data)),
+                None => {
+                    self.pc = self.script.len();
+                    Err(ScriptErrorReason::MalformedPush)
+                }
+            },
+            _ => Ok(Instruction::Op(opcode)),
+        };
+        Some((offset, instruction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn pushes_and_opcodes_with_offsets() {
+        // OP_0 <0xac> OP_PUSHDATA2 <2 bytes> OP_CHECKSIG
+        let script = [
+            OP_0,
+            0x01,
+            0xac,
+            OP_PUSHDATA2,
+            0x02,
+            0x00,
+            0xaa,
+            0xbb,
+            OP_CHECKSIG,
+        ];
+        let instructions: Vec<_> = Instructions::new(&script).collect();
+        assert_eq!(
+            instructions,
+            [
+                (0, Ok(Instruction::Op(OP_0))),
+                (1, Ok(Instruction::Push(&[0xac][..]))),
+                (3, Ok(Instruction::Push(&[0xaa, 0xbb][..]))),
+                (8, Ok(Instruction::Op(OP_CHECKSIG))),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_push_ends_the_iteration() {
+        let script = [OP_CHECKSIG, OP_PUSHDATA1, 0x05, 0x00, OP_CHECKSIG];
+        let instructions: Vec<_> = Instructions::new(&script).collect();
+        assert_eq!(
+            instructions,
+            [
+                (0, Ok(Instruction::Op(OP_CHECKSIG))),
+                (1, Err(ScriptErrorReason::MalformedPush)),
+            ]
+        );
+        assert_eq!(
+            Instructions::new(&[OP_PUSHDATA4, 0x00]).next(),
+            Some((0, Err(ScriptErrorReason::MalformedPush)))
+        );
+    }
+}
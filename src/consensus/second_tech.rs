@@ -10,23 +10,19 @@ use alloc::vec::Vec;
 
 use crate::types::{hashes::sha256d, hashes::Hash, OutPoint, Txid};
 
+use crate::consensus::fold::ChainState;
+#[cfg(feature = "schnorr-verify")]
+use crate::consensus::memo::check_hop_signature;
+use crate::consensus::memo::{hop_txid, HopMemo};
 use crate::consensus::{
-    tx_preimage, tx_signed_hex, ConsensusEngine, TxInPreimage, TxOutPreimage, VerificationOutput,
-    VtxoId,
+    enforce_conservation, tx_preimage, tx_signed_hex, ConsensusEngine, ExitLeaf, TxInPreimage,
+    TxOutPreimage, VerificationOutput, VtxoId,
 };
 use crate::error::VPackError;
-use crate::payload::tree::{GenesisItem, SiblingNode, VPackTree};
-
-#[cfg(feature = "schnorr-verify")]
-use crate::consensus::taproot_sighash::{
-    extract_verify_key, taproot_sighash, verify_schnorr_bip340,
+use crate::payload::tree::{
+    GenesisItem, GenesisItemRef, SiblingNode, SiblingNodeRef, VPackTree, VPackTreeRef, VtxoLeafRef,
 };
 
-struct ReconstructedOutput {
-    value: u64,
-    script_pubkey: Vec<u8>,
-}
-
 /// Second Tech V3-Plain consensus engine (Variant 0x03).
 ///
 /// Reconstructs VTXO identity via the **Recursive Transaction Chain**: each path step is a
@@ -40,183 +36,213 @@ impl ConsensusEngine for SecondTechV3 {
         tree: &VPackTree,
         anchor_value: Option<u64>,
     ) -> Result<VerificationOutput, VPackError> {
-        // If path is empty, this is a leaf node
-        if tree.path.is_empty() {
-            if tree.leaf_siblings.is_empty() && !tree.fee_anchor_script.is_empty() {
-                return Err(VPackError::FeeAnchorMissing);
-            }
-            return self.compute_leaf_vtxo_id(tree, anchor_value);
+        self.compute_vtxo_id_ref(&VPackTreeRef::from(tree), anchor_value)
+    }
+
+    fn exclusivity_merkle_root(&self, tree: &VPackTreeRef<'_>) -> Result<[u8; 32], VPackError> {
+        if tree.asp_expiry_script.is_empty() {
+            return Err(VPackError::MissingExclusivityData);
+        }
+        bark_merkle_root_from_scripts(
+            tree.asp_expiry_script,
+            tree.leaf_siblings.iter().filter_map(|s| match *s {
+                SiblingNodeRef::Compact { script, .. } => Some(script),
+                SiblingNodeRef::Full { .. } => None,
+            }),
+        )
+    }
+
+    fn tap_leaf_hashes(&self, tree: &VPackTree) -> Result<(Vec<[u8; 32]>, usize), VPackError> {
+        bark_tap_leaf_hashes_for_merkle_path(tree)
+    }
+
+    fn exit_leaf(&self, tree: &VPackTree) -> Result<ExitLeaf, VPackError> {
+        let script = bark_delayed_sign_script(tree)?;
+        Ok(ExitLeaf {
+            leaf_hashes: vec![tap_leaf_hash(&script)],
+            script,
+            index: 0,
+        })
+    }
+
+    fn compute_vtxo_id_ref(
+        &self,
+        tree: &VPackTreeRef<'_>,
+        anchor_value: Option<u64>,
+    ) -> Result<VerificationOutput, VPackError> {
+        self.compute_vtxo_id_memoized(tree, anchor_value, &mut HopMemo::default())
+    }
+}
+
+impl SecondTechV3 {
+    /// [`ConsensusEngine::compute_vtxo_id_ref`], reusing txids and verified signatures from
+    /// `memo` (see [`crate::verify_batch`]). The path signatures are checked as one batch when
+    /// the walk ends.
+    pub(crate) fn compute_vtxo_id_memoized(
+        &self,
+        tree: &VPackTreeRef<'_>,
+        anchor_value: Option<u64>,
+        memo: &mut HopMemo,
+    ) -> Result<VerificationOutput, VPackError> {
+        let walk = Self::walk_path(tree, anchor_value, memo);
+        memo.finish_tree(walk)
+    }
+
+    fn walk_path(
+        tree: &VPackTreeRef<'_>,
+        anchor_value: Option<u64>,
+        memo: &mut HopMemo,
+    ) -> Result<VerificationOutput, VPackError> {
+        if tree.path.is_empty()
+            && tree.leaf_siblings.is_empty()
+            && !tree.fee_anchor_script.is_empty()
+        {
+            return Err(VPackError::FeeAnchorMissing);
         }
 
         // Top-down chaining: start with on-chain anchor
-        let mut current_prevout = tree.anchor;
-        let mut last_outpoint = None;
-        let mut prev_outputs: Option<Vec<ReconstructedOutput>> = None;
-        let mut input_amount: Option<u64> = anchor_value;
+        let mut chain = ChainState::new(tree.anchor, anchor_value);
+        let mut prev_outputs: Option<Vec<TxOutPreimage<'_>>> = None;
         let mut signed_txs = Vec::with_capacity(tree.path.len() + 1);
 
         // Iterate through path (top-down from root to leaf). Fee anchor is last sibling (adapter provides it).
+        // Hand-off vout is the next item's parent_index, or leaf.vout for the last step.
         for (i, genesis_item) in tree.path.iter().enumerate() {
-            let outputs = Self::reconstruct_link(genesis_item)?;
-
-            if let Some(expected) = input_amount {
-                let sum = outputs
-                    .iter()
-                    .try_fold(0u64, |acc, o| acc.checked_add(o.value));
-                match sum {
-                    None => {
-                        return Err(crate::consensus::value_mismatch_for_output_sum(
-                            expected, &outputs,
-                        ));
-                    }
-                    Some(s) if s != expected => {
-                        return Err(VPackError::ValueMismatch {
-                            expected,
-                            actual: s,
-                        });
-                    }
-                    Some(_) => {}
-                }
-                let vout = if i + 1 < tree.path.len() {
-                    tree.path[i + 1].parent_index
-                } else {
-                    tree.leaf.vout
-                };
-                input_amount = outputs.get(vout as usize).map(|o| o.value);
-            }
+            let child_vout = tree
+                .path
+                .get(i + 1)
+                .map_or(tree.leaf.vout, |next| next.parent_index);
+            let (outputs, signed_hex) = Self::apply_hop(
+                &mut chain,
+                genesis_item,
+                child_vout,
+                prev_outputs.as_deref(),
+                tree.leaf.script_pubkey,
+                Some(&mut *memo),
+            )?;
+            signed_txs.push(signed_hex);
+            prev_outputs = Some(outputs);
+        }
 
-            // Build input spending current_prevout; use sequence from data
-            let input = TxInPreimage {
-                prev_out_txid: current_prevout.txid.to_byte_array(),
-                prev_out_vout: current_prevout.vout,
-                sequence: genesis_item.sequence,
-            };
+        let (id, leaf_signed_hex) = Self::finish_chain(&chain, &tree.leaf, &tree.leaf_siblings)?;
+        signed_txs.extend(leaf_signed_hex);
+        Ok(VerificationOutput { id, signed_txs })
+    }
 
-            #[cfg(feature = "schnorr-verify")]
-            if let Some(sig) = genesis_item.signature {
-                if i > 0 {
-                    let verify_key = extract_verify_key(tree.leaf.script_pubkey.as_slice())
-                        .or_else(|| {
-                            if tree.leaf.script_pubkey.len() == 33 {
-                                tree.leaf.script_pubkey[1..33].try_into().ok()
-                            } else {
-                                None
-                            }
-                        });
-                    let verify_key = verify_key.ok_or(VPackError::InvalidSignature)?;
-                    let prev = prev_outputs.as_ref().ok_or(VPackError::EncodingError)?;
-                    let idx = current_prevout.vout as usize;
-                    if idx >= prev.len() {
-                        return Err(VPackError::InvalidVout(current_prevout.vout));
-                    }
-                    let parent_amount = prev[idx].value;
-                    let parent_script = prev[idx].script_pubkey.as_slice();
-                    let sighash =
-                        taproot_sighash(3, 0, &input, parent_amount, parent_script, &outputs, 0x00);
-                    verify_schnorr_bip340(&verify_key, &sighash, &sig)?;
-                }
+    /// Applies one chain link to `chain`: builds the transaction spending the current prevout,
+    /// enforces conservation of value and the link signature, and hands off to `child_vout`.
+    ///
+    /// Returns the link's outputs (the next hop's `prev_outputs`) and its signed transaction.
+    pub(crate) fn apply_hop<'a>(
+        chain: &mut ChainState,
+        genesis_item: &GenesisItemRef<'a>,
+        child_vout: u32,
+        prev_outputs: Option<&[TxOutPreimage<'_>]>,
+        leaf_script: &[u8],
+        mut memo: Option<&mut HopMemo>,
+    ) -> Result<(Vec<TxOutPreimage<'a>>, Vec<u8>), VPackError> {
+        let outputs = Self::reconstruct_link_ref(genesis_item)?;
+
+        if chain.input_amount.is_some() {
+            enforce_conservation(chain.input_amount, &outputs)?;
+            chain.input_amount = outputs.get(child_vout as usize).map(|o| o.value);
+        }
+
+        // Build input spending current_prevout; use sequence from data
+        let input = TxInPreimage {
+            prev_out_txid: chain.current_prevout.txid.to_byte_array(),
+            prev_out_vout: chain.current_prevout.vout,
+            sequence: genesis_item.sequence,
+        };
+
+        // Hash transaction → OutPoint
+        let txid_bytes = hop_txid(memo.as_deref_mut(), &input, &outputs);
+
+        #[cfg(feature = "schnorr-verify")]
+        if let Some(sig) = genesis_item.signature {
+            if chain.hops > 0 {
+                check_hop_signature(
+                    memo,
+                    &txid_bytes,
+                    &input,
+                    &outputs,
+                    prev_outputs,
+                    leaf_script,
+                    sig,
+                )?;
             }
+        }
+        #[cfg(not(feature = "schnorr-verify"))]
+        let _ = (prev_outputs, leaf_script, memo);
 
-            let sig = [genesis_item.signature];
-            let signed_hex = tx_signed_hex(3, core::slice::from_ref(&input), &outputs, &sig, 0);
-            signed_txs.push(signed_hex);
+        let sig = [genesis_item.signature.copied()];
+        let signed_hex = tx_signed_hex(3, core::slice::from_ref(&input), &outputs, &sig, 0);
 
-            // Hash transaction → OutPoint
-            let txid_bytes = Self::hash_transaction(3, &[input], &outputs, 0)?;
-            let txid = Txid::from_byte_array(txid_bytes);
+        chain.last_txid = Some(txid_bytes);
+        chain.hops += 1;
 
-            // Determine vout for hand-off: use next item's parent_index, or leaf.vout if last
-            let vout = if i + 1 < tree.path.len() {
-                tree.path[i + 1].parent_index
-            } else {
-                tree.leaf.vout
-            };
+        // Hand-off: Convert to OutPoint for next step
+        chain.current_prevout = OutPoint {
+            txid: Txid::from_byte_array(txid_bytes),
+            vout: child_vout,
+        };
 
-            // Store the last transaction's OutPoint
-            last_outpoint = Some(OutPoint { txid, vout });
-
-            prev_outputs = Some(
-                outputs
-                    .iter()
-                    .map(|o| ReconstructedOutput {
-                        value: o.value,
-                        script_pubkey: o.script_pubkey.to_vec(),
-                    })
-                    .collect(),
-            );
-
-            // Hand-off: Convert to OutPoint for next step
-            current_prevout = OutPoint { txid, vout };
-        }
+        Ok((outputs, signed_hex))
+    }
 
-        // Final step: Build leaf transaction spending current_prevout (if leaf is valid)
-        // If leaf has empty script_pubkey, return the ID from the last path transaction
-        if tree.leaf.script_pubkey.is_empty() {
-            Ok(VerificationOutput {
-                id: VtxoId::OutPoint(last_outpoint.expect("path should have at least one item")),
-                signed_txs,
-            })
-        } else {
-            let (id, leaf_signed_hex) =
-                self.compute_leaf_vtxo_id_with_prevout(tree, current_prevout, input_amount)?;
-            signed_txs.push(leaf_signed_hex);
-            Ok(VerificationOutput { id, signed_txs })
+    /// Final step after the path has been applied.
+    ///
+    /// Builds the leaf transaction spending `current_prevout` (the anchor for a path-less leaf).
+    /// If the leaf has an empty script_pubkey, the ID is the last path transaction's hand-off
+    /// OutPoint and no leaf transaction is produced.
+    pub(crate) fn finish_chain(
+        chain: &ChainState,
+        leaf: &VtxoLeafRef<'_>,
+        leaf_siblings: &[SiblingNodeRef<'_>],
+    ) -> Result<(VtxoId, Option<Vec<u8>>), VPackError> {
+        if chain.hops > 0 && leaf.script_pubkey.is_empty() {
+            return Ok((VtxoId::OutPoint(chain.current_prevout), None));
         }
+        let (id, signed_hex) = Self::compute_leaf_vtxo_id_with_prevout(
+            leaf,
+            leaf_siblings,
+            chain.current_prevout,
+            chain.input_amount,
+        )?;
+        Ok((id, Some(signed_hex)))
     }
-}
 
-impl SecondTechV3 {
-    /// Compute VTXO ID for a leaf node (no path, final link).
+    /// Compute VTXO ID for the leaf transaction spending `prevout`.
     ///
-    /// A leaf has two outputs:
+    /// A leaf typically has two outputs:
     /// - Output 0: The final leaf script - uses script_pubkey from VtxoLeaf
     /// - Output 1: The Fee Anchor (51024e73)
-    fn compute_leaf_vtxo_id(
-        &self,
-        tree: &VPackTree,
-        anchor_value: Option<u64>,
-    ) -> Result<VerificationOutput, VPackError> {
-        let (id, signed_hex) =
-            self.compute_leaf_vtxo_id_with_prevout(tree, tree.anchor, anchor_value)?;
-        Ok(VerificationOutput {
-            id,
-            signed_txs: vec![signed_hex],
-        })
-    }
-
-    /// Compute VTXO ID for a leaf node with a custom prevout.
-    /// Used for the final leaf transaction in recursive path traversal.
+    ///
     /// Returns (VtxoId, signed_tx_bytes). Leaf output is placed at index leaf.vout per V-PACK data.
     fn compute_leaf_vtxo_id_with_prevout(
-        &self,
-        tree: &VPackTree,
+        leaf: &VtxoLeafRef<'_>,
+        leaf_siblings: &[SiblingNodeRef<'_>],
         prevout: OutPoint,
         input_amount: Option<u64>,
     ) -> Result<(VtxoId, Vec<u8>), VPackError> {
-        let num_outputs = 1 + tree.leaf_siblings.len();
-        if tree.leaf.vout >= num_outputs as u32 {
-            return Err(VPackError::InvalidVout(tree.leaf.vout));
+        let num_outputs = 1 + leaf_siblings.len();
+        if leaf.vout >= num_outputs as u32 {
+            return Err(VPackError::InvalidVout(leaf.vout));
         }
         // Build outputs: leaf at index leaf.vout, siblings at other indices (matches reconstruct_link logic)
         let mut outputs = Vec::with_capacity(num_outputs);
-        let mut sibling_iter = tree.leaf_siblings.iter();
+        let mut sibling_iter = leaf_siblings.iter();
         for i in 0..num_outputs {
-            if i == tree.leaf.vout as usize {
+            if i == leaf.vout as usize {
                 outputs.push(TxOutPreimage {
-                    value: tree.leaf.amount,
-                    script_pubkey: tree.leaf.script_pubkey.as_slice(),
+                    value: leaf.amount,
+                    script_pubkey: leaf.script_pubkey,
                 });
             } else {
                 let sibling = sibling_iter.next().ok_or(VPackError::EncodingError)?;
-                let (value, script) = match sibling {
-                    SiblingNode::Compact { value, script, .. } => (*value, script.as_slice()),
-                    SiblingNode::Full(txout) => {
-                        (txout.value.to_sat(), txout.script_pubkey.as_bytes())
-                    }
-                };
                 outputs.push(TxOutPreimage {
-                    value,
-                    script_pubkey: script,
+                    value: sibling.value(),
+                    script_pubkey: sibling.script(),
                 });
             }
         }
@@ -224,31 +250,13 @@ impl SecondTechV3 {
             return Err(VPackError::EncodingError);
         }
 
-        if let Some(expected) = input_amount {
-            let sum = outputs
-                .iter()
-                .try_fold(0u64, |acc, o| acc.checked_add(o.value));
-            match sum {
-                None => {
-                    return Err(crate::consensus::value_mismatch_for_output_sum(
-                        expected, &outputs,
-                    ));
-                }
-                Some(s) if s != expected => {
-                    return Err(VPackError::ValueMismatch {
-                        expected,
-                        actual: s,
-                    });
-                }
-                Some(_) => {}
-            }
-        }
+        enforce_conservation(input_amount, &outputs)?;
 
         // Build input from prevout OutPoint; use sequence from data
         let input = TxInPreimage {
             prev_out_txid: prevout.txid.to_byte_array(),
             prev_out_vout: prevout.vout,
-            sequence: tree.leaf.sequence,
+            sequence: leaf.sequence,
         };
 
         // Signed hex: leaf has no signature in schema, use empty witness
@@ -259,7 +267,7 @@ impl SecondTechV3 {
         let txid = Txid::from_byte_array(txid_bytes);
         let outpoint = OutPoint {
             txid,
-            vout: tree.leaf.vout,
+            vout: leaf.vout,
         };
 
         Ok((VtxoId::OutPoint(outpoint), signed_hex))
@@ -274,6 +282,13 @@ impl SecondTechV3 {
     ///   - Else: Place the next sibling from the siblings array (order preserved).
     pub fn reconstruct_link<'a>(
         genesis_item: &'a GenesisItem,
+    ) -> Result<Vec<TxOutPreimage<'a>>, VPackError> {
+        Self::reconstruct_link_ref(&GenesisItemRef::from(genesis_item))
+    }
+
+    /// [`Self::reconstruct_link`] over a borrowed path step.
+    pub fn reconstruct_link_ref<'a>(
+        genesis_item: &GenesisItemRef<'a>,
     ) -> Result<Vec<TxOutPreimage<'a>>, VPackError> {
         let siblings_count = genesis_item.siblings.len();
         let parent_index = genesis_item.parent_index as usize;
@@ -290,22 +305,16 @@ impl SecondTechV3 {
             if i == parent_index {
                 outputs.push(TxOutPreimage {
                     value: genesis_item.child_amount,
-                    script_pubkey: genesis_item.child_script_pubkey.as_slice(),
+                    script_pubkey: genesis_item.child_script_pubkey,
                 });
             } else {
                 if sibling_idx >= siblings_count {
                     return Err(VPackError::EncodingError);
                 }
                 let sibling = &genesis_item.siblings[sibling_idx];
-                let (value, script) = match sibling {
-                    SiblingNode::Compact { value, script, .. } => (*value, script.as_slice()),
-                    SiblingNode::Full(txout) => {
-                        (txout.value.to_sat(), txout.script_pubkey.as_bytes())
-                    }
-                };
                 outputs.push(TxOutPreimage {
-                    value,
-                    script_pubkey: script,
+                    value: sibling.value(),
+                    script_pubkey: sibling.script(),
                 });
                 sibling_idx += 1;
             }
@@ -587,13 +596,20 @@ pub fn compile_bark_delayed_sign_script(exit_delta: u16, user_xonly: &[u8; 32])
 ///
 /// Returns `Err(InvalidBarkScript)` if `tree.leaf.script_pubkey.len() < 33`.
 pub fn compute_bark_vtxo_tapscript_root(tree: &VPackTree) -> Result<[u8; 32], VPackError> {
+    Ok(tap_leaf_hash(&bark_delayed_sign_script(tree)?))
+}
+
+/// The single leaf of [`compute_bark_vtxo_tapscript_root`].
+fn bark_delayed_sign_script(tree: &VPackTree) -> Result<Vec<u8>, VPackError> {
     if tree.leaf.script_pubkey.len() < 33 {
         return Err(VPackError::InvalidBarkScript);
     }
     let mut user_xonly = [0u8; 32];
     user_xonly.copy_from_slice(&tree.leaf.script_pubkey[1..33]);
-    let exit_script = compile_bark_delayed_sign_script(tree.leaf.exit_delta, &user_xonly);
-    Ok(tap_leaf_hash(&exit_script))
+    Ok(compile_bark_delayed_sign_script(
+        tree.leaf.exit_delta,
+        &user_xonly,
+    ))
 }
 
 /// Computes the Taproot Merkle root from raw parts (expiry script + sibling list).
@@ -634,22 +650,36 @@ pub fn compute_bark_merkle_root_from_parts(
 /// recompiling each match. All collected leaf hashes are passed to
 /// `compute_balanced_merkle_root`.
 pub fn compute_bark_merkle_root(tree: &VPackTree) -> Result<[u8; 32], VPackError> {
-    if tree.asp_expiry_script.is_empty() {
+    bark_merkle_root_from_scripts(
+        &tree.asp_expiry_script,
+        tree.leaf_siblings
+            .iter()
+            .filter_map(|sibling| match sibling {
+                SiblingNode::Compact { script, .. } => Some(script.as_slice()),
+                SiblingNode::Full(_) => None,
+            }),
+    )
+}
+
+/// [`compute_bark_merkle_root`] from the expiry script and the scripts of the compact leaf siblings.
+pub(crate) fn bark_merkle_root_from_scripts<'s>(
+    asp_expiry_script: &[u8],
+    compact_sibling_scripts: impl Iterator<Item = &'s [u8]>,
+) -> Result<[u8; 32], VPackError> {
+    if asp_expiry_script.is_empty() {
         return Err(VPackError::InvalidBarkScript);
     }
 
     let mut leaf_hashes: Vec<[u8; 32]> = Vec::new();
 
-    let (cltv_value, server_key) = parse_bark_expiry_script(&tree.asp_expiry_script)?;
+    let (cltv_value, server_key) = parse_bark_expiry_script(asp_expiry_script)?;
     let expiry_script = compile_bark_expiry_script(cltv_value, &server_key);
     leaf_hashes.push(tap_leaf_hash(&expiry_script));
 
-    for sibling in &tree.leaf_siblings {
-        if let SiblingNode::Compact { script, .. } = sibling {
-            if let Ok((hash160, musig_key)) = parse_bark_unlock_script(script) {
-                let unlock = compile_bark_unlock_script(&hash160, &musig_key);
-                leaf_hashes.push(tap_leaf_hash(&unlock));
-            }
+    for script in compact_sibling_scripts {
+        if let Ok((hash160, musig_key)) = parse_bark_unlock_script(script) {
+            let unlock = compile_bark_unlock_script(&hash160, &musig_key);
+            leaf_hashes.push(tap_leaf_hash(&unlock));
         }
     }
 
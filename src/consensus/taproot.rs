@@ -127,31 +127,8 @@ pub fn compute_taproot_tweak(internal_key: [u8; 32], merkle_root: [u8; 32]) -> O
 
 #[cfg(all(feature = "schnorr-verify", not(feature = "bitcoin")))]
 pub fn compute_taproot_tweak(internal_key: [u8; 32], merkle_root: [u8; 32]) -> Option<[u8; 32]> {
-    use k256::elliptic_curve::ff::PrimeField;
-    use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
-    use k256::{AffinePoint, EncodedPoint, ProjectivePoint, Scalar};
-
-    let mut payload = [0u8; 64];
-    payload[..32].copy_from_slice(&internal_key);
-    payload[32..].copy_from_slice(&merkle_root);
-    let tweak_hash = tagged_hash(b"TapTweak", &payload);
-
-    let mut compressed = [0u8; 33];
-    compressed[0] = 0x02;
-    compressed[1..].copy_from_slice(&internal_key);
-    let encoded = EncodedPoint::from_bytes(compressed).ok()?;
-    let p: AffinePoint = Option::from(AffinePoint::from_encoded_point(&encoded))?;
-
-    let tweak_scalar: Scalar = Scalar::from_repr_vartime(tweak_hash.into())?;
-
-    let q: ProjectivePoint = ProjectivePoint::from(p) + ProjectivePoint::GENERATOR * tweak_scalar;
-    let q_affine = q.to_affine();
-    let q_encoded = q_affine.to_encoded_point(false);
-
-    let x = q_encoded.x()?;
-    let mut result = [0u8; 32];
-    result.copy_from_slice(x.as_ref());
-    Some(result)
+    let (x, _) = compute_taproot_tweaked_key_x_and_parity(internal_key, merkle_root)?;
+    Some(x)
 }
 
 // -----------------------------------------------------------------------------
@@ -316,3 +293,36 @@ pub fn compute_taproot_tweaked_key_x_and_parity(
     let x = tweaked.to_x_only_public_key().serialize();
     Some((x, parity.to_u8()))
 }
+
+/// BIP-341 tweaked output key and parity bit, computed with **k256** for builds without `bitcoin`.
+#[cfg(all(feature = "schnorr-verify", not(feature = "bitcoin")))]
+pub fn compute_taproot_tweaked_key_x_and_parity(
+    internal_key: [u8; 32],
+    merkle_root: [u8; 32],
+) -> Option<([u8; 32], u8)> {
+    use k256::elliptic_curve::ff::PrimeField;
+    use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+    use k256::{AffinePoint, EncodedPoint, ProjectivePoint, Scalar};
+
+    let mut payload = [0u8; 64];
+    payload[..32].copy_from_slice(&internal_key);
+    payload[32..].copy_from_slice(&merkle_root);
+    let tweak_hash = tagged_hash(b"TapTweak", &payload);
+
+    let mut compressed = [0u8; 33];
+    compressed[0] = 0x02;
+    compressed[1..].copy_from_slice(&internal_key);
+    let encoded = EncodedPoint::from_bytes(compressed).ok()?;
+    let p: AffinePoint = Option::from(AffinePoint::from_encoded_point(&encoded))?;
+
+    let tweak_scalar: Scalar = Scalar::from_repr_vartime(tweak_hash.into())?;
+
+    let q: ProjectivePoint = ProjectivePoint::from(p) + ProjectivePoint::GENERATOR * tweak_scalar;
+    let q_encoded = q.to_affine().to_encoded_point(true);
+
+    // Compressed tag: 0x02 even y, 0x03 odd y.
+    let parity = q_encoded.tag() as u8 & 1;
+    let mut x = [0u8; 32];
+    x.copy_from_slice(q_encoded.x()?.as_ref());
+    Some((x, parity))
+}
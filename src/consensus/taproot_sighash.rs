@@ -1,12 +1,17 @@
 //! BIP-341 Taproot Sighash (SigMsg + TapSighash tagged hash).
 //!
-//! no_std; supports hash types 0x00 (DEFAULT), 0x01 (ALL), 0x81 (ALL|ANYONECANPAY).
-//! Used for GenesisItem Schnorr verification and tree-wide sighash policy audit.
+//! no_std; supports every BIP-341 hash type: DEFAULT, ALL, NONE, SINGLE and the ANYONECANPAY
+//! variants of the last three. Used for GenesisItem Schnorr verification and tree-wide sighash
+//! policy audit.
+//!
+//! Key-path spends: [`taproot_sighash`] (single input), [`taproot_key_spend_sighash`]
+//! (any input of a multi-input transaction). Script-path spends (exit and forfeit leaves):
+//! [`taproot_script_spend_sighash`], which adds the BIP-342 extension (`ext_flag = 1`).
+//! [`taproot_signature_hash`] covers both and spends with an annex. All of them report hash types
+//! BIP-341 does not define as errors.
 //!
 //! **Known limitations:**
 //! - Epoch 0 only (the `0x00` byte prefixing the tagged hash payload).
-//! - No annex support (`spend_type` is hardcoded to `0x00`).
-//! - Single-input virtual transactions only.
 
 use alloc::vec::Vec;
 
@@ -15,14 +20,31 @@ use k256::schnorr::signature::hazmat::PrehashVerifier;
 use k256::schnorr::{Signature, VerifyingKey};
 
 use crate::compact_size::write_compact_size;
+use crate::consensus::schnorr_batch::SchnorrBatch;
 use crate::consensus::taproot::tagged_hash;
 use crate::consensus::{TxInPreimage, TxOutPreimage};
 use crate::error::VPackError;
+use crate::policy::SighashPolicy;
 use crate::types::hashes::sha256::Hash as Sha256Hash;
 use crate::types::hashes::Hash;
 
 const TAP_SIGHASH_TAG: &[u8] = b"TapSighash";
 
+/// BIP-342 `key_version` of BIP-340 public keys in tapscript.
+const KEY_VERSION_0: u8 = 0x00;
+
+/// `codesep_pos` of a script-path spend that executed no `OP_CODESEPARATOR`.
+pub const NO_CODESEPARATOR: u32 = u32::MAX;
+
+const SIGHASH_NONE: u8 = 0x02;
+const SIGHASH_SINGLE: u8 = 0x03;
+const SIGHASH_ANYONECANPAY: u8 = 0x80;
+/// Bits of the hash type selecting the outputs signed (ALL, NONE or SINGLE; DEFAULT is ALL).
+const SIGHASH_OUTPUT_MASK: u8 = 0x03;
+
+/// First byte of every annex (BIP-341).
+const ANNEX_TAG: u8 = 0x50;
+
 /// P2TR script prefix: OP_1 (0x51) push 32 bytes (0x20).
 const P2TR_SCRIPT_PREFIX: &[u8] = &[0x51, 0x20];
 
@@ -107,9 +129,16 @@ fn serialize_output(value: u64, script_pubkey: &[u8]) -> Vec<u8> {
 /// Supports `hash_type` values:
 /// - `0x00` (SIGHASH_DEFAULT): identical commitment to ALL, but hash_type byte is 0x00.
 /// - `0x01` (SIGHASH_ALL): commits to all inputs and all outputs.
-/// - `0x81` (SIGHASH_ALL | ANYONECANPAY): commits to all outputs but only this input.
+/// - `0x02` (SIGHASH_NONE): commits to all inputs and no output.
+/// - `0x03` (SIGHASH_SINGLE): commits to all inputs and the output at the input's index.
+/// - `0x81`, `0x82`, `0x83` (ANYONECANPAY): as above, but of the inputs only this one.
+///
+/// Key-path spend without annex (`spend_type = 0x00`).
 ///
-/// Assumes BIP-341 **Epoch 0** and **no annex** (`spend_type = 0x00`).
+/// # Errors
+///
+/// As for [`taproot_signature_hash`]: `hash_type` is not one of the above, or is SINGLE and
+/// `outputs` is empty.
 pub fn taproot_sighash(
     version: u32,
     locktime: u32,
@@ -118,8 +147,137 @@ pub fn taproot_sighash(
     parent_script_pubkey: &[u8],
     outputs: &[TxOutPreimage<'_>],
     hash_type: u8,
-) -> [u8; 32] {
-    let anyonecanpay = hash_type & 0x80 != 0;
+) -> Result<[u8; 32], VPackError> {
+    let prevout = TxOutPreimage {
+        value: parent_amount,
+        script_pubkey: parent_script_pubkey,
+    };
+    taproot_key_spend_sighash(
+        version,
+        locktime,
+        core::slice::from_ref(input),
+        core::slice::from_ref(&prevout),
+        outputs,
+        0,
+        hash_type,
+    )
+}
+
+/// Compute the BIP-341 key-path sighash of input `input_index` of a multi-input transaction.
+///
+/// `prevouts[i]` is the output spent by `inputs[i]`. Hash types as for [`taproot_sighash`].
+///
+/// # Errors
+///
+/// As for [`taproot_signature_hash`].
+///
+/// # Panics
+///
+/// If `prevouts.len() != inputs.len()` or `input_index` is out of range.
+pub fn taproot_key_spend_sighash(
+    version: u32,
+    locktime: u32,
+    inputs: &[TxInPreimage],
+    prevouts: &[TxOutPreimage<'_>],
+    outputs: &[TxOutPreimage<'_>],
+    input_index: usize,
+    hash_type: u8,
+) -> Result<[u8; 32], VPackError> {
+    taproot_signature_hash(
+        version,
+        locktime,
+        inputs,
+        prevouts,
+        outputs,
+        input_index,
+        hash_type,
+        None,
+        None,
+    )
+}
+
+/// Compute the BIP-342 script-path sighash of input `input_index`, spending the tapscript leaf
+/// with hash `leaf_hash`.
+///
+/// `codesep_pos` is the opcode position of the last executed `OP_CODESEPARATOR`, or
+/// [`NO_CODESEPARATOR`]. Other arguments, hash types, errors and panics as for
+/// [`taproot_key_spend_sighash`].
+#[allow(clippy::too_many_arguments)]
+pub fn taproot_script_spend_sighash(
+    version: u32,
+    locktime: u32,
+    inputs: &[TxInPreimage],
+    prevouts: &[TxOutPreimage<'_>],
+    outputs: &[TxOutPreimage<'_>],
+    input_index: usize,
+    leaf_hash: &[u8; 32],
+    codesep_pos: u32,
+    hash_type: u8,
+) -> Result<[u8; 32], VPackError> {
+    taproot_signature_hash(
+        version,
+        locktime,
+        inputs,
+        prevouts,
+        outputs,
+        input_index,
+        hash_type,
+        None,
+        Some((leaf_hash, codesep_pos)),
+    )
+}
+
+/// Compute the BIP-341 sighash of input `input_index` for any hash type, spend path and annex.
+///
+/// - `annex`: the annex of the spend (last witness element, `0x50` prefix included), if any.
+/// - `leaf`: `(tapleaf_hash, codesep_pos)` of a script-path spend, adding the BIP-342 extension
+///   (`ext_flag = 1`); `None` for a key-path spend.
+///
+/// # Errors
+///
+/// - [`VPackError::InvalidSighashFlag`] if `hash_type` is not a BIP-341 hash type.
+/// - [`VPackError::SighashSingleWithoutOutput`] for SINGLE when `outputs` has no entry at
+///   `input_index`.
+/// - [`VPackError::InvalidAnnex`] if `annex` is empty or does not start with `0x50`.
+///
+/// # Panics
+///
+/// If `prevouts.len() != inputs.len()` or `input_index` is out of range.
+#[allow(clippy::too_many_arguments)]
+pub fn taproot_signature_hash(
+    version: u32,
+    locktime: u32,
+    inputs: &[TxInPreimage],
+    prevouts: &[TxOutPreimage<'_>],
+    outputs: &[TxOutPreimage<'_>],
+    input_index: usize,
+    hash_type: u8,
+    annex: Option<&[u8]>,
+    leaf: Option<(&[u8; 32], u32)>,
+) -> Result<[u8; 32], VPackError> {
+    assert_eq!(
+        prevouts.len(),
+        inputs.len(),
+        "prevouts.len() must equal inputs.len()"
+    );
+    let input = &inputs[input_index];
+    let prevout = &prevouts[input_index];
+    if crate::policy::sighash_type_name(hash_type).is_none() {
+        return Err(VPackError::InvalidSighashFlag(hash_type));
+    }
+    let anyonecanpay = hash_type & SIGHASH_ANYONECANPAY != 0;
+    let output_type = hash_type & SIGHASH_OUTPUT_MASK;
+    let single_output = if output_type == SIGHASH_SINGLE {
+        let output = outputs
+            .get(input_index)
+            .ok_or(VPackError::SighashSingleWithoutOutput(input_index as u32))?;
+        Some(output)
+    } else {
+        None
+    };
+    if annex.is_some_and(|a| a.first() != Some(&ANNEX_TAG)) {
+        return Err(VPackError::InvalidAnnex);
+    }
 
     let mut sig_msg = Vec::with_capacity(256);
 
@@ -138,38 +296,52 @@ pub fn taproot_sighash(
 
     if !anyonecanpay {
         // BIP-341 §SigMsg: sha_prevouts — SHA256 of all input outpoints
-        let prevouts = serialize_prevout(&input.prev_out_txid, input.prev_out_vout);
-        let sha_prevouts = Sha256Hash::hash(&prevouts);
+        let mut prevouts_ser = Vec::with_capacity(36 * inputs.len());
+        for inp in inputs {
+            prevouts_ser
+                .extend_from_slice(&serialize_prevout(&inp.prev_out_txid, inp.prev_out_vout));
+        }
+        let sha_prevouts = Sha256Hash::hash(&prevouts_ser);
         sig_msg.extend_from_slice(&sha_prevouts.to_byte_array());
 
         // BIP-341 §SigMsg: sha_amounts — SHA256 of all spent output amounts
-        let mut amounts = [0u8; 8];
-        LittleEndian::write_u64(&mut amounts, parent_amount);
+        let mut amounts = Vec::with_capacity(8 * prevouts.len());
+        for p in prevouts {
+            amounts.extend_from_slice(&p.value.to_le_bytes());
+        }
         let sha_amounts = Sha256Hash::hash(&amounts);
         sig_msg.extend_from_slice(&sha_amounts.to_byte_array());
 
         // BIP-341 §SigMsg: sha_scriptpubkeys — SHA256 of all spent scriptPubKeys
-        let script_ser = serialize_script_for_ctxout(parent_script_pubkey);
-        let sha_scriptpubkeys = Sha256Hash::hash(&script_ser);
+        let mut scripts_ser = Vec::new();
+        for p in prevouts {
+            scripts_ser.extend_from_slice(&serialize_script_for_ctxout(p.script_pubkey));
+        }
+        let sha_scriptpubkeys = Sha256Hash::hash(&scripts_ser);
         sig_msg.extend_from_slice(&sha_scriptpubkeys.to_byte_array());
 
         // BIP-341 §SigMsg: sha_sequences — SHA256 of all input nSequence values
-        let mut seqs = [0u8; 4];
-        LittleEndian::write_u32(&mut seqs, input.sequence);
+        let mut seqs = Vec::with_capacity(4 * inputs.len());
+        for inp in inputs {
+            seqs.extend_from_slice(&inp.sequence.to_le_bytes());
+        }
         let sha_sequences = Sha256Hash::hash(&seqs);
         sig_msg.extend_from_slice(&sha_sequences.to_byte_array());
     }
 
-    // BIP-341 §SigMsg: sha_outputs — SHA256 of all outputs (for DEFAULT/ALL/ALL|ACP)
-    let mut outputs_ser = Vec::new();
-    for o in outputs {
-        outputs_ser.extend_from_slice(&serialize_output(o.value, o.script_pubkey));
+    if output_type != SIGHASH_NONE && output_type != SIGHASH_SINGLE {
+        // BIP-341 §SigMsg: sha_outputs — SHA256 of all outputs (DEFAULT/ALL, with or without ACP)
+        let mut outputs_ser = Vec::new();
+        for o in outputs {
+            outputs_ser.extend_from_slice(&serialize_output(o.value, o.script_pubkey));
+        }
+        let sha_outputs = Sha256Hash::hash(&outputs_ser);
+        sig_msg.extend_from_slice(&sha_outputs.to_byte_array());
     }
-    let sha_outputs = Sha256Hash::hash(&outputs_ser);
-    sig_msg.extend_from_slice(&sha_outputs.to_byte_array());
 
-    // BIP-341 §SigMsg: spend_type (no annex, no extension → 0x00)
-    sig_msg.push(0x00u8);
+    // BIP-341 §SigMsg: spend_type = ext_flag * 2 + annex_present
+    let ext_flag = if leaf.is_some() { 1 } else { 0 };
+    sig_msg.push(ext_flag * 2 + u8::from(annex.is_some()));
 
     if anyonecanpay {
         // BIP-341 §SigMsg (ANYONECANPAY): outpoint (36 bytes)
@@ -180,20 +352,41 @@ pub fn taproot_sighash(
 
         // BIP-341 §SigMsg (ANYONECANPAY): amount (8 bytes LE)
         let mut amt_buf = [0u8; 8];
-        LittleEndian::write_u64(&mut amt_buf, parent_amount);
+        LittleEndian::write_u64(&mut amt_buf, prevout.value);
         sig_msg.extend_from_slice(&amt_buf);
 
         // BIP-341 §SigMsg (ANYONECANPAY): scriptPubKey (compact_size + script)
-        write_compact_size(&mut sig_msg, parent_script_pubkey.len() as u64);
-        sig_msg.extend_from_slice(parent_script_pubkey);
+        write_compact_size(&mut sig_msg, prevout.script_pubkey.len() as u64);
+        sig_msg.extend_from_slice(prevout.script_pubkey);
 
         // BIP-341 §SigMsg (ANYONECANPAY): nSequence (4 bytes LE)
         let mut seq_buf = [0u8; 4];
         LittleEndian::write_u32(&mut seq_buf, input.sequence);
         sig_msg.extend_from_slice(&seq_buf);
     } else {
-        // BIP-341 §SigMsg: input_index (4 bytes LE, always 0 for single-input)
-        sig_msg.extend_from_slice(&[0u8; 4]);
+        // BIP-341 §SigMsg: input_index (4 bytes LE)
+        sig_msg.extend_from_slice(&(input_index as u32).to_le_bytes());
+    }
+
+    if let Some(annex) = annex {
+        // BIP-341 §SigMsg: sha_annex — SHA256 of the annex as a compact-size-prefixed string
+        let mut annex_ser = Vec::with_capacity(annex.len() + 9);
+        write_compact_size(&mut annex_ser, annex.len() as u64);
+        annex_ser.extend_from_slice(annex);
+        sig_msg.extend_from_slice(&Sha256Hash::hash(&annex_ser).to_byte_array());
+    }
+
+    if let Some(output) = single_output {
+        // BIP-341 §SigMsg (SINGLE): sha_single_output — SHA256 of the output at input_index
+        let output_ser = serialize_output(output.value, output.script_pubkey);
+        sig_msg.extend_from_slice(&Sha256Hash::hash(&output_ser).to_byte_array());
+    }
+
+    if let Some((leaf_hash, codesep_pos)) = leaf {
+        // BIP-342 extension: tapleaf_hash (32), key_version (1), codesep_pos (4 bytes LE)
+        sig_msg.extend_from_slice(leaf_hash);
+        sig_msg.push(KEY_VERSION_0);
+        sig_msg.extend_from_slice(&codesep_pos.to_le_bytes());
     }
 
     // BIP-341: TapSighash = taggedHash("TapSighash", 0x00 || SigMsg)
@@ -201,22 +394,7 @@ pub fn taproot_sighash(
     let mut payload = Vec::with_capacity(1 + sig_msg.len());
     payload.push(0x00u8);
     payload.extend_from_slice(&sig_msg);
-    tagged_hash(TAP_SIGHASH_TAG, &payload)
-}
-
-// ---------------------------------------------------------------------------
-// Allowed SIGHASH flags for tree-wide policy audit
-// ---------------------------------------------------------------------------
-
-const SIGHASH_DEFAULT: u8 = 0x00;
-const SIGHASH_ALL: u8 = 0x01;
-const SIGHASH_ALL_ANYONECANPAY: u8 = 0x81;
-
-fn is_allowed_sighash_flag(flag: u8) -> bool {
-    matches!(
-        flag,
-        SIGHASH_DEFAULT | SIGHASH_ALL | SIGHASH_ALL_ANYONECANPAY
-    )
+    Ok(tagged_hash(TAP_SIGHASH_TAG, &payload))
 }
 
 // ---------------------------------------------------------------------------
@@ -224,8 +402,8 @@ fn is_allowed_sighash_flag(flag: u8) -> bool {
 // ---------------------------------------------------------------------------
 
 /// Walks every `GenesisItem` in the tree path, validates each sighash flag against
-/// a strict allow-list, and verifies BIP-341 Taproot signatures using sequentially
-/// reconstructed prevouts.
+/// [`SighashPolicy::STRICT`], and verifies BIP-341 Taproot signatures using sequentially
+/// reconstructed prevouts. The signatures are batch-verified ([`SchnorrBatch`]).
 ///
 /// The `anchor_value` and `anchor_script` describe the on-chain UTXO that the
 /// root transaction spends (the L1 anchor). The auditor uses them as the initial
@@ -242,6 +420,56 @@ pub fn audit_sighash_policy(
     variant: crate::header::TxVariant,
     anchor_value: u64,
     anchor_script: &[u8],
+) -> Result<(), VPackError> {
+    audit_sighash_policy_with(
+        tree,
+        variant,
+        anchor_value,
+        anchor_script,
+        &SighashPolicy::STRICT,
+    )
+}
+
+/// [`audit_sighash_policy`] with the sighash types `policy` allows. The first path step using
+/// another type fails with [`VPackError::InvalidSighashFlag`] carrying that type, which
+/// [`crate::sighash_type_name`] names; signatures of allowed types are verified whatever their
+/// type, so [`SighashPolicy::ANY`] checks a foreign tree's signatures without judging its types.
+///
+/// # Errors
+///
+/// As for [`audit_sighash_policy`], plus [`VPackError::SighashSingleWithoutOutput`] for a SINGLE
+/// signature on a transaction without outputs.
+pub fn audit_sighash_policy_with(
+    tree: &crate::payload::tree::VPackTree,
+    variant: crate::header::TxVariant,
+    anchor_value: u64,
+    anchor_script: &[u8],
+    policy: &SighashPolicy,
+) -> Result<(), VPackError> {
+    // Signatures are collected during the walk and checked as one batch; an invalid one is
+    // reported ahead of any later failure, as a check in place would have.
+    let mut signatures = SchnorrBatch::new();
+    let walk = audit_walk(
+        tree,
+        variant,
+        anchor_value,
+        anchor_script,
+        policy,
+        &mut signatures,
+    );
+    signatures
+        .verify()
+        .map_err(|_| VPackError::InvalidSignature)?;
+    walk
+}
+
+fn audit_walk(
+    tree: &crate::payload::tree::VPackTree,
+    variant: crate::header::TxVariant,
+    anchor_value: u64,
+    anchor_script: &[u8],
+    policy: &SighashPolicy,
+    signatures: &mut SchnorrBatch,
 ) -> Result<(), VPackError> {
     use crate::consensus::tx_factory::tx_preimage;
     use crate::payload::tree::SiblingNode;
@@ -251,16 +479,17 @@ pub fn audit_sighash_policy(
     let mut current_vout = tree.anchor.vout;
     let mut current_prevout_value = anchor_value;
     let mut current_prevout_script: Vec<u8> = anchor_script.to_vec();
+    let variant = variant.builtin()?;
 
     for (i, genesis_item) in tree.path.iter().enumerate() {
         // --- Policy filter: reject disallowed sighash types early ---
-        if !is_allowed_sighash_flag(genesis_item.sighash_flag) {
+        if !policy.allows(genesis_item.sighash_flag) {
             return Err(VPackError::InvalidSighashFlag(genesis_item.sighash_flag));
         }
 
         // --- Reconstruct outputs (variant-specific) ---
         let outputs: Vec<TxOutPreimage<'_>> = match variant {
-            crate::header::TxVariant::V3Anchored => {
+            crate::header::BuiltinVariant::V3Anchored => {
                 let mut outs = Vec::new();
                 if !genesis_item.child_script_pubkey.is_empty() {
                     outs.push(TxOutPreimage {
@@ -281,7 +510,7 @@ pub fn audit_sighash_policy(
                 }
                 outs
             }
-            crate::header::TxVariant::V3Plain => {
+            crate::header::BuiltinVariant::V3Plain => {
                 crate::consensus::second_tech::SecondTechV3::reconstruct_link(genesis_item)?
             }
         };
@@ -297,16 +526,22 @@ pub fn audit_sighash_policy(
         if let Some(ref sig) = genesis_item.signature {
             let verify_key =
                 extract_verify_key(&current_prevout_script).ok_or(VPackError::InvalidSignature)?;
-            let sighash = taproot_sighash(
+            let prevout = TxOutPreimage {
+                value: current_prevout_value,
+                script_pubkey: &current_prevout_script,
+            };
+            let sighash = taproot_signature_hash(
                 3,
                 0,
-                &input,
-                current_prevout_value,
-                &current_prevout_script,
+                core::slice::from_ref(&input),
+                core::slice::from_ref(&prevout),
                 &outputs,
+                0,
                 genesis_item.sighash_flag,
-            );
-            verify_schnorr_bip340(&verify_key, &sighash, sig)?;
+                None,
+                None,
+            )?;
+            signatures.push(verify_key, sighash, *sig);
         }
 
         // --- Compute txid for hand-off to next depth ---
@@ -316,8 +551,8 @@ pub fn audit_sighash_policy(
 
         // --- Determine next prevout vout ---
         let next_vout = match variant {
-            crate::header::TxVariant::V3Anchored => 0u32,
-            crate::header::TxVariant::V3Plain => {
+            crate::header::BuiltinVariant::V3Anchored => 0u32,
+            crate::header::BuiltinVariant::V3Plain => {
                 if i + 1 < tree.path.len() {
                     tree.path[i + 1].parent_index
                 } else {
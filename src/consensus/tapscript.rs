@@ -0,0 +1,346 @@
+//! Tapscript evaluation for the opcodes Ark templates use (BIP-342 semantics).
+//!
+//! Path exclusivity proves that the Taproot tree holds only the expected leaves. It does not
+//! prove that the user can satisfy their exit leaf. [`eval_tapscript`] runs a leaf script against
+//! a witness stack, the spend's sighash and the spending transaction's timelock fields, so the
+//! full exit spend can be dry-run while the ASP is still around.
+//!
+//! Supported: data pushes, `OP_0`, `OP_1NEGATE`, `OP_1`..`OP_16`, `OP_VERIFY`, `OP_DROP`,
+//! `OP_EQUAL(VERIFY)`, `OP_NUMEQUAL(VERIFY)` (to close `OP_CHECKSIGADD` thresholds),
+//! `OP_HASH160`, `OP_CHECKSIG(VERIFY)`, `OP_CHECKSIGADD`, `OP_CHECKLOCKTIMEVERIFY` and
+//! `OP_CHECKSEQUENCEVERIFY`. Every other opcode fails with
+//! [`ScriptErrorReason::UnsupportedOpcode`], `OP_SUCCESSx` included: consensus accepts those
+//! unconditionally, but a dry run should not vouch for such a leaf. For the same reason
+//! non-32-byte public keys (BIP-342 "unknown key types") fail instead of passing unchecked, and
+//! numbers must be minimally encoded, as relay policy requires.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::consensus::script::{
+    Instruction, Instructions, OP_0, OP_1, OP_16, OP_1NEGATE, OP_CHECKSIG, OP_CHECKSIGADD,
+    OP_CHECKSIGVERIFY, OP_CLTV, OP_CSV, OP_DROP, OP_EQUAL, OP_EQUALVERIFY, OP_HASH160, OP_NUMEQUAL,
+    OP_NUMEQUALVERIFY, OP_VERIFY,
+};
+use crate::consensus::taproot_sighash::verify_schnorr_bip340;
+use crate::consensus::timelocks::{
+    LOCKTIME_THRESHOLD, SEQUENCE_DISABLE_BIT, SEQUENCE_MAG_MASK, SEQUENCE_TYPE_BIT,
+};
+use crate::error::{ScriptErrorReason, VPackError};
+use crate::types::hashes::{hash160, Hash};
+
+/// Consensus limits on a stack element and on the stack depth.
+const MAX_ELEMENT_SIZE: usize = 520;
+const MAX_STACK_SIZE: usize = 1000;
+
+/// Operand width of arithmetic (`OP_CHECKSIGADD`, `OP_NUMEQUAL`) and of timelock opcodes.
+const MAX_NUM_SIZE: usize = 4;
+const MAX_LOCKTIME_NUM_SIZE: usize = 5;
+
+/// What the spend commits to: the sighash its signatures sign and the fields CLTV / CSV read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpendContext {
+    /// BIP-341 script-path sighash of the spend, for the hash type the witness signatures use.
+    pub sighash: [u8; 32],
+    /// `nVersion` of the spending transaction (CSV needs >= 2).
+    pub tx_version: u32,
+    /// `nLockTime` of the spending transaction.
+    pub locktime: u32,
+    /// `nSequence` of the input spending the leaf.
+    pub sequence: u32,
+}
+
+/// Runs `script` with `witness` as the initial stack (bottom first, i.e. witness order without
+/// the script and control block).
+///
+/// Succeeds when execution ends with exactly one true element (BIP-342 clean stack). Fails with
+/// [`VPackError::ScriptFailed`], carrying the byte offset of the failing opcode (`script.len()`
+/// for the final stack checks).
+pub fn eval_tapscript(
+    script: &[u8],
+    witness: &[&[u8]],
+    ctx: &SpendContext,
+) -> Result<(), VPackError> {
+    let fail = |offset: usize, reason| VPackError::ScriptFailed {
+        offset: offset as u32,
+        reason,
+    };
+    if witness.len() > MAX_STACK_SIZE {
+        return Err(fail(0, ScriptErrorReason::StackSize));
+    }
+    if witness.iter().any(|item| item.len() > MAX_ELEMENT_SIZE) {
+        return Err(fail(0, ScriptErrorReason::PushSize));
+    }
+    let mut stack = Stack(witness.iter().map(|item| item.to_vec()).collect());
+
+    for (offset, instruction) in Instructions::new(script) {
+        instruction
+            .and_then(|instruction| step(instruction, &mut stack, ctx))
+            .map_err(|reason| fail(offset, reason))?;
+        if stack.0.len() > MAX_STACK_SIZE {
+            return Err(fail(offset, ScriptErrorReason::StackSize));
+        }
+    }
+
+    match stack.0.as_slice() {
+        [top] if cast_to_bool(top) => Ok(()),
+        [_] => Err(fail(script.len(), ScriptErrorReason::EvalFalse)),
+        _ => Err(fail(script.len(), ScriptErrorReason::CleanStack)),
+    }
+}
+
+struct Stack(Vec<Vec<u8>>);
+
+impl Stack {
+    fn pop(&mut self) -> Result<Vec<u8>, ScriptErrorReason> {
+        self.0.pop().ok_or(ScriptErrorReason::StackUnderflow)
+    }
+
+    fn top(&self) -> Result<&[u8], ScriptErrorReason> {
+        self.0
+            .last()
+            .map(Vec::as_slice)
+            .ok_or(ScriptErrorReason::StackUnderflow)
+    }
+
+    fn push_bool(&mut self, value: bool) {
+        self.0.push(if value { vec![1] } else { Vec::new() });
+    }
+}
+
+/// Executes one instruction.
+fn step(
+    instruction: Instruction,
+    stack: &mut Stack,
+    ctx: &SpendContext,
+) -> Result<(), ScriptErrorReason> {
+    let opcode = match instruction {
+        Instruction::Push(data) if data.len() > MAX_ELEMENT_SIZE => {
+            return Err(ScriptErrorReason::PushSize)
+        }
+        Instruction::Push(data) => {
+            stack.0.push(data.to_vec());
+            return Ok(());
+        }
+        Instruction::Op(opcode) => opcode,
+    };
+    match opcode {
+        OP_0 => stack.0.push(Vec::new()),
+        OP_1NEGATE => stack.0.push(vec![0x81]),
+        OP_1..=OP_16 => stack.0.push(vec![opcode - OP_1 + 1]),
+        OP_VERIFY => {
+            if !cast_to_bool(&stack.pop()?) {
+                return Err(ScriptErrorReason::Verify);
+            }
+        }
+        OP_DROP => {
+            stack.pop()?;
+        }
+        OP_EQUAL | OP_EQUALVERIFY => {
+            let b = stack.pop()?;
+            let a = stack.pop()?;
+            if opcode == OP_EQUALVERIFY {
+                if a != b {
+                    return Err(ScriptErrorReason::Verify);
+                }
+            } else {
+                stack.push_bool(a == b);
+            }
+        }
+        OP_NUMEQUAL | OP_NUMEQUALVERIFY => {
+            let b = script_num(&stack.pop()?, MAX_NUM_SIZE)?;
+            let a = script_num(&stack.pop()?, MAX_NUM_SIZE)?;
+            if opcode == OP_NUMEQUALVERIFY {
+                if a != b {
+                    return Err(ScriptErrorReason::Verify);
+                }
+            } else {
+                stack.push_bool(a == b);
+            }
+        }
+        OP_HASH160 => {
+            let data = stack.pop()?;
+            stack
+                .0
+                .push(hash160::Hash::hash(&data).to_byte_array().to_vec());
+        }
+        OP_CHECKSIG | OP_CHECKSIGVERIFY => {
+            let pubkey = stack.pop()?;
+            let sig = stack.pop()?;
+            let valid = check_signature(&sig, &pubkey, ctx)?;
+            if opcode == OP_CHECKSIGVERIFY {
+                if !valid {
+                    return Err(ScriptErrorReason::Verify);
+                }
+            } else {
+                stack.push_bool(valid);
+            }
+        }
+        OP_CHECKSIGADD => {
+            let pubkey = stack.pop()?;
+            let n = script_num(&stack.pop()?, MAX_NUM_SIZE)?;
+            let sig = stack.pop()?;
+            let valid = check_signature(&sig, &pubkey, ctx)?;
+            stack.0.push(encode_num(n + i64::from(valid)));
+        }
+        OP_CLTV => check_locktime(script_num(stack.top()?, MAX_LOCKTIME_NUM_SIZE)?, ctx)?,
+        OP_CSV => check_sequence(script_num(stack.top()?, MAX_LOCKTIME_NUM_SIZE)?, ctx)?,
+        _ => return Err(ScriptErrorReason::UnsupportedOpcode(opcode)),
+    }
+    Ok(())
+}
+
+/// BIP-342 signature opcode semantics: an empty signature is a `false` result, any other
+/// signature must be valid.
+fn check_signature(
+    sig: &[u8],
+    pubkey: &[u8],
+    ctx: &SpendContext,
+) -> Result<bool, ScriptErrorReason> {
+    let pubkey: &[u8; 32] = pubkey
+        .try_into()
+        .map_err(|_| ScriptErrorReason::PublicKeyType)?;
+    let sig: &[u8; 64] = match sig.len() {
+        0 => return Ok(false),
+        64 => sig
+            .try_into()
+            .map_err(|_| ScriptErrorReason::SignatureEncoding)?,
+        // An explicit hash type byte must not restate SIGHASH_DEFAULT.
+        65 if sig[64] != 0x00 => sig[..64]
+            .try_into()
+            .map_err(|_| ScriptErrorReason::SignatureEncoding)?,
+        _ => return Err(ScriptErrorReason::SignatureEncoding),
+    };
+    verify_schnorr_bip340(pubkey, &ctx.sighash, sig).map_err(|_| ScriptErrorReason::Signature)?;
+    Ok(true)
+}
+
+/// BIP-65 `OP_CHECKLOCKTIMEVERIFY` against the spending transaction.
+fn check_locktime(required: i64, ctx: &SpendContext) -> Result<(), ScriptErrorReason> {
+    if required < 0 {
+        return Err(ScriptErrorReason::NegativeLocktime);
+    }
+    let threshold = i64::from(LOCKTIME_THRESHOLD);
+    let locktime = i64::from(ctx.locktime);
+    let same_unit = (required < threshold) == (locktime < threshold);
+    // A final input (nSequence 0xFFFFFFFF) disables nLockTime.
+    if !same_unit || required > locktime || ctx.sequence == u32::MAX {
+        return Err(ScriptErrorReason::UnsatisfiedLocktime);
+    }
+    Ok(())
+}
+
+/// BIP-112 `OP_CHECKSEQUENCEVERIFY` against the spending input.
+fn check_sequence(required: i64, ctx: &SpendContext) -> Result<(), ScriptErrorReason> {
+    if required < 0 {
+        return Err(ScriptErrorReason::NegativeLocktime);
+    }
+    // A 5-byte operand may exceed `u32`; bits above the BIP-68 fields are ignored.
+    if required & i64::from(SEQUENCE_DISABLE_BIT) != 0 {
+        return Ok(());
+    }
+    let mask = SEQUENCE_TYPE_BIT | SEQUENCE_MAG_MASK;
+    let (required, sequence) = ((required & i64::from(mask)) as u32, ctx.sequence & mask);
+    if ctx.tx_version < 2
+        || ctx.sequence & SEQUENCE_DISABLE_BIT != 0
+        || (required & SEQUENCE_TYPE_BIT) != (sequence & SEQUENCE_TYPE_BIT)
+        || required > sequence
+    {
+        return Err(ScriptErrorReason::UnsatisfiedLocktime);
+    }
+    Ok(())
+}
+
+/// Minimally encoded CScriptNum of at most `max_len` bytes.
+fn script_num(bytes: &[u8], max_len: usize) -> Result<i64, ScriptErrorReason> {
+    let Some(&last) = bytes.last() else {
+        return Ok(0);
+    };
+    if bytes.len() > max_len {
+        return Err(ScriptErrorReason::InvalidNumber);
+    }
+    // Minimal: the last byte carries more than the sign, unless it only makes room for it.
+    if last & 0x7f == 0 && (bytes.len() == 1 || bytes[bytes.len() - 2] & 0x80 == 0) {
+        return Err(ScriptErrorReason::InvalidNumber);
+    }
+    let magnitude = bytes
+        .iter()
+        .rev()
+        .fold(0i64, |acc, &b| (acc << 8) | i64::from(b))
+        & !(0x80i64 << (8 * (bytes.len() - 1)));
+    Ok(if last & 0x80 != 0 {
+        -magnitude
+    } else {
+        magnitude
+    })
+}
+
+fn encode_num(n: i64) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut magnitude = n.unsigned_abs();
+    while magnitude > 0 {
+        out.push(magnitude as u8);
+        magnitude >>= 8;
+    }
+    match out.last_mut() {
+        Some(last) if *last & 0x80 != 0 => out.push(if n < 0 { 0x80 } else { 0x00 }),
+        Some(last) if n < 0 => *last |= 0x80,
+        _ => {}
+    }
+    out
+}
+
+/// Script truthiness: any non-zero byte, except a lone sign bit in the last byte (negative zero).
+fn cast_to_bool(data: &[u8]) -> bool {
+    data.iter().enumerate().any(|(i, &b)| {
+        if i == data.len() - 1 {
+            b & 0x7f != 0
+        } else {
+            b != 0
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_num_round_trips_and_rejects_non_minimal() {
+        for n in [
+            0i64,
+            1,
+            -1,
+            127,
+            128,
+            -128,
+            255,
+            32_767,
+            -32_768,
+            0x7fff_ffff,
+        ] {
+            assert_eq!(script_num(&encode_num(n), 5), Ok(n), "{n}");
+        }
+        assert_eq!(
+            script_num(&[0x00], 4),
+            Err(ScriptErrorReason::InvalidNumber)
+        );
+        assert_eq!(
+            script_num(&[0x01, 0x00], 4),
+            Err(ScriptErrorReason::InvalidNumber)
+        );
+        assert_eq!(script_num(&[0x80, 0x00], 4), Ok(128));
+        assert_eq!(
+            script_num(&[1, 2, 3, 4, 5], 4),
+            Err(ScriptErrorReason::InvalidNumber)
+        );
+    }
+
+    #[test]
+    fn negative_zero_is_false() {
+        assert!(!cast_to_bool(&[]));
+        assert!(!cast_to_bool(&[0x00, 0x80]));
+        assert!(cast_to_bool(&[0x00, 0x01]));
+        assert!(cast_to_bool(&[0x81]));
+    }
+}
@@ -18,16 +18,16 @@ const OP_CLTV: u8 = 0xb1;
 const OP_CSV: u8 = 0xb2;
 
 /// BIP-113 threshold: locktimes below this are block heights; at or above are Unix timestamps.
-const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+pub(crate) const LOCKTIME_THRESHOLD: u32 = 500_000_000;
 
 /// BIP-68 `SEQUENCE_LOCKTIME_DISABLE_FLAG` — when set, relative locktime is not enforced.
-const SEQUENCE_DISABLE_BIT: u32 = 1 << 31;
+pub(crate) const SEQUENCE_DISABLE_BIT: u32 = 1 << 31;
 
 /// BIP-68 `SEQUENCE_LOCKTIME_TYPE_FLAG` — `0` = blocks, `1` = 512-second units.
-const SEQUENCE_TYPE_BIT: u32 = 1 << 22;
+pub(crate) const SEQUENCE_TYPE_BIT: u32 = 1 << 22;
 
 /// BIP-68 consensus applies relative magnitude only in the lower 16 bits.
-const SEQUENCE_MAG_MASK: u32 = 0x0000_FFFF;
+pub(crate) const SEQUENCE_MAG_MASK: u32 = 0x0000_FFFF;
 
 /// Strictest CSV / CLTV operands seen while scanning a script (max per category).
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -73,7 +73,9 @@ fn absorb_cltv_operand(v: u32, out: &mut RequiredTimelocks) {
 }
 
 /// Scans the full script and records the **maximum** requirement per CSV/CLTV class.
-fn extract_timelock_requirements(script: &[u8]) -> Result<RequiredTimelocks, VPackError> {
+pub(crate) fn extract_timelock_requirements(
+    script: &[u8],
+) -> Result<RequiredTimelocks, VPackError> {
     let mut out = RequiredTimelocks::default();
     let mut i = 0usize;
     let mut pending: Option<u32> = None;
@@ -197,7 +199,10 @@ fn decode_push_at(script: &[u8], i: usize) -> Result<Option<(usize, Option<u32>)
     Ok(None)
 }
 
-fn validate_n_sequence_for_csv(reqs: &RequiredTimelocks, sequence: u32) -> Result<(), VPackError> {
+pub(crate) fn validate_n_sequence_for_csv(
+    reqs: &RequiredTimelocks,
+    sequence: u32,
+) -> Result<(), VPackError> {
     let needs_csv = reqs.max_csv_blocks.is_some() || reqs.max_csv_seconds.is_some();
     if !needs_csv {
         return Ok(());
@@ -257,7 +262,10 @@ fn validate_n_sequence_for_csv(reqs: &RequiredTimelocks, sequence: u32) -> Resul
     Ok(())
 }
 
-fn validate_expiry_for_cltv(reqs: &RequiredTimelocks, expiry: u32) -> Result<(), VPackError> {
+pub(crate) fn validate_expiry_for_cltv(
+    reqs: &RequiredTimelocks,
+    expiry: u32,
+) -> Result<(), VPackError> {
     if let Some(exp) = reqs.max_cltv_height {
         if expiry >= LOCKTIME_THRESHOLD {
             return Err(VPackError::TimelockViolation {
@@ -299,6 +307,43 @@ fn validate_expiry_for_cltv(reqs: &RequiredTimelocks, expiry: u32) -> Result<(),
     Ok(())
 }
 
+impl RequiredTimelocks {
+    /// `true` when the script has a CSV requirement.
+    pub(crate) fn needs_csv(&self) -> bool {
+        self.max_csv_blocks.is_some() || self.max_csv_seconds.is_some()
+    }
+
+    /// `true` when the script has a CLTV requirement.
+    pub(crate) fn needs_cltv(&self) -> bool {
+        self.max_cltv_height.is_some() || self.max_cltv_time.is_some()
+    }
+
+    /// Observed minus required relative lock for `sequence`, in the unit its type bit selects.
+    /// `None` when relative locktime is disabled or the script has no requirement of that type.
+    pub(crate) fn csv_margin(&self, sequence: u32) -> Option<i64> {
+        if (sequence & SEQUENCE_DISABLE_BIT) != 0 {
+            return None;
+        }
+        let required = if (sequence & SEQUENCE_TYPE_BIT) != 0 {
+            self.max_csv_seconds?
+        } else {
+            self.max_csv_blocks?
+        };
+        Some(i64::from(sequence & SEQUENCE_MAG_MASK) - i64::from(required))
+    }
+
+    /// Observed minus required absolute lock for `expiry` (height or time, per BIP-113). `None`
+    /// when the script has no requirement of that kind.
+    pub(crate) fn cltv_margin(&self, expiry: u32) -> Option<i64> {
+        let required = if expiry < LOCKTIME_THRESHOLD {
+            self.max_cltv_height?
+        } else {
+            self.max_cltv_time?
+        };
+        Some(i64::from(expiry) - i64::from(required))
+    }
+}
+
 /// Validates that path and leaf timelock fields satisfy the strictest CSV / CLTV thresholds in
 /// `tree.asp_expiry_script`.
 ///
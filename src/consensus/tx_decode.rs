@@ -0,0 +1,127 @@
+//! Consensus decoding of raw transactions, the inverse of
+//! [`tx_factory`](crate::consensus::tx_factory). Accepts serializations with or without witness
+//! data.
+
+use alloc::vec::Vec;
+
+use crate::compact_size::read_compact_size;
+use crate::error::VPackError;
+use crate::types::hashes::{sha256d, Hash};
+
+/// Consensus-decoded transaction.
+pub(crate) struct ParsedTx<'a> {
+    pub(crate) version: u32,
+    /// (prevout txid, prevout vout, sequence)
+    pub(crate) inputs: Vec<([u8; 32], u32, u32)>,
+    /// (value, scriptPubKey)
+    pub(crate) outputs: Vec<(u64, &'a [u8])>,
+    /// Witness stack of each input; empty without the segwit marker.
+    pub(crate) witnesses: Vec<Vec<&'a [u8]>>,
+    pub(crate) locktime: u32,
+    /// sha256d of the serialization without witness data.
+    pub(crate) txid: [u8; 32],
+    /// BIP-141 weight: stripped size × 3 + total size.
+    pub(crate) weight: u64,
+    /// Serialized with the segwit marker and witness stacks.
+    pub(crate) segwit: bool,
+}
+
+impl<'a> ParsedTx<'a> {
+    pub(crate) fn parse(raw: &'a [u8]) -> Result<Self, VPackError> {
+        let mut r = Cursor { data: raw, pos: 0 };
+        let version = r.u32()?;
+        let segwit = r.data.get(r.pos..r.pos + 2) == Some(&[0x00, 0x01]);
+        if segwit {
+            r.pos += 2;
+        }
+        let body_start = r.pos;
+
+        let input_count = r.compact_size()?;
+        let mut inputs = Vec::new();
+        for _ in 0..input_count {
+            let txid: [u8; 32] = r
+                .take(32)?
+                .try_into()
+                .map_err(|_| VPackError::EncodingError)?;
+            let vout = r.u32()?;
+            let script_len = r.compact_size()?;
+            r.take(script_len)?;
+            inputs.push((txid, vout, r.u32()?));
+        }
+        let output_count = r.compact_size()?;
+        let mut outputs = Vec::new();
+        for _ in 0..output_count {
+            let value = r.u64()?;
+            let script_len = r.compact_size()?;
+            outputs.push((value, r.take(script_len)?));
+        }
+        let body_end = r.pos;
+
+        let mut witnesses = Vec::new();
+        if segwit {
+            for _ in 0..inputs.len() {
+                let mut stack = Vec::new();
+                for _ in 0..r.compact_size()? {
+                    let item_len = r.compact_size()?;
+                    stack.push(r.take(item_len)?);
+                }
+                witnesses.push(stack);
+            }
+        }
+        let locktime = r.u32()?;
+        if r.pos != raw.len() {
+            return Err(VPackError::TrailingData(raw.len() - r.pos));
+        }
+
+        let mut stripped = Vec::with_capacity(body_end - body_start + 8);
+        stripped.extend_from_slice(&raw[..4]);
+        stripped.extend_from_slice(&raw[body_start..body_end]);
+        stripped.extend_from_slice(&locktime.to_le_bytes());
+        Ok(Self {
+            version,
+            inputs,
+            outputs,
+            witnesses,
+            locktime,
+            txid: sha256d::Hash::hash(&stripped).to_byte_array(),
+            weight: (stripped.len() * 3 + raw.len()) as u64,
+            segwit,
+        })
+    }
+}
+
+/// Little-endian reader over a byte slice; running out of bytes is [`VPackError::IncompleteData`].
+pub(crate) struct Cursor<'a> {
+    pub(crate) data: &'a [u8],
+    pub(crate) pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], VPackError> {
+        let end = self.pos.checked_add(n).ok_or(VPackError::IncompleteData)?;
+        let bytes = self
+            .data
+            .get(self.pos..end)
+            .ok_or(VPackError::IncompleteData)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, VPackError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, VPackError> {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    pub(crate) fn compact_size(&mut self) -> Result<usize, VPackError> {
+        let (n, len) =
+            read_compact_size(&self.data[self.pos..]).ok_or(VPackError::IncompleteData)?;
+        self.pos += len;
+        usize::try_from(n).map_err(|_| VPackError::IncompleteData)
+    }
+}
@@ -14,7 +14,7 @@ use crate::compact_size::write_compact_size;
 // -----------------------------------------------------------------------------
 
 /// One input for the transaction preimage. scriptSig is always empty for virtual txs.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TxInPreimage {
     /// Previous output txid in wire (internal) order.
     pub prev_out_txid: [u8; 32],
@@ -174,6 +174,44 @@ pub fn tx_signed_hex(
     out
 }
 
+/// Serialize a transaction with arbitrary witness stacks (`witnesses[i]` for input `i`), e.g. a
+/// tapscript spend `[signatures…, script, control_block]`. SegWit format always.
+///
+/// # Panics
+///
+/// If `witnesses.len() != inputs.len()`.
+pub fn tx_with_witnesses(
+    version: u32,
+    inputs: &[TxInPreimage],
+    outputs: &[TxOutPreimage<'_>],
+    witnesses: &[&[&[u8]]],
+    locktime: u32,
+) -> Vec<u8> {
+    assert_eq!(
+        witnesses.len(),
+        inputs.len(),
+        "witnesses.len() must equal inputs.len()"
+    );
+    let preimage = tx_preimage(version, inputs, outputs, locktime);
+    let body = &preimage[4..preimage.len() - 4];
+
+    let mut out = Vec::with_capacity(preimage.len() + 2);
+    out.extend_from_slice(&preimage[..4]);
+    // Marker + Flag
+    out.push(0x00);
+    out.push(0x01);
+    out.extend_from_slice(body);
+    for stack in witnesses {
+        write_compact_size(&mut out, stack.len() as u64);
+        for item in stack.iter() {
+            write_compact_size(&mut out, item.len() as u64);
+            out.extend_from_slice(item);
+        }
+    }
+    out.extend_from_slice(&preimage[preimage.len() - 4..]);
+    out
+}
+
 fn estimate_signed_capacity(
     inputs: &[TxInPreimage],
     outputs: &[TxOutPreimage<'_>],
@@ -0,0 +1,189 @@
+//! CPFP children for the fee anchors of an exit.
+//!
+//! Exit transactions pay no fee; each carries a zero-value pay-to-anchor output (`51024e73`) so a
+//! child can pay for it. [`build_cpfp_children`] builds that child for every transaction of
+//! [`VerificationOutput::signed_txs`]: a version-3 (TRUC) transaction spending the parent's anchor
+//! and one wallet P2TR output, returning change to the wallet. Each parent and its child form a
+//! 1-parent-1-child package paying `feerate` for both.
+//!
+//! The hops of an exit confirm one after another, so the children chain through their change:
+//! the first spends the funding UTXO, each later one the change of the previous child (output
+//! 0). Children are returned unsigned with the BIP-341 key-path sighash (SIGHASH_DEFAULT) of the
+//! wallet input; the anchor input needs no witness.
+//!
+//! [`VerificationOutput::signed_txs`]: crate::consensus::VerificationOutput::signed_txs
+
+use alloc::vec::Vec;
+
+use crate::consensus::taproot_sighash::taproot_key_spend_sighash;
+use crate::consensus::tx_decode::ParsedTx;
+use crate::consensus::{tx_preimage, TxInPreimage, TxOutPreimage, VerificationOutput};
+use crate::error::VPackError;
+use crate::exit_cost::{exit_tx_weight, vbytes, CPFP_CHILD_WEIGHT};
+use crate::psbt::Psbt;
+use crate::types::hashes::{sha256d, Hash};
+use crate::types::OutPoint;
+
+pub use crate::consensus::script::P2A_SCRIPT;
+
+/// TRUC (BIP-431) limit on the virtual size of a child of an unconfirmed TRUC parent.
+pub const TRUC_CHILD_MAX_VSIZE: u64 = 1_000;
+
+/// Dust threshold of a P2TR output at the default dust relay feerate.
+pub(crate) const P2TR_DUST: u64 = 330;
+
+/// nSequence of both child inputs: final for locktime purposes, no relative lock.
+const CHILD_SEQUENCE: u32 = 0xFFFF_FFFD;
+
+const _: () = assert!(vbytes(CPFP_CHILD_WEIGHT) <= TRUC_CHILD_MAX_VSIZE);
+
+/// Wallet-controlled P2TR output paying for the first child.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FundingUtxo<'a> {
+    pub outpoint: OutPoint,
+    pub value: u64,
+    /// P2TR scriptPubKey; the wallet signs for it by key path.
+    pub script_pubkey: &'a [u8],
+}
+
+/// Unsigned CPFP child of one exit transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpfpChild {
+    /// Txid of the exit transaction whose anchor the child spends (wire order).
+    pub parent_txid: [u8; 32],
+    /// Legacy serialization without witness: `[anchor, wallet]` inputs, one change output.
+    pub unsigned_tx: Vec<u8>,
+    /// Txid of the child (wire order); unchanged by signing.
+    pub txid: [u8; 32],
+    /// BIP-341 SIGHASH_DEFAULT sighash of input 1 (the wallet input).
+    pub sighash: [u8; 32],
+    /// Fee paid by the child for the whole package.
+    pub fee: u64,
+    /// Value of the change output.
+    pub change_value: u64,
+    /// Value of the spent fee anchor (input 0).
+    pub anchor_value: u64,
+    /// Value of the spent wallet output (input 1).
+    pub wallet_value: u64,
+    /// scriptPubKey of the spent wallet output: the funding UTXO's, then the change script.
+    pub wallet_script: Vec<u8>,
+}
+
+impl CpfpChild {
+    /// PSBT v2 of the child, with `witness_utxo` set on both inputs. The wallet signs input 1
+    /// (`tap_key_sig`); [`Psbt::extract_tx`] gives the anchor input its empty witness.
+    ///
+    /// # Errors
+    ///
+    /// As for [`Psbt::from_tx`], if `unsigned_tx` is not a transaction with two inputs.
+    pub fn to_psbt(&self) -> Result<Psbt, VPackError> {
+        let prevouts = [
+            TxOutPreimage {
+                value: self.anchor_value,
+                script_pubkey: &P2A_SCRIPT,
+            },
+            TxOutPreimage {
+                value: self.wallet_value,
+                script_pubkey: &self.wallet_script,
+            },
+        ];
+        Psbt::from_tx(&self.unsigned_tx, &prevouts)
+    }
+}
+
+/// Builds one CPFP child per exit transaction of `output`, anchor-spend first.
+///
+/// `feerate` is in sat/vB and applies to each package (parent and child together); change goes
+/// to `change_script`, which must be a P2TR output of the same wallet since later children spend
+/// it by key path.
+///
+/// # Errors
+///
+/// - [`VPackError::FeeAnchorMissing`] if an exit transaction has no P2A output.
+/// - [`VPackError::InsufficientFunds`] when the funds left cannot pay a package fee plus a
+///   non-dust change output.
+pub fn build_cpfp_children(
+    output: &VerificationOutput,
+    funding: &FundingUtxo<'_>,
+    change_script: &[u8],
+    feerate: u64,
+) -> Result<Vec<CpfpChild>, VPackError> {
+    let child_vsize = vbytes(CPFP_CHILD_WEIGHT);
+    let mut wallet_input = TxInPreimage {
+        prev_out_txid: funding.outpoint.txid.to_byte_array(),
+        prev_out_vout: funding.outpoint.vout,
+        sequence: CHILD_SEQUENCE,
+    };
+    let mut wallet_value = funding.value;
+    let mut wallet_script = funding.script_pubkey;
+
+    let mut children = Vec::with_capacity(output.signed_txs.len());
+    for raw in &output.signed_txs {
+        let parent = ParsedTx::parse(raw)?;
+        let (anchor_vout, anchor_value) = parent
+            .outputs
+            .iter()
+            .enumerate()
+            .find(|(_, (_, script))| *script == P2A_SCRIPT)
+            .map(|(vout, (value, _))| (vout as u32, *value))
+            .ok_or(VPackError::FeeAnchorMissing)?;
+
+        let fee = (vbytes(exit_tx_weight(raw)?) + child_vsize).saturating_mul(feerate);
+        let available = wallet_value.saturating_add(anchor_value);
+        let required = fee.saturating_add(P2TR_DUST);
+        if available < required {
+            return Err(VPackError::InsufficientFunds {
+                required,
+                available,
+            });
+        }
+        let change_value = available - fee;
+
+        let inputs = [
+            TxInPreimage {
+                prev_out_txid: parent.txid,
+                prev_out_vout: anchor_vout,
+                sequence: CHILD_SEQUENCE,
+            },
+            wallet_input,
+        ];
+        let prevouts = [
+            TxOutPreimage {
+                value: anchor_value,
+                script_pubkey: &P2A_SCRIPT,
+            },
+            TxOutPreimage {
+                value: wallet_value,
+                script_pubkey: wallet_script,
+            },
+        ];
+        let outputs = [TxOutPreimage {
+            value: change_value,
+            script_pubkey: change_script,
+        }];
+        let unsigned_tx = tx_preimage(3, &inputs, &outputs, 0);
+        let txid = sha256d::Hash::hash(&unsigned_tx).to_byte_array();
+        let sighash = taproot_key_spend_sighash(3, 0, &inputs, &prevouts, &outputs, 1, 0x00)?;
+
+        let spent_wallet_script = wallet_script.to_vec();
+        wallet_input = TxInPreimage {
+            prev_out_txid: txid,
+            prev_out_vout: 0,
+            sequence: CHILD_SEQUENCE,
+        };
+        wallet_value = change_value;
+        wallet_script = change_script;
+        children.push(CpfpChild {
+            parent_txid: parent.txid,
+            unsigned_tx,
+            txid,
+            sighash,
+            fee,
+            change_value,
+            anchor_value,
+            wallet_value: prevouts[1].value,
+            wallet_script: spent_wallet_script,
+        });
+    }
+    Ok(children)
+}
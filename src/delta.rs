@@ -0,0 +1,246 @@
+//! Delta encoding between successive versions of the same VTXO lineage.
+//!
+//! An out-of-round (arkoor) hop turns a VTXO's tree into the old path plus one more
+//! [`GenesisItem`] and a new leaf. [`diff`] keeps the longest common path prefix by reference and
+//! ships everything else; [`apply`] rebuilds the new tree from the old one.
+//!
+//! ```text
+//! Delta
+//!   magic:         "VPD"   (3)
+//!   version:       u8      (1)
+//!   flags:         u8      — FLAG_PROOF_COMPACT / FLAG_HAS_ASSET_ID of the tail payload
+//!   base_path_len: u32 LE  — path steps taken from the old tree
+//!   base_digest:   [u8;32] — SHA-256 of those steps in payload wire format
+//!   tail:          V-PACK payload of the new tree with the first base_path_len steps removed
+//!   checksum:      u32 LE  — CRC32 over everything above
+//! ```
+//!
+//! The tail reuses the V-PACK payload encoding, so the prefix, leaf, leaf siblings and trailer
+//! travel in full and only the shared path is elided. A delta made against a strict path prefix
+//! (the common case) applies to any tree that starts with the same steps; [`apply`] checks the
+//! digest and fails with [`VPackError::DeltaBaseMismatch`] otherwise. The rebuilt tree is not
+//! trusted: [`apply_vpack`] re-packs and re-verifies it against the new VTXO ID.
+
+use alloc::vec::Vec;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::consensus::VtxoId;
+use crate::error::VPackError;
+use crate::header::{
+    Header, Network, TxVariant, CURRENT_VERSION, FLAG_HAS_ASSET_ID, FLAG_PROOF_COMPACT,
+    HEADER_SIZE, MAX_PAYLOAD_SIZE, MAX_TREE_ARITY, MAX_TREE_DEPTH,
+};
+use crate::pack::{pack_with_extensions, serialize_payload_inner, write_genesis_item};
+use crate::payload::reader::BoundedReader;
+use crate::payload::tree::{GenesisItem, VPackTree};
+use crate::types::hashes::sha256::Hash as Sha256Hash;
+use crate::types::hashes::Hash;
+
+/// Delta magic bytes: "VPD".
+pub const DELTA_MAGIC: [u8; 3] = [0x56, 0x50, 0x44];
+/// Current delta format version.
+pub const DELTA_VERSION: u8 = 1;
+/// Fixed bytes around the tail payload (magic, version, flags, base fields, checksum).
+const DELTA_OVERHEAD: usize = 3 + 1 + 1 + 4 + 32 + 4;
+
+/// Patch from one version of a VTXO tree to the next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VPackDelta {
+    /// Number of leading path steps reused from the old tree.
+    pub base_path_len: u32,
+    /// SHA-256 over those steps, so a delta is never applied to an unrelated tree.
+    pub base_digest: [u8; 32],
+    /// The new tree without its first `base_path_len` path steps.
+    pub tail: VPackTree,
+}
+
+impl VPackDelta {
+    /// Serializes the delta. All siblings in the tail must share one form (Compact or Full),
+    /// as in a V-PACK payload; mixed forms fail with [`VPackError::EncodingError`].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, VPackError> {
+        let mut flags = if tail_is_compact(&self.tail)? {
+            FLAG_PROOF_COMPACT
+        } else {
+            0
+        };
+        if self.tail.asset_id.is_some() {
+            flags |= FLAG_HAS_ASSET_ID;
+        }
+        let tail = serialize_payload_inner(&self.tail, self.tail.asset_id.is_some())?;
+
+        let mut out = Vec::with_capacity(DELTA_OVERHEAD + tail.len());
+        out.extend_from_slice(&DELTA_MAGIC);
+        out.push(DELTA_VERSION);
+        out.push(flags);
+        out.extend_from_slice(&self.base_path_len.to_le_bytes());
+        out.extend_from_slice(&self.base_digest);
+        out.extend_from_slice(&tail);
+        let checksum = crc32fast::hash(&out);
+        out.extend_from_slice(&checksum.to_le_bytes());
+        Ok(out)
+    }
+
+    /// Parses [`Self::to_bytes`] output; the tail goes through the bounded V-PACK reader.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VPackError> {
+        if bytes.len() < DELTA_OVERHEAD {
+            return Err(VPackError::IncompleteData);
+        }
+        let (body, crc) = bytes.split_at(bytes.len() - 4);
+        if body[0..3] != DELTA_MAGIC {
+            return Err(VPackError::InvalidMagic);
+        }
+        if body[3] != DELTA_VERSION {
+            return Err(VPackError::UnsupportedVersion(body[3]));
+        }
+        let expected = LittleEndian::read_u32(crc);
+        let found = crc32fast::hash(body);
+        if found != expected {
+            return Err(VPackError::ChecksumMismatch { expected, found });
+        }
+
+        let flags = body[4];
+        if flags & !(FLAG_PROOF_COMPACT | FLAG_HAS_ASSET_ID) != 0 {
+            return Err(VPackError::EncodingError);
+        }
+        let base_path_len = LittleEndian::read_u32(&body[5..9]);
+        let mut base_digest = [0u8; 32];
+        base_digest.copy_from_slice(&body[9..41]);
+        let tail = &body[41..];
+        if tail.len() > MAX_PAYLOAD_SIZE as usize {
+            return Err(VPackError::PayloadTooLarge(tail.len() as u32));
+        }
+
+        // Limits are the consensus maxima; V3Plain skips the fee-anchor prefix check, which the
+        // re-verification of the rebuilt V-PACK performs for V3-Anchored trees.
+        let template = Header {
+            flags,
+            version: CURRENT_VERSION,
+            tx_variant: TxVariant::V3Plain,
+            tree_arity: MAX_TREE_ARITY,
+            tree_depth: MAX_TREE_DEPTH,
+            node_count: 0,
+            asset_type: 0,
+            payload_len: tail.len() as u32,
+            checksum: 0,
+        };
+        let tail = VPackTree::from(&BoundedReader::parse_ref(&template, tail)?);
+        Ok(Self {
+            base_path_len,
+            base_digest,
+            tail,
+        })
+    }
+}
+
+/// Computes the patch from `old` to `new`: the longest common path prefix is referenced, the
+/// rest of `new` is carried in the tail.
+pub fn diff(old: &VPackTree, new: &VPackTree) -> Result<VPackDelta, VPackError> {
+    let shared = old
+        .path
+        .iter()
+        .zip(&new.path)
+        .take_while(|(a, b)| a == b)
+        .count();
+    Ok(VPackDelta {
+        base_path_len: shared as u32,
+        base_digest: path_digest(&old.path[..shared])?,
+        tail: VPackTree {
+            path: new.path[shared..].to_vec(),
+            ..new.clone()
+        },
+    })
+}
+
+/// Rebuilds the new tree: the first `base_path_len` steps of `old` followed by the delta's tail.
+///
+/// Fails with [`VPackError::DeltaBaseMismatch`] when `old` does not start with the steps the
+/// delta was made against. The result is unverified; see [`apply_vpack`].
+pub fn apply(old: &VPackTree, delta: &VPackDelta) -> Result<VPackTree, VPackError> {
+    let shared = delta.base_path_len as usize;
+    let base = old
+        .path
+        .get(..shared)
+        .ok_or(VPackError::DeltaBaseMismatch)?;
+    if path_digest(base)? != delta.base_digest {
+        return Err(VPackError::DeltaBaseMismatch);
+    }
+    let depth = shared + delta.tail.path.len();
+    if depth > MAX_TREE_DEPTH as usize {
+        return Err(VPackError::ExceededMaxDepth(
+            depth.min(u16::MAX as usize) as u16
+        ));
+    }
+    let mut path = Vec::with_capacity(depth);
+    path.extend_from_slice(base);
+    path.extend_from_slice(&delta.tail.path);
+    Ok(VPackTree {
+        path,
+        ..delta.tail.clone()
+    })
+}
+
+/// Applies `delta` to a complete V-PACK and returns the new V-PACK after running [`crate::verify`]
+/// on it against `expected_id`.
+///
+/// The header is carried over from `old_vpack` (network, variant, version and extensions), with
+/// siblings normalized to Compact form, limits widened to fit the new path, and any ASP signature
+/// dropped since it does not cover the new bytes.
+pub fn apply_vpack(
+    old_vpack: &[u8],
+    delta: &VPackDelta,
+    expected_id: &VtxoId,
+    anchor_value: u64,
+    network: Network,
+) -> Result<Vec<u8>, VPackError> {
+    if old_vpack.len() < HEADER_SIZE {
+        return Err(VPackError::IncompleteData);
+    }
+    let header = Header::from_bytes(&old_vpack[..HEADER_SIZE])?;
+    let (payload, _) = header.split_body(&old_vpack[HEADER_SIZE..])?;
+    header.verify_checksum(payload)?;
+    let (old_tree, extensions) = BoundedReader::parse_with_extensions(&header, payload)?;
+
+    let (mut new_header, new_tree) = apply(&old_tree, delta)?.to_compact(&header);
+    let widest = new_tree
+        .path
+        .iter()
+        .map(|item| item.siblings.len())
+        .chain(core::iter::once(new_tree.leaf_siblings.len()))
+        .max()
+        .unwrap_or(0);
+    new_header.tree_depth = new_header
+        .tree_depth
+        .max(new_tree.path.len().min(MAX_TREE_DEPTH as usize) as u16);
+    new_header.tree_arity = new_header
+        .tree_arity
+        .max(widest.min(MAX_TREE_ARITY as usize) as u16);
+
+    let bytes = pack_with_extensions(&new_header, &new_tree, &extensions)?;
+    crate::verify(&bytes, expected_id, anchor_value, network)?;
+    Ok(bytes)
+}
+
+fn path_digest(path: &[GenesisItem]) -> Result<[u8; 32], VPackError> {
+    let mut buf = Vec::new();
+    for item in path {
+        write_genesis_item(item, &mut buf)?;
+    }
+    Ok(Sha256Hash::hash(&buf).to_byte_array())
+}
+
+/// `true` when every sibling is Compact (or there are none); mixed forms are an error.
+fn tail_is_compact(tree: &VPackTree) -> Result<bool, VPackError> {
+    let mut forms = tree
+        .leaf_siblings
+        .iter()
+        .chain(tree.path.iter().flat_map(|item| &item.siblings))
+        .map(|s| s.is_compact());
+    let Some(first) = forms.next() else {
+        return Ok(true);
+    };
+    if forms.any(|compact| compact != first) {
+        return Err(VPackError::EncodingError);
+    }
+    Ok(first)
+}
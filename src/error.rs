@@ -22,6 +22,58 @@ pub enum TimelockViolationReason {
     LocktimeDisabled,
 }
 
+/// Why [`VPackError::InvalidText`] was raised (bech32m `vpack1…` text encoding).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextErrorReason {
+    /// A part was longer than [`crate::text::MAX_PART_LEN`] characters.
+    TooLong,
+    /// Missing `1` separator, mixed case, or a character outside the bech32 alphabet.
+    InvalidChar,
+    /// The bech32m checksum of a part did not verify.
+    Checksum,
+    /// The human-readable part was not `vpack` / `tvpack`, or differed between parts.
+    UnknownHrp,
+    /// The human-readable part disagrees with the header's `FLAG_TESTNET`.
+    NetworkMismatch,
+    /// Part numbering was inconsistent, duplicated, or incomplete.
+    Parts,
+    /// Non-zero or over-long padding in the 5-bit to 8-bit conversion.
+    Padding,
+}
+
+/// Why [`VPackError::ScriptFailed`] was raised (tapscript evaluation, BIP-342).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScriptErrorReason {
+    /// An opcode outside the supported Ark subset.
+    UnsupportedOpcode(u8),
+    /// A push runs past the end of the script.
+    MalformedPush,
+    /// A stack element exceeds 520 bytes.
+    PushSize,
+    /// The stack exceeds 1000 elements.
+    StackSize,
+    /// An opcode needed more stack elements than there were.
+    StackUnderflow,
+    /// A numeric operand is too long or not minimally encoded.
+    InvalidNumber,
+    /// `OP_VERIFY` (or a `*VERIFY` opcode) found false.
+    Verify,
+    /// A public key is not a 32-byte x-only key.
+    PublicKeyType,
+    /// A signature is neither empty, 64 bytes, nor 65 bytes with a non-default hash type.
+    SignatureEncoding,
+    /// A non-empty signature did not verify.
+    Signature,
+    /// A CLTV / CSV operand is negative.
+    NegativeLocktime,
+    /// The spending transaction does not satisfy a CLTV / CSV operand.
+    UnsatisfiedLocktime,
+    /// Execution ended with other than exactly one stack element.
+    CleanStack,
+    /// Execution ended with a false element.
+    EvalFalse,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum VPackError {
     /// The data stream ended before the header or payload could be fully read.
@@ -30,7 +82,7 @@ pub enum VPackError {
     /// The Magic Bytes were not 'VPK'.
     InvalidMagic,
 
-    /// The Header Version is not supported by this library (currently only V1).
+    /// The Header Version is not supported by this library (currently V1 and V2).
     UnsupportedVersion(u8),
 
     /// Tree Arity must be >= 2. (0 or 1 creates invalid/degenerate trees).
@@ -42,6 +94,10 @@ pub enum VPackError {
     /// The Payload length exceeded the software limit (1MB).
     PayloadTooLarge(u32),
 
+    /// LZ4 payload (`FLAG_COMPRESSION_LZ4`) failed to decompress to its declared length, or was
+    /// passed to a zero-copy entry point that cannot decompress.
+    CompressionError,
+
     /// The Tree Depth exceeded the Header limit (32).
     ExceededMaxDepth(u16),
 
@@ -58,6 +114,15 @@ pub enum VPackError {
     /// Generic encoding/decoding error (Borsh failure).
     EncodingError,
 
+    /// Header flags carry an undefined network selector (flags byte).
+    InvalidNetwork(u8),
+
+    /// The V-PACK was issued for a different network than the caller expected.
+    NetworkMismatch {
+        expected: crate::header::Network,
+        found: crate::header::Network,
+    },
+
     /// Tx Variant byte was not 0x03 (V3-Plain) or 0x04 (V3-Anchored).
     InvalidTxVariant(u8),
 
@@ -95,13 +160,57 @@ pub enum VPackError {
     /// Payload had trailing bytes after full VPackTree parse (cursor desynchronization).
     TrailingData(usize),
 
+    /// Version-2 extension section carries a record of an unknown even (must-understand) type.
+    UnknownExtension(u64),
+
+    /// Version-2 extension record is out of order, duplicated, or has a malformed value (type).
+    InvalidExtension(u64),
+
+    /// Dedup store: a path transaction (txid, internal byte order) was inserted with outputs,
+    /// signature or sibling data that differ from the stored copy.
+    StoreConflict([u8; 32]),
+
+    /// A `VPackDelta` was applied to a tree that does not start with the path it was made against.
+    DeltaBaseMismatch,
+
+    /// Bech32m text encoding could not be decoded.
+    InvalidText(TextErrorReason),
+
+    /// Fountain frame belongs to a different transfer than the frames already received
+    /// (payload type, fragment count, length or message checksum differ).
+    FrameMismatch,
+
+    /// Streaming parse: a single record buffered more bytes than the reader's record cap
+    /// (buffered length) without completing.
+    RecordTooLarge(usize),
+
     /// A GenesisItem signature failed Taproot (BIP-340/341) verification.
     InvalidSignature,
 
-    /// The sighash flag on a GenesisItem is not in the allowed policy set
-    /// {0x00 (DEFAULT), 0x01 (ALL), 0x81 (ALL|ANYONECANPAY)}.
+    /// An ASP signature was required but the V-PACK does not carry `FLAG_ASP_SIGNED`.
+    MissingAspSignature,
+
+    /// The ASP signature over the header and payload did not verify against the expected key.
+    InvalidAspSignature,
+
+    /// The verification policy asks for a check this build cannot perform (e.g. signature checks
+    /// without the `schnorr-verify` feature), or for the SIGHASH audit without an anchor script.
+    UnsupportedPolicy,
+
+    /// The sighash flag on a GenesisItem is not a BIP-341 sighash type, or is one the
+    /// [`crate::SighashPolicy`] in force does not allow (by default anything but
+    /// {0x00 (DEFAULT), 0x01 (ALL), 0x81 (ALL|ANYONECANPAY)}).
     InvalidSighashFlag(u8),
 
+    /// A SIGHASH_SINGLE signature on this input index, which has no output at the same index.
+    SighashSingleWithoutOutput(u32),
+
+    /// An annex is empty or does not start with `0x50`.
+    InvalidAnnex,
+
+    /// A PSBT is malformed or cannot be finalized; names the offending field or rule.
+    InvalidPsbt(&'static str),
+
     /// Bark script template failed zero-trust validation (CLTV expiry or unlock clause).
     InvalidBarkScript,
 
@@ -147,6 +256,16 @@ pub enum VPackError {
 
     /// The VTXO exit chain exceeds the 100-hop HWW on-device limit.
     ExceedsHWWCapacity,
+
+    /// A tapscript did not succeed with the given witness; `offset` is the byte offset of the
+    /// failing opcode (the script length for the final stack checks).
+    ScriptFailed {
+        offset: u32,
+        reason: ScriptErrorReason,
+    },
+
+    /// A wallet input cannot pay the fee and leave a non-dust output.
+    InsufficientFunds { required: u64, available: u64 },
 }
 
 // Manual implementation of Display for no_std environments.
@@ -159,6 +278,7 @@ impl core::fmt::Display for VPackError {
             Self::InvalidArity(a) => write!(f, "Invalid Arity: {} (Must be >= 2)", a),
             Self::EmptyPayload => write!(f, "Payload is empty"),
             Self::PayloadTooLarge(s) => write!(f, "Payload too large: {} bytes", s),
+            Self::CompressionError => write!(f, "LZ4 payload decompression failed"),
             Self::ExceededMaxDepth(d) => write!(f, "Tree Depth {} exceeds limit", d),
             Self::ExceededMaxArity(a) => write!(f, "Tree Arity {} exceeds limit", a),
             Self::NodeCountMismatch(count, limit) => {
@@ -170,6 +290,14 @@ impl core::fmt::Display for VPackError {
                 expected, found
             ),
             Self::EncodingError => write!(f, "Binary encoding/decoding error"),
+            Self::InvalidNetwork(flags) => {
+                write!(f, "Invalid network selector in header flags 0x{:02x}", flags)
+            }
+            Self::NetworkMismatch { expected, found } => write!(
+                f,
+                "Network mismatch: expected {:?}, V-PACK is for {:?}",
+                expected, found
+            ),
             Self::InvalidTxVariant(v) => {
                 write!(f, "Invalid Tx Variant: 0x{:02x} (expected 0x03 or 0x04)", v)
             }
@@ -234,15 +362,73 @@ impl core::fmt::Display for VPackError {
                 "Invalid VTXO ID format (expected 64-char hex or Hash:Index)"
             ),
             Self::TrailingData(n) => write!(f, "Trailing data: {} bytes left after parse", n),
+            Self::UnknownExtension(t) => {
+                write!(f, "Unknown required extension type {} (even types must be understood)", t)
+            }
+            Self::InvalidExtension(t) => write!(f, "Invalid extension record of type {}", t),
+            Self::StoreConflict(txid) => {
+                write!(f, "Store conflict for path transaction ")?;
+                fmt_hash32_full(f, txid)
+            }
+            Self::DeltaBaseMismatch => write!(
+                f,
+                "Delta base mismatch: old tree does not start with the delta's shared path"
+            ),
+            Self::InvalidText(reason) => {
+                let why = match reason {
+                    TextErrorReason::TooLong => "part too long",
+                    TextErrorReason::InvalidChar => "invalid character or missing separator",
+                    TextErrorReason::Checksum => "bech32m checksum mismatch",
+                    TextErrorReason::UnknownHrp => "unknown or inconsistent prefix",
+                    TextErrorReason::NetworkMismatch => "prefix does not match header network",
+                    TextErrorReason::Parts => "missing, duplicate or inconsistent parts",
+                    TextErrorReason::Padding => "invalid padding",
+                };
+                write!(f, "Invalid V-PACK text encoding: {}", why)
+            }
+            Self::FrameMismatch => write!(
+                f,
+                "Fountain frame belongs to a different transfer than the frames already received"
+            ),
+            Self::RecordTooLarge(n) => {
+                write!(f, "Streaming record exceeds buffer cap: {} bytes buffered", n)
+            }
             Self::InvalidSignature => write!(
                 f,
                 "Invalid signature: GenesisItem Schnorr signature verification failed"
             ),
-            Self::InvalidSighashFlag(flag) => write!(
+            Self::MissingAspSignature => write!(f, "V-PACK is not ASP-signed"),
+            Self::InvalidAspSignature => write!(
+                f,
+                "Invalid ASP signature: BIP-340 signature over header and payload did not verify"
+            ),
+            Self::UnsupportedPolicy => write!(
                 f,
-                "Invalid sighash flag: 0x{:02x} (allowed: 0x00, 0x01, 0x81)",
-                flag
+                "Verification policy requires a check this build or policy cannot perform"
             ),
+            #[cfg(any(feature = "bitcoin", feature = "wasm"))]
+            Self::InvalidSighashFlag(flag) => match crate::policy::sighash_type_name(*flag) {
+                Some(name) => write!(
+                    f,
+                    "Invalid sighash flag: 0x{:02x} ({} not allowed by policy)",
+                    flag, name
+                ),
+                None => write!(
+                    f,
+                    "Invalid sighash flag: 0x{:02x} (not a BIP-341 sighash type)",
+                    flag
+                ),
+            },
+            // Only the SIGHASH audit reports the flag, and it needs `bitcoin` or `wasm`.
+            #[cfg(not(any(feature = "bitcoin", feature = "wasm")))]
+            Self::InvalidSighashFlag(flag) => write!(f, "Invalid sighash flag: 0x{:02x}", flag),
+            Self::SighashSingleWithoutOutput(index) => write!(
+                f,
+                "SIGHASH_SINGLE on input {} without an output at that index",
+                index
+            ),
+            Self::InvalidAnnex => write!(f, "Invalid annex: must start with 0x50"),
+            Self::InvalidPsbt(reason) => write!(f, "Invalid PSBT: {}", reason),
             Self::InvalidBarkScript => write!(
                 f,
                 "Invalid Bark script template (CLTV expiry or unlock clause)"
@@ -320,6 +506,41 @@ impl core::fmt::Display for VPackError {
                 f,
                 "Exceeds HWW capacity: VTXO exit chain has more than 100 hops"
             ),
+            Self::ScriptFailed { offset, reason } => {
+                write!(f, "Tapscript failed at byte {}: ", offset)?;
+                match reason {
+                    ScriptErrorReason::UnsupportedOpcode(op) => {
+                        write!(f, "unsupported opcode 0x{:02x}", op)
+                    }
+                    ScriptErrorReason::MalformedPush => write!(f, "push past end of script"),
+                    ScriptErrorReason::PushSize => write!(f, "stack element over 520 bytes"),
+                    ScriptErrorReason::StackSize => write!(f, "stack over 1000 elements"),
+                    ScriptErrorReason::StackUnderflow => write!(f, "stack underflow"),
+                    ScriptErrorReason::InvalidNumber => {
+                        write!(f, "number too long or not minimally encoded")
+                    }
+                    ScriptErrorReason::Verify => write!(f, "verify failed"),
+                    ScriptErrorReason::PublicKeyType => write!(f, "public key is not 32 bytes"),
+                    ScriptErrorReason::SignatureEncoding => write!(f, "invalid signature encoding"),
+                    ScriptErrorReason::Signature => write!(f, "signature verification failed"),
+                    ScriptErrorReason::NegativeLocktime => write!(f, "negative locktime operand"),
+                    ScriptErrorReason::UnsatisfiedLocktime => {
+                        write!(f, "spending transaction does not satisfy the timelock")
+                    }
+                    ScriptErrorReason::CleanStack => {
+                        write!(f, "stack does not end with exactly one element")
+                    }
+                    ScriptErrorReason::EvalFalse => write!(f, "script evaluated to false"),
+                }
+            }
+            Self::InsufficientFunds {
+                required,
+                available,
+            } => write!(
+                f,
+                "Insufficient funds: need {} sats, input has {}",
+                required, available
+            ),
         }
     }
 }
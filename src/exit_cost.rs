@@ -0,0 +1,230 @@
+//! Exact cost of a unilateral exit.
+//!
+//! Exiting a VTXO broadcasts every transaction of [`VerificationOutput::signed_txs`] and then
+//! sweeps the leaf output through one of its tapscript leaves once the timelock matures. Exit
+//! transactions pay no fee themselves (their outputs sum to what they spend), so each one goes out
+//! as a 1-parent-1-child TRUC package: a CPFP child spends its fee anchor together with one wallet
+//! P2TR input and returns change to one P2TR output.
+//!
+//! [`ExitCostReport`] holds the BIP-141 weight of every piece:
+//!
+//! - exit transactions are measured from their serialization; one still without witness data
+//!   (the anchor spend, signed by the round) is charged a 64-byte key-path signature per input;
+//! - the CPFP child has a fixed shape: P2A anchor input (empty witness), key-path wallet input,
+//!   P2TR change output;
+//! - the sweep spends the leaf by script path with one 64-byte signature per signature opcode of
+//!   the leaf script, the script itself and its control block (`33 + 32 × depth` bytes, depth
+//!   taken from the engine's tap leaves). Witness items other than signatures (hash preimages)
+//!   are not known from the tree and are not counted. The sweep pays its own fee.
+//!
+//! [`VerificationOutput::signed_txs`]: crate::consensus::VerificationOutput::signed_txs
+
+use alloc::vec::Vec;
+
+use crate::consensus::script::{
+    Instruction, Instructions, OP_CHECKSIG, OP_CHECKSIGADD, OP_CHECKSIGVERIFY,
+};
+use crate::consensus::taproot::{balanced_merkle_sibling_path, tap_leaf_hash};
+use crate::consensus::tx_decode::ParsedTx;
+use crate::consensus::{builtin_engine, ConsensusEngine};
+use crate::error::VPackError;
+use crate::header::TxVariant;
+use crate::payload::tree::VPackTree;
+
+/// version (4) + input count (1) + output count (1) + locktime (4).
+const TX_OVERHEAD: u64 = 10;
+/// Segwit marker and flag, witness weight.
+const SEGWIT_MARKER: u64 = 2;
+/// Outpoint (36) + empty scriptSig (1) + sequence (4).
+const INPUT_SIZE: u64 = 41;
+/// Value (8) + script length (1) + OP_1 <32 bytes> (34).
+const P2TR_OUTPUT_SIZE: u64 = 43;
+/// Item count (1) + length (1) + 64-byte BIP-340 signature with the default sighash.
+const KEY_PATH_WITNESS: u64 = 66;
+/// Item count (1) of the empty witness spending a P2A anchor.
+const ANCHOR_WITNESS: u64 = 1;
+/// Length (1) + 64-byte signature.
+const SIGNATURE_ITEM: u64 = 65;
+
+/// CPFP child: anchor input + wallet input, one change output.
+pub(crate) const CPFP_CHILD_WEIGHT: u64 = (TX_OVERHEAD + 2 * INPUT_SIZE + P2TR_OUTPUT_SIZE) * 4
+    + SEGWIT_MARKER
+    + ANCHOR_WITNESS
+    + KEY_PATH_WITNESS;
+
+/// Weights of a VTXO's unilateral exit package and the fees it implies.
+///
+/// Feerates are whole sat/vB; a transaction's vsize is its weight divided by four, rounded up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExitCostReport {
+    /// Weight of each exit transaction, anchor-spend first, leaf-spend last.
+    pub tx_weights: Vec<u64>,
+    /// Weight of the CPFP child broadcast with each exit transaction.
+    pub cpfp_child_weight: u64,
+    /// Weight of the script-path sweep of the leaf output.
+    pub sweep_weight: u64,
+    /// Size of the sweep's control block.
+    pub control_block_len: usize,
+    /// Value of the VTXO (`tree.leaf.amount`).
+    pub amount: u64,
+}
+
+impl ExitCostReport {
+    /// Virtual size of everything: exit transactions, one CPFP child each, and the sweep.
+    pub fn total_vbytes(&self) -> u64 {
+        self.package_vbytes() + vbytes(self.sweep_weight)
+    }
+
+    /// Fee the CPFP children must pay so every exit package reaches `feerate` (sat/vB).
+    pub fn cpfp_fee(&self, feerate: u64) -> u64 {
+        self.package_vbytes().saturating_mul(feerate)
+    }
+
+    /// Fee of the sweep at `feerate` (sat/vB), taken from the swept amount.
+    pub fn sweep_fee(&self, feerate: u64) -> u64 {
+        vbytes(self.sweep_weight).saturating_mul(feerate)
+    }
+
+    /// [`Self::cpfp_fee`] plus [`Self::sweep_fee`].
+    pub fn total_fee(&self, feerate: u64) -> u64 {
+        self.total_vbytes().saturating_mul(feerate)
+    }
+
+    /// Highest feerate (sat/vB) at which the whole exit still costs no more than [`Self::amount`];
+    /// above it, exiting this VTXO costs more than it is worth.
+    pub fn break_even_feerate(&self) -> u64 {
+        self.amount / self.total_vbytes()
+    }
+
+    fn package_vbytes(&self) -> u64 {
+        let children = vbytes(self.cpfp_child_weight) * self.tx_weights.len() as u64;
+        self.tx_weights.iter().map(|&w| vbytes(w)).sum::<u64>() + children
+    }
+}
+
+/// Builds the [`ExitCostReport`] of `tree`, sweeping the leaf through `sweep_script` (e.g.
+/// [`crate::consensus::ark_labs::compile_exit_script`] for Ark Labs).
+///
+/// `variant` selects the engine exactly as for [`crate::reconstruct_control_block`].
+/// `sweep_script` must be one of the engine's tap leaves, else
+/// [`VPackError::ControlBlockReconstructionFailed`].
+pub fn exit_cost_report(
+    tree: &VPackTree,
+    variant: TxVariant,
+    sweep_script: &[u8],
+) -> Result<ExitCostReport, VPackError> {
+    exit_cost_report_with(builtin_engine(variant)?, tree, sweep_script)
+}
+
+/// [`exit_cost_report`] with the reconstruction and Taproot leaf rules of `engine`.
+pub fn exit_cost_report_with(
+    engine: &dyn ConsensusEngine,
+    tree: &VPackTree,
+    sweep_script: &[u8],
+) -> Result<ExitCostReport, VPackError> {
+    let output = engine.compute_vtxo_id(tree, None)?;
+    let tx_weights = output
+        .signed_txs
+        .iter()
+        .map(|raw| exit_tx_weight(raw))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (hashes, _) = engine.tap_leaf_hashes(tree)?;
+    let leaf_hash = tap_leaf_hash(sweep_script);
+    let merkle_path = hashes
+        .iter()
+        .position(|h| *h == leaf_hash)
+        .and_then(|idx| balanced_merkle_sibling_path(&hashes, idx))
+        .ok_or(VPackError::ControlBlockReconstructionFailed)?;
+    let control_block_len = 33 + 32 * merkle_path.len();
+
+    Ok(ExitCostReport {
+        tx_weights,
+        cpfp_child_weight: CPFP_CHILD_WEIGHT,
+        sweep_weight: sweep_weight(sweep_script, control_block_len)?,
+        control_block_len,
+        amount: tree.leaf.amount,
+    })
+}
+
+/// Weight of a serialized exit transaction once signed: its own weight when it carries witness
+/// data, else plus one key-path signature per input.
+pub fn exit_tx_weight(raw: &[u8]) -> Result<u64, VPackError> {
+    let parsed = ParsedTx::parse(raw)?;
+    if parsed.segwit {
+        return Ok(parsed.weight);
+    }
+    let inputs = parsed.inputs.len() as u64;
+    Ok(parsed.weight + SEGWIT_MARKER + KEY_PATH_WITNESS * inputs)
+}
+
+/// One script-path input, one P2TR output.
+fn sweep_weight(script: &[u8], control_block_len: usize) -> Result<u64, VPackError> {
+    let signatures = signature_count(script)?;
+    let witness = SEGWIT_MARKER
+        + compact_size_len(signatures + 2)
+        + SIGNATURE_ITEM * signatures
+        + compact_size_len(script.len() as u64)
+        + script.len() as u64
+        + compact_size_len(control_block_len as u64)
+        + control_block_len as u64;
+    Ok((TX_OVERHEAD + INPUT_SIZE + P2TR_OUTPUT_SIZE) * 4 + witness)
+}
+
+/// Signature opcodes in `script`, skipping push data.
+pub(crate) fn signature_count(script: &[u8]) -> Result<u64, VPackError> {
+    let mut count = 0;
+    for (offset, instruction) in Instructions::new(script) {
+        let instruction = instruction.map_err(|reason| VPackError::ScriptFailed {
+            offset: offset as u32,
+            reason,
+        })?;
+        if let Instruction::Op(OP_CHECKSIG | OP_CHECKSIGVERIFY | OP_CHECKSIGADD) = instruction {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+fn compact_size_len(n: u64) -> u64 {
+    match n {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+/// Virtual size of `weight`: a quarter of it, rounded up.
+pub const fn vbytes(weight: u64) -> u64 {
+    weight.div_ceil(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ScriptErrorReason;
+    use alloc::vec;
+
+    #[test]
+    fn cpfp_child_is_153_vbytes() {
+        assert_eq!(CPFP_CHILD_WEIGHT, 609);
+        assert_eq!(vbytes(CPFP_CHILD_WEIGHT), 153);
+    }
+
+    #[test]
+    fn signature_count_skips_push_data() {
+        // <0xac> OP_DROP <32 bytes of 0xad> OP_CHECKSIGVERIFY OP_CHECKSIG
+        let mut script = vec![0x01, 0xac, 0x75, 0x20];
+        script.extend_from_slice(&[0xad; 32]);
+        script.extend_from_slice(&[0xad, 0xac]);
+        assert_eq!(signature_count(&script), Ok(2));
+        assert_eq!(
+            signature_count(&[0xac, 0x4c]),
+            Err(VPackError::ScriptFailed {
+                offset: 1,
+                reason: ScriptErrorReason::MalformedPush
+            })
+        );
+    }
+}
@@ -13,7 +13,7 @@ use byteorder::{ByteOrder, LittleEndian};
 use crate::consensus::hash_sibling_birth_tx;
 use crate::error::VPackError;
 use crate::header::{
-    Header, TxVariant, FLAG_PROOF_COMPACT, FLAG_TESTNET, MAX_PAYLOAD_SIZE, MAX_TREE_ARITY,
+    Header, Network, TxVariant, FLAG_PROOF_COMPACT, MAX_PAYLOAD_SIZE, MAX_TREE_ARITY,
     MAX_TREE_DEPTH,
 };
 use crate::pack;
@@ -187,23 +187,18 @@ pub struct SecondTechIngredients {
 fn header_from_tree(
     tx_variant: TxVariant,
     tree: &VPackTree,
-    is_testnet: bool,
+    network: Network,
 ) -> Result<Header, VPackError> {
     let tree_depth = tree.path.len() as u32;
-    let (node_count, tree_arity) =
-        tree.path
-            .iter()
-            .fold((0u32, 0u32), |(count, max_arity), item| {
-                let n = item.siblings.len() as u32;
-                (count + n, core::cmp::max(max_arity, n))
-            });
+    let tree_arity = tree
+        .path
+        .iter()
+        .map(|item| item.siblings.len() as u32)
+        .fold(0u32, core::cmp::max);
     let tree_arity = core::cmp::max(2, tree_arity);
     let tree_depth = core::cmp::min(tree_depth, MAX_TREE_DEPTH as u32) as u16;
     let tree_arity = core::cmp::min(tree_arity, MAX_TREE_ARITY as u32) as u16;
-    let node_count = core::cmp::min(
-        node_count,
-        (MAX_TREE_DEPTH as u32) * (MAX_TREE_ARITY as u32),
-    ) as u16;
+    let node_count = tree.node_count();
 
     let payload = pack::serialize_payload_for_header(tree)?;
     let payload_len = payload.len();
@@ -212,7 +207,7 @@ fn header_from_tree(
     }
     let payload_len = payload_len as u32;
 
-    let flags = FLAG_PROOF_COMPACT | if is_testnet { FLAG_TESTNET } else { 0 };
+    let flags = FLAG_PROOF_COMPACT | network.flags();
     let mut header_buf = [0u8; 20];
     header_buf[0..3].copy_from_slice(&crate::header::MAGIC_BYTES);
     header_buf[3] = flags;
@@ -442,17 +437,23 @@ fn tree_from_second_tech_ingredients(
 
 /// Builds a full V-PACK (Header + Prefix + Tree) from Ark Labs silo ingredients.
 /// Fee anchor script and nSequence are applied per forensic requirements.
-pub fn create_vpack_ark_labs(ingredients: ArkLabsIngredients) -> Result<Vec<u8>, VPackError> {
+pub fn create_vpack_ark_labs(
+    ingredients: ArkLabsIngredients,
+    network: Network,
+) -> Result<Vec<u8>, VPackError> {
     let tree = tree_from_ark_labs_ingredients(&ingredients)?;
-    let header = header_from_tree(TxVariant::V3Anchored, &tree, false)?;
+    let header = header_from_tree(TxVariant::V3Anchored, &tree, network)?;
     pack::pack(&header, &tree)
 }
 
 /// Builds a full V-PACK (Header + Prefix + Tree) from Second Tech silo ingredients.
 /// nSequence is enforced as 0; identity is OutPoint (Hash:Index).
-pub fn create_vpack_second_tech(ingredients: SecondTechIngredients) -> Result<Vec<u8>, VPackError> {
+pub fn create_vpack_second_tech(
+    ingredients: SecondTechIngredients,
+    network: Network,
+) -> Result<Vec<u8>, VPackError> {
     let tree = tree_from_second_tech_ingredients(&ingredients)?;
-    let header = header_from_tree(TxVariant::V3Plain, &tree, false)?;
+    let header = header_from_tree(TxVariant::V3Plain, &tree, network)?;
     pack::pack(&header, &tree)
 }
 
@@ -461,8 +462,8 @@ pub fn create_vpack_second_tech(ingredients: SecondTechIngredients) -> Result<Ve
 pub fn create_vpack_from_tree(
     tree: &VPackTree,
     tx_variant: TxVariant,
-    is_testnet: bool,
+    network: Network,
 ) -> Result<Vec<u8>, VPackError> {
-    let header = header_from_tree(tx_variant, tree, is_testnet)?;
+    let header = header_from_tree(tx_variant, tree, network)?;
     pack::pack(&header, tree)
 }
@@ -0,0 +1,507 @@
+//! Fountain-coded multi-part frames for animated QR transfer (BC-UR style).
+//!
+//! A message (V-PACK bytes, or [`VpackExitWaterfall::to_bytes`] output) is split into
+//! `seq_len` equal fragments. Frames `1..=seq_len` carry one fragment each; every later frame
+//! carries the XOR of a pseudo-random subset of fragments, chosen deterministically from the
+//! frame's sequence number and the message checksum. A receiver can therefore start scanning at
+//! any point, miss frames, or see repeats, and still finish once it has enough independent
+//! frames (typically a little over `seq_len`).
+//!
+//! ```text
+//! Frame
+//!   magic:        "VPF"   (3)
+//!   version:      u8      (1)
+//!   payload_type: u8      — 0 = V-PACK, 1 = exit waterfall
+//!   seq_num:      u32 LE  — 1-based, unbounded
+//!   seq_len:      u32 LE  — number of fragments
+//!   message_len:  u32 LE
+//!   message_crc:  u32 LE  — CRC32 of the whole message
+//!   fragment:     [u8; fragment_len]  (last fragment zero-padded)
+//!   frame_crc:    u32 LE  — CRC32 of all preceding frame bytes
+//! ```
+//!
+//! The encoding is in the style of BC-UR (Luby-transform fragments, degree distribution ∝ 1/d,
+//! xoshiro256** selection) but is not wire-compatible with `ur:` strings. [`FountainDecoder`] is
+//! `no_std` + `alloc` and holds at most `seq_len` fragments plus `seq_len` pending mixed frames.
+//!
+//! [`VpackExitWaterfall::to_bytes`]: crate::dehydration::VpackExitWaterfall::to_bytes
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::error::VPackError;
+use crate::header::{Header, ASP_SIGNATURE_SIZE, HEADER_SIZE, MAX_PAYLOAD_SIZE};
+
+/// Frame magic bytes: "VPF".
+pub const FOUNTAIN_MAGIC: [u8; 3] = [0x56, 0x50, 0x46];
+/// Current frame format version.
+pub const FOUNTAIN_VERSION: u8 = 1;
+/// Bytes of framing around each fragment (21-byte header + 4-byte frame CRC).
+pub const FRAME_OVERHEAD: usize = 21 + 4;
+/// Largest message accepted (a maximal, ASP-signed V-PACK).
+pub const MAX_MESSAGE_LEN: usize = HEADER_SIZE + MAX_PAYLOAD_SIZE as usize + ASP_SIGNATURE_SIZE;
+
+/// What the reassembled message contains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FountainPayload {
+    /// A complete V-PACK (header + payload).
+    VPack = 0,
+    /// Output of `VpackExitWaterfall::to_bytes`.
+    ExitWaterfall = 1,
+}
+
+impl FountainPayload {
+    fn from_u8(byte: u8) -> Result<Self, VPackError> {
+        match byte {
+            0 => Ok(Self::VPack),
+            1 => Ok(Self::ExitWaterfall),
+            _ => Err(VPackError::EncodingError),
+        }
+    }
+}
+
+/// Transfer parameters shared by every frame of one message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FrameParams {
+    payload_type: FountainPayload,
+    seq_len: u32,
+    message_len: u32,
+    message_crc: u32,
+    fragment_len: usize,
+}
+
+/// Produces an endless sequence of frames for one message.
+#[derive(Debug, Clone)]
+pub struct FountainEncoder {
+    params: FrameParams,
+    /// Message zero-padded to `seq_len * fragment_len`.
+    padded: Vec<u8>,
+    seq_num: u32,
+}
+
+impl FountainEncoder {
+    /// Splits `message` into fragments of at most `max_fragment_len` bytes (fragments are sized
+    /// evenly, so the last one is not much shorter than the rest).
+    pub fn new(
+        payload_type: FountainPayload,
+        message: &[u8],
+        max_fragment_len: usize,
+    ) -> Result<Self, VPackError> {
+        if message.is_empty() {
+            return Err(VPackError::EmptyPayload);
+        }
+        if message.len() > MAX_MESSAGE_LEN {
+            return Err(VPackError::PayloadTooLarge(message.len() as u32));
+        }
+        if max_fragment_len == 0 {
+            return Err(VPackError::EncodingError);
+        }
+        let seq_len = message.len().div_ceil(max_fragment_len);
+        let fragment_len = message.len().div_ceil(seq_len);
+        let mut padded = message.to_vec();
+        padded.resize(seq_len * fragment_len, 0);
+        Ok(Self {
+            params: FrameParams {
+                payload_type,
+                seq_len: seq_len as u32,
+                message_len: message.len() as u32,
+                message_crc: crc32fast::hash(message),
+                fragment_len,
+            },
+            padded,
+            seq_num: 0,
+        })
+    }
+
+    /// Encoder for a complete V-PACK; the header is validated first.
+    pub fn for_vpack(vpack_bytes: &[u8], max_fragment_len: usize) -> Result<Self, VPackError> {
+        if vpack_bytes.len() < HEADER_SIZE {
+            return Err(VPackError::IncompleteData);
+        }
+        Header::from_bytes(&vpack_bytes[..HEADER_SIZE])?;
+        Self::new(FountainPayload::VPack, vpack_bytes, max_fragment_len)
+    }
+
+    /// Number of fragments; at least this many frames are needed to decode.
+    pub fn seq_len(&self) -> u32 {
+        self.params.seq_len
+    }
+
+    /// Size of every frame produced by this encoder.
+    pub fn frame_len(&self) -> usize {
+        FRAME_OVERHEAD + self.params.fragment_len
+    }
+
+    /// Returns the next frame. The first `seq_len` frames are the plain fragments in order.
+    pub fn next_frame(&mut self) -> Vec<u8> {
+        self.seq_num = self.seq_num.checked_add(1).unwrap_or(1);
+        self.frame(self.seq_num)
+    }
+
+    /// Returns frame `seq_num` (1-based) without advancing the encoder.
+    pub fn frame(&self, seq_num: u32) -> Vec<u8> {
+        let p = &self.params;
+        let mut fragment = vec![0u8; p.fragment_len];
+        for index in choose_fragments(seq_num, p.seq_len, p.message_crc) {
+            let start = index as usize * p.fragment_len;
+            xor_into(&mut fragment, &self.padded[start..start + p.fragment_len]);
+        }
+
+        let mut out = Vec::with_capacity(self.frame_len());
+        out.extend_from_slice(&FOUNTAIN_MAGIC);
+        out.push(FOUNTAIN_VERSION);
+        out.push(p.payload_type as u8);
+        let mut buf = [0u8; 16];
+        LittleEndian::write_u32(&mut buf[0..4], seq_num);
+        LittleEndian::write_u32(&mut buf[4..8], p.seq_len);
+        LittleEndian::write_u32(&mut buf[8..12], p.message_len);
+        LittleEndian::write_u32(&mut buf[12..16], p.message_crc);
+        out.extend_from_slice(&buf);
+        out.extend_from_slice(&fragment);
+        let frame_crc = crc32fast::hash(&out);
+        out.extend_from_slice(&frame_crc.to_le_bytes());
+        out
+    }
+}
+
+/// A received frame not yet reduced to a single fragment.
+#[derive(Debug, Clone)]
+struct MixedPart {
+    indexes: Vec<u32>,
+    data: Vec<u8>,
+}
+
+/// Reassembles a message from frames received in any order, with gaps and repeats.
+#[derive(Debug, Clone, Default)]
+pub struct FountainDecoder {
+    params: Option<FrameParams>,
+    fragments: Vec<Option<Vec<u8>>>,
+    known: usize,
+    mixed: Vec<MixedPart>,
+    message: Option<Vec<u8>>,
+}
+
+impl FountainDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds one scanned frame; returns `true` once the message is complete.
+    ///
+    /// Corrupted frames fail with [`VPackError::ChecksumMismatch`] and frames of another transfer
+    /// with [`VPackError::FrameMismatch`]; neither changes the decoder state, so the caller can
+    /// keep scanning. Frames received after completion are ignored.
+    pub fn receive(&mut self, frame: &[u8]) -> Result<bool, VPackError> {
+        let (seq_num, params, fragment) = parse_frame(frame)?;
+        match self.params {
+            Some(expected) if expected != params => return Err(VPackError::FrameMismatch),
+            Some(_) => {}
+            None => {
+                self.params = Some(params);
+                self.fragments = vec![None; params.seq_len as usize];
+            }
+        }
+        if self.message.is_some() {
+            return Ok(true);
+        }
+
+        let indexes = choose_fragments(seq_num, params.seq_len, params.message_crc);
+        self.reduce(MixedPart {
+            indexes,
+            data: fragment.to_vec(),
+        });
+
+        if self.known < self.fragments.len() {
+            return Ok(false);
+        }
+        let mut message = Vec::with_capacity(params.seq_len as usize * params.fragment_len);
+        for fragment in self.fragments.iter().flatten() {
+            message.extend_from_slice(fragment);
+        }
+        message.truncate(params.message_len as usize);
+        let found = crc32fast::hash(&message);
+        if found != params.message_crc {
+            // Every frame passed its own CRC, so this is a forged transfer; start over.
+            *self = Self::new();
+            return Err(VPackError::ChecksumMismatch {
+                expected: params.message_crc,
+                found,
+            });
+        }
+        self.message = Some(message);
+        self.mixed.clear();
+        Ok(true)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.message.is_some()
+    }
+
+    /// `(fragments recovered, fragments total)`; `(0, 0)` before the first frame.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.known, self.fragments.len())
+    }
+
+    /// Payload type announced by the frames, once one has been received.
+    pub fn payload_type(&self) -> Option<FountainPayload> {
+        self.params.map(|p| p.payload_type)
+    }
+
+    /// The reassembled message (CRC32 verified), once complete.
+    pub fn message(&self) -> Option<&[u8]> {
+        self.message.as_deref()
+    }
+
+    /// Peeling decoder: strips known fragments from `part`; a part left with one fragment is
+    /// recorded and in turn stripped from the pending mixed parts.
+    fn reduce(&mut self, part: MixedPart) {
+        let mut queue = vec![part];
+        while let Some(mut part) = queue.pop() {
+            let fragments = &self.fragments;
+            part.indexes.retain(|&i| match &fragments[i as usize] {
+                Some(known) => {
+                    xor_into(&mut part.data, known);
+                    false
+                }
+                None => true,
+            });
+            match part.indexes.as_slice() {
+                [] => {}
+                [index] => {
+                    let index = *index;
+                    self.fragments[index as usize] = Some(part.data);
+                    self.known += 1;
+                    let (hit, pending): (Vec<MixedPart>, Vec<MixedPart>) =
+                        core::mem::take(&mut self.mixed)
+                            .into_iter()
+                            .partition(|m| m.indexes.contains(&index));
+                    self.mixed = pending;
+                    queue.extend(hit);
+                }
+                indexes => {
+                    let seen = self.mixed.iter().any(|m| m.indexes == indexes);
+                    if !seen && self.mixed.len() < self.fragments.len() {
+                        self.mixed.push(part);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn parse_frame(frame: &[u8]) -> Result<(u32, FrameParams, &[u8]), VPackError> {
+    if frame.len() < FRAME_OVERHEAD + 1 {
+        return Err(VPackError::IncompleteData);
+    }
+    if frame[0..3] != FOUNTAIN_MAGIC {
+        return Err(VPackError::InvalidMagic);
+    }
+    if frame[3] != FOUNTAIN_VERSION {
+        return Err(VPackError::UnsupportedVersion(frame[3]));
+    }
+    let (body, crc) = frame.split_at(frame.len() - 4);
+    let expected = LittleEndian::read_u32(crc);
+    let found = crc32fast::hash(body);
+    if found != expected {
+        return Err(VPackError::ChecksumMismatch { expected, found });
+    }
+
+    let payload_type = FountainPayload::from_u8(body[4])?;
+    let seq_num = LittleEndian::read_u32(&body[5..9]);
+    let seq_len = LittleEndian::read_u32(&body[9..13]);
+    let message_len = LittleEndian::read_u32(&body[13..17]);
+    let message_crc = LittleEndian::read_u32(&body[17..21]);
+    let fragment = &body[21..];
+
+    // The fragment length must be the even split the encoder would have chosen.
+    let (len, n, fl) = (message_len as usize, seq_len as usize, fragment.len());
+    if seq_num == 0 || n == 0 || len > MAX_MESSAGE_LEN || n > len || len.div_ceil(n) != fl {
+        return Err(VPackError::EncodingError);
+    }
+    Ok((
+        seq_num,
+        FrameParams {
+            payload_type,
+            seq_len,
+            message_len,
+            message_crc,
+            fragment_len: fl,
+        },
+        fragment,
+    ))
+}
+
+fn xor_into(dst: &mut [u8], src: &[u8]) {
+    for (d, s) in dst.iter_mut().zip(src) {
+        *d ^= s;
+    }
+}
+
+/// Fragment indexes XORed into frame `seq_num` (sorted, distinct).
+fn choose_fragments(seq_num: u32, seq_len: u32, message_crc: u32) -> Vec<u32> {
+    if seq_num <= seq_len {
+        return vec![seq_num - 1];
+    }
+    let mut rng = Xoshiro256::new((u64::from(message_crc) << 32) | u64::from(seq_num));
+
+    // Degree d in 1..=seq_len with probability proportional to 1/d.
+    let total: f64 = (1..=seq_len).map(|d| 1.0 / f64::from(d)).sum();
+    let mut target = rng.next_f64() * total;
+    let mut degree = seq_len;
+    for d in 1..=seq_len {
+        target -= 1.0 / f64::from(d);
+        if target < 0.0 {
+            degree = d;
+            break;
+        }
+    }
+
+    // Partial Fisher-Yates: the first `degree` entries of a shuffled 0..seq_len.
+    let mut pool: Vec<u32> = (0..seq_len).collect();
+    for i in 0..degree as usize {
+        let j = i + (rng.next_u64() % (pool.len() - i) as u64) as usize;
+        pool.swap(i, j);
+    }
+    pool.truncate(degree as usize);
+    pool.sort_unstable();
+    pool
+}
+
+/// xoshiro256** seeded through splitmix64; only needs to match between encoder and decoder.
+struct Xoshiro256([u64; 4]);
+
+impl Xoshiro256 {
+    fn new(seed: u64) -> Self {
+        let mut x = seed;
+        let mut next = || {
+            x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        Self([next(), next(), next(), next()])
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.0;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 + 7) as u8).collect()
+    }
+
+    #[test]
+    fn test_fountain_recovers_with_missing_and_repeated_frames() {
+        let msg = message(5_000);
+        let mut encoder = FountainEncoder::new(FountainPayload::ExitWaterfall, &msg, 200).unwrap();
+        assert_eq!(encoder.seq_len(), 25);
+
+        // Join late, drop every third frame, and see each received frame twice.
+        let mut decoder = FountainDecoder::new();
+        let mut sent = 0;
+        for seq_num in 10u32.. {
+            let frame = encoder.frame(seq_num);
+            assert_eq!(frame.len(), encoder.frame_len());
+            if seq_num % 3 == 0 {
+                continue;
+            }
+            sent += 1;
+            decoder.receive(&frame).unwrap();
+            if decoder.receive(&frame).unwrap() {
+                break;
+            }
+            assert!(sent < 200, "decoder did not converge");
+        }
+        assert_eq!(decoder.payload_type(), Some(FountainPayload::ExitWaterfall));
+        assert_eq!(decoder.progress(), (25, 25));
+        assert_eq!(decoder.message(), Some(msg.as_slice()));
+
+        // Frames after completion are ignored; the encoder's own sequence starts at 1.
+        assert_eq!(encoder.next_frame(), encoder.frame(1));
+        assert_eq!(decoder.receive(&encoder.frame(1)), Ok(true));
+    }
+
+    #[test]
+    fn test_fountain_single_fragment_and_frame_errors() {
+        let msg = message(100);
+        let encoder = FountainEncoder::new(FountainPayload::VPack, &msg, 400).unwrap();
+        assert_eq!(encoder.seq_len(), 1);
+        let mut decoder = FountainDecoder::new();
+        assert_eq!(decoder.receive(&encoder.frame(7)), Ok(true));
+        assert_eq!(decoder.message(), Some(msg.as_slice()));
+
+        let encoder = FountainEncoder::new(FountainPayload::VPack, &msg, 30).unwrap();
+        let mut decoder = FountainDecoder::new();
+        assert_eq!(decoder.receive(&encoder.frame(1)), Ok(false));
+
+        let mut corrupted = encoder.frame(2);
+        corrupted[FRAME_OVERHEAD] ^= 0x01;
+        assert!(matches!(
+            decoder.receive(&corrupted),
+            Err(VPackError::ChecksumMismatch { .. })
+        ));
+        let other = FountainEncoder::new(FountainPayload::VPack, &message(101), 30).unwrap();
+        assert_eq!(
+            decoder.receive(&other.frame(2)),
+            Err(VPackError::FrameMismatch)
+        );
+        assert_eq!(decoder.progress(), (1, 4));
+        for seq_num in 2..=4 {
+            decoder.receive(&encoder.frame(seq_num)).unwrap();
+        }
+        assert_eq!(decoder.message(), Some(msg.as_slice()));
+
+        assert_eq!(
+            FountainEncoder::for_vpack(&msg, 30).err(),
+            Some(VPackError::InvalidMagic)
+        );
+    }
+
+    #[cfg(any(feature = "bitcoin", feature = "wasm"))]
+    #[test]
+    fn test_fountain_exit_waterfall_round_trip() {
+        use crate::dehydration::VpackExitWaterfall;
+
+        // 60 hops at 97 bytes (amount unchanged after the first): several KB, many QR frames.
+        let mut bytes = vec![0x11u8; 36];
+        bytes.push(60);
+        for hop in 0..60u8 {
+            bytes.push(if hop == 0 { 0x80 } else { 0 });
+            bytes.extend_from_slice(&[hop; 64]);
+            if hop == 0 {
+                bytes.extend_from_slice(&50_000u64.to_le_bytes());
+            }
+            bytes.extend_from_slice(&[hop.wrapping_add(1); 32]);
+        }
+        let waterfall = VpackExitWaterfall::from_bytes(&bytes).unwrap();
+        let wire = waterfall.to_bytes();
+
+        let mut encoder = FountainEncoder::new(FountainPayload::ExitWaterfall, &wire, 500).unwrap();
+        let mut decoder = FountainDecoder::new();
+        while !decoder.receive(&encoder.next_frame()).unwrap() {}
+        let decoded = VpackExitWaterfall::from_bytes(decoder.message().unwrap()).unwrap();
+        assert_eq!(decoded.to_bytes(), wire);
+    }
+}
@@ -11,27 +11,132 @@ pub const MAX_TREE_ARITY: u16 = 16;
 pub const MAX_PAYLOAD_SIZE: u32 = 1_048_576; // 1MB Hard Cap
 pub const HEADER_SIZE: usize = 24;
 pub const CURRENT_VERSION: u8 = 1;
+/// Version 2: the payload ends with a (possibly empty) TLV extension section, see
+/// `payload::extensions`. Writers keep emitting version 1 unless extensions are attached.
+pub const VERSION_TLV: u8 = 2;
 
 /// Header Flags
 pub const FLAG_COMPRESSION_LZ4: u8 = 0x01;
 pub const FLAG_TESTNET: u8 = 0x02;
 pub const FLAG_PROOF_COMPACT: u8 = 0x04;
 pub const FLAG_HAS_ASSET_ID: u8 = 0x08;
+/// Network selector bits (4-5), meaningful only with `FLAG_TESTNET` set: 0 = testnet,
+/// `FLAG_NETWORK_SIGNET`, `FLAG_NETWORK_REGTEST`; 0x30 is reserved.
+pub const FLAG_NETWORK_MASK: u8 = 0x30;
+pub const FLAG_NETWORK_SIGNET: u8 = 0x10;
+pub const FLAG_NETWORK_REGTEST: u8 = 0x20;
+/// ASP-signed V-PACK: a BIP-340 signature by the ASP key follows the payload (see `auth`).
+pub const FLAG_ASP_SIGNED: u8 = 0x40;
+
+/// Size of the ASP signature appended to V-PACKs with `FLAG_ASP_SIGNED`.
+pub const ASP_SIGNATURE_SIZE: usize = 64;
+
+/// Bitcoin network a V-PACK belongs to, encoded in the header flags.
+///
+/// `FLAG_TESTNET` is set for every non-mainnet network, so readers that only know that bit still
+/// tell mainnet from test networks; V-PACKs written before the selector bits read as `Testnet`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+    Signet,
+    Regtest,
+}
+
+impl Network {
+    /// Header flag bits for this network (`FLAG_TESTNET` plus the selector).
+    pub const fn flags(self) -> u8 {
+        match self {
+            Network::Mainnet => 0,
+            Network::Testnet => FLAG_TESTNET,
+            Network::Signet => FLAG_TESTNET | FLAG_NETWORK_SIGNET,
+            Network::Regtest => FLAG_TESTNET | FLAG_NETWORK_REGTEST,
+        }
+    }
+
+    /// Decodes the network from header flags; other flag bits are ignored.
+    pub const fn from_flags(flags: u8) -> Result<Self, VPackError> {
+        let testnet = (flags & FLAG_TESTNET) != 0;
+        match (testnet, flags & FLAG_NETWORK_MASK) {
+            (false, 0) => Ok(Network::Mainnet),
+            (true, 0) => Ok(Network::Testnet),
+            (true, FLAG_NETWORK_SIGNET) => Ok(Network::Signet),
+            (true, FLAG_NETWORK_REGTEST) => Ok(Network::Regtest),
+            _ => Err(VPackError::InvalidNetwork(flags)),
+        }
+    }
+}
 
 /// Tx Variant (V-BIP-01: 0x03 = V3-Plain, 0x04 = V3-Anchored).
-/// Wire format is u8; internal logic uses this enum for exhaustive matching.
-#[repr(u8)]
+/// Wire format is u8 ([`TxVariant::as_u8`]); internal logic uses this enum for exhaustive
+/// matching, or [`TxVariant::builtin`] where only the built-in engines apply.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TxVariant {
     /// Second Tech: Recursive Transaction Chain; OutPoint identity (Hash:Index). V3 tx chain, not struct-hash.
-    V3Plain = 0x03,
+    V3Plain,
     /// Ark Labs: transaction tree with mandatory Fee Anchor; flat 32-byte hash identity.
-    V3Anchored = 0x04,
+    V3Anchored,
+    /// Private dialect byte, verified by an engine registered in an
+    /// [`EngineRegistry`](crate::consensus::registry::EngineRegistry). Built with
+    /// [`TxVariant::custom`] or [`TxVariant::from_byte`].
+    Custom(CustomVariant),
+}
+
+/// Variant byte of [`TxVariant::Custom`]: any byte but 0x03 and 0x04.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CustomVariant(u8);
+
+impl CustomVariant {
+    pub const fn as_u8(self) -> u8 {
+        self.0
+    }
+}
+
+/// A variant with a built-in engine; see [`TxVariant::builtin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinVariant {
+    V3Plain,
+    V3Anchored,
 }
 
 impl TxVariant {
     pub const fn as_u8(self) -> u8 {
-        self as u8
+        match self {
+            TxVariant::V3Plain => 0x03,
+            TxVariant::V3Anchored => 0x04,
+            TxVariant::Custom(custom) => custom.0,
+        }
+    }
+
+    /// Any wire byte: the built-in variants for 0x03 / 0x04, [`TxVariant::Custom`] otherwise.
+    /// Unlike `TryFrom<u8>`, this does not reject unknown dialects.
+    pub const fn from_byte(byte: u8) -> Self {
+        match byte {
+            0x03 => TxVariant::V3Plain,
+            0x04 => TxVariant::V3Anchored,
+            other => TxVariant::Custom(CustomVariant(other)),
+        }
+    }
+
+    /// [`TxVariant::Custom`] for `byte`; `None` for 0x03 and 0x04, which are built in.
+    pub const fn custom(byte: u8) -> Option<Self> {
+        match byte {
+            0x03 | 0x04 => None,
+            other => Some(TxVariant::Custom(CustomVariant(other))),
+        }
+    }
+
+    /// The built-in dialect, for code paths that have no [`EngineRegistry`] to dispatch
+    /// through. [`TxVariant::Custom`] fails with [`VPackError::InvalidTxVariant`].
+    ///
+    /// [`EngineRegistry`]: crate::consensus::registry::EngineRegistry
+    pub const fn builtin(self) -> Result<BuiltinVariant, VPackError> {
+        match self {
+            TxVariant::V3Plain => Ok(BuiltinVariant::V3Plain),
+            TxVariant::V3Anchored => Ok(BuiltinVariant::V3Anchored),
+            TxVariant::Custom(custom) => Err(VPackError::InvalidTxVariant(custom.0)),
+        }
     }
 }
 
@@ -68,6 +173,15 @@ impl Header {
     /// Zero-Allocation parsing: Reads the header from the first 24 bytes of a slice.
     /// Manually parses fields to ensure consistency regardless of architecture.
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, VPackError> {
+        Self::from_bytes_with_variants(bytes, |_| false)
+    }
+
+    /// [`Self::from_bytes`] that also accepts the variant bytes for which `is_custom` returns
+    /// `true`, as [`TxVariant::Custom`].
+    pub(crate) fn from_bytes_with_variants(
+        bytes: &[u8],
+        is_custom: impl Fn(u8) -> bool,
+    ) -> Result<Self, VPackError> {
         if bytes.len() < HEADER_SIZE {
             return Err(VPackError::IncompleteData);
         }
@@ -81,7 +195,12 @@ impl Header {
         // 2. Parse Fields manually (Little-Endian)
         let flags = bytes[3];
         let version = bytes[4];
-        let tx_variant = TxVariant::try_from(bytes[5])?;
+        let tx_variant = match TxVariant::try_from(bytes[5]) {
+            Err(VPackError::InvalidTxVariant(byte)) if is_custom(byte) => {
+                TxVariant::Custom(CustomVariant(byte))
+            }
+            other => other?,
+        };
 
         let tree_arity = LittleEndian::read_u16(&bytes[6..8]);
         let tree_depth = LittleEndian::read_u16(&bytes[8..10]);
@@ -132,10 +251,13 @@ impl Header {
     /// Performs structural and logical validation of the Header fields.
     pub fn validate(&self) -> Result<(), VPackError> {
         // Version Lock
-        if self.version != CURRENT_VERSION {
+        if self.version != CURRENT_VERSION && self.version != VERSION_TLV {
             return Err(VPackError::UnsupportedVersion(self.version));
         }
 
+        // Network selector must be a defined combination
+        Network::from_flags(self.flags)?;
+
         // Sanity Check: Arity (Must be >= 2)
         if self.tree_arity < 2 {
             return Err(VPackError::InvalidArity(self.tree_arity));
@@ -206,12 +328,49 @@ impl Header {
         Ok(())
     }
 
+    /// Splits the bytes after the header into the payload and, when `FLAG_ASP_SIGNED` is set,
+    /// the trailing 64-byte ASP signature. Payload length is left to [`Self::verify_checksum`].
+    pub fn split_body<'a>(
+        &self,
+        body: &'a [u8],
+    ) -> Result<(&'a [u8], Option<&'a [u8; ASP_SIGNATURE_SIZE]>), VPackError> {
+        if !self.is_signed() {
+            return Ok((body, None));
+        }
+        if body.len() < ASP_SIGNATURE_SIZE {
+            return Err(VPackError::IncompleteData);
+        }
+        let (payload, signature) = body.split_at(body.len() - ASP_SIGNATURE_SIZE);
+        let signature = <&[u8; ASP_SIGNATURE_SIZE]>::try_from(signature)
+            .map_err(|_| VPackError::IncompleteData)?;
+        Ok((payload, Some(signature)))
+    }
+
     // --- Helpers (Const for Compile-Time Evaluation) ---
 
+    pub const fn is_compressed(&self) -> bool {
+        (self.flags & FLAG_COMPRESSION_LZ4) != 0
+    }
+
+    /// `true` for every non-mainnet network (see [`Self::network`]).
     pub const fn is_testnet(&self) -> bool {
         (self.flags & FLAG_TESTNET) != 0
     }
 
+    /// The network encoded in the flags. Always `Ok` for headers from [`Self::from_bytes`].
+    pub const fn network(&self) -> Result<Network, VPackError> {
+        Network::from_flags(self.flags)
+    }
+
+    /// `true` for version-2 headers, whose payload ends with an extension section.
+    pub const fn has_extensions(&self) -> bool {
+        self.version >= VERSION_TLV
+    }
+
+    pub const fn is_signed(&self) -> bool {
+        (self.flags & FLAG_ASP_SIGNED) != 0
+    }
+
     pub const fn is_compact(&self) -> bool {
         (self.flags & FLAG_PROOF_COMPACT) != 0
     }
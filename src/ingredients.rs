@@ -6,7 +6,7 @@ use alloc::vec::Vec;
 use core::str::FromStr;
 
 use crate::error::VPackError;
-use crate::header::TxVariant;
+use crate::header::{BuiltinVariant, TxVariant};
 use crate::payload::tree::{GenesisItem, SiblingNode, VPackTree, VtxoLeaf};
 use crate::VtxoId;
 
@@ -308,12 +308,17 @@ impl LogicAdapter for SecondTechAdapter {
 }
 
 /// Dispatch by variant: try logic adapter first; returns None if ingredients are incomplete.
+/// Custom variants have no adapter and fail with [`VPackError::InvalidTxVariant`].
 pub fn tree_from_ingredients(
     variant: TxVariant,
     reconstruction_ingredients: &serde_json::Value,
 ) -> Option<Result<VPackTree, VPackError>> {
+    let variant = match variant.builtin() {
+        Ok(variant) => variant,
+        Err(e) => return Some(Err(e)),
+    };
     match variant {
-        TxVariant::V3Anchored => {
+        BuiltinVariant::V3Anchored => {
             if reconstruction_ingredients.get("parent_outpoint").is_some()
                 || reconstruction_ingredients.get("anchor_outpoint").is_some()
             {
@@ -322,7 +327,7 @@ pub fn tree_from_ingredients(
                 None
             }
         }
-        TxVariant::V3Plain => {
+        BuiltinVariant::V3Plain => {
             if reconstruction_ingredients.get("amount").is_some()
                 && (reconstruction_ingredients
                     .get("script_pubkey_hex")
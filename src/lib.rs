@@ -8,15 +8,26 @@ extern crate alloc;
 
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
 pub mod adapters;
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub mod auth;
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub mod bundle;
 pub mod compact_size;
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
 pub mod consensus;
+#[cfg(all(feature = "schnorr-verify", any(feature = "bitcoin", feature = "wasm")))]
+pub mod cpfp;
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
 pub mod dehydration;
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub mod delta;
 pub mod error;
 pub use error::VPackError;
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub mod exit_cost;
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
 pub mod export;
+pub mod fountain;
 pub mod header;
 #[cfg(all(any(feature = "bitcoin", feature = "wasm"), feature = "export-json"))]
 mod json_hex;
@@ -24,9 +35,22 @@ mod json_hex;
 pub mod pack;
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
 pub mod payload;
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub mod policy;
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub mod psbt;
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub mod report;
 #[cfg(all(any(feature = "bitcoin", feature = "wasm"), feature = "export-json"))]
 pub mod state;
 
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub mod store;
+
+pub mod text;
+
+#[cfg(all(feature = "schnorr-verify", any(feature = "bitcoin", feature = "wasm")))]
+pub mod sweep;
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
 pub mod types;
 
@@ -42,37 +66,66 @@ pub mod ingredients;
 ))]
 pub use ingredients::{tree_from_ingredients, ArkLabsAdapter, LogicAdapter, SecondTechAdapter};
 
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub use bundle::{pack_bundle, unpack_bundle, verify_bundle, BundleReader};
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
 pub use consensus::taproot;
-#[cfg(all(feature = "schnorr-verify", any(feature = "bitcoin", feature = "wasm")))]
-pub use consensus::verify_path_exclusivity;
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
 pub use consensus::{
     compute_ark_labs_merkle_root, compute_bark_merkle_root, compute_bark_vtxo_tapscript_root,
-    validate_exit_ready_completeness, validate_timelocks, validate_tree_completeness,
-    vtxo_id_mismatch_diagnostic_bytes, vtxo_id_mismatch_diagnostic_vout, ArkLabsV3,
-    ConsensusEngine, SecondTechV3, VerificationOutput, VtxoId,
+    localize_mismatch, validate_exit_ready_completeness, validate_timelocks,
+    validate_tree_completeness, vtxo_id_mismatch_diagnostic_bytes,
+    vtxo_id_mismatch_diagnostic_vout, ArkLabsV3, ConsensusEngine, EngineRegistry, SecondTechV3,
+    VerificationOutput, VtxoId, VtxoIdFold,
 };
 #[cfg(feature = "bitcoin")]
 pub use consensus::{reconstruct_control_block, verify_control_block};
+#[cfg(all(feature = "schnorr-verify", any(feature = "bitcoin", feature = "wasm")))]
+pub use consensus::{verify_path_exclusivity, verify_path_exclusivity_ref};
+#[cfg(all(feature = "schnorr-verify", any(feature = "bitcoin", feature = "wasm")))]
+pub use cpfp::{build_cpfp_children, CpfpChild, FundingUtxo};
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
 pub use dehydration::{bark_dehydrate, HopData, VpackExitWaterfall, VpackSovereigntyEnvelope};
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub use delta::VPackDelta;
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub use exit_cost::{exit_cost_report, ExitCostReport};
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
 pub use export::{
     create_vpack_ark_labs, create_vpack_from_tree, create_vpack_second_tech, ArkLabsIngredients,
     ArkLabsOutput, ArkLabsSibling, SecondTechGenesisStep, SecondTechIngredients, SecondTechSibling,
 };
-pub use header::TxVariant;
+pub use fountain::{FountainDecoder, FountainEncoder, FountainPayload};
+pub use header::{Network, TxVariant};
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub use payload::extensions::Extensions;
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub use payload::stream::{Progress, StreamRecord, StreamingReader};
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub use payload::tree::{VPackTree, VPackTreeRef};
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub use policy::{sighash_type_name, SighashPolicy, VerificationPolicy, VerificationProfile};
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
-pub use payload::tree::VPackTree;
+pub use psbt::{exit_psbts, exit_psbts_with_keys, Psbt, PsbtInput, PsbtOutput, TapKeySpend};
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub use report::{verify_report, VerificationReport};
 #[cfg(all(any(feature = "bitcoin", feature = "wasm"), feature = "export-json"))]
 pub use state::{VpackImplementation, VpackIngredients, VpackState};
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub use store::VtxoStore;
+#[cfg(all(feature = "schnorr-verify", any(feature = "bitcoin", feature = "wasm")))]
+pub use sweep::{build_exit_sweep, ExitSweep};
+pub use text::{from_text, to_text};
 
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
 use crate::header::{Header, HEADER_SIZE};
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
+use crate::payload::compression::decompress_payload;
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
 use crate::payload::reader::BoundedReader;
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+use alloc::vec::Vec;
 
 /// Verifies a V-PACK byte array against an expected VTXO ID with conservation of value.
 ///
@@ -80,49 +133,336 @@ use crate::payload::reader::BoundedReader;
 /// * `vpack_bytes` - Complete V-PACK byte array. The first 24 bytes must be the header.
 /// * `expected_id` - The expected VTXO ID to verify against.
 /// * `anchor_value` - Value (in satoshis) of the L1 UTXO that anchors the tree.
+/// * `network` - Network the caller operates on; the header's network must match.
 ///
 /// # Returns
 /// * `Ok(VPackTree)` - Verification succeeded, returns the parsed tree
-/// * `Err(VPackError)` - Verification failed (checksum, parsing, ID mismatch, or ValueMismatch)
+/// * `Err(VPackError)` - Verification failed (network, checksum, parsing, ID mismatch, or
+///   ValueMismatch)
+///
+/// An ASP signature (`FLAG_ASP_SIGNED`) is accepted but not checked; use
+/// [`verify_authenticated`] to require one. Engines come from [`EngineRegistry::default`]; verify
+/// a private dialect with [`EngineRegistry::verify`].
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
 pub fn verify(
     vpack_bytes: &[u8],
     expected_id: &VtxoId,
     anchor_value: u64,
+    network: Network,
 ) -> Result<VPackTree, VPackError> {
-    // Step 1: Parse Header (first 24 bytes)
-    let header = Header::from_bytes(&vpack_bytes[..HEADER_SIZE])?;
+    verify_in(
+        &EngineRegistry::default(),
+        None,
+        vpack_bytes,
+        expected_id,
+        anchor_value,
+        network,
+    )
+}
+
+/// [`verify`] over many V-PACKs, typically every VTXO of one round, with `(vpack_bytes,
+/// expected_id, anchor_value)` per item. Returns one result per item, in input order.
+///
+/// Path transactions shared by several trees are hashed once and their signatures verified once
+/// per batch; every other check runs per item exactly as in [`verify`].
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub fn verify_batch(
+    items: &[(&[u8], VtxoId, u64)],
+    network: Network,
+) -> Vec<Result<VPackTree, VPackError>> {
+    let registry = EngineRegistry::default();
+    let mut memo = crate::consensus::memo::HopMemo::default();
+    items
+        .iter()
+        .map(|(vpack_bytes, expected_id, anchor_value)| {
+            verify_in(
+                &registry,
+                Some(&mut memo),
+                vpack_bytes,
+                expected_id,
+                *anchor_value,
+                network,
+            )
+        })
+        .collect()
+}
 
-    // Step 2: Extract Payload
-    let payload = &vpack_bytes[HEADER_SIZE..];
+/// [`verify`] with the engines of `registry`; with `memo`, the built-in engines share work across
+/// calls instead.
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub(crate) fn verify_in(
+    registry: &EngineRegistry,
+    memo: Option<&mut crate::consensus::memo::HopMemo>,
+    vpack_bytes: &[u8],
+    expected_id: &VtxoId,
+    anchor_value: u64,
+    network: Network,
+) -> Result<VPackTree, VPackError> {
+    // Step 1: Parse Header (first 24 bytes) and check the network
+    if vpack_bytes.len() < HEADER_SIZE {
+        return Err(VPackError::IncompleteData);
+    }
+    let header = registry.parse_header(&vpack_bytes[..HEADER_SIZE])?;
+    check_network(&header, network)?;
+
+    // Step 2: Extract Payload (without the trailing ASP signature, if any)
+    let (payload, _) = header.split_body(&vpack_bytes[HEADER_SIZE..])?;
 
     // Step 3: Verify Checksum
     header.verify_checksum(payload)?;
 
-    // Step 4: Parse Payload
-    let tree = BoundedReader::parse(&header, payload)?;
+    verify_checked(registry, memo, &header, payload, expected_id, anchor_value)
+}
+
+/// Steps 4-8 of [`verify`] over a checksum-verified payload, compressed or not.
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn verify_checked(
+    registry: &EngineRegistry,
+    mut memo: Option<&mut crate::consensus::memo::HopMemo>,
+    header: &Header,
+    payload: &[u8],
+    expected_id: &VtxoId,
+    anchor_value: u64,
+) -> Result<VPackTree, VPackError> {
+    // Steps 4-7 run on the borrowed tree; the owned tree is materialized once on success.
+    if header.is_compressed() {
+        let raw = decompress_payload(payload)?;
+        let tree = verify_payload(
+            registry,
+            memo.as_deref_mut(),
+            header,
+            &raw,
+            expected_id,
+            anchor_value,
+        )?;
+        return Ok(VPackTree::from(&tree));
+    }
+    let tree = verify_payload(registry, memo, header, payload, expected_id, anchor_value)?;
+
+    // Step 8: Return the parsed tree
+    Ok(VPackTree::from(&tree))
+}
+
+/// Zero-copy [`verify`]: the returned tree borrows its scripts and signatures from `vpack_bytes`.
+///
+/// No per-script allocation happens anywhere on this path. A compressed V-PACK
+/// (`FLAG_COMPRESSION_LZ4`) cannot be borrowed from and is rejected with
+/// [`VPackError::CompressionError`]; use [`verify`] for those.
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub fn verify_ref<'a>(
+    vpack_bytes: &'a [u8],
+    expected_id: &VtxoId,
+    anchor_value: u64,
+    network: Network,
+) -> Result<VPackTreeRef<'a>, VPackError> {
+    verify_ref_in(
+        &EngineRegistry::default(),
+        vpack_bytes,
+        expected_id,
+        anchor_value,
+        network,
+    )
+}
+
+/// [`verify_ref`] with the engines of `registry`.
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub(crate) fn verify_ref_in<'a>(
+    registry: &EngineRegistry,
+    vpack_bytes: &'a [u8],
+    expected_id: &VtxoId,
+    anchor_value: u64,
+    network: Network,
+) -> Result<VPackTreeRef<'a>, VPackError> {
+    if vpack_bytes.len() < HEADER_SIZE {
+        return Err(VPackError::IncompleteData);
+    }
+    let header = registry.parse_header(&vpack_bytes[..HEADER_SIZE])?;
+    check_network(&header, network)?;
+    let (payload, _) = header.split_body(&vpack_bytes[HEADER_SIZE..])?;
+    header.verify_checksum(payload)?;
+    if header.is_compressed() {
+        return Err(VPackError::CompressionError);
+    }
+    verify_payload(registry, None, &header, payload, expected_id, anchor_value)
+}
+
+/// [`verify`] that first requires a valid ASP signature by `asp_pubkey` (x-only), so a V-PACK
+/// from an unexpected server is rejected before any reconstruction work.
+#[cfg(all(feature = "schnorr-verify", any(feature = "bitcoin", feature = "wasm")))]
+pub fn verify_authenticated(
+    vpack_bytes: &[u8],
+    expected_id: &VtxoId,
+    anchor_value: u64,
+    network: Network,
+    asp_pubkey: &[u8; 32],
+) -> Result<VPackTree, VPackError> {
+    if vpack_bytes.len() < HEADER_SIZE {
+        return Err(VPackError::IncompleteData);
+    }
+    let registry = EngineRegistry::default();
+    let header = registry.parse_header(&vpack_bytes[..HEADER_SIZE])?;
+    check_network(&header, network)?;
+    let (payload, signature) = header.split_body(&vpack_bytes[HEADER_SIZE..])?;
+    header.verify_checksum(payload)?;
+    auth::check_signature(&header, payload, signature, asp_pubkey)?;
+    verify_checked(&registry, None, &header, payload, expected_id, anchor_value)
+}
+
+/// [`verify`] plus the checks selected by `policy` (network, ASP signature, completeness,
+/// timelocks, SIGHASH audit), in one call.
+///
+/// The ASP signature is checked first and the static completeness and timelock checks run before
+/// the engine, so cheap rejections do not pay for tree reconstruction. A policy asking for
+/// signature checks in a build without `schnorr-verify`, or for the SIGHASH audit without an
+/// anchor script, fails with [`VPackError::UnsupportedPolicy`].
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub fn verify_with(
+    vpack_bytes: &[u8],
+    expected_id: &VtxoId,
+    anchor_value: u64,
+    policy: &VerificationPolicy,
+) -> Result<VPackTree, VPackError> {
+    verify_with_in(
+        &EngineRegistry::default(),
+        vpack_bytes,
+        expected_id,
+        anchor_value,
+        policy,
+    )
+}
+
+/// [`verify_with`] with the engines of `registry`.
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub(crate) fn verify_with_in(
+    registry: &EngineRegistry,
+    vpack_bytes: &[u8],
+    expected_id: &VtxoId,
+    anchor_value: u64,
+    policy: &VerificationPolicy,
+) -> Result<VPackTree, VPackError> {
+    if vpack_bytes.len() < HEADER_SIZE {
+        return Err(VPackError::IncompleteData);
+    }
+    let header = registry.parse_header(&vpack_bytes[..HEADER_SIZE])?;
+    check_network(&header, policy.network)?;
+    if policy.audit_sighash && policy.anchor_script.is_none() {
+        return Err(VPackError::UnsupportedPolicy);
+    }
+
+    #[cfg(feature = "schnorr-verify")]
+    if let Some(asp_pubkey) = &policy.asp_pubkey {
+        auth::verify_asp_signature(vpack_bytes, asp_pubkey)?;
+    }
+    #[cfg(not(feature = "schnorr-verify"))]
+    if policy.asp_pubkey.is_some() || policy.audit_sighash {
+        return Err(VPackError::UnsupportedPolicy);
+    }
+
+    let (payload, _) = header.split_body(&vpack_bytes[HEADER_SIZE..])?;
+    header.verify_checksum(payload)?;
+    let raw;
+    let payload = if header.is_compressed() {
+        raw = decompress_payload(payload)?;
+        &raw[..]
+    } else {
+        payload
+    };
+
+    let tree_ref = BoundedReader::parse_ref(&header, payload)?;
+    let tree = VPackTree::from(&tree_ref);
+    if policy.require_exit_ready {
+        crate::consensus::validate_exit_ready_completeness(&tree)?;
+    }
+    if policy.check_timelocks {
+        crate::consensus::validate_timelocks(&tree)?;
+    }
+
+    check_parsed(
+        registry,
+        None,
+        &header,
+        &tree_ref,
+        expected_id,
+        anchor_value,
+    )?;
 
+    #[cfg(feature = "schnorr-verify")]
+    if let (true, Some(anchor_script)) = (policy.audit_sighash, &policy.anchor_script) {
+        crate::consensus::taproot_sighash::audit_sighash_policy_with(
+            &tree,
+            header.tx_variant,
+            anchor_value,
+            anchor_script,
+            &policy.sighash_policy,
+        )?;
+    }
+
+    Ok(tree)
+}
+
+/// [`verify`] for the bech32m text encoding: decodes `parts` with [`from_text`] (header, network
+/// prefix and checksum checks) and verifies the resulting bytes.
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+pub fn verify_text<S: AsRef<str>>(
+    parts: &[S],
+    expected_id: &VtxoId,
+    anchor_value: u64,
+    network: Network,
+) -> Result<VPackTree, VPackError> {
+    let vpack_bytes = from_text(parts)?;
+    verify(&vpack_bytes, expected_id, anchor_value, network)
+}
+
+/// Rejects a V-PACK issued for a different network than `expected`.
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn check_network(header: &Header, expected: Network) -> Result<(), VPackError> {
+    let found = header.network()?;
+    if found != expected {
+        return Err(VPackError::NetworkMismatch { expected, found });
+    }
+    Ok(())
+}
+
+/// Steps 4-7 of [`verify`] over an uncompressed, checksum-verified payload.
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn verify_payload<'a>(
+    registry: &EngineRegistry,
+    memo: Option<&mut crate::consensus::memo::HopMemo>,
+    header: &Header,
+    payload: &'a [u8],
+    expected_id: &VtxoId,
+    anchor_value: u64,
+) -> Result<VPackTreeRef<'a>, VPackError> {
+    // Step 4: Parse Payload (borrowed; no script copies)
+    let tree = BoundedReader::parse_ref(header, payload)?;
+    check_parsed(registry, memo, header, &tree, expected_id, anchor_value)?;
+    Ok(tree)
+}
+
+/// Steps 5-7 of [`verify`] over a parsed tree.
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn check_parsed(
+    registry: &EngineRegistry,
+    memo: Option<&mut crate::consensus::memo::HopMemo>,
+    header: &Header,
+    tree: &VPackTreeRef<'_>,
+    expected_id: &VtxoId,
+    anchor_value: u64,
+) -> Result<(), VPackError> {
     // Step 5: Validate global policy invariants (fee_anchor, sequence consistency)
-    crate::payload::validate_invariants(&header, &tree)?;
+    crate::payload::validate_invariants_ref(header, tree)?;
 
-    // Step 6: Dispatch by Variant and Verify (only 0x03 and 0x04 are valid per TxVariant::try_from)
-    match header.tx_variant {
-        crate::header::TxVariant::V3Anchored => {
-            let engine = crate::consensus::ArkLabsV3;
-            engine.verify(&tree, expected_id, anchor_value)?;
-        }
-        crate::header::TxVariant::V3Plain => {
-            let engine = crate::consensus::SecondTechV3;
-            engine.verify(&tree, expected_id, anchor_value)?;
-        }
+    // Step 6: Dispatch by Variant and Verify (engines from the registry, or the batch memo)
+    match memo {
+        Some(memo) => memo.verify_tree(header.tx_variant, tree, expected_id, anchor_value)?,
+        None => registry.verify_tree_ref(header.tx_variant, tree, expected_id, anchor_value)?,
     }
 
     // Step 7: Path Exclusivity — verify Taproot tree has no hidden spend paths
     #[cfg(feature = "schnorr-verify")]
-    crate::consensus::verify_path_exclusivity(&tree, header.tx_variant)?;
+    registry.verify_path_exclusivity_ref(tree, header.tx_variant)?;
 
-    // Step 8: Return the parsed tree
-    Ok(tree)
+    Ok(())
 }
 
 /// Test-only: compute the VTXO ID that would be verified for this V-PACK. Used to fill expected_vtxo_id in vectors.
@@ -130,16 +470,12 @@ pub fn verify(
 #[cfg(all(feature = "std", any(feature = "bitcoin", feature = "wasm")))]
 pub fn compute_vtxo_id_from_bytes(vpack_bytes: &[u8]) -> Result<VtxoId, VPackError> {
     let header = Header::from_bytes(&vpack_bytes[..HEADER_SIZE])?;
-    header.verify_checksum(&vpack_bytes[HEADER_SIZE..])?;
-    let tree = BoundedReader::parse(&header, &vpack_bytes[HEADER_SIZE..])?;
-    match header.tx_variant {
-        crate::header::TxVariant::V3Anchored => crate::consensus::ArkLabsV3
-            .compute_vtxo_id(&tree, None)
-            .map(|o| o.id),
-        crate::header::TxVariant::V3Plain => crate::consensus::SecondTechV3
-            .compute_vtxo_id(&tree, None)
-            .map(|o| o.id),
-    }
+    let (payload, _) = header.split_body(&vpack_bytes[HEADER_SIZE..])?;
+    header.verify_checksum(payload)?;
+    let tree = BoundedReader::parse(&header, payload)?;
+    crate::consensus::builtin_engine(header.tx_variant)?
+        .compute_vtxo_id(&tree, None)
+        .map(|o| o.id)
 }
 
 /// Tests that mirror wasm_verify: auto-inference over ArkLabs then SecondTech, create_vpack_from_tree + verify.
@@ -150,7 +486,7 @@ mod wasm_auto_inference_test {
 
     use crate::{
         create_vpack_from_tree, verify, ArkLabsAdapter, ArkLabsV3, ConsensusEngine, LogicAdapter,
-        SecondTechAdapter, SecondTechV3, TxVariant, VtxoId,
+        Network, SecondTechAdapter, SecondTechV3, TxVariant, VtxoId,
     };
 
     fn run_auto_inference(json_path: &str) -> Result<(String, String), String> {
@@ -170,10 +506,11 @@ mod wasm_auto_inference_test {
             .ok_or("missing reconstruction_ingredients")?;
 
         if let Ok(tree) = ArkLabsAdapter::map_ingredients(ri) {
-            let bytes = create_vpack_from_tree(&tree, TxVariant::V3Anchored, false)
+            let bytes = create_vpack_from_tree(&tree, TxVariant::V3Anchored, Network::Mainnet)
                 .map_err(|e| e.to_string())?;
             let anchor_value = value["anchor_value"].as_u64().unwrap_or(1100u64);
-            verify(&bytes, &expected_id, anchor_value).map_err(|e| e.to_string())?;
+            verify(&bytes, &expected_id, anchor_value, Network::Mainnet)
+                .map_err(|e| e.to_string())?;
             let output = ArkLabsV3
                 .compute_vtxo_id(&tree, None)
                 .map_err(|e| e.to_string())?;
@@ -181,10 +518,11 @@ mod wasm_auto_inference_test {
         }
 
         if let Ok(tree) = SecondTechAdapter::map_ingredients(ri) {
-            let bytes = create_vpack_from_tree(&tree, TxVariant::V3Plain, false)
+            let bytes = create_vpack_from_tree(&tree, TxVariant::V3Plain, Network::Mainnet)
                 .map_err(|e| e.to_string())?;
             let anchor_value = value["anchor_value"].as_u64().unwrap_or(10_000u64);
-            verify(&bytes, &expected_id, anchor_value).map_err(|e| e.to_string())?;
+            verify(&bytes, &expected_id, anchor_value, Network::Mainnet)
+                .map_err(|e| e.to_string())?;
             let output = SecondTechV3
                 .compute_vtxo_id(&tree, None)
                 .map_err(|e| e.to_string())?;
@@ -11,7 +11,9 @@ use byteorder::LittleEndian;
 use crate::compact_size::write_compact_size;
 use crate::error::VPackError;
 use crate::header::{Header, HEADER_SIZE, MAGIC_BYTES};
-use crate::payload::tree::{SiblingNode, VPackTree};
+use crate::payload::compression::compress_payload;
+use crate::payload::extensions::Extensions;
+use crate::payload::tree::{GenesisItem, SiblingNode, VPackTree};
 use crate::types::hashes::Hash;
 
 /// Packs a pre-built payload (prefix + tree section) with the given header into a complete V-PACK.
@@ -48,8 +50,35 @@ pub(crate) fn serialize_payload_for_header(tree: &VPackTree) -> Result<Vec<u8>,
 
 /// Packs a header and tree into a complete V-PACK byte buffer.
 /// Checksum is computed over bytes 0..20 of header + payload per V-BIP-01.
+/// When `FLAG_COMPRESSION_LZ4` is set, the payload is LZ4-compressed and `payload_len` /
+/// checksum cover the compressed bytes.
 pub fn pack(header: &Header, tree: &VPackTree) -> Result<Vec<u8>, VPackError> {
-    let payload = serialize_payload(header, tree)?;
+    pack_with_extensions(header, tree, &Extensions::default())
+}
+
+/// [`pack`] with a TLV extension section after the trailer. The header must be version 2
+/// ([`crate::header::VERSION_TLV`]) unless `extensions` is empty; otherwise
+/// [`VPackError::EncodingError`].
+pub fn pack_with_extensions(
+    header: &Header,
+    tree: &VPackTree,
+    extensions: &Extensions,
+) -> Result<Vec<u8>, VPackError> {
+    // The signature covers the packed bytes; sign afterwards with `auth::attach_signature`.
+    if header.is_signed() {
+        return Err(VPackError::EncodingError);
+    }
+    let mut payload = serialize_payload(header, tree)?;
+    if header.has_extensions() {
+        extensions.encode(&mut payload);
+    } else if !extensions.is_empty() {
+        return Err(VPackError::EncodingError);
+    }
+    let payload = if header.is_compressed() {
+        compress_payload(&payload)?
+    } else {
+        payload
+    };
     let payload_len = payload.len();
     if payload_len > crate::header::MAX_PAYLOAD_SIZE as usize {
         return Err(VPackError::PayloadTooLarge(payload_len as u32));
@@ -83,7 +112,7 @@ fn serialize_payload(header: &Header, tree: &VPackTree) -> Result<Vec<u8>, VPack
     serialize_payload_inner(tree, header.has_asset_id())
 }
 
-fn serialize_payload_inner(
+pub(crate) fn serialize_payload_inner(
     tree: &VPackTree,
     include_asset_id: bool,
 ) -> Result<Vec<u8>, VPackError> {
@@ -117,24 +146,7 @@ fn serialize_payload_inner(
         .serialize(&mut out)
         .map_err(|_| VPackError::EncodingError)?;
     for sibling in &tree.leaf_siblings {
-        match sibling {
-            SiblingNode::Compact {
-                hash,
-                value,
-                script,
-            } => {
-                out.extend_from_slice(hash);
-                let mut val_buf = [0u8; 8];
-                LittleEndian::write_u64(&mut val_buf, *value);
-                out.extend_from_slice(&val_buf);
-                script
-                    .serialize(&mut out)
-                    .map_err(|_| VPackError::EncodingError)?;
-            }
-            SiblingNode::Full(txout) => {
-                encode_txout(txout, &mut out)?;
-            }
-        }
+        write_sibling(sibling, &mut out)?;
     }
 
     // Tree: path_len (Borsh u32)
@@ -144,48 +156,7 @@ fn serialize_payload_inner(
         .map_err(|_| VPackError::EncodingError)?;
 
     for item in tree.path.iter() {
-        // siblings_len (Borsh u32)
-        let siblings_len = item.siblings.len() as u32;
-        siblings_len
-            .serialize(&mut out)
-            .map_err(|_| VPackError::EncodingError)?;
-
-        for sibling in item.siblings.iter() {
-            match sibling {
-                SiblingNode::Compact {
-                    hash,
-                    value,
-                    script,
-                } => {
-                    out.extend_from_slice(hash);
-                    let mut val_buf = [0u8; 8];
-                    LittleEndian::write_u64(&mut val_buf, *value);
-                    out.extend_from_slice(&val_buf);
-                    script
-                        .serialize(&mut out)
-                        .map_err(|_| VPackError::EncodingError)?;
-                }
-                SiblingNode::Full(txout) => {
-                    encode_txout(txout, &mut out)?;
-                }
-            }
-        }
-
-        item.parent_index
-            .serialize(&mut out)
-            .map_err(|_| VPackError::EncodingError)?;
-        item.sequence
-            .serialize(&mut out)
-            .map_err(|_| VPackError::EncodingError)?;
-        item.child_amount
-            .serialize(&mut out)
-            .map_err(|_| VPackError::EncodingError)?;
-        item.child_script_pubkey
-            .serialize(&mut out)
-            .map_err(|_| VPackError::EncodingError)?;
-        item.signature
-            .serialize(&mut out)
-            .map_err(|_| VPackError::EncodingError)?;
+        write_genesis_item(item, &mut out)?;
     }
 
     // internal_key (32 raw bytes)
@@ -199,6 +170,52 @@ fn serialize_payload_inner(
     Ok(out)
 }
 
+/// One sibling: Compact is hash (32) + value (8 LE) + Borsh script; Full is a consensus `TxOut`.
+fn write_sibling(sibling: &SiblingNode, out: &mut Vec<u8>) -> Result<(), VPackError> {
+    match sibling {
+        SiblingNode::Compact {
+            hash,
+            value,
+            script,
+        } => {
+            out.extend_from_slice(hash);
+            let mut val_buf = [0u8; 8];
+            LittleEndian::write_u64(&mut val_buf, *value);
+            out.extend_from_slice(&val_buf);
+            script.serialize(out).map_err(|_| VPackError::EncodingError)
+        }
+        SiblingNode::Full(txout) => encode_txout(txout, out),
+    }
+}
+
+/// One path step in payload wire format (symmetric to `BoundedReader::read_genesis_item`).
+pub(crate) fn write_genesis_item(item: &GenesisItem, out: &mut Vec<u8>) -> Result<(), VPackError> {
+    // siblings_len (Borsh u32)
+    let siblings_len = item.siblings.len() as u32;
+    siblings_len
+        .serialize(out)
+        .map_err(|_| VPackError::EncodingError)?;
+    for sibling in item.siblings.iter() {
+        write_sibling(sibling, out)?;
+    }
+
+    item.parent_index
+        .serialize(out)
+        .map_err(|_| VPackError::EncodingError)?;
+    item.sequence
+        .serialize(out)
+        .map_err(|_| VPackError::EncodingError)?;
+    item.child_amount
+        .serialize(out)
+        .map_err(|_| VPackError::EncodingError)?;
+    item.child_script_pubkey
+        .serialize(out)
+        .map_err(|_| VPackError::EncodingError)?;
+    item.signature
+        .serialize(out)
+        .map_err(|_| VPackError::EncodingError)
+}
+
 /// Bitcoin consensus encoding for TxOut: value (8 LE) + compact size (script len) + script.
 ///
 /// SYMMETRY NOTE: This uses `write_compact_size` for script length, which matches
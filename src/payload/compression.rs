@@ -0,0 +1,65 @@
+//! LZ4 payload compression (`FLAG_COMPRESSION_LZ4`).
+//!
+//! When the flag is set, the payload following the header is stored compressed:
+//!
+//! ```text
+//! raw_len: u32 LE  — length of the uncompressed payload (1..=MAX_PAYLOAD_SIZE)
+//! block:   [u8]    — LZ4 block (no frame header) of the uncompressed payload
+//! ```
+//!
+//! `payload_len` and the CRC32 checksum in the header cover the **compressed** bytes exactly as
+//! stored. `raw_len` is checked against [`MAX_PAYLOAD_SIZE`] before any allocation, so a small
+//! compressed payload cannot expand past the same hard cap that bounds uncompressed V-PACKs.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::error::VPackError;
+use crate::header::MAX_PAYLOAD_SIZE;
+
+/// Size of the `raw_len` prefix in front of the LZ4 block.
+pub const COMPRESSED_PREFIX_LEN: usize = 4;
+
+/// Compresses a serialized payload into the `raw_len || lz4_block` wire layout.
+pub fn compress_payload(raw: &[u8]) -> Result<Vec<u8>, VPackError> {
+    if raw.is_empty() {
+        return Err(VPackError::EmptyPayload);
+    }
+    if raw.len() > MAX_PAYLOAD_SIZE as usize {
+        return Err(VPackError::PayloadTooLarge(raw.len() as u32));
+    }
+    let block = lz4_flex::block::compress(raw);
+    let mut out = Vec::with_capacity(COMPRESSED_PREFIX_LEN + block.len());
+    let mut len_buf = [0u8; COMPRESSED_PREFIX_LEN];
+    LittleEndian::write_u32(&mut len_buf, raw.len() as u32);
+    out.extend_from_slice(&len_buf);
+    out.extend_from_slice(&block);
+    Ok(out)
+}
+
+/// Decompresses a `raw_len || lz4_block` payload.
+///
+/// Rejects a declared `raw_len` of zero or above [`MAX_PAYLOAD_SIZE`] before allocating, and
+/// requires the LZ4 block to expand to exactly `raw_len` bytes.
+pub fn decompress_payload(data: &[u8]) -> Result<Vec<u8>, VPackError> {
+    if data.len() < COMPRESSED_PREFIX_LEN {
+        return Err(VPackError::IncompleteData);
+    }
+    let raw_len = LittleEndian::read_u32(&data[..COMPRESSED_PREFIX_LEN]);
+    if raw_len == 0 {
+        return Err(VPackError::EmptyPayload);
+    }
+    if raw_len > MAX_PAYLOAD_SIZE {
+        return Err(VPackError::PayloadTooLarge(raw_len));
+    }
+
+    let mut out = vec![0u8; raw_len as usize];
+    let written = lz4_flex::block::decompress_into(&data[COMPRESSED_PREFIX_LEN..], &mut out)
+        .map_err(|_| VPackError::CompressionError)?;
+    if written != raw_len as usize {
+        return Err(VPackError::CompressionError);
+    }
+    Ok(out)
+}
@@ -0,0 +1,119 @@
+//! Version-2 extension section: optional metadata appended to the payload as TLV records.
+//!
+//! ```text
+//! record := type (CompactSize) || length (CompactSize) || value (length bytes)
+//! ```
+//!
+//! Records follow the trailer (`asp_expiry_script`) and run to the end of the (uncompressed)
+//! payload, so they are covered by `payload_len`, the checksum and LZ4 compression. Types are
+//! strictly ascending. Following the "it's OK to be odd" rule, a reader that meets an unknown
+//! **even** type must reject the V-PACK; unknown **odd** types are skipped.
+//!
+//! Extensions never feed into VTXO ID reconstruction: a v2 V-PACK verifies to the same ID as the
+//! v1 V-PACK with the same tree.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::compact_size::{read_compact_size, write_compact_size};
+use crate::error::VPackError;
+use crate::types::hashes::Hash;
+use crate::types::Txid;
+
+/// Txid of the round (commitment) transaction the VTXO was issued in; 32 bytes, internal order.
+pub const TLV_ROUND_TXID: u64 = 1;
+/// Public key of the issuing ASP; 33-byte compressed SEC1.
+pub const TLV_ASP_PUBKEY: u64 = 3;
+/// Creation time in Unix seconds; u64 LE.
+pub const TLV_CREATED_AT: u64 = 5;
+/// Free-form wallet label; UTF-8.
+pub const TLV_LABEL: u64 = 7;
+
+/// Decoded extension section. All known types are odd (informational), so every field is optional.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Extensions {
+    pub round_txid: Option<Txid>,
+    pub asp_pubkey: Option<[u8; 33]>,
+    pub created_at: Option<u64>,
+    pub label: Option<String>,
+}
+
+impl Extensions {
+    /// `true` when no record would be written.
+    pub fn is_empty(&self) -> bool {
+        self.round_txid.is_none()
+            && self.asp_pubkey.is_none()
+            && self.created_at.is_none()
+            && self.label.is_none()
+    }
+
+    /// Decodes a complete extension section (all of `data`).
+    ///
+    /// Unknown odd types are ignored; unknown even types fail with
+    /// [`VPackError::UnknownExtension`]. Out-of-order or duplicate types, and known types with a
+    /// malformed value, fail with [`VPackError::InvalidExtension`].
+    pub fn decode(mut data: &[u8]) -> Result<Self, VPackError> {
+        let mut ext = Self::default();
+        let mut prev_type: Option<u64> = None;
+        while !data.is_empty() {
+            let (tlv_type, n) = read_compact_size(data).ok_or(VPackError::IncompleteData)?;
+            data = &data[n..];
+            let (len, n) = read_compact_size(data).ok_or(VPackError::IncompleteData)?;
+            data = &data[n..];
+            if prev_type.is_some_and(|prev| prev >= tlv_type) {
+                return Err(VPackError::InvalidExtension(tlv_type));
+            }
+            prev_type = Some(tlv_type);
+            let len = usize::try_from(len).map_err(|_| VPackError::IncompleteData)?;
+            if data.len() < len {
+                return Err(VPackError::IncompleteData);
+            }
+            let (value, rest) = data.split_at(len);
+            data = rest;
+
+            match tlv_type {
+                TLV_ROUND_TXID => {
+                    ext.round_txid = Some(Txid::from_byte_array(fixed(tlv_type, value)?));
+                }
+                TLV_ASP_PUBKEY => ext.asp_pubkey = Some(fixed(tlv_type, value)?),
+                TLV_CREATED_AT => {
+                    ext.created_at = Some(u64::from_le_bytes(fixed(tlv_type, value)?));
+                }
+                TLV_LABEL => {
+                    let label = core::str::from_utf8(value)
+                        .map_err(|_| VPackError::InvalidExtension(tlv_type))?;
+                    ext.label = Some(String::from(label));
+                }
+                even if even % 2 == 0 => return Err(VPackError::UnknownExtension(even)),
+                _ => {}
+            }
+        }
+        Ok(ext)
+    }
+
+    /// Appends the records for the fields that are set, in ascending type order.
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        if let Some(txid) = &self.round_txid {
+            write_record(out, TLV_ROUND_TXID, &txid.to_byte_array());
+        }
+        if let Some(pubkey) = &self.asp_pubkey {
+            write_record(out, TLV_ASP_PUBKEY, pubkey);
+        }
+        if let Some(created_at) = self.created_at {
+            write_record(out, TLV_CREATED_AT, &created_at.to_le_bytes());
+        }
+        if let Some(label) = &self.label {
+            write_record(out, TLV_LABEL, label.as_bytes());
+        }
+    }
+}
+
+fn write_record(out: &mut Vec<u8>, tlv_type: u64, value: &[u8]) {
+    write_compact_size(out, tlv_type);
+    write_compact_size(out, value.len() as u64);
+    out.extend_from_slice(value);
+}
+
+fn fixed<const N: usize>(tlv_type: u64, value: &[u8]) -> Result<[u8; N], VPackError> {
+    <[u8; N]>::try_from(value).map_err(|_| VPackError::InvalidExtension(tlv_type))
+}
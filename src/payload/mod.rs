@@ -1,4 +1,7 @@
+pub mod compression;
+pub mod extensions;
 pub mod reader;
+pub mod stream;
 pub mod tree;
 
 #[cfg(test)]
@@ -6,11 +9,16 @@ mod tests;
 
 use crate::error::VPackError;
 use crate::header::Header;
-use crate::payload::tree::{SiblingNode, VPackTree};
+use crate::payload::tree::{SiblingNodeRef, VPackTree, VPackTreeRef};
 
 /// Validates global policy invariants: fee_anchor_script consistency and sequence/exit_delta
 /// consistency along the path. Call after parsing and before engine verification.
 pub fn validate_invariants(header: &Header, tree: &VPackTree) -> Result<(), VPackError> {
+    validate_invariants_ref(header, &VPackTreeRef::from(tree))
+}
+
+/// [`validate_invariants`] over a borrowed tree.
+pub fn validate_invariants_ref(header: &Header, tree: &VPackTreeRef<'_>) -> Result<(), VPackError> {
     // Sequence consistency: every path item must match leaf sequence.
     for item in &tree.path {
         if item.sequence != tree.leaf.sequence {
@@ -23,15 +31,10 @@ pub fn validate_invariants(header: &Header, tree: &VPackTree) -> Result<(), VPac
     if matches!(header.tx_variant, crate::header::TxVariant::V3Anchored)
         && !tree.fee_anchor_script.is_empty()
     {
-        let has_fee_anchor = |siblings: &[SiblingNode]| {
-            siblings.iter().any(|s| match s {
-                SiblingNode::Compact { script, .. } => {
-                    script.as_slice() == tree.fee_anchor_script.as_slice()
-                }
-                SiblingNode::Full(txout) => {
-                    txout.script_pubkey.as_bytes() == tree.fee_anchor_script.as_slice()
-                }
-            })
+        let has_fee_anchor = |siblings: &[SiblingNodeRef<'_>]| {
+            siblings
+                .iter()
+                .any(|s| s.script() == tree.fee_anchor_script)
         };
         for item in &tree.path {
             if !item.siblings.is_empty() && !has_fee_anchor(&item.siblings) {
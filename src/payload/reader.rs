@@ -5,22 +5,96 @@
 
 use crate::error::VPackError;
 use crate::header::{Header, TxVariant};
-use crate::payload::tree::{GenesisItem, SiblingNode, VPackTree, VtxoLeaf};
-use crate::types::{decode_outpoint, Amount, ScriptBuf, TxOut};
+use crate::payload::compression::decompress_payload;
+use crate::payload::extensions::Extensions;
+use crate::payload::tree::{GenesisItemRef, SiblingNodeRef, VPackTree, VPackTreeRef, VtxoLeafRef};
+use crate::types::{decode_outpoint, OutPoint};
 use alloc::vec::Vec;
 use byteorder::{ByteOrder, LittleEndian};
 
+/// Prefix section fields: (asset_id, anchor, fee_anchor_script).
+pub(crate) type PrefixFields<'a> = (Option<[u8; 32]>, OutPoint, &'a [u8]);
+
 /// The Bounded Reader.
-/// Parses a byte slice into a VPackTree, enforcing Header limits and
-/// using the correct serialization format for each field type.
+/// Parses a byte slice into a VPackTree (or a borrowed VPackTreeRef), enforcing Header limits
+/// and using the correct serialization format for each field type.
 pub struct BoundedReader;
 
 impl BoundedReader {
-    pub fn parse(header: &Header, mut data: &[u8]) -> Result<VPackTree, VPackError> {
+    /// Parses the payload that follows `header`. When `FLAG_COMPRESSION_LZ4` is set, `data` is
+    /// the stored (compressed) payload and is decompressed before parsing.
+    pub fn parse(header: &Header, data: &[u8]) -> Result<VPackTree, VPackError> {
+        Self::parse_with_extensions(header, data).map(|(tree, _)| tree)
+    }
+
+    /// [`Self::parse`] that also returns the version-2 extension section (empty for version 1).
+    pub fn parse_with_extensions(
+        header: &Header,
+        data: &[u8],
+    ) -> Result<(VPackTree, Extensions), VPackError> {
+        if header.is_compressed() {
+            let raw = decompress_payload(data)?;
+            let (tree, ext) = Self::parse_ref_with_extensions(header, &raw)?;
+            return Ok((VPackTree::from(&tree), ext));
+        }
+        let (tree, ext) = Self::parse_ref_with_extensions(header, data)?;
+        Ok((VPackTree::from(&tree), ext))
+    }
+
+    /// Zero-copy parse: scripts and signatures in the returned tree borrow from `data`.
+    ///
+    /// `data` must be the **uncompressed** payload; `FLAG_COMPRESSION_LZ4` is not handled here
+    /// (decompress with [`decompress_payload`] first and parse from that buffer).
+    pub fn parse_ref<'a>(header: &Header, data: &'a [u8]) -> Result<VPackTreeRef<'a>, VPackError> {
+        Self::parse_ref_with_extensions(header, data).map(|(tree, _)| tree)
+    }
+
+    /// [`Self::parse_ref`] that also returns the version-2 extension section.
+    pub fn parse_ref_with_extensions<'a>(
+        header: &Header,
+        mut data: &'a [u8],
+    ) -> Result<(VPackTreeRef<'a>, Extensions), VPackError> {
         // ---------------------------------------------------------
         // 1. Parse Prefix Section (Fail-Fast). All three before Tree.
-        // Order: Asset ID (conditional) → Anchor OutPoint → fee_anchor_script.
         // ---------------------------------------------------------
+        let (asset_id, anchor, fee_anchor_script) = Self::read_prefix(header, &mut data)?;
+
+        // ---------------------------------------------------------
+        // 2. Parse Tree Section (explicit slice advancing only)
+        // ---------------------------------------------------------
+        let (leaf, leaf_siblings, path_len) = Self::read_leaf(header, &mut data)?;
+
+        let mut path = Vec::with_capacity(path_len as usize);
+        for _item_idx in 0..path_len {
+            path.push(Self::read_genesis_item(header, &mut data)?);
+        }
+
+        let (internal_key, asp_expiry_script) = Self::read_trailer(&mut data)?;
+
+        // ---------------------------------------------------------
+        // 3. Extension Section (v2) — or nothing at all (v1)
+        // ---------------------------------------------------------
+        let extensions = Self::read_extensions(header, data)?;
+
+        let tree = VPackTreeRef {
+            leaf,
+            leaf_siblings,
+            path,
+            anchor,
+            asset_id,
+            fee_anchor_script,
+            internal_key,
+            asp_expiry_script,
+        };
+        Ok((tree, extensions))
+    }
+
+    /// Prefix section: Asset ID (conditional) → Anchor OutPoint → fee_anchor_script.
+    pub(crate) fn read_prefix<'a>(
+        header: &Header,
+        cursor: &mut &'a [u8],
+    ) -> Result<PrefixFields<'a>, VPackError> {
+        let mut data = *cursor;
 
         // A. Asset ID (Optional, 32 bytes if Flags & 0x08)
         let asset_id = if header.has_asset_id() {
@@ -56,14 +130,21 @@ impl BoundedReader {
         }
         let (script_bytes, rest) = data.split_at(script_len);
         data = rest;
-        let fee_anchor_script = script_bytes.to_vec();
+        let fee_anchor_script = script_bytes;
         if matches!(header.tx_variant, TxVariant::V3Anchored) && fee_anchor_script.is_empty() {
             return Err(VPackError::FeeAnchorMissing);
         }
 
-        // ---------------------------------------------------------
-        // 2. Parse Tree Section (explicit slice advancing only)
-        // ---------------------------------------------------------
+        *cursor = data;
+        Ok((asset_id, anchor, fee_anchor_script))
+    }
+
+    /// Leaf record: the VTXO leaf, its siblings, and the (depth-checked) path length that follows.
+    pub(crate) fn read_leaf<'a>(
+        header: &Header,
+        cursor: &mut &'a [u8],
+    ) -> Result<(VtxoLeafRef<'a>, Vec<SiblingNodeRef<'a>>, u32), VPackError> {
+        let mut data = *cursor;
 
         // A. Leaf: amount(8) + vout(4) + sequence(4) + expiry(4) + exit_delta(2) + Borsh Vec<u8>(4+len)
         const LEAF_FIXED: usize = 8 + 4 + 4 + 4 + 2; // 22
@@ -85,13 +166,13 @@ impl BoundedReader {
         }
         let (leaf_script_bytes, rest) = data.split_at(leaf_script_len);
         data = rest;
-        let leaf = VtxoLeaf {
+        let leaf = VtxoLeafRef {
             amount,
             vout,
             sequence,
             expiry,
             exit_delta,
-            script_pubkey: leaf_script_bytes.to_vec(),
+            script_pubkey: leaf_script_bytes,
         };
 
         // B. leaf_siblings length (Borsh u32 = 4 bytes LE)
@@ -121,86 +202,99 @@ impl BoundedReader {
             return Err(VPackError::ExceededMaxDepth(path_len as u16));
         }
 
-        let mut path = Vec::with_capacity(path_len as usize);
+        *cursor = data;
+        Ok((leaf, leaf_siblings, path_len))
+    }
 
-        for _item_idx in 0..path_len {
-            // D. Siblings length (V-PACK standard: Borsh u32 = 4 bytes LE; do not use u16)
-            if data.len() < 4 {
-                return Err(VPackError::IncompleteData);
-            }
-            let siblings_len = LittleEndian::read_u32(&data[0..4]);
-            let (_, rest) = data.split_at(4);
-            data = rest;
+    /// One path step (`GenesisItem`).
+    pub(crate) fn read_genesis_item<'a>(
+        header: &Header,
+        cursor: &mut &'a [u8],
+    ) -> Result<GenesisItemRef<'a>, VPackError> {
+        let mut data = *cursor;
 
-            // SECURITY CHECK: Tree Arity
-            if siblings_len > header.tree_arity as u32 {
-                return Err(VPackError::ExceededMaxArity(siblings_len as u16));
-            }
+        // D. Siblings length (V-PACK standard: Borsh u32 = 4 bytes LE; do not use u16)
+        if data.len() < 4 {
+            return Err(VPackError::IncompleteData);
+        }
+        let siblings_len = LittleEndian::read_u32(&data[0..4]);
+        let (_, rest) = data.split_at(4);
+        data = rest;
 
-            let siblings = Self::parse_siblings(header, &mut data, siblings_len as usize)?;
+        // SECURITY CHECK: Tree Arity
+        if siblings_len > header.tree_arity as u32 {
+            return Err(VPackError::ExceededMaxArity(siblings_len as u16));
+        }
 
-            // E. parent_index(4) + sequence(4) + child_amount(8) + child_script_pubkey(4+len) + signature(1 or 1+64)
-            if data.len() < 4 {
-                return Err(VPackError::IncompleteData);
-            }
-            let parent_index = LittleEndian::read_u32(&data[0..4]);
-            let (_, rest) = data.split_at(4);
-            data = rest;
-            if data.len() < 4 {
-                return Err(VPackError::IncompleteData);
-            }
-            let sequence = LittleEndian::read_u32(&data[0..4]);
-            let (_, rest) = data.split_at(4);
-            data = rest;
-            if data.len() < 8 {
-                return Err(VPackError::IncompleteData);
-            }
-            let child_amount = LittleEndian::read_u64(&data[0..8]);
-            let (_, rest) = data.split_at(8);
-            data = rest;
-            if data.len() < 4 {
-                return Err(VPackError::IncompleteData);
-            }
-            let child_script_len = LittleEndian::read_u32(&data[0..4]) as usize;
-            let (_, rest) = data.split_at(4);
-            data = rest;
-            if data.len() < child_script_len {
-                return Err(VPackError::IncompleteData);
-            }
-            let (child_script_slice, rest) = data.split_at(child_script_len);
-            data = rest;
-            let child_script_pubkey = child_script_slice.to_vec();
-            if data.is_empty() {
+        let siblings = Self::parse_siblings(header, &mut data, siblings_len as usize)?;
+
+        // E. parent_index(4) + sequence(4) + child_amount(8) + child_script_pubkey(4+len) + signature(1 or 1+64)
+        if data.len() < 4 {
+            return Err(VPackError::IncompleteData);
+        }
+        let parent_index = LittleEndian::read_u32(&data[0..4]);
+        let (_, rest) = data.split_at(4);
+        data = rest;
+        if data.len() < 4 {
+            return Err(VPackError::IncompleteData);
+        }
+        let sequence = LittleEndian::read_u32(&data[0..4]);
+        let (_, rest) = data.split_at(4);
+        data = rest;
+        if data.len() < 8 {
+            return Err(VPackError::IncompleteData);
+        }
+        let child_amount = LittleEndian::read_u64(&data[0..8]);
+        let (_, rest) = data.split_at(8);
+        data = rest;
+        if data.len() < 4 {
+            return Err(VPackError::IncompleteData);
+        }
+        let child_script_len = LittleEndian::read_u32(&data[0..4]) as usize;
+        let (_, rest) = data.split_at(4);
+        data = rest;
+        if data.len() < child_script_len {
+            return Err(VPackError::IncompleteData);
+        }
+        let (child_script_slice, rest) = data.split_at(child_script_len);
+        data = rest;
+        let child_script_pubkey = child_script_slice;
+        if data.is_empty() {
+            return Err(VPackError::IncompleteData);
+        }
+        let sig_tag = data[0];
+        let (_, rest) = data.split_at(1);
+        data = rest;
+        let signature = if sig_tag == 0 {
+            None
+        } else if sig_tag == 1 {
+            if data.len() < 64 {
                 return Err(VPackError::IncompleteData);
             }
-            let sig_tag = data[0];
-            let (_, rest) = data.split_at(1);
+            let (sig_bytes, rest) = data.split_at(64);
             data = rest;
-            let signature = if sig_tag == 0 {
-                None
-            } else if sig_tag == 1 {
-                if data.len() < 64 {
-                    return Err(VPackError::IncompleteData);
-                }
-                let (sig_bytes, rest) = data.split_at(64);
-                data = rest;
-                let mut arr = [0u8; 64];
-                arr.copy_from_slice(sig_bytes);
-                Some(arr)
-            } else {
-                return Err(VPackError::EncodingError);
-            };
+            Some(<&[u8; 64]>::try_from(sig_bytes).map_err(|_| VPackError::IncompleteData)?)
+        } else {
+            return Err(VPackError::EncodingError);
+        };
 
-            path.push(GenesisItem {
-                siblings,
-                parent_index,
-                sequence,
-                child_amount,
-                child_script_pubkey,
-                signature,
-                ..Default::default()
-            });
-        }
+        *cursor = data;
+        Ok(GenesisItemRef {
+            siblings,
+            parent_index,
+            sequence,
+            child_amount,
+            child_script_pubkey,
+            signature,
+            sighash_flag: 0x00,
+        })
+    }
+
+    /// Trailer: internal_key (32 raw bytes) → asp_expiry_script.
+    pub(crate) fn read_trailer<'a>(
+        cursor: &mut &'a [u8],
+    ) -> Result<([u8; 32], &'a [u8]), VPackError> {
+        let mut data = *cursor;
 
         // G. internal_key (32 raw bytes)
         if data.len() < 32 {
@@ -222,29 +316,29 @@ impl BoundedReader {
         }
         let (asp_script_bytes, rest) = data.split_at(asp_script_len);
         data = rest;
-        let asp_expiry_script = asp_script_bytes.to_vec();
+        let asp_expiry_script = asp_script_bytes;
 
-        if !data.is_empty() {
-            return Err(VPackError::TrailingData(data.len()));
-        }
+        *cursor = data;
+        Ok((internal_key, asp_expiry_script))
+    }
 
-        Ok(VPackTree {
-            leaf,
-            leaf_siblings,
-            path,
-            anchor,
-            asset_id,
-            fee_anchor_script,
-            internal_key,
-            asp_expiry_script,
-        })
+    /// Everything after the trailer: the TLV extension section for version 2; version 1
+    /// payloads must end at the trailer.
+    pub(crate) fn read_extensions(header: &Header, data: &[u8]) -> Result<Extensions, VPackError> {
+        if header.has_extensions() {
+            Extensions::decode(data)
+        } else if !data.is_empty() {
+            Err(VPackError::TrailingData(data.len()))
+        } else {
+            Ok(Extensions::default())
+        }
     }
 
-    fn parse_siblings(
+    fn parse_siblings<'a>(
         header: &Header,
-        data: &mut &[u8],
+        data: &mut &'a [u8],
         len: usize,
-    ) -> Result<Vec<SiblingNode>, VPackError> {
+    ) -> Result<Vec<SiblingNodeRef<'a>>, VPackError> {
         let mut siblings = Vec::with_capacity(len);
         for _ in 0..len {
             let sibling = if header.is_compact() {
@@ -257,9 +351,10 @@ impl BoundedReader {
                 if data.len() < 32 {
                     return Err(VPackError::IncompleteData);
                 }
-                let mut hash = [0u8; 32];
-                hash.copy_from_slice(&data[..32]);
-                *data = &data[32..];
+                let (hash_bytes, rest) = data.split_at(32);
+                let hash =
+                    <&[u8; 32]>::try_from(hash_bytes).map_err(|_| VPackError::IncompleteData)?;
+                *data = rest;
 
                 // 2. Read Value (8B LE)
                 if data.len() < 8 {
@@ -280,9 +375,9 @@ impl BoundedReader {
                 }
                 let (script_slice, rest) = data.split_at(script_len);
                 *data = rest;
-                let script = script_slice.to_vec();
+                let script = script_slice;
 
-                SiblingNode::Compact {
+                SiblingNodeRef::Compact {
                     hash,
                     value,
                     script,
@@ -336,11 +431,7 @@ impl BoundedReader {
                 }
                 let script_bytes = &cursor[..script_len_usize];
 
-                // 4. Reconstruct TxOut and advance data slice
-                let txout = TxOut {
-                    value: Amount::from_sat(value),
-                    script_pubkey: ScriptBuf::from_bytes(script_bytes.to_vec()),
-                };
+                // 4. Advance data slice (script borrowed, TxOut materialized only on to-owned)
 
                 // Calculate total consumed: 8 (value) + VarInt bytes + script bytes
                 let varint_bytes = if script_len < 0xfd {
@@ -355,7 +446,10 @@ impl BoundedReader {
                 let total_consumed = 8 + varint_bytes + script_len_usize;
                 *data = &data[total_consumed..]; // EXPLICITLY ADVANCE THE SLICE
 
-                SiblingNode::Full(txout)
+                SiblingNodeRef::Full {
+                    value,
+                    script: script_bytes,
+                }
             };
             siblings.push(sibling);
         }
@@ -0,0 +1,287 @@
+//! Incremental V-PACK parser.
+//!
+//! [`StreamingReader`] accepts a V-PACK in arbitrary chunks (BLE frames, QR parts, socket reads)
+//! and yields one [`StreamRecord`] at a time, in wire order:
+//!
+//! ```text
+//! Header → Prefix → Leaf → Step × path_len → Trailer
+//! ```
+//!
+//! For version-2 V-PACKs the Trailer record also carries the extension section, which runs to the
+//! end of the payload and is therefore buffered together with the trailer.
+//!
+//! At most one record is buffered at a time (bounded by [`StreamingReader::with_max_record_len`]),
+//! and the CRC32 is updated as bytes arrive instead of over a fully buffered payload.
+//!
+//! **Records are unauthenticated until the Trailer.** The checksum can only be compared once the
+//! last payload byte has been seen, so the Trailer record is emitted only after the CRC matches.
+//! Consumers must not act on earlier records (other than folding them, e.g. with
+//! [`VtxoIdFold`](crate::consensus::VtxoIdFold)) until the Trailer arrives. Because records are
+//! parsed before the CRC is known, a corrupted payload may surface as a structural error
+//! (e.g. [`VPackError::ExceededMaxArity`]) rather than [`VPackError::ChecksumMismatch`].
+//!
+//! For ASP-signed V-PACKs (`FLAG_ASP_SIGNED`) the 64-byte signature after the payload is not
+//! consumed and is left to the caller, like any other trailing input.
+//!
+//! Compressed payloads (`FLAG_COMPRESSION_LZ4`) cannot be streamed and are rejected with
+//! [`VPackError::CompressionError`] at the header.
+
+use alloc::vec::Vec;
+
+use crate::error::VPackError;
+use crate::header::{Header, HEADER_SIZE};
+use crate::payload::extensions::Extensions;
+use crate::payload::reader::BoundedReader;
+use crate::payload::tree::{GenesisItem, SiblingNode, VtxoLeaf};
+use crate::types::OutPoint;
+
+/// Default cap on the bytes buffered for a single record (64 KiB).
+pub const DEFAULT_MAX_RECORD_LEN: usize = 64 * 1024;
+
+/// One parsed section of a V-PACK, in wire order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamRecord {
+    /// The validated 24-byte header.
+    Header(Header),
+    /// Prefix section: optional Asset ID, anchor OutPoint, fee anchor script.
+    Prefix {
+        asset_id: Option<[u8; 32]>,
+        anchor: OutPoint,
+        fee_anchor_script: Vec<u8>,
+    },
+    /// The VTXO leaf, its siblings, and the number of `Step` records that follow.
+    Leaf {
+        leaf: VtxoLeaf,
+        leaf_siblings: Vec<SiblingNode>,
+        path_len: u32,
+    },
+    /// One path step (root first).
+    Step(GenesisItem),
+    /// Exclusivity data and the extension section (empty for version 1). Emitted only after the
+    /// payload CRC32 has been verified.
+    Trailer {
+        internal_key: [u8; 32],
+        asp_expiry_script: Vec<u8>,
+        extensions: Extensions,
+    },
+}
+
+/// Result of one [`StreamingReader::feed`] call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Progress {
+    /// All of the input was buffered; feed more bytes.
+    NeedMore,
+    /// A record was completed. `consumed` bytes of the input were taken; feed the rest again
+    /// (an empty slice is fine — already buffered bytes are parsed first).
+    Record {
+        consumed: usize,
+        record: StreamRecord,
+    },
+    /// The V-PACK is complete; `consumed` is always 0 and any input belongs to the caller.
+    Done { consumed: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Header,
+    Prefix,
+    Leaf,
+    Path,
+    Trailer,
+    Done,
+}
+
+/// Push-style V-PACK parser. See the [module docs](self) for the record order and trust model.
+///
+/// After `feed` returns an error the reader is in an unspecified state and should be dropped.
+pub struct StreamingReader {
+    stage: Stage,
+    header: Option<Header>,
+    pending: Vec<u8>,
+    hasher: crc32fast::Hasher,
+    payload_seen: usize,
+    paths_remaining: u32,
+    max_record_len: usize,
+}
+
+impl Default for StreamingReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamingReader {
+    /// Creates a reader with the [`DEFAULT_MAX_RECORD_LEN`] record cap.
+    pub fn new() -> Self {
+        Self {
+            stage: Stage::Header,
+            header: None,
+            pending: Vec::new(),
+            hasher: crc32fast::Hasher::new(),
+            payload_seen: 0,
+            paths_remaining: 0,
+            max_record_len: DEFAULT_MAX_RECORD_LEN,
+        }
+    }
+
+    /// Sets the maximum number of bytes buffered for one record (never below the header size).
+    pub fn with_max_record_len(mut self, max_record_len: usize) -> Self {
+        self.max_record_len = max_record_len.max(HEADER_SIZE);
+        self
+    }
+
+    /// The parsed header, once the `Header` record has been emitted.
+    pub fn header(&self) -> Option<&Header> {
+        self.header.as_ref()
+    }
+
+    /// `true` once the Trailer has been emitted (CRC verified).
+    pub fn is_done(&self) -> bool {
+        self.stage == Stage::Done
+    }
+
+    /// Feeds the next chunk of the V-PACK.
+    ///
+    /// Takes at most what the current record can still need (never past `payload_len`), then
+    /// tries to complete one record. Bytes past the end of the V-PACK are never consumed.
+    pub fn feed(&mut self, input: &[u8]) -> Result<Progress, VPackError> {
+        if self.stage == Stage::Done {
+            return Ok(Progress::Done { consumed: 0 });
+        }
+
+        let room = self.max_record_len.saturating_sub(self.pending.len());
+        let take = match self.header {
+            None => (HEADER_SIZE - self.pending.len()).min(input.len()),
+            Some(header) => {
+                let remaining = header.payload_len as usize - self.payload_seen;
+                let take = remaining.min(input.len()).min(room);
+                self.hasher.update(&input[..take]);
+                self.payload_seen += take;
+                take
+            }
+        };
+        self.pending.extend_from_slice(&input[..take]);
+
+        match self.next_record() {
+            Ok(Some(record)) => Ok(Progress::Record {
+                consumed: take,
+                record,
+            }),
+            Ok(None) if take < input.len() => Err(VPackError::RecordTooLarge(self.pending.len())),
+            Ok(None) => Ok(Progress::NeedMore),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parses one record from `pending`. `Ok(None)` means more payload bytes are needed.
+    fn next_record(&mut self) -> Result<Option<StreamRecord>, VPackError> {
+        let Some(header) = self.header else {
+            if self.pending.len() < HEADER_SIZE {
+                return Ok(None);
+            }
+            let header = Header::from_bytes(&self.pending)?;
+            if header.is_compressed() {
+                return Err(VPackError::CompressionError);
+            }
+            // CRC covers header bytes 0..20 (checksum field excluded), then the payload.
+            self.hasher.update(&self.pending[..20]);
+            self.pending.clear();
+            self.header = Some(header);
+            self.stage = Stage::Prefix;
+            return Ok(Some(StreamRecord::Header(header)));
+        };
+
+        let mut cursor = self.pending.as_slice();
+        let parsed =
+            match self.stage {
+                Stage::Prefix => BoundedReader::read_prefix(&header, &mut cursor).map(
+                    |(asset_id, anchor, fee)| StreamRecord::Prefix {
+                        asset_id,
+                        anchor,
+                        fee_anchor_script: fee.to_vec(),
+                    },
+                ),
+                Stage::Leaf => BoundedReader::read_leaf(&header, &mut cursor).map(
+                    |(leaf, leaf_siblings, path_len)| StreamRecord::Leaf {
+                        leaf: VtxoLeaf::from(&leaf),
+                        leaf_siblings: leaf_siblings.iter().map(SiblingNode::from).collect(),
+                        path_len,
+                    },
+                ),
+                Stage::Path => BoundedReader::read_genesis_item(&header, &mut cursor)
+                    .map(|item| StreamRecord::Step(GenesisItem::from(&item))),
+                Stage::Trailer => BoundedReader::read_trailer(&mut cursor).and_then(
+                    |(internal_key, asp_expiry_script)| {
+                        let extensions = if header.has_extensions() {
+                            // The extension section has no length prefix: wait for the whole payload.
+                            if self.payload_seen < header.payload_len as usize {
+                                return Err(VPackError::IncompleteData);
+                            }
+                            let ext = BoundedReader::read_extensions(&header, cursor)?;
+                            cursor = &[];
+                            ext
+                        } else {
+                            Extensions::default()
+                        };
+                        Ok(StreamRecord::Trailer {
+                            internal_key,
+                            asp_expiry_script: asp_expiry_script.to_vec(),
+                            extensions,
+                        })
+                    },
+                ),
+                Stage::Header | Stage::Done => return Err(VPackError::EncodingError),
+            };
+
+        let record = match parsed {
+            Ok(record) => record,
+            Err(VPackError::IncompleteData) => {
+                return if self.payload_seen == header.payload_len as usize {
+                    // Every payload byte is buffered and the record is still short.
+                    Err(VPackError::IncompleteData)
+                } else if self.pending.len() >= self.max_record_len {
+                    Err(VPackError::RecordTooLarge(self.pending.len()))
+                } else {
+                    Ok(None)
+                };
+            }
+            Err(e) => return Err(e),
+        };
+        let used = self.pending.len() - cursor.len();
+
+        self.stage = match &record {
+            StreamRecord::Header(_) => Stage::Prefix,
+            StreamRecord::Prefix { .. } => Stage::Leaf,
+            StreamRecord::Leaf { path_len: 0, .. } => Stage::Trailer,
+            StreamRecord::Leaf { path_len, .. } => {
+                self.paths_remaining = *path_len;
+                Stage::Path
+            }
+            StreamRecord::Step(_) => {
+                self.paths_remaining -= 1;
+                if self.paths_remaining == 0 {
+                    Stage::Trailer
+                } else {
+                    Stage::Path
+                }
+            }
+            StreamRecord::Trailer { .. } => {
+                let trailing =
+                    (self.pending.len() - used) + (header.payload_len as usize - self.payload_seen);
+                if trailing != 0 {
+                    return Err(VPackError::TrailingData(trailing));
+                }
+                let found = self.hasher.clone().finalize();
+                if found != header.checksum {
+                    return Err(VPackError::ChecksumMismatch {
+                        expected: header.checksum,
+                        found,
+                    });
+                }
+                Stage::Done
+            }
+        };
+        self.pending.drain(..used);
+        Ok(Some(record))
+    }
+}
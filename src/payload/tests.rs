@@ -1,13 +1,24 @@
 //! Payload serialization round-trip tests.
 
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
-use crate::header::{Header, TxVariant, FLAG_PROOF_COMPACT, HEADER_SIZE};
+use crate::error::VPackError;
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
-use crate::pack::pack;
+use crate::header::{
+    Header, Network, TxVariant, FLAG_COMPRESSION_LZ4, FLAG_NETWORK_REGTEST, FLAG_NETWORK_SIGNET,
+    FLAG_PROOF_COMPACT, FLAG_TESTNET, HEADER_SIZE, MAX_PAYLOAD_SIZE, VERSION_TLV,
+};
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+use crate::pack::{pack, pack_from_payload, pack_with_extensions};
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+use crate::payload::compression::decompress_payload;
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+use crate::payload::extensions::Extensions;
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
 use crate::payload::reader::BoundedReader;
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
-use crate::payload::tree::{VPackTree, VtxoLeaf};
+use crate::payload::stream::{Progress, StreamRecord, StreamingReader};
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+use crate::payload::tree::{VPackTree, VPackTreeRef, VtxoLeaf};
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
 use crate::types::hashes::Hash;
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
@@ -15,6 +26,43 @@ use crate::types::{OutPoint, Txid};
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
 use alloc::vec::Vec;
 
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn sample_tree() -> VPackTree {
+    let txid = Txid::from_byte_array([0x42u8; 32]);
+    VPackTree {
+        leaf: VtxoLeaf {
+            amount: 1000,
+            vout: 0,
+            sequence: 0,
+            expiry: 0,
+            exit_delta: 0,
+            script_pubkey: alloc::vec![0x51, 0x20, 0x00],
+        },
+        leaf_siblings: Vec::new(),
+        path: Vec::new(),
+        anchor: OutPoint { txid, vout: 0 },
+        asset_id: None,
+        fee_anchor_script: alloc::vec![0x51, 0x02, 0x4e, 0x73],
+        internal_key: [0xAAu8; 32],
+        asp_expiry_script: alloc::vec![0x51, 0x02],
+    }
+}
+
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn sample_header(flags: u8) -> Header {
+    Header {
+        flags,
+        version: 1,
+        tx_variant: TxVariant::V3Plain,
+        tree_arity: 16,
+        tree_depth: 32,
+        node_count: 0,
+        asset_type: 0,
+        payload_len: 0,
+        checksum: 0,
+    }
+}
+
 /// Round-trip: serialize VPackTree to bytes, deserialize back, assert equality.
 #[test]
 #[cfg(any(feature = "bitcoin", feature = "wasm"))]
@@ -65,3 +113,358 @@ fn test_vpack_tree_serialization_roundtrip() {
     assert_eq!(parsed_tree.internal_key, [0xAAu8; 32]);
     assert_eq!(parsed_tree.asp_expiry_script, asp_expiry_script);
 }
+
+/// LZ4 round-trip: compressed payload parses back to the same tree; checksum covers compressed bytes.
+#[test]
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn test_vpack_tree_lz4_roundtrip() {
+    let tree = sample_tree();
+    let plain = pack(&sample_header(FLAG_PROOF_COMPACT), &tree).expect("pack plain");
+    let packed = pack(
+        &sample_header(FLAG_PROOF_COMPACT | FLAG_COMPRESSION_LZ4),
+        &tree,
+    )
+    .expect("pack lz4");
+
+    let parsed_header = Header::from_bytes(&packed[..HEADER_SIZE]).expect("parse header");
+    assert!(parsed_header.is_compressed());
+    let payload = &packed[HEADER_SIZE..];
+    assert_eq!(parsed_header.payload_len as usize, payload.len());
+    parsed_header
+        .verify_checksum(payload)
+        .expect("checksum over compressed bytes");
+    assert_eq!(
+        decompress_payload(payload).expect("decompress"),
+        &plain[HEADER_SIZE..]
+    );
+
+    let parsed_tree = BoundedReader::parse(&parsed_header, payload).expect("parse payload");
+    assert_eq!(tree, parsed_tree);
+}
+
+/// Declared uncompressed length above MAX_PAYLOAD_SIZE is rejected before decompressing.
+#[test]
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn test_lz4_decompressed_size_bounded() {
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&(MAX_PAYLOAD_SIZE + 1).to_le_bytes());
+    payload.extend_from_slice(&[0x00; 8]);
+    let header = sample_header(FLAG_PROOF_COMPACT | FLAG_COMPRESSION_LZ4);
+    assert_eq!(
+        BoundedReader::parse(&header, &payload),
+        Err(VPackError::PayloadTooLarge(MAX_PAYLOAD_SIZE + 1))
+    );
+}
+
+/// Declared uncompressed length that the LZ4 block does not fill is rejected.
+#[test]
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn test_lz4_length_mismatch_rejected() {
+    let tree = sample_tree();
+    let packed = pack(
+        &sample_header(FLAG_PROOF_COMPACT | FLAG_COMPRESSION_LZ4),
+        &tree,
+    )
+    .expect("pack lz4");
+    let mut payload = packed[HEADER_SIZE..].to_vec();
+    let raw_len = u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]);
+    payload[..4].copy_from_slice(&(raw_len + 1).to_le_bytes());
+    let header = sample_header(FLAG_PROOF_COMPACT | FLAG_COMPRESSION_LZ4);
+    assert_eq!(
+        BoundedReader::parse(&header, &payload),
+        Err(VPackError::CompressionError)
+    );
+}
+
+/// Zero-copy parse borrows scripts from the input buffer and converts back to the owned tree.
+#[test]
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn test_parse_ref_borrows_payload() {
+    let tree = sample_tree();
+    let packed = pack(&sample_header(FLAG_PROOF_COMPACT), &tree).expect("pack");
+    let header = Header::from_bytes(&packed[..HEADER_SIZE]).expect("parse header");
+    let payload = &packed[HEADER_SIZE..];
+
+    let tree_ref = BoundedReader::parse_ref(&header, payload).expect("parse_ref");
+    let range = payload.as_ptr_range();
+    assert!(range.contains(&tree_ref.leaf.script_pubkey.as_ptr()));
+    assert!(range.contains(&tree_ref.asp_expiry_script.as_ptr()));
+    assert_eq!(VPackTree::from(&tree_ref), tree);
+    assert_eq!(VPackTreeRef::from(&tree), tree_ref);
+}
+
+/// A compressed V-PACK cannot be borrowed from; `verify_ref` rejects it after the checksum.
+#[test]
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn test_verify_ref_rejects_compressed() {
+    let tree = sample_tree();
+    let packed = pack(
+        &sample_header(FLAG_PROOF_COMPACT | FLAG_COMPRESSION_LZ4),
+        &tree,
+    )
+    .expect("pack lz4");
+    let id = crate::consensus::VtxoId::Raw([0u8; 32]);
+    assert_eq!(
+        crate::verify_ref(&packed, &id, 1000, Network::Mainnet),
+        Err(VPackError::CompressionError)
+    );
+}
+
+/// Input shorter than a header is rejected before any slicing.
+#[test]
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn test_verify_ref_rejects_short_input() {
+    let id = crate::consensus::VtxoId::Raw([0u8; 32]);
+    for len in [0, 1, HEADER_SIZE - 1] {
+        assert_eq!(
+            crate::verify_ref(&[0u8; HEADER_SIZE][..len], &id, 1000, Network::Mainnet),
+            Err(VPackError::IncompleteData)
+        );
+    }
+}
+
+/// Feeds `bytes` in one chunk until the reader is done; returns the records and bytes consumed.
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn stream_all(
+    reader: &mut StreamingReader,
+    mut bytes: &[u8],
+) -> Result<(Vec<StreamRecord>, usize), VPackError> {
+    let total = bytes.len();
+    let mut records = Vec::new();
+    loop {
+        match reader.feed(bytes)? {
+            Progress::Record { consumed, record } => {
+                bytes = &bytes[consumed..];
+                records.push(record);
+            }
+            Progress::NeedMore | Progress::Done { .. } => {
+                return Ok((records, total - bytes.len()))
+            }
+        }
+    }
+}
+
+/// Streaming reader stops at `payload_len` and leaves bytes after the V-PACK to the caller.
+#[test]
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn test_streaming_stops_at_payload_end() {
+    let tree = sample_tree();
+    let mut bytes = pack(&sample_header(FLAG_PROOF_COMPACT), &tree).expect("pack");
+    let packed_len = bytes.len();
+    bytes.extend_from_slice(&[0xFF; 3]);
+
+    let mut reader = StreamingReader::new();
+    let (records, consumed) = stream_all(&mut reader, &bytes).expect("stream");
+    assert!(reader.is_done());
+    assert_eq!(consumed, packed_len);
+    assert_eq!(records.len(), 4);
+    assert!(matches!(records[0], StreamRecord::Header(_)));
+    assert!(matches!(records[3], StreamRecord::Trailer { .. }));
+}
+
+/// A flipped payload byte that keeps the structure valid is caught by the CRC at the trailer.
+#[test]
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn test_streaming_checksum_mismatch() {
+    let tree = sample_tree();
+    let mut bytes = pack(&sample_header(FLAG_PROOF_COMPACT), &tree).expect("pack");
+    let header = Header::from_bytes(&bytes[..HEADER_SIZE]).expect("header");
+    // Last byte of asp_expiry_script.
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0x01;
+
+    let mut reader = StreamingReader::new();
+    let err = stream_all(&mut reader, &bytes).unwrap_err();
+    assert!(matches!(
+        err,
+        VPackError::ChecksumMismatch { expected, .. } if expected == header.checksum
+    ));
+    assert!(!reader.is_done());
+}
+
+/// Compressed V-PACKs are rejected at the header; a record above the cap is rejected.
+#[test]
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn test_streaming_rejects_compressed_and_oversized_records() {
+    let tree = sample_tree();
+    let compressed = pack(
+        &sample_header(FLAG_PROOF_COMPACT | FLAG_COMPRESSION_LZ4),
+        &tree,
+    )
+    .expect("pack lz4");
+    assert_eq!(
+        stream_all(&mut StreamingReader::new(), &compressed).unwrap_err(),
+        VPackError::CompressionError
+    );
+
+    // Prefix record is 36 (anchor) + 4 + 4 (fee anchor script) = 44 bytes.
+    let plain = pack(&sample_header(FLAG_PROOF_COMPACT), &tree).expect("pack");
+    let mut reader = StreamingReader::new().with_max_record_len(40);
+    assert_eq!(
+        stream_all(&mut reader, &plain).unwrap_err(),
+        VPackError::RecordTooLarge(40)
+    );
+}
+
+/// Each network survives pack/parse; selector bits without `FLAG_TESTNET`, or both set, are rejected.
+#[test]
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn test_header_network_flags() {
+    let tree = sample_tree();
+    for network in [
+        Network::Mainnet,
+        Network::Testnet,
+        Network::Signet,
+        Network::Regtest,
+    ] {
+        let packed =
+            pack(&sample_header(FLAG_PROOF_COMPACT | network.flags()), &tree).expect("pack");
+        let header = Header::from_bytes(&packed[..HEADER_SIZE]).expect("parse header");
+        assert_eq!(header.network(), Ok(network));
+        assert_eq!(header.is_testnet(), network != Network::Mainnet);
+    }
+
+    for flags in [
+        FLAG_NETWORK_SIGNET,
+        FLAG_NETWORK_REGTEST,
+        FLAG_TESTNET | FLAG_NETWORK_SIGNET | FLAG_NETWORK_REGTEST,
+    ] {
+        let mut packed = pack(&sample_header(FLAG_PROOF_COMPACT), &tree).expect("pack");
+        packed[3] |= flags;
+        assert_eq!(
+            Header::from_bytes(&packed[..HEADER_SIZE]),
+            Err(VPackError::InvalidNetwork(FLAG_PROOF_COMPACT | flags))
+        );
+    }
+}
+
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn sample_extensions() -> Extensions {
+    let mut asp_pubkey = [0x11u8; 33];
+    asp_pubkey[0] = 0x02;
+    Extensions {
+        round_txid: Some(Txid::from_byte_array([0x77u8; 32])),
+        asp_pubkey: Some(asp_pubkey),
+        created_at: Some(1_767_225_600),
+        label: Some("savings".into()),
+    }
+}
+
+/// v2 extensions survive pack/parse, plain and LZ4-compressed; the tree is unchanged.
+#[test]
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn test_v2_extensions_round_trip() {
+    let tree = sample_tree();
+    let ext = sample_extensions();
+    for flags in [
+        FLAG_PROOF_COMPACT,
+        FLAG_PROOF_COMPACT | FLAG_COMPRESSION_LZ4,
+    ] {
+        let mut header = sample_header(flags);
+        header.version = VERSION_TLV;
+        let packed = pack_with_extensions(&header, &tree, &ext).expect("pack v2");
+        let parsed_header = Header::from_bytes(&packed[..HEADER_SIZE]).expect("parse header");
+        assert!(parsed_header.has_extensions());
+        let payload = &packed[HEADER_SIZE..];
+        parsed_header.verify_checksum(payload).expect("checksum");
+        let (parsed_tree, parsed_ext) =
+            BoundedReader::parse_with_extensions(&parsed_header, payload).expect("parse v2");
+        assert_eq!(parsed_tree, tree);
+        assert_eq!(parsed_ext, ext);
+        assert_eq!(
+            BoundedReader::parse(&parsed_header, payload),
+            Ok(tree.clone())
+        );
+    }
+
+    // A v2 header without extensions carries an empty section.
+    let mut header = sample_header(FLAG_PROOF_COMPACT);
+    header.version = VERSION_TLV;
+    let packed = pack(&header, &tree).expect("pack v2");
+    let v1 = pack(&sample_header(FLAG_PROOF_COMPACT), &tree).expect("pack v1");
+    assert_eq!(packed[HEADER_SIZE..], v1[HEADER_SIZE..]);
+}
+
+/// Unknown odd types are skipped, unknown even types and out-of-order records are rejected, and
+/// v1 payloads still reject anything after the trailer.
+#[test]
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn test_v2_extension_type_rules() {
+    let tree = sample_tree();
+    let mut header = sample_header(FLAG_PROOF_COMPACT);
+    header.version = VERSION_TLV;
+    let base = pack(&header, &tree).expect("pack v2");
+    let parse_with = |records: &[u8]| {
+        let mut payload = base[HEADER_SIZE..].to_vec();
+        payload.extend_from_slice(records);
+        let packed = pack_from_payload(&header, &payload).expect("pack_from_payload");
+        let parsed = Header::from_bytes(&packed[..HEADER_SIZE]).expect("parse header");
+        BoundedReader::parse_with_extensions(&parsed, &packed[HEADER_SIZE..]).map(|(_, e)| e)
+    };
+
+    // Type 7 (label "a") then unknown odd type 9.
+    let ext = parse_with(&[0x07, 0x01, b'a', 0x09, 0x02, 0xde, 0xad]).expect("odd skipped");
+    assert_eq!(ext.label.as_deref(), Some("a"));
+    assert_eq!(
+        parse_with(&[0x08, 0x00]),
+        Err(VPackError::UnknownExtension(8))
+    );
+    assert_eq!(
+        parse_with(&[0x09, 0x00, 0x07, 0x00]),
+        Err(VPackError::InvalidExtension(7))
+    );
+    assert_eq!(
+        parse_with(&[0x05, 0x01, 0x00]),
+        Err(VPackError::InvalidExtension(5))
+    );
+    assert_eq!(
+        parse_with(&[0x07, 0x05, b'a']),
+        Err(VPackError::IncompleteData)
+    );
+
+    let v1 = sample_header(FLAG_PROOF_COMPACT);
+    assert_eq!(
+        pack_with_extensions(&v1, &tree, &sample_extensions()),
+        Err(VPackError::EncodingError)
+    );
+    let mut payload = base[HEADER_SIZE..].to_vec();
+    payload.extend_from_slice(&[0x09, 0x00]);
+    assert_eq!(
+        BoundedReader::parse(&v1, &payload),
+        Err(VPackError::TrailingData(2))
+    );
+
+    let mut v3 = base.clone();
+    v3[4] = 3;
+    assert_eq!(
+        Header::from_bytes(&v3[..HEADER_SIZE]),
+        Err(VPackError::UnsupportedVersion(3))
+    );
+}
+
+/// The streaming reader returns the extension section with the Trailer record.
+#[test]
+#[cfg(any(feature = "bitcoin", feature = "wasm"))]
+fn test_streaming_v2_extensions() {
+    let tree = sample_tree();
+    let mut header = sample_header(FLAG_PROOF_COMPACT);
+    header.version = VERSION_TLV;
+    let packed = pack_with_extensions(&header, &tree, &sample_extensions()).expect("pack v2");
+
+    let mut reader = StreamingReader::new();
+    let mut records = Vec::new();
+    for chunk in packed.chunks(7) {
+        let mut chunk = chunk;
+        while let Progress::Record { consumed, record } = reader.feed(chunk).expect("feed") {
+            chunk = &chunk[consumed..];
+            records.push(record);
+        }
+    }
+    assert!(reader.is_done());
+    match records.last() {
+        Some(StreamRecord::Trailer { extensions, .. }) => {
+            assert_eq!(*extensions, sample_extensions())
+        }
+        other => panic!("expected Trailer, got {:?}", other),
+    }
+}
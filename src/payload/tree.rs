@@ -1,4 +1,6 @@
-use crate::types::{OutPoint, TxOut};
+use crate::consensus::hash_sibling_birth_tx;
+use crate::header::{Header, FLAG_ASP_SIGNED, FLAG_PROOF_COMPACT, MAX_TREE_ARITY, MAX_TREE_DEPTH};
+use crate::types::{Amount, OutPoint, ScriptBuf, TxOut};
 use alloc::vec::Vec;
 use borsh::{BorshDeserialize, BorshSerialize};
 
@@ -25,6 +27,66 @@ pub struct VPackTree {
     pub asp_expiry_script: Vec<u8>,
 }
 
+impl VPackTree {
+    /// Total siblings across the path, clamped to the header's `node_count` range.
+    pub fn node_count(&self) -> u16 {
+        let count: usize = self.path.iter().map(|item| item.siblings.len()).sum();
+        core::cmp::min(count, MAX_TREE_DEPTH as usize * MAX_TREE_ARITY as usize) as u16
+    }
+
+    /// Converts every sibling to [`SiblingNode::Compact`] (the form the consensus engines read).
+    ///
+    /// Returns the tree with a header template for it: `FLAG_PROOF_COMPACT` set, `FLAG_ASP_SIGNED`
+    /// cleared (the signature does not cover the re-packed bytes) and `node_count` recomputed;
+    /// `payload_len` and `checksum` are zero ([`crate::pack::pack`] fills them in).
+    pub fn to_compact(&self, header: &Header) -> (Header, VPackTree) {
+        self.convert(header, true)
+    }
+
+    /// Converts every sibling to [`SiblingNode::Full`], for tools that expect hydrated `TxOut`s.
+    ///
+    /// Header handling mirrors [`Self::to_compact`], with `FLAG_PROOF_COMPACT` cleared. Compact
+    /// hashes are dropped; [`Self::to_compact`] recomputes them as birth-tx hashes.
+    pub fn to_full(&self, header: &Header) -> (Header, VPackTree) {
+        self.convert(header, false)
+    }
+
+    fn convert(&self, header: &Header, compact: bool) -> (Header, VPackTree) {
+        let form = |node: &SiblingNode| {
+            if compact {
+                node.to_compact()
+            } else {
+                node.to_full()
+            }
+        };
+        let tree = VPackTree {
+            leaf_siblings: self.leaf_siblings.iter().map(form).collect(),
+            path: self
+                .path
+                .iter()
+                .map(|item| GenesisItem {
+                    siblings: item.siblings.iter().map(form).collect(),
+                    ..item.clone()
+                })
+                .collect(),
+            ..self.clone()
+        };
+        let flags = if compact {
+            (header.flags | FLAG_PROOF_COMPACT) & !FLAG_ASP_SIGNED
+        } else {
+            header.flags & !(FLAG_PROOF_COMPACT | FLAG_ASP_SIGNED)
+        };
+        let header = Header {
+            flags,
+            node_count: tree.node_count(),
+            payload_len: 0,
+            checksum: 0,
+            ..*header
+        };
+        (header, tree)
+    }
+}
+
 /// The User's specific UTXO leaf.
 /// Fixed-width fields first, variable-length last for efficient no_std parsing.
 /// Field order matches V-BIP-01 v1.1.0 and Borsh wire format.
@@ -112,4 +174,238 @@ impl SiblingNode {
     pub fn is_compact(&self) -> bool {
         matches!(self, SiblingNode::Compact { .. })
     }
+
+    /// Compact form; a `Full` sibling gets the hash of its canonical birth transaction
+    /// ([`hash_sibling_birth_tx`]).
+    pub fn to_compact(&self) -> SiblingNode {
+        match self {
+            SiblingNode::Compact { .. } => self.clone(),
+            SiblingNode::Full(txout) => {
+                let script = txout.script_pubkey.as_bytes();
+                SiblingNode::Compact {
+                    hash: hash_sibling_birth_tx(txout.value.to_sat(), script),
+                    value: txout.value.to_sat(),
+                    script: script.to_vec(),
+                }
+            }
+        }
+    }
+
+    /// Full form (`TxOut` with the same value and script); the compact hash is dropped.
+    pub fn to_full(&self) -> SiblingNode {
+        match self {
+            SiblingNode::Compact { value, script, .. } => SiblingNode::Full(TxOut {
+                value: Amount::from_sat(*value),
+                script_pubkey: ScriptBuf::from_bytes(script.clone()),
+            }),
+            SiblingNode::Full(_) => self.clone(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Borrowed (zero-copy) view
+// ---------------------------------------------------------------------------
+
+/// Borrowed view of a [`VPackTree`].
+///
+/// Produced by [`crate::payload::reader::BoundedReader::parse_ref`]: scripts and signatures are
+/// slices into the payload buffer, so parsing and ID reconstruction do not allocate per script.
+/// Convert with `VPackTree::from(&tree_ref)` when an owned tree is needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VPackTreeRef<'a> {
+    pub leaf: VtxoLeafRef<'a>,
+    pub leaf_siblings: Vec<SiblingNodeRef<'a>>,
+    pub path: Vec<GenesisItemRef<'a>>,
+    pub anchor: OutPoint,
+    pub asset_id: Option<[u8; 32]>,
+    pub fee_anchor_script: &'a [u8],
+    pub internal_key: [u8; 32],
+    pub asp_expiry_script: &'a [u8],
+}
+
+/// Borrowed counterpart of [`VtxoLeaf`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VtxoLeafRef<'a> {
+    pub amount: u64,
+    pub vout: u32,
+    pub sequence: u32,
+    pub expiry: u32,
+    pub exit_delta: u16,
+    pub script_pubkey: &'a [u8],
+}
+
+/// Borrowed counterpart of [`GenesisItem`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisItemRef<'a> {
+    pub siblings: Vec<SiblingNodeRef<'a>>,
+    pub parent_index: u32,
+    pub sequence: u32,
+    pub child_amount: u64,
+    pub child_script_pubkey: &'a [u8],
+    pub signature: Option<&'a [u8; 64]>,
+    /// Runtime-only sighash type annotation (see [`GenesisItem::sighash_flag`]).
+    pub sighash_flag: u8,
+}
+
+/// Borrowed counterpart of [`SiblingNode`]. `Full` carries the decoded TxOut fields directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SiblingNodeRef<'a> {
+    Compact {
+        hash: &'a [u8; 32],
+        value: u64,
+        script: &'a [u8],
+    },
+    Full {
+        value: u64,
+        script: &'a [u8],
+    },
+}
+
+impl<'a> SiblingNodeRef<'a> {
+    pub fn is_compact(&self) -> bool {
+        matches!(self, SiblingNodeRef::Compact { .. })
+    }
+
+    /// Output value in satoshis.
+    pub fn value(&self) -> u64 {
+        match *self {
+            SiblingNodeRef::Compact { value, .. } | SiblingNodeRef::Full { value, .. } => value,
+        }
+    }
+
+    /// Output scriptPubKey bytes.
+    pub fn script(&self) -> &'a [u8] {
+        match *self {
+            SiblingNodeRef::Compact { script, .. } | SiblingNodeRef::Full { script, .. } => script,
+        }
+    }
+}
+
+impl<'a> From<&'a SiblingNode> for SiblingNodeRef<'a> {
+    fn from(node: &'a SiblingNode) -> Self {
+        match node {
+            SiblingNode::Compact {
+                hash,
+                value,
+                script,
+            } => SiblingNodeRef::Compact {
+                hash,
+                value: *value,
+                script,
+            },
+            SiblingNode::Full(txout) => SiblingNodeRef::Full {
+                value: txout.value.to_sat(),
+                script: txout.script_pubkey.as_bytes(),
+            },
+        }
+    }
+}
+
+impl From<&SiblingNodeRef<'_>> for SiblingNode {
+    fn from(node: &SiblingNodeRef<'_>) -> Self {
+        match *node {
+            SiblingNodeRef::Compact {
+                hash,
+                value,
+                script,
+            } => SiblingNode::Compact {
+                hash: *hash,
+                value,
+                script: script.to_vec(),
+            },
+            SiblingNodeRef::Full { value, script } => SiblingNode::Full(TxOut {
+                value: Amount::from_sat(value),
+                script_pubkey: ScriptBuf::from_bytes(script.to_vec()),
+            }),
+        }
+    }
+}
+
+impl<'a> From<&'a VtxoLeaf> for VtxoLeafRef<'a> {
+    fn from(leaf: &'a VtxoLeaf) -> Self {
+        Self {
+            amount: leaf.amount,
+            vout: leaf.vout,
+            sequence: leaf.sequence,
+            expiry: leaf.expiry,
+            exit_delta: leaf.exit_delta,
+            script_pubkey: &leaf.script_pubkey,
+        }
+    }
+}
+
+impl From<&VtxoLeafRef<'_>> for VtxoLeaf {
+    fn from(leaf: &VtxoLeafRef<'_>) -> Self {
+        Self {
+            amount: leaf.amount,
+            vout: leaf.vout,
+            sequence: leaf.sequence,
+            expiry: leaf.expiry,
+            exit_delta: leaf.exit_delta,
+            script_pubkey: leaf.script_pubkey.to_vec(),
+        }
+    }
+}
+
+impl<'a> From<&'a GenesisItem> for GenesisItemRef<'a> {
+    fn from(item: &'a GenesisItem) -> Self {
+        Self {
+            siblings: item.siblings.iter().map(SiblingNodeRef::from).collect(),
+            parent_index: item.parent_index,
+            sequence: item.sequence,
+            child_amount: item.child_amount,
+            child_script_pubkey: &item.child_script_pubkey,
+            signature: item.signature.as_ref(),
+            sighash_flag: item.sighash_flag,
+        }
+    }
+}
+
+impl From<&GenesisItemRef<'_>> for GenesisItem {
+    fn from(item: &GenesisItemRef<'_>) -> Self {
+        Self {
+            siblings: item.siblings.iter().map(SiblingNode::from).collect(),
+            parent_index: item.parent_index,
+            sequence: item.sequence,
+            child_amount: item.child_amount,
+            child_script_pubkey: item.child_script_pubkey.to_vec(),
+            signature: item.signature.copied(),
+            sighash_flag: item.sighash_flag,
+        }
+    }
+}
+
+impl<'a> From<&'a VPackTree> for VPackTreeRef<'a> {
+    fn from(tree: &'a VPackTree) -> Self {
+        Self {
+            leaf: VtxoLeafRef::from(&tree.leaf),
+            leaf_siblings: tree
+                .leaf_siblings
+                .iter()
+                .map(SiblingNodeRef::from)
+                .collect(),
+            path: tree.path.iter().map(GenesisItemRef::from).collect(),
+            anchor: tree.anchor,
+            asset_id: tree.asset_id,
+            fee_anchor_script: &tree.fee_anchor_script,
+            internal_key: tree.internal_key,
+            asp_expiry_script: &tree.asp_expiry_script,
+        }
+    }
+}
+
+impl From<&VPackTreeRef<'_>> for VPackTree {
+    fn from(tree: &VPackTreeRef<'_>) -> Self {
+        Self {
+            leaf: VtxoLeaf::from(&tree.leaf),
+            leaf_siblings: tree.leaf_siblings.iter().map(SiblingNode::from).collect(),
+            path: tree.path.iter().map(GenesisItem::from).collect(),
+            anchor: tree.anchor,
+            asset_id: tree.asset_id,
+            fee_anchor_script: tree.fee_anchor_script.to_vec(),
+            internal_key: tree.internal_key,
+            asp_expiry_script: tree.asp_expiry_script.to_vec(),
+        }
+    }
 }
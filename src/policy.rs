@@ -0,0 +1,174 @@
+//! Verification policies: which checks [`crate::verify_with`] runs on top of [`crate::verify`].
+//!
+//! [`crate::verify`] proves that the tree reconstructs to the expected VTXO ID (checksum, parse,
+//! invariants, engine, path exclusivity). Whether the VTXO is actually *usable* depends on where
+//! it is in its lifecycle, so the remaining checks are opt-in:
+//!
+//! | Profile      | Timelocks | Exit-ready completeness | SIGHASH audit |
+//! |--------------|-----------|-------------------------|---------------|
+//! | `Strict`     | yes       | yes                     | yes           |
+//! | `ExitReady`  | yes       | yes                     | no            |
+//! | `Boarding`   | yes       | no                      | no            |
+//! | `Permissive` | no        | no                      | no            |
+//!
+//! `Permissive` is exactly [`crate::verify`]. The SIGHASH audit starts from the anchor output, so
+//! `Strict` needs [`VerificationPolicy::with_anchor_script`]. An ASP key can be required under
+//! any profile with [`VerificationPolicy::with_asp_pubkey`]. The SIGHASH audit checks path
+//! signatures against a [`SighashPolicy`] allow-list, [`SighashPolicy::STRICT`] unless replaced
+//! with [`VerificationPolicy::with_sighash_policy`].
+//!
+//! A private variant registered in an [`EngineRegistry`](crate::EngineRegistry) goes through
+//! [`EngineRegistry::verify_with`](crate::EngineRegistry::verify_with). The SIGHASH audit walks
+//! the built-in dialects only and fails such a V-PACK with
+//! [`VPackError::InvalidTxVariant`](crate::error::VPackError::InvalidTxVariant).
+
+use alloc::vec::Vec;
+
+use crate::header::Network;
+
+/// Named starting points for a [`VerificationPolicy`]. See the [module](self) table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationProfile {
+    /// Everything: exit-ready data, timelocks and the tree-wide SIGHASH audit.
+    Strict,
+    /// The VTXO can be unilaterally exited: every path step is signed and timelocks hold.
+    ExitReady,
+    /// Boarding / early out-of-round VTXOs: signatures may still be missing.
+    Boarding,
+    /// Same checks as [`crate::verify`].
+    Permissive,
+}
+
+/// Checks run by [`crate::verify_with`]. Start from [`VerificationPolicy::new`] and adjust the
+/// fields as needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationPolicy {
+    /// Network the caller operates on; the header's network must match.
+    pub network: Network,
+    /// Run [`crate::validate_timelocks`] against `asp_expiry_script`.
+    pub check_timelocks: bool,
+    /// Run [`crate::validate_exit_ready_completeness`] (every path step signed).
+    pub require_exit_ready: bool,
+    /// Re-verify every path signature with its declared sighash type and check the type against
+    /// [`Self::sighash_policy`] (`audit_sighash_policy`). Requires [`Self::anchor_script`] and the
+    /// `schnorr-verify` feature.
+    pub audit_sighash: bool,
+    /// Sighash types the SIGHASH audit accepts.
+    pub sighash_policy: SighashPolicy,
+    /// scriptPubKey of the L1 anchor output, the first prevout of the SIGHASH audit.
+    pub anchor_script: Option<Vec<u8>>,
+    /// Require a valid ASP signature by this x-only key, checked before any reconstruction.
+    /// Requires the `schnorr-verify` feature.
+    pub asp_pubkey: Option<[u8; 32]>,
+}
+
+impl VerificationPolicy {
+    /// Policy for `profile` on `network`, with no anchor script and no ASP key.
+    pub fn new(profile: VerificationProfile, network: Network) -> Self {
+        let (check_timelocks, require_exit_ready, audit_sighash) = match profile {
+            VerificationProfile::Strict => (true, true, true),
+            VerificationProfile::ExitReady => (true, true, false),
+            VerificationProfile::Boarding => (true, false, false),
+            VerificationProfile::Permissive => (false, false, false),
+        };
+        Self {
+            network,
+            check_timelocks,
+            require_exit_ready,
+            audit_sighash,
+            sighash_policy: SighashPolicy::STRICT,
+            anchor_script: None,
+            asp_pubkey: None,
+        }
+    }
+
+    /// Sets the anchor scriptPubKey used by the SIGHASH audit.
+    pub fn with_anchor_script(mut self, anchor_script: Vec<u8>) -> Self {
+        self.anchor_script = Some(anchor_script);
+        self
+    }
+
+    /// Requires an ASP signature by `asp_pubkey` (x-only).
+    pub fn with_asp_pubkey(mut self, asp_pubkey: [u8; 32]) -> Self {
+        self.asp_pubkey = Some(asp_pubkey);
+        self
+    }
+
+    /// Replaces the sighash types the SIGHASH audit accepts.
+    pub fn with_sighash_policy(mut self, sighash_policy: SighashPolicy) -> Self {
+        self.sighash_policy = sighash_policy;
+        self
+    }
+}
+
+/// The seven BIP-341 sighash types; bit `i` of [`SighashPolicy`] stands for entry `i`.
+const SIGHASH_TYPES: [(u8, &str); 7] = [
+    (0x00, "SIGHASH_DEFAULT"),
+    (0x01, "SIGHASH_ALL"),
+    (0x02, "SIGHASH_NONE"),
+    (0x03, "SIGHASH_SINGLE"),
+    (0x81, "SIGHASH_ALL|ANYONECANPAY"),
+    (0x82, "SIGHASH_NONE|ANYONECANPAY"),
+    (0x83, "SIGHASH_SINGLE|ANYONECANPAY"),
+];
+
+/// Name of the BIP-341 sighash type `flag`, or `None` if `flag` is not one.
+pub fn sighash_type_name(flag: u8) -> Option<&'static str> {
+    SIGHASH_TYPES
+        .iter()
+        .find(|(f, _)| *f == flag)
+        .map(|(_, name)| *name)
+}
+
+/// Allow-list of sighash types for path signatures.
+///
+/// Any of the seven BIP-341 types can be allowed; a byte that is not a BIP-341 type is never
+/// allowed. The default is [`SighashPolicy::STRICT`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SighashPolicy {
+    allowed: u8,
+}
+
+impl SighashPolicy {
+    /// DEFAULT, ALL and ALL|ANYONECANPAY: the types that commit to every output. NONE lets the
+    /// outputs be replaced and SINGLE covers only the output at the signer's index; under
+    /// ANYONECANPAY only other inputs may be added, which is how fees are bumped.
+    pub const STRICT: Self = Self {
+        allowed: 0b001_0011,
+    };
+
+    /// Every BIP-341 type: recognizes all of them, so that only the signatures are checked.
+    pub const ANY: Self = Self {
+        allowed: 0b111_1111,
+    };
+
+    /// Whether path signatures may use `flag`.
+    pub fn allows(&self, flag: u8) -> bool {
+        Self::bit(flag).is_some_and(|bit| self.allowed & bit != 0)
+    }
+
+    /// Also allows `flag`; no effect if it is not a BIP-341 type.
+    pub fn allow(mut self, flag: u8) -> Self {
+        self.allowed |= Self::bit(flag).unwrap_or(0);
+        self
+    }
+
+    /// Stops allowing `flag`.
+    pub fn deny(mut self, flag: u8) -> Self {
+        self.allowed &= !Self::bit(flag).unwrap_or(0);
+        self
+    }
+
+    fn bit(flag: u8) -> Option<u8> {
+        SIGHASH_TYPES
+            .iter()
+            .position(|(f, _)| *f == flag)
+            .map(|i| 1 << i)
+    }
+}
+
+impl Default for SighashPolicy {
+    fn default() -> Self {
+        Self::STRICT
+    }
+}
@@ -0,0 +1,816 @@
+//! PSBT version 2 (BIP-174 / BIP-370) export and import of exit transactions.
+//!
+//! Signing stacks and hardware wallets take PSBTs rather than raw transactions. [`exit_psbts`]
+//! turns every transaction of [`VerificationOutput::signed_txs`] into a PSBT whose inputs carry
+//! the output they spend as `PSBT_IN_WITNESS_UTXO`: the L1 anchor for the first transaction, an
+//! output of an earlier exit transaction for the others. Signatures the tree already holds come
+//! along as `PSBT_IN_TAP_KEY_SIG`. The leaf sweep and the CPFP children have their own
+//! `to_psbt`; the sweep also fills in `PSBT_IN_TAP_INTERNAL_KEY`, `PSBT_IN_TAP_LEAF_SCRIPT` (with
+//! the control block) and `PSBT_IN_TAP_MERKLE_ROOT`.
+//!
+//! Exit transaction inputs spend the L1 anchor and the outputs of earlier exit transactions,
+//! whose output keys aggregate the round's cosigners; the tree holds only those tweaked output
+//! keys, not the internal keys or script trees behind them. The one internal key it does hold
+//! belongs to the leaf output, which only the sweep spends. [`exit_psbts`] therefore leaves
+//! `PSBT_IN_TAP_INTERNAL_KEY` and `PSBT_IN_TAP_MERKLE_ROOT` out; signers that need them for
+//! key-path signing supply them per input from their own session data through
+//! [`exit_psbts_with_keys`].
+//!
+//! [`Psbt::parse`] reads the signed PSBT back and [`Psbt::extract_tx`] finalizes it into a
+//! broadcastable transaction. Fields not modeled here (BIP-32 derivations, MuSig2 data,
+//! proprietary keys) are kept as raw key-value pairs and written back unchanged. Version 0 PSBTs
+//! are rejected.
+//!
+//! [`VerificationOutput::signed_txs`]: crate::consensus::VerificationOutput::signed_txs
+
+use alloc::vec::Vec;
+
+use crate::compact_size::{read_compact_size, write_compact_size};
+use crate::consensus::script::{
+    Instruction, Instructions, OP_CHECKSIG, OP_CHECKSIGADD, OP_CHECKSIGVERIFY, P2A_SCRIPT,
+};
+use crate::consensus::taproot::tap_leaf_hash_with_version;
+use crate::consensus::tx_decode::{Cursor, ParsedTx};
+use crate::consensus::{
+    tx_preimage, tx_with_witnesses, TxInPreimage, TxOutPreimage, VerificationOutput,
+};
+use crate::error::VPackError;
+use crate::types::hashes::{sha256d, Hash};
+
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+const PSBT_VERSION_2: u32 = 2;
+
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const PSBT_GLOBAL_TX_VERSION: u8 = 0x02;
+const PSBT_GLOBAL_FALLBACK_LOCKTIME: u8 = 0x03;
+const PSBT_GLOBAL_INPUT_COUNT: u8 = 0x04;
+const PSBT_GLOBAL_OUTPUT_COUNT: u8 = 0x05;
+const PSBT_GLOBAL_VERSION: u8 = 0xfb;
+
+const PSBT_IN_WITNESS_UTXO: u8 = 0x01;
+const PSBT_IN_SIGHASH_TYPE: u8 = 0x03;
+const PSBT_IN_FINAL_SCRIPTWITNESS: u8 = 0x08;
+const PSBT_IN_PREVIOUS_TXID: u8 = 0x0e;
+const PSBT_IN_OUTPUT_INDEX: u8 = 0x0f;
+const PSBT_IN_SEQUENCE: u8 = 0x10;
+const PSBT_IN_TAP_KEY_SIG: u8 = 0x13;
+const PSBT_IN_TAP_SCRIPT_SIG: u8 = 0x14;
+const PSBT_IN_TAP_LEAF_SCRIPT: u8 = 0x15;
+const PSBT_IN_TAP_INTERNAL_KEY: u8 = 0x17;
+const PSBT_IN_TAP_MERKLE_ROOT: u8 = 0x18;
+
+const PSBT_OUT_AMOUNT: u8 = 0x03;
+const PSBT_OUT_SCRIPT: u8 = 0x04;
+
+/// nSequence of an input without `PSBT_IN_SEQUENCE`.
+const DEFAULT_SEQUENCE: u32 = 0xFFFF_FFFF;
+
+/// Output spent by an input (`PSBT_IN_WITNESS_UTXO`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessUtxo {
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+/// Tapscript leaf the input can be spent with (`PSBT_IN_TAP_LEAF_SCRIPT`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapLeafScript {
+    /// `[control_byte || internal_key || merkle_path…]`; the key of the PSBT entry.
+    pub control_block: Vec<u8>,
+    pub script: Vec<u8>,
+    pub leaf_version: u8,
+}
+
+/// Signature by `pubkey` for the leaf `leaf_hash` (`PSBT_IN_TAP_SCRIPT_SIG`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapScriptSig {
+    pub pubkey: [u8; 32],
+    pub leaf_hash: [u8; 32],
+    /// 64 bytes, or 65 with an explicit sighash type.
+    pub signature: Vec<u8>,
+}
+
+/// Taproot key data of an exit transaction input (`PSBT_IN_TAP_INTERNAL_KEY`,
+/// `PSBT_IN_TAP_MERKLE_ROOT`), supplied by the signer to [`exit_psbts_with_keys`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TapKeySpend {
+    pub internal_key: [u8; 32],
+    /// `None` for an output key without a script tree.
+    pub merkle_root: Option<[u8; 32]>,
+}
+
+/// Per-input map of a PSBT v2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PsbtInput {
+    /// Txid of the spent output (wire order).
+    pub previous_txid: [u8; 32],
+    pub output_index: u32,
+    pub sequence: u32,
+    pub witness_utxo: Option<WitnessUtxo>,
+    pub sighash_type: Option<u32>,
+    /// Key-path signature, 64 or 65 bytes.
+    pub tap_key_sig: Option<Vec<u8>>,
+    pub tap_script_sigs: Vec<TapScriptSig>,
+    pub tap_leaf_scripts: Vec<TapLeafScript>,
+    pub tap_internal_key: Option<[u8; 32]>,
+    pub tap_merkle_root: Option<[u8; 32]>,
+    /// Finalized witness stack; takes precedence over the signatures when extracting.
+    pub final_script_witness: Option<Vec<Vec<u8>>>,
+    /// Other pairs, verbatim: `(key type || key data, value)`.
+    pub unknown: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl PsbtInput {
+    /// Input spending `previous_txid:output_index` with no other field set.
+    pub fn new(previous_txid: [u8; 32], output_index: u32, sequence: u32) -> Self {
+        Self {
+            previous_txid,
+            output_index,
+            sequence,
+            witness_utxo: None,
+            sighash_type: None,
+            tap_key_sig: None,
+            tap_script_sigs: Vec::new(),
+            tap_leaf_scripts: Vec::new(),
+            tap_internal_key: None,
+            tap_merkle_root: None,
+            final_script_witness: None,
+            unknown: Vec::new(),
+        }
+    }
+
+    /// Witness that spends the input, from the most complete data available: the final witness,
+    /// the key-path signature, a tapscript leaf with a signature for each of its keys, or the
+    /// empty witness of a pay-to-anchor output.
+    fn witness(&self) -> Result<Vec<&[u8]>, VPackError> {
+        if let Some(stack) = &self.final_script_witness {
+            return Ok(stack.iter().map(Vec::as_slice).collect());
+        }
+        if let Some(sig) = &self.tap_key_sig {
+            return Ok(alloc::vec![sig.as_slice()]);
+        }
+        for leaf in &self.tap_leaf_scripts {
+            let leaf_hash = tap_leaf_hash_with_version(leaf.leaf_version, &leaf.script);
+            let keys = match checksig_keys(&leaf.script) {
+                Some(keys) if !keys.is_empty() => keys,
+                _ => continue,
+            };
+            // The last key checked consumes the bottom signature.
+            let signatures: Option<Vec<&[u8]>> = keys
+                .iter()
+                .rev()
+                .map(|key| {
+                    self.tap_script_sigs
+                        .iter()
+                        .find(|s| s.pubkey == *key && s.leaf_hash == leaf_hash)
+                        .map(|s| s.signature.as_slice())
+                })
+                .collect();
+            if let Some(mut stack) = signatures {
+                stack.push(&leaf.script);
+                stack.push(&leaf.control_block);
+                return Ok(stack);
+            }
+        }
+        if self
+            .witness_utxo
+            .as_ref()
+            .is_some_and(|utxo| utxo.script_pubkey == P2A_SCRIPT)
+        {
+            return Ok(Vec::new());
+        }
+        Err(VPackError::InvalidPsbt("input is not signed"))
+    }
+}
+
+/// Per-output map of a PSBT v2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PsbtOutput {
+    pub amount: u64,
+    pub script: Vec<u8>,
+    /// Other pairs, verbatim: `(key type || key data, value)`.
+    pub unknown: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl PsbtOutput {
+    /// Output paying `amount` to `script` with no other field set.
+    pub fn new(amount: u64, script: Vec<u8>) -> Self {
+        Self {
+            amount,
+            script,
+            unknown: Vec::new(),
+        }
+    }
+}
+
+/// A PSBT version 2: the transaction fields and the signing data of each input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Psbt {
+    pub tx_version: u32,
+    /// nLockTime; BIP-370 required locktimes are not modeled and stay in `unknown`.
+    pub fallback_locktime: Option<u32>,
+    pub inputs: Vec<PsbtInput>,
+    pub outputs: Vec<PsbtOutput>,
+    /// Other global pairs, verbatim: `(key type || key data, value)`.
+    pub unknown: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Psbt {
+    /// PSBT of the consensus-encoded transaction `raw`, whose input `i` spends `prevouts[i]`.
+    ///
+    /// A witness of one 64- or 65-byte item becomes the input's `tap_key_sig`; any other
+    /// non-empty witness its `final_script_witness`.
+    ///
+    /// # Errors
+    ///
+    /// Parse errors of `raw`, or [`VPackError::InvalidPsbt`] if `prevouts` does not have one
+    /// entry per input.
+    pub fn from_tx(raw: &[u8], prevouts: &[TxOutPreimage<'_>]) -> Result<Self, VPackError> {
+        let tx = ParsedTx::parse(raw)?;
+        if prevouts.len() != tx.inputs.len() {
+            return Err(VPackError::InvalidPsbt("one prevout per input"));
+        }
+        let mut inputs = Vec::with_capacity(prevouts.len());
+        for (i, (&(txid, vout, sequence), prevout)) in tx.inputs.iter().zip(prevouts).enumerate() {
+            let mut input = PsbtInput::new(txid, vout, sequence);
+            input.witness_utxo = Some(WitnessUtxo {
+                value: prevout.value,
+                script_pubkey: prevout.script_pubkey.to_vec(),
+            });
+            match tx.witnesses.get(i).map(Vec::as_slice) {
+                None | Some([]) => {}
+                Some([sig]) if sig.len() == 64 || sig.len() == 65 => {
+                    input.tap_key_sig = Some(sig.to_vec());
+                }
+                Some(stack) => {
+                    input.final_script_witness = Some(stack.iter().map(|i| i.to_vec()).collect());
+                }
+            }
+            inputs.push(input);
+        }
+        let outputs = tx
+            .outputs
+            .iter()
+            .map(|&(amount, script)| PsbtOutput::new(amount, script.to_vec()))
+            .collect();
+        Ok(Self {
+            tx_version: tx.version,
+            fallback_locktime: Some(tx.locktime),
+            inputs,
+            outputs,
+            unknown: Vec::new(),
+        })
+    }
+
+    /// Serialization without witness data.
+    pub fn unsigned_tx(&self) -> Vec<u8> {
+        let (inputs, outputs) = self.preimages();
+        tx_preimage(self.tx_version, &inputs, &outputs, self.locktime())
+    }
+
+    /// Txid (wire order); unchanged by signing.
+    pub fn txid(&self) -> [u8; 32] {
+        sha256d::Hash::hash(&self.unsigned_tx()).to_byte_array()
+    }
+
+    /// Finalizes every input and returns the broadcastable transaction.
+    ///
+    /// An input's witness is its `final_script_witness`, else its `tap_key_sig`, else the first
+    /// `tap_leaf_scripts` entry whose every checked key has a `tap_script_sigs` signature (the
+    /// signatures ordered for a script that checks its keys one after another), else empty when
+    /// it spends a pay-to-anchor output.
+    ///
+    /// # Errors
+    ///
+    /// [`VPackError::InvalidPsbt`] if an input has none of these.
+    pub fn extract_tx(&self) -> Result<Vec<u8>, VPackError> {
+        let witnesses = self
+            .inputs
+            .iter()
+            .map(PsbtInput::witness)
+            .collect::<Result<Vec<_>, _>>()?;
+        let (inputs, outputs) = self.preimages();
+        if witnesses.iter().all(Vec::is_empty) {
+            return Ok(tx_preimage(
+                self.tx_version,
+                &inputs,
+                &outputs,
+                self.locktime(),
+            ));
+        }
+        let stacks: Vec<&[&[u8]]> = witnesses.iter().map(Vec::as_slice).collect();
+        Ok(tx_with_witnesses(
+            self.tx_version,
+            &inputs,
+            &outputs,
+            &stacks,
+            self.locktime(),
+        ))
+    }
+
+    /// BIP-174 serialization: magic, global map, one map per input, one per output.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(256);
+        out.extend_from_slice(PSBT_MAGIC);
+
+        write_pair(
+            &mut out,
+            PSBT_GLOBAL_TX_VERSION,
+            &[],
+            &self.tx_version.to_le_bytes(),
+        );
+        if let Some(locktime) = self.fallback_locktime {
+            write_pair(
+                &mut out,
+                PSBT_GLOBAL_FALLBACK_LOCKTIME,
+                &[],
+                &locktime.to_le_bytes(),
+            );
+        }
+        let mut count = Vec::new();
+        write_compact_size(&mut count, self.inputs.len() as u64);
+        write_pair(&mut out, PSBT_GLOBAL_INPUT_COUNT, &[], &count);
+        count.clear();
+        write_compact_size(&mut count, self.outputs.len() as u64);
+        write_pair(&mut out, PSBT_GLOBAL_OUTPUT_COUNT, &[], &count);
+        write_pair(
+            &mut out,
+            PSBT_GLOBAL_VERSION,
+            &[],
+            &PSBT_VERSION_2.to_le_bytes(),
+        );
+        write_unknown(&mut out, &self.unknown);
+
+        for input in &self.inputs {
+            if let Some(utxo) = &input.witness_utxo {
+                let mut value = Vec::with_capacity(9 + utxo.script_pubkey.len());
+                value.extend_from_slice(&utxo.value.to_le_bytes());
+                write_compact_size(&mut value, utxo.script_pubkey.len() as u64);
+                value.extend_from_slice(&utxo.script_pubkey);
+                write_pair(&mut out, PSBT_IN_WITNESS_UTXO, &[], &value);
+            }
+            if let Some(sighash_type) = input.sighash_type {
+                write_pair(
+                    &mut out,
+                    PSBT_IN_SIGHASH_TYPE,
+                    &[],
+                    &sighash_type.to_le_bytes(),
+                );
+            }
+            if let Some(stack) = &input.final_script_witness {
+                let mut value = Vec::new();
+                write_compact_size(&mut value, stack.len() as u64);
+                for item in stack {
+                    write_compact_size(&mut value, item.len() as u64);
+                    value.extend_from_slice(item);
+                }
+                write_pair(&mut out, PSBT_IN_FINAL_SCRIPTWITNESS, &[], &value);
+            }
+            write_pair(&mut out, PSBT_IN_PREVIOUS_TXID, &[], &input.previous_txid);
+            write_pair(
+                &mut out,
+                PSBT_IN_OUTPUT_INDEX,
+                &[],
+                &input.output_index.to_le_bytes(),
+            );
+            write_pair(
+                &mut out,
+                PSBT_IN_SEQUENCE,
+                &[],
+                &input.sequence.to_le_bytes(),
+            );
+            if let Some(sig) = &input.tap_key_sig {
+                write_pair(&mut out, PSBT_IN_TAP_KEY_SIG, &[], sig);
+            }
+            for sig in &input.tap_script_sigs {
+                let mut key = [0u8; 64];
+                key[..32].copy_from_slice(&sig.pubkey);
+                key[32..].copy_from_slice(&sig.leaf_hash);
+                write_pair(&mut out, PSBT_IN_TAP_SCRIPT_SIG, &key, &sig.signature);
+            }
+            for leaf in &input.tap_leaf_scripts {
+                let mut value = Vec::with_capacity(leaf.script.len() + 1);
+                value.extend_from_slice(&leaf.script);
+                value.push(leaf.leaf_version);
+                write_pair(
+                    &mut out,
+                    PSBT_IN_TAP_LEAF_SCRIPT,
+                    &leaf.control_block,
+                    &value,
+                );
+            }
+            if let Some(key) = &input.tap_internal_key {
+                write_pair(&mut out, PSBT_IN_TAP_INTERNAL_KEY, &[], key);
+            }
+            if let Some(root) = &input.tap_merkle_root {
+                write_pair(&mut out, PSBT_IN_TAP_MERKLE_ROOT, &[], root);
+            }
+            write_unknown(&mut out, &input.unknown);
+        }
+
+        for output in &self.outputs {
+            write_pair(&mut out, PSBT_OUT_AMOUNT, &[], &output.amount.to_le_bytes());
+            write_pair(&mut out, PSBT_OUT_SCRIPT, &[], &output.script);
+            write_unknown(&mut out, &output.unknown);
+        }
+        out
+    }
+
+    /// Parses a serialized PSBT v2.
+    ///
+    /// # Errors
+    ///
+    /// - [`VPackError::InvalidPsbt`] naming the offending part: bad magic, a version other than
+    ///   2, a duplicate key, a missing required field or a field of the wrong size.
+    /// - [`VPackError::IncompleteData`] / [`VPackError::TrailingData`] if the maps are truncated
+    ///   or followed by more bytes.
+    pub fn parse(bytes: &[u8]) -> Result<Self, VPackError> {
+        let body = bytes
+            .strip_prefix(PSBT_MAGIC)
+            .ok_or(VPackError::InvalidPsbt("magic"))?;
+        let mut r = Cursor { data: body, pos: 0 };
+
+        let mut tx_version = None;
+        let mut fallback_locktime = None;
+        let mut input_count = None;
+        let mut output_count = None;
+        let mut version = None;
+        let mut unknown = Vec::new();
+        for (key, value) in read_map(&mut r)? {
+            match (key[0], key.len()) {
+                (PSBT_GLOBAL_UNSIGNED_TX, _) => {
+                    return Err(VPackError::InvalidPsbt("PSBT_GLOBAL_UNSIGNED_TX"))
+                }
+                (PSBT_GLOBAL_TX_VERSION, 1) => {
+                    tx_version = Some(le_u32(value, "PSBT_GLOBAL_TX_VERSION")?)
+                }
+                (PSBT_GLOBAL_FALLBACK_LOCKTIME, 1) => {
+                    fallback_locktime = Some(le_u32(value, "PSBT_GLOBAL_FALLBACK_LOCKTIME")?)
+                }
+                (PSBT_GLOBAL_INPUT_COUNT, 1) => {
+                    input_count = Some(count(value, "PSBT_GLOBAL_INPUT_COUNT")?)
+                }
+                (PSBT_GLOBAL_OUTPUT_COUNT, 1) => {
+                    output_count = Some(count(value, "PSBT_GLOBAL_OUTPUT_COUNT")?)
+                }
+                (PSBT_GLOBAL_VERSION, 1) => version = Some(le_u32(value, "PSBT_GLOBAL_VERSION")?),
+                _ => unknown.push((key.to_vec(), value.to_vec())),
+            }
+        }
+        if version != Some(PSBT_VERSION_2) {
+            return Err(VPackError::InvalidPsbt("PSBT_GLOBAL_VERSION"));
+        }
+        let tx_version = tx_version.ok_or(VPackError::InvalidPsbt("PSBT_GLOBAL_TX_VERSION"))?;
+        let input_count = input_count.ok_or(VPackError::InvalidPsbt("PSBT_GLOBAL_INPUT_COUNT"))?;
+        let output_count =
+            output_count.ok_or(VPackError::InvalidPsbt("PSBT_GLOBAL_OUTPUT_COUNT"))?;
+
+        let mut inputs = Vec::new();
+        for _ in 0..input_count {
+            inputs.push(parse_input(read_map(&mut r)?)?);
+        }
+        let mut outputs = Vec::new();
+        for _ in 0..output_count {
+            outputs.push(parse_output(read_map(&mut r)?)?);
+        }
+        if r.pos != body.len() {
+            return Err(VPackError::TrailingData(body.len() - r.pos));
+        }
+        Ok(Self {
+            tx_version,
+            fallback_locktime,
+            inputs,
+            outputs,
+            unknown,
+        })
+    }
+
+    fn locktime(&self) -> u32 {
+        self.fallback_locktime.unwrap_or(0)
+    }
+
+    fn preimages(&self) -> (Vec<TxInPreimage>, Vec<TxOutPreimage<'_>>) {
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|input| TxInPreimage {
+                prev_out_txid: input.previous_txid,
+                prev_out_vout: input.output_index,
+                sequence: input.sequence,
+            })
+            .collect();
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|output| TxOutPreimage {
+                value: output.amount,
+                script_pubkey: &output.script,
+            })
+            .collect();
+        (inputs, outputs)
+    }
+}
+
+/// One PSBT per transaction of `output`, anchor-spend first.
+///
+/// `anchor_value` and `anchor_script` describe the L1 anchor output, spent by the inputs of the
+/// first transaction; every other input must spend an output of an earlier exit transaction.
+/// Inputs carry no Taproot internal key or merkle root (see the [module docs](self)).
+///
+/// # Errors
+///
+/// - [`VPackError::EncodingError`] if a later transaction spends something else.
+/// - [`VPackError::InvalidVout`] if it spends an output the earlier transaction lacks.
+pub fn exit_psbts(
+    output: &VerificationOutput,
+    anchor_value: u64,
+    anchor_script: &[u8],
+) -> Result<Vec<Psbt>, VPackError> {
+    exit_psbts_with_keys(output, anchor_value, anchor_script, |_, _| None)
+}
+
+/// [`exit_psbts`] with the Taproot internal key and merkle root of each input from `tap_key`,
+/// called with the transaction's index in `output.signed_txs` and the input as built so far
+/// (outpoint and `witness_utxo` set). Inputs it returns `None` for are left without them.
+///
+/// # Errors
+///
+/// As for [`exit_psbts`].
+pub fn exit_psbts_with_keys(
+    output: &VerificationOutput,
+    anchor_value: u64,
+    anchor_script: &[u8],
+    mut tap_key: impl FnMut(usize, &PsbtInput) -> Option<TapKeySpend>,
+) -> Result<Vec<Psbt>, VPackError> {
+    let parsed = output
+        .signed_txs
+        .iter()
+        .map(|raw| ParsedTx::parse(raw))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut psbts = Vec::with_capacity(parsed.len());
+    for (i, (raw, tx)) in output.signed_txs.iter().zip(&parsed).enumerate() {
+        let prevouts = tx
+            .inputs
+            .iter()
+            .map(
+                |&(txid, vout, _)| match parsed[..i].iter().find(|p| p.txid == txid) {
+                    Some(parent) => parent
+                        .outputs
+                        .get(vout as usize)
+                        .map(|&(value, script_pubkey)| TxOutPreimage {
+                            value,
+                            script_pubkey,
+                        })
+                        .ok_or(VPackError::InvalidVout(vout)),
+                    None if i == 0 => Ok(TxOutPreimage {
+                        value: anchor_value,
+                        script_pubkey: anchor_script,
+                    }),
+                    None => Err(VPackError::EncodingError),
+                },
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut psbt = Psbt::from_tx(raw, &prevouts)?;
+        for input in &mut psbt.inputs {
+            if let Some(key) = tap_key(i, input) {
+                input.tap_internal_key = Some(key.internal_key);
+                input.tap_merkle_root = key.merkle_root;
+            }
+        }
+        psbts.push(psbt);
+    }
+    Ok(psbts)
+}
+
+fn write_pair(out: &mut Vec<u8>, key_type: u8, key_data: &[u8], value: &[u8]) {
+    write_compact_size(out, 1 + key_data.len() as u64);
+    out.push(key_type);
+    out.extend_from_slice(key_data);
+    write_compact_size(out, value.len() as u64);
+    out.extend_from_slice(value);
+}
+
+fn write_unknown(out: &mut Vec<u8>, pairs: &[(Vec<u8>, Vec<u8>)]) {
+    for (key, value) in pairs {
+        write_compact_size(out, key.len() as u64);
+        out.extend_from_slice(key);
+        write_compact_size(out, value.len() as u64);
+        out.extend_from_slice(value);
+    }
+    out.push(0x00);
+}
+
+type Pairs<'a> = Vec<(&'a [u8], &'a [u8])>;
+
+/// Key-value pairs up to the `0x00` separator; keys are unique and non-empty.
+fn read_map<'a>(r: &mut Cursor<'a>) -> Result<Pairs<'a>, VPackError> {
+    let mut pairs: Pairs<'a> = Vec::new();
+    loop {
+        let key_len = r.compact_size()?;
+        if key_len == 0 {
+            return Ok(pairs);
+        }
+        let key = r.take(key_len)?;
+        let value_len = r.compact_size()?;
+        let value = r.take(value_len)?;
+        if pairs.iter().any(|(k, _)| *k == key) {
+            return Err(VPackError::InvalidPsbt("duplicate key"));
+        }
+        pairs.push((key, value));
+    }
+}
+
+fn parse_input(pairs: Pairs<'_>) -> Result<PsbtInput, VPackError> {
+    let mut previous_txid = None;
+    let mut output_index = None;
+    let mut input = PsbtInput::new([0u8; 32], 0, DEFAULT_SEQUENCE);
+    for (key, value) in pairs {
+        let key_data = &key[1..];
+        match (key[0], key_data.len()) {
+            (PSBT_IN_WITNESS_UTXO, 0) => {
+                let invalid = VPackError::InvalidPsbt("PSBT_IN_WITNESS_UTXO");
+                let mut r = Cursor {
+                    data: value,
+                    pos: 0,
+                };
+                let amount = r.u64().map_err(|_| invalid)?;
+                let script_len = r.compact_size().map_err(|_| invalid)?;
+                let script = r.take(script_len).map_err(|_| invalid)?;
+                if r.pos != value.len() {
+                    return Err(invalid);
+                }
+                input.witness_utxo = Some(WitnessUtxo {
+                    value: amount,
+                    script_pubkey: script.to_vec(),
+                });
+            }
+            (PSBT_IN_SIGHASH_TYPE, 0) => {
+                input.sighash_type = Some(le_u32(value, "PSBT_IN_SIGHASH_TYPE")?)
+            }
+            (PSBT_IN_FINAL_SCRIPTWITNESS, 0) => {
+                input.final_script_witness = Some(witness_stack(value)?)
+            }
+            (PSBT_IN_PREVIOUS_TXID, 0) => {
+                previous_txid = Some(array32(value, "PSBT_IN_PREVIOUS_TXID")?)
+            }
+            (PSBT_IN_OUTPUT_INDEX, 0) => {
+                output_index = Some(le_u32(value, "PSBT_IN_OUTPUT_INDEX")?)
+            }
+            (PSBT_IN_SEQUENCE, 0) => input.sequence = le_u32(value, "PSBT_IN_SEQUENCE")?,
+            (PSBT_IN_TAP_KEY_SIG, 0) => {
+                input.tap_key_sig = Some(signature(value, "PSBT_IN_TAP_KEY_SIG")?)
+            }
+            (PSBT_IN_TAP_SCRIPT_SIG, 64) => input.tap_script_sigs.push(TapScriptSig {
+                pubkey: array32(&key_data[..32], "PSBT_IN_TAP_SCRIPT_SIG")?,
+                leaf_hash: array32(&key_data[32..], "PSBT_IN_TAP_SCRIPT_SIG")?,
+                signature: signature(value, "PSBT_IN_TAP_SCRIPT_SIG")?,
+            }),
+            (PSBT_IN_TAP_LEAF_SCRIPT, len) if len >= 33 && (len - 33) % 32 == 0 => {
+                let (&leaf_version, script) = value
+                    .split_last()
+                    .ok_or(VPackError::InvalidPsbt("PSBT_IN_TAP_LEAF_SCRIPT"))?;
+                input.tap_leaf_scripts.push(TapLeafScript {
+                    control_block: key_data.to_vec(),
+                    script: script.to_vec(),
+                    leaf_version,
+                });
+            }
+            (PSBT_IN_TAP_INTERNAL_KEY, 0) => {
+                input.tap_internal_key = Some(array32(value, "PSBT_IN_TAP_INTERNAL_KEY")?)
+            }
+            (PSBT_IN_TAP_MERKLE_ROOT, 0) => {
+                input.tap_merkle_root = Some(array32(value, "PSBT_IN_TAP_MERKLE_ROOT")?)
+            }
+            (
+                PSBT_IN_WITNESS_UTXO
+                | PSBT_IN_SIGHASH_TYPE
+                | PSBT_IN_FINAL_SCRIPTWITNESS
+                | PSBT_IN_PREVIOUS_TXID
+                | PSBT_IN_OUTPUT_INDEX
+                | PSBT_IN_SEQUENCE
+                | PSBT_IN_TAP_KEY_SIG
+                | PSBT_IN_TAP_SCRIPT_SIG
+                | PSBT_IN_TAP_LEAF_SCRIPT
+                | PSBT_IN_TAP_INTERNAL_KEY
+                | PSBT_IN_TAP_MERKLE_ROOT,
+                _,
+            ) => return Err(VPackError::InvalidPsbt("input key data")),
+            _ => input.unknown.push((key.to_vec(), value.to_vec())),
+        }
+    }
+    input.previous_txid = previous_txid.ok_or(VPackError::InvalidPsbt("PSBT_IN_PREVIOUS_TXID"))?;
+    input.output_index = output_index.ok_or(VPackError::InvalidPsbt("PSBT_IN_OUTPUT_INDEX"))?;
+    Ok(input)
+}
+
+fn parse_output(pairs: Pairs<'_>) -> Result<PsbtOutput, VPackError> {
+    let mut amount = None;
+    let mut script = None;
+    let mut unknown = Vec::new();
+    for (key, value) in pairs {
+        match (key[0], key.len()) {
+            (PSBT_OUT_AMOUNT, 1) => {
+                let bytes: [u8; 8] = value
+                    .try_into()
+                    .map_err(|_| VPackError::InvalidPsbt("PSBT_OUT_AMOUNT"))?;
+                amount = Some(u64::from_le_bytes(bytes));
+            }
+            (PSBT_OUT_SCRIPT, 1) => script = Some(value.to_vec()),
+            _ => unknown.push((key.to_vec(), value.to_vec())),
+        }
+    }
+    Ok(PsbtOutput {
+        amount: amount.ok_or(VPackError::InvalidPsbt("PSBT_OUT_AMOUNT"))?,
+        script: script.ok_or(VPackError::InvalidPsbt("PSBT_OUT_SCRIPT"))?,
+        unknown,
+    })
+}
+
+fn le_u32(value: &[u8], field: &'static str) -> Result<u32, VPackError> {
+    value
+        .try_into()
+        .map(u32::from_le_bytes)
+        .map_err(|_| VPackError::InvalidPsbt(field))
+}
+
+fn array32(value: &[u8], field: &'static str) -> Result<[u8; 32], VPackError> {
+    value.try_into().map_err(|_| VPackError::InvalidPsbt(field))
+}
+
+fn signature(value: &[u8], field: &'static str) -> Result<Vec<u8>, VPackError> {
+    match value.len() {
+        64 | 65 => Ok(value.to_vec()),
+        _ => Err(VPackError::InvalidPsbt(field)),
+    }
+}
+
+/// A compact size filling all of `value`.
+fn count(value: &[u8], field: &'static str) -> Result<usize, VPackError> {
+    match read_compact_size(value) {
+        Some((n, len)) if len == value.len() => {
+            usize::try_from(n).map_err(|_| VPackError::InvalidPsbt(field))
+        }
+        _ => Err(VPackError::InvalidPsbt(field)),
+    }
+}
+
+fn witness_stack(value: &[u8]) -> Result<Vec<Vec<u8>>, VPackError> {
+    let invalid = VPackError::InvalidPsbt("PSBT_IN_FINAL_SCRIPTWITNESS");
+    let mut r = Cursor {
+        data: value,
+        pos: 0,
+    };
+    let items = r.compact_size().map_err(|_| invalid)?;
+    let mut stack = Vec::new();
+    for _ in 0..items {
+        let len = r.compact_size().map_err(|_| invalid)?;
+        stack.push(r.take(len).map_err(|_| invalid)?.to_vec());
+    }
+    if r.pos != value.len() {
+        return Err(invalid);
+    }
+    Ok(stack)
+}
+
+/// Keys checked by `script` in order, each pushed right before its `OP_CHECKSIG`,
+/// `OP_CHECKSIGVERIFY` or `OP_CHECKSIGADD`. `None` if a check takes its key from elsewhere or a
+/// push is truncated.
+fn checksig_keys(script: &[u8]) -> Option<Vec<[u8; 32]>> {
+    let mut keys = Vec::new();
+    let mut last_push: Option<&[u8]> = None;
+    for (_, instruction) in Instructions::new(script) {
+        match instruction.ok()? {
+            Instruction::Push(data) => {
+                last_push = Some(data);
+                continue;
+            }
+            Instruction::Op(OP_CHECKSIG | OP_CHECKSIGVERIFY | OP_CHECKSIGADD) => {
+                keys.push(last_push?.try_into().ok()?);
+            }
+            Instruction::Op(_) => {}
+        }
+        last_push = None;
+    }
+    Some(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksig_keys_in_script_order() {
+        let (a, b) = ([0x0a; 32], [0x0b; 32]);
+        let mut script = alloc::vec![0x51, 0x69, 0x02, 0x90, 0x00, 0xb2, 0x75, 0x20];
+        script.extend_from_slice(&a);
+        script.extend_from_slice(&[OP_CHECKSIGVERIFY, 0x20]);
+        script.extend_from_slice(&b);
+        script.push(OP_CHECKSIG);
+        assert_eq!(checksig_keys(&script), Some(alloc::vec![a, b]));
+
+        // Key taken from the witness: the signatures cannot be ordered.
+        assert_eq!(checksig_keys(&[OP_CHECKSIG]), None);
+        // Truncated push.
+        assert_eq!(checksig_keys(&[0x20, 0x00]), None);
+    }
+}
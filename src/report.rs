@@ -0,0 +1,534 @@
+//! Non-fail-fast verification: every check and its outcome.
+//!
+//! [`crate::verify`] stops at the first failing check. [`verify_report`] runs the same checks but
+//! records each one and keeps going where it can, so an auditor sees the whole picture: the
+//! checksum, the reconstructed txid of every path step, the conservation-of-value sum per level,
+//! every signature check, timelock margins and the path-exclusivity root.
+//!
+//! Entries are tree-wide (`step: None`) or belong to one transaction: `Some(i)` for `path[i]`
+//! (top-down from the anchor) and `Some(path.len())` for the leaf transaction. A failure that
+//! makes later checks meaningless (header, checksum, parse, a step whose outputs cannot be built)
+//! ends the walk; the checks it prevents are not recorded.
+//!
+//! Timelock entries go beyond [`crate::verify`] and do not affect
+//! [`VerificationReport::is_valid`]; use [`crate::verify_with`] to enforce them.
+
+use alloc::vec::Vec;
+
+use crate::consensus::chain::{reconstruct_chain, ChainTx};
+use crate::consensus::timelocks::{
+    extract_timelock_requirements, validate_expiry_for_cltv, validate_n_sequence_for_csv,
+};
+use crate::consensus::{check_expected_id, enforce_conservation, TxOutPreimage, VtxoId};
+use crate::error::VPackError;
+use crate::header::{Header, Network, TxVariant, CURRENT_VERSION, HEADER_SIZE};
+use crate::payload::compression::decompress_payload;
+use crate::payload::reader::BoundedReader;
+use crate::payload::tree::{VPackTree, VPackTreeRef};
+
+/// What a [`ReportEntry`] checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "export-json", derive(serde::Serialize))]
+#[cfg_attr(feature = "export-json", serde(rename_all = "snake_case"))]
+pub enum Check {
+    /// Header decoding (magic, version, variant, flags).
+    Header,
+    /// Header network against the caller's network.
+    Network,
+    /// CRC32 over the header and payload.
+    Checksum,
+    /// Bounded payload parse (and decompression).
+    Parse,
+    /// `validate_invariants`: sequence and fee-anchor consistency.
+    Invariants,
+    /// Reconstruction of one transaction; carries its txid.
+    Transaction,
+    /// Outputs of one transaction sum to the value they spend.
+    Conservation,
+    /// BIP-340 signature of one path step.
+    Signature,
+    /// Reconstructed VTXO ID against the expected ID.
+    VtxoId,
+    /// Leaf key commits to exactly the expected Taproot script tree.
+    PathExclusivity,
+    /// One CSV / CLTV field against `asp_expiry_script`; informational.
+    Timelock,
+}
+
+impl Check {
+    /// `false` for checks that [`crate::verify`] does not run.
+    pub fn is_required(self) -> bool {
+        !matches!(self, Check::Timelock)
+    }
+}
+
+/// Outcome of one check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "export-json", derive(serde::Serialize))]
+#[cfg_attr(feature = "export-json", serde(rename_all = "snake_case"))]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// Not applicable or not checkable here (e.g. the first step's signature, whose prevout is
+    /// the L1 anchor, or signature checks without `schnorr-verify`).
+    Skipped,
+}
+
+/// One check. Only the detail fields relevant to [`Self::check`] are set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "export-json", derive(serde::Serialize))]
+pub struct ReportEntry {
+    pub check: Check,
+    /// Transaction the check belongs to; see the [module documentation](self).
+    pub step: Option<u32>,
+    pub status: CheckStatus,
+    #[cfg_attr(
+        feature = "export-json",
+        serde(
+            serialize_with = "json::error",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub error: Option<VPackError>,
+    /// `Transaction`: reconstructed txid (internal byte order; display order in JSON).
+    #[cfg_attr(
+        feature = "export-json",
+        serde(serialize_with = "json::txid", skip_serializing_if = "Option::is_none")
+    )]
+    pub txid: Option<[u8; 32]>,
+    /// `Conservation`: value of the spent output.
+    #[cfg_attr(
+        feature = "export-json",
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    pub input_value: Option<u64>,
+    /// `Conservation`: sum of the transaction's outputs (`None` on overflow).
+    #[cfg_attr(
+        feature = "export-json",
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    pub output_sum: Option<u64>,
+    /// `Timelock`: observed minus required, in blocks, 512-second units, height or seconds.
+    #[cfg_attr(
+        feature = "export-json",
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    pub margin: Option<i64>,
+    /// `PathExclusivity`: Taproot Merkle root rebuilt from the tree's scripts.
+    #[cfg_attr(
+        feature = "export-json",
+        serde(serialize_with = "json::hash", skip_serializing_if = "Option::is_none")
+    )]
+    pub root: Option<[u8; 32]>,
+    /// `VtxoId`: the reconstructed ID.
+    #[cfg_attr(
+        feature = "export-json",
+        serde(
+            serialize_with = "json::vtxo_id",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub vtxo_id: Option<VtxoId>,
+}
+
+impl ReportEntry {
+    fn new(check: Check, step: Option<u32>, result: Result<(), VPackError>) -> Self {
+        let (status, error) = match result {
+            Ok(()) => (CheckStatus::Pass, None),
+            Err(e) => (CheckStatus::Fail, Some(e)),
+        };
+        Self {
+            check,
+            step,
+            status,
+            error,
+            txid: None,
+            input_value: None,
+            output_sum: None,
+            margin: None,
+            root: None,
+            vtxo_id: None,
+        }
+    }
+
+    fn skipped(check: Check, step: Option<u32>) -> Self {
+        Self {
+            status: CheckStatus::Skipped,
+            ..Self::new(check, step, Ok(()))
+        }
+    }
+}
+
+/// Every check run by [`verify_report`], in execution order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "export-json", derive(serde::Serialize))]
+pub struct VerificationReport {
+    pub entries: Vec<ReportEntry>,
+    /// The parsed tree, when parsing succeeded.
+    #[cfg_attr(feature = "export-json", serde(skip))]
+    pub tree: Option<VPackTree>,
+}
+
+impl VerificationReport {
+    /// `true` exactly when [`crate::verify`] would accept the V-PACK: no required check failed
+    /// and the VTXO ID matched.
+    pub fn is_valid(&self) -> bool {
+        self.failures().next().is_none()
+            && self
+                .entries
+                .iter()
+                .any(|e| e.check == Check::VtxoId && e.status == CheckStatus::Pass)
+    }
+
+    /// Failed required checks, in execution order.
+    pub fn failures(&self) -> impl Iterator<Item = &ReportEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.status == CheckStatus::Fail && e.check.is_required())
+    }
+
+    /// Entries belonging to transaction `step`.
+    pub fn step(&self, step: u32) -> impl Iterator<Item = &ReportEntry> {
+        self.entries.iter().filter(move |e| e.step == Some(step))
+    }
+
+    /// Reconstructed txid of transaction `step`, if it was built.
+    pub fn txid(&self, step: u32) -> Option<[u8; 32]> {
+        self.step(step)
+            .find(|e| e.check == Check::Transaction)
+            .and_then(|e| e.txid)
+    }
+
+    /// JSON rendering of [`Self::entries`].
+    #[cfg(feature = "export-json")]
+    pub fn to_json(&self) -> Result<alloc::string::String, VPackError> {
+        serde_json::to_string(self).map_err(|_| VPackError::EncodingError)
+    }
+
+    fn push(&mut self, entry: ReportEntry) {
+        self.entries.push(entry);
+    }
+}
+
+/// Runs every check of [`crate::verify`] (plus timelocks) without stopping at the first failure.
+/// Like `verify`, an ASP signature is accepted but not checked.
+pub fn verify_report(
+    vpack_bytes: &[u8],
+    expected_id: &VtxoId,
+    anchor_value: u64,
+    network: Network,
+) -> VerificationReport {
+    let mut report = VerificationReport::default();
+
+    let header = match vpack_bytes
+        .get(..HEADER_SIZE)
+        .ok_or(VPackError::IncompleteData)
+        .and_then(Header::from_bytes)
+    {
+        Ok(header) => header,
+        Err(e) => {
+            report.push(ReportEntry::new(Check::Header, None, Err(e)));
+            return report;
+        }
+    };
+    report.push(ReportEntry::new(Check::Header, None, Ok(())));
+    let network_check = header.network().and_then(|found| {
+        if found == network {
+            Ok(())
+        } else {
+            Err(VPackError::NetworkMismatch {
+                expected: network,
+                found,
+            })
+        }
+    });
+    report.push(ReportEntry::new(Check::Network, None, network_check));
+
+    let payload = match header
+        .split_body(&vpack_bytes[HEADER_SIZE..])
+        .and_then(|(payload, _)| header.verify_checksum(payload).map(|()| payload))
+    {
+        Ok(payload) => payload,
+        Err(e) => {
+            report.push(ReportEntry::new(Check::Checksum, None, Err(e)));
+            return report;
+        }
+    };
+    report.push(ReportEntry::new(Check::Checksum, None, Ok(())));
+
+    let raw;
+    let payload = if header.is_compressed() {
+        match decompress_payload(payload) {
+            Ok(bytes) => {
+                raw = bytes;
+                &raw[..]
+            }
+            Err(e) => {
+                report.push(ReportEntry::new(Check::Parse, None, Err(e)));
+                return report;
+            }
+        }
+    } else {
+        payload
+    };
+    let tree = match BoundedReader::parse_ref(&header, payload) {
+        Ok(tree) => tree,
+        Err(e) => {
+            report.push(ReportEntry::new(Check::Parse, None, Err(e)));
+            return report;
+        }
+    };
+    report.push(ReportEntry::new(Check::Parse, None, Ok(())));
+
+    report_parsed(&mut report, &header, &tree, expected_id, anchor_value);
+    report.tree = Some(VPackTree::from(&tree));
+    report
+}
+
+/// [`verify_report`] for an already parsed tree: the checks from `validate_invariants` onwards.
+pub fn report_tree(
+    tree: &VPackTree,
+    tx_variant: TxVariant,
+    expected_id: &VtxoId,
+    anchor_value: u64,
+) -> VerificationReport {
+    // Only the variant matters past parsing.
+    let header = Header {
+        flags: 0,
+        version: CURRENT_VERSION,
+        tx_variant,
+        tree_arity: 0,
+        tree_depth: 0,
+        node_count: 0,
+        asset_type: 0,
+        payload_len: 0,
+        checksum: 0,
+    };
+    let mut report = VerificationReport::default();
+    report_parsed(
+        &mut report,
+        &header,
+        &VPackTreeRef::from(tree),
+        expected_id,
+        anchor_value,
+    );
+    report.tree = Some(tree.clone());
+    report
+}
+
+fn report_parsed(
+    report: &mut VerificationReport,
+    header: &Header,
+    tree: &VPackTreeRef<'_>,
+    expected_id: &VtxoId,
+    anchor_value: u64,
+) {
+    report.push(ReportEntry::new(
+        Check::Invariants,
+        None,
+        crate::payload::validate_invariants_ref(header, tree),
+    ));
+    report_chain(report, header.tx_variant, tree, expected_id, anchor_value);
+
+    #[cfg(feature = "schnorr-verify")]
+    {
+        let mut entry = ReportEntry::new(
+            Check::PathExclusivity,
+            None,
+            crate::consensus::verify_path_exclusivity_ref(tree, header.tx_variant),
+        );
+        entry.root = crate::consensus::exclusivity_merkle_root(tree, header.tx_variant).ok();
+        report.push(entry);
+    }
+    #[cfg(not(feature = "schnorr-verify"))]
+    report.push(ReportEntry::skipped(Check::PathExclusivity, None));
+
+    report_timelocks(report, tree);
+}
+
+/// The engine walk (same transactions as `compute_vtxo_id_ref`), recording each check instead of
+/// returning on the first error. A failed conservation or signature check does not change the
+/// transactions, so the walk continues past it.
+fn report_chain(
+    report: &mut VerificationReport,
+    variant: TxVariant,
+    tree: &VPackTreeRef<'_>,
+    expected_id: &VtxoId,
+    anchor_value: u64,
+) {
+    let walk = reconstruct_chain(tree, variant);
+    let mut input_value = Some(anchor_value);
+    for (i, tx) in walk.txs.iter().enumerate() {
+        let step = Some(i as u32);
+        let prev = i.checked_sub(1).map(|p| &walk.txs[p]);
+        if let Some(prev) = prev {
+            input_value = input_value
+                .and(prev.outputs.get(tx.input.prev_out_vout as usize))
+                .map(|o| o.value);
+        }
+        report.push(conservation_entry(step, input_value, &tx.outputs));
+
+        if let Some(sig) = tree.path.get(i).and_then(|item| item.signature) {
+            let entry = match prev {
+                // The first step spends the L1 anchor, whose script the tree does not carry.
+                None => ReportEntry::skipped(Check::Signature, step),
+                Some(prev) => signature_entry(step, tx, &prev.outputs, tree, sig),
+            };
+            report.push(entry);
+        }
+
+        let mut entry = ReportEntry::new(Check::Transaction, step, Ok(()));
+        entry.txid = Some(tx.txid);
+        report.push(entry);
+    }
+
+    match walk.result {
+        Ok(id) => {
+            let mut entry =
+                ReportEntry::new(Check::VtxoId, None, check_expected_id(&id, expected_id));
+            entry.vtxo_id = Some(id);
+            report.push(entry);
+        }
+        // Outputs of a path step could not be built.
+        Err(e) if walk.txs.len() < tree.path.len() => {
+            let step = Some(walk.txs.len() as u32);
+            report.push(ReportEntry::new(Check::Transaction, step, Err(e)));
+        }
+        Err(e) => report.push(ReportEntry::new(Check::VtxoId, None, Err(e))),
+    }
+}
+
+fn conservation_entry(
+    step: Option<u32>,
+    input_value: Option<u64>,
+    outputs: &[TxOutPreimage<'_>],
+) -> ReportEntry {
+    let Some(input) = input_value else {
+        return ReportEntry::skipped(Check::Conservation, step);
+    };
+    let mut entry = ReportEntry::new(
+        Check::Conservation,
+        step,
+        enforce_conservation(Some(input), outputs),
+    );
+    entry.input_value = Some(input);
+    entry.output_sum = outputs
+        .iter()
+        .try_fold(0u64, |acc, o| acc.checked_add(o.value));
+    entry
+}
+
+#[cfg(feature = "schnorr-verify")]
+fn signature_entry(
+    step: Option<u32>,
+    tx: &ChainTx<'_>,
+    prev_outputs: &[TxOutPreimage<'_>],
+    tree: &VPackTreeRef<'_>,
+    sig: &[u8; 64],
+) -> ReportEntry {
+    ReportEntry::new(
+        Check::Signature,
+        step,
+        crate::consensus::verify_hop_signature(
+            &tx.input,
+            &tx.outputs,
+            Some(prev_outputs),
+            tree.leaf.script_pubkey,
+            sig,
+        ),
+    )
+}
+
+#[cfg(not(feature = "schnorr-verify"))]
+fn signature_entry(
+    step: Option<u32>,
+    _tx: &ChainTx<'_>,
+    _prev_outputs: &[TxOutPreimage<'_>],
+    _tree: &VPackTreeRef<'_>,
+    _sig: &[u8; 64],
+) -> ReportEntry {
+    ReportEntry::skipped(Check::Signature, step)
+}
+
+/// One entry per checked `nSequence` (path steps, then the leaf) and one for the leaf expiry,
+/// each with its margin over the script's requirement.
+fn report_timelocks(report: &mut VerificationReport, tree: &VPackTreeRef<'_>) {
+    let reqs = match extract_timelock_requirements(tree.asp_expiry_script) {
+        Ok(reqs) => reqs,
+        Err(e) => {
+            report.push(ReportEntry::new(Check::Timelock, None, Err(e)));
+            return;
+        }
+    };
+    let leaf_step = tree.path.len() as u32;
+    if reqs.needs_csv() {
+        let sequences = tree
+            .path
+            .iter()
+            .map(|item| item.sequence)
+            .chain(core::iter::once(tree.leaf.sequence));
+        for (step, sequence) in (0..).zip(sequences) {
+            let mut entry = ReportEntry::new(
+                Check::Timelock,
+                Some(step),
+                validate_n_sequence_for_csv(&reqs, sequence),
+            );
+            entry.margin = reqs.csv_margin(sequence);
+            report.push(entry);
+        }
+    }
+    if reqs.needs_cltv() {
+        let mut entry = ReportEntry::new(
+            Check::Timelock,
+            Some(leaf_step),
+            validate_expiry_for_cltv(&reqs, tree.leaf.expiry),
+        );
+        entry.margin = reqs.cltv_margin(tree.leaf.expiry);
+        report.push(entry);
+    }
+}
+
+#[cfg(feature = "export-json")]
+mod json {
+    use alloc::string::{String, ToString};
+
+    use serde::Serializer;
+
+    use crate::consensus::VtxoId;
+    use crate::error::VPackError;
+
+    pub fn error<S: Serializer>(error: &Option<VPackError>, s: S) -> Result<S::Ok, S::Error> {
+        match error {
+            Some(e) => s.serialize_str(&e.to_string()),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn txid<S: Serializer>(txid: &Option<[u8; 32]>, s: S) -> Result<S::Ok, S::Error> {
+        match txid {
+            Some(bytes) => {
+                let display: String = bytes
+                    .iter()
+                    .rev()
+                    .map(|b| alloc::format!("{:02x}", b))
+                    .collect();
+                s.serialize_str(&display)
+            }
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn hash<S: Serializer>(hash: &Option<[u8; 32]>, s: S) -> Result<S::Ok, S::Error> {
+        match hash {
+            Some(bytes) => s.serialize_str(&hex::encode(bytes)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn vtxo_id<S: Serializer>(id: &Option<VtxoId>, s: S) -> Result<S::Ok, S::Error> {
+        match id {
+            Some(id) => s.serialize_str(&id.to_string()),
+            None => s.serialize_none(),
+        }
+    }
+}
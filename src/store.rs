@@ -0,0 +1,788 @@
+//! Content-addressed VTXO store with path-prefix deduplication.
+//!
+//! VTXOs from the same round share their upper path: the same root transaction and the same
+//! branch transactions, each carried in full (sibling scripts, signature) by every V-PACK.
+//! [`VtxoStore`] keeps each path transaction once, keyed by the txid the consensus engines
+//! compute for it, and keeps per-VTXO only what is unique: the leaf, the leaf siblings, the prefix
+//! and trailer, and a list of `(txid, parent_index, child slot)` references into the shared table.
+//!
+//! Trees are rebuilt on demand ([`VtxoStore::tree`], [`VtxoStore::vpack`]). Version-2 extension
+//! records are kept per VTXO, so a V-PACK comes back out of the store byte for byte (less the
+//! ASP signature).
+//!
+//! Two bulk exports: [`VtxoStore::to_bytes`] serializes the store itself, each path transaction
+//! once, as the compact backup blob; [`VtxoStore::to_bundle`] packs every stored VTXO once as a
+//! standalone V-PACK into a [bundle](crate::bundle) that any V-PACK reader can verify. Each
+//! V-PACK in a bundle carries its full path again, so only the store blob shrinks with shared
+//! rounds.
+
+use alloc::collections::btree_map::Entry;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::bundle::{pack_bundle, BundleEntry};
+use crate::consensus::tx_decode::ParsedTx;
+use crate::consensus::{builtin_engine, VtxoId, VtxoIdKey};
+use crate::error::VPackError;
+use crate::header::{BuiltinVariant, Header, TxVariant, FLAG_ASP_SIGNED, HEADER_SIZE};
+use crate::pack::pack_with_extensions;
+use crate::payload::extensions::Extensions;
+use crate::payload::reader::BoundedReader;
+use crate::payload::tree::{GenesisItem, SiblingNode, VPackTree, VPackTreeRef, VtxoLeaf};
+use crate::types::hashes::Hash;
+use crate::types::{Amount, OutPoint, ScriptBuf, TxOut, Txid};
+
+/// Store backup magic bytes: "VPS".
+pub const STORE_MAGIC: [u8; 3] = [0x56, 0x50, 0x53];
+/// Current store backup format version.
+pub const STORE_VERSION: u8 = 1;
+
+/// How an output appears when it is a sibling (rather than the child) of a path step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+enum SiblingForm {
+    Compact([u8; 32]),
+    Full,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+struct StoredOutput {
+    value: u64,
+    script: Vec<u8>,
+    /// `None` until some stored VTXO references this output as a sibling.
+    sibling: Option<SiblingForm>,
+}
+
+/// One path transaction, shared by every VTXO whose path passes through it.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+struct StoredTx {
+    sequence: u32,
+    signature: Option<[u8; 64]>,
+    sighash_flag: u8,
+    outputs: Vec<StoredOutput>,
+    #[borsh(skip)]
+    refs: u32,
+}
+
+/// Where the path step's child sits in the transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+enum ChildSlot {
+    /// The child is output `vout`.
+    Output(u32),
+    /// Ark Labs branch template: empty child script, not an output (amount kept verbatim).
+    Detached(u64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+struct StoredStep {
+    txid: [u8; 32],
+    parent_index: u32,
+    child: ChildSlot,
+}
+
+/// Header fields needed to re-pack; `payload_len` and the checksum are recomputed by `pack`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+struct StoredHeader {
+    flags: u8,
+    version: u8,
+    tx_variant: u8,
+    tree_arity: u16,
+    tree_depth: u16,
+    node_count: u16,
+    asset_type: u32,
+}
+
+impl StoredHeader {
+    fn new(header: &Header) -> Self {
+        Self {
+            // A re-packed V-PACK is no longer covered by the ASP signature.
+            flags: header.flags & !FLAG_ASP_SIGNED,
+            version: header.version,
+            tx_variant: header.tx_variant.as_u8(),
+            tree_arity: header.tree_arity,
+            tree_depth: header.tree_depth,
+            node_count: header.node_count,
+            asset_type: header.asset_type,
+        }
+    }
+
+    fn to_header(self) -> Result<Header, VPackError> {
+        Ok(Header {
+            flags: self.flags,
+            version: self.version,
+            tx_variant: TxVariant::try_from(self.tx_variant)?,
+            tree_arity: self.tree_arity,
+            tree_depth: self.tree_depth,
+            node_count: self.node_count,
+            asset_type: self.asset_type,
+            payload_len: 0,
+            checksum: 0,
+        })
+    }
+}
+
+/// Version-2 extension records of one VTXO.
+#[derive(Debug, Clone, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+struct StoredExtensions {
+    round_txid: Option<[u8; 32]>,
+    asp_pubkey: Option<[u8; 33]>,
+    created_at: Option<u64>,
+    label: Option<String>,
+}
+
+impl StoredExtensions {
+    fn new(extensions: &Extensions) -> Self {
+        Self {
+            round_txid: extensions.round_txid.map(|txid| txid.to_byte_array()),
+            asp_pubkey: extensions.asp_pubkey,
+            created_at: extensions.created_at,
+            label: extensions.label.clone(),
+        }
+    }
+
+    fn to_extensions(&self) -> Extensions {
+        Extensions {
+            round_txid: self.round_txid.map(Txid::from_byte_array),
+            asp_pubkey: self.asp_pubkey,
+            created_at: self.created_at,
+            label: self.label.clone(),
+        }
+    }
+}
+
+/// Per-VTXO data that is not shared.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+struct StoredVtxo {
+    header: StoredHeader,
+    anchor_txid: [u8; 32],
+    anchor_vout: u32,
+    asset_id: Option<[u8; 32]>,
+    fee_anchor_script: Vec<u8>,
+    leaf: VtxoLeaf,
+    leaf_siblings: Vec<StoredOutput>,
+    path: Vec<StoredStep>,
+    internal_key: [u8; 32],
+    asp_expiry_script: Vec<u8>,
+    extensions: StoredExtensions,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct StoreImage {
+    txs: BTreeMap<[u8; 32], StoredTx>,
+    vtxos: BTreeMap<VtxoIdKey, StoredVtxo>,
+}
+
+/// Deduplicating VTXO store. See the [module docs](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VtxoStore {
+    txs: BTreeMap<[u8; 32], StoredTx>,
+    vtxos: BTreeMap<VtxoIdKey, StoredVtxo>,
+}
+
+impl VtxoStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of stored VTXOs.
+    pub fn len(&self) -> usize {
+        self.vtxos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vtxos.is_empty()
+    }
+
+    /// Number of distinct path transactions held.
+    pub fn tx_count(&self) -> usize {
+        self.txs.len()
+    }
+
+    pub fn contains(&self, id: &VtxoId) -> bool {
+        self.vtxos.contains_key(&id.key())
+    }
+
+    /// IDs of all stored VTXOs, in key order.
+    pub fn ids(&self) -> impl Iterator<Item = VtxoId> + '_ {
+        self.vtxos.keys().filter_map(|key| VtxoId::from_key(*key))
+    }
+
+    /// Inserts a parsed tree and returns its VTXO ID.
+    ///
+    /// The engine for `header.tx_variant` is run over the path (without conservation of value) to
+    /// key each transaction by txid, so signatures are checked as in [`crate::verify`]. A txid
+    /// already in the store must carry identical outputs, signature and sibling data, else
+    /// [`VPackError::StoreConflict`]; nothing is modified on error. Re-inserting a stored ID is a
+    /// no-op.
+    pub fn insert(&mut self, header: &Header, tree: &VPackTree) -> Result<VtxoId, VPackError> {
+        self.insert_with_extensions(header, tree, &Extensions::default())
+    }
+
+    /// [`Self::insert`] keeping the version-2 extension records, which [`Self::vpack`] writes
+    /// back. Extensions of an already stored ID are left unchanged.
+    pub fn insert_with_extensions(
+        &mut self,
+        header: &Header,
+        tree: &VPackTree,
+        extensions: &Extensions,
+    ) -> Result<VtxoId, VPackError> {
+        let tree_ref = VPackTreeRef::from(tree);
+        let (txids, id) = chain_txids(header.tx_variant, &tree_ref)?;
+        if self.contains(&id) {
+            return Ok(id);
+        }
+
+        let mut steps = Vec::with_capacity(tree.path.len());
+        let mut split = Vec::with_capacity(tree.path.len());
+        for (i, (item, txid)) in tree.path.iter().zip(&txids).enumerate() {
+            let child_vout = tree
+                .path
+                .get(i + 1)
+                .map_or(tree.leaf.vout, |next| next.parent_index);
+            let (tx, child) = split_step(header.tx_variant, item, child_vout)?;
+            if let Some(existing) = self.txs.get(txid) {
+                check_same_tx(existing, &tx, txid)?;
+            }
+            steps.push(StoredStep {
+                txid: *txid,
+                parent_index: item.parent_index,
+                child,
+            });
+            split.push((*txid, tx));
+        }
+
+        // All checks passed: commit. Sibling data learned from this VTXO fills in outputs that
+        // earlier VTXOs only saw as their child.
+        for (txid, tx) in split {
+            match self.txs.entry(txid) {
+                Entry::Vacant(slot) => {
+                    slot.insert(StoredTx { refs: 1, ..tx });
+                }
+                Entry::Occupied(mut slot) => {
+                    let stored = slot.get_mut();
+                    for (output, new) in stored.outputs.iter_mut().zip(tx.outputs) {
+                        output.sibling = output.sibling.or(new.sibling);
+                    }
+                    stored.refs += 1;
+                }
+            }
+        }
+
+        self.vtxos.insert(
+            id.key(),
+            StoredVtxo {
+                header: StoredHeader::new(header),
+                anchor_txid: tree.anchor.txid.to_byte_array(),
+                anchor_vout: tree.anchor.vout,
+                asset_id: tree.asset_id,
+                fee_anchor_script: tree.fee_anchor_script.clone(),
+                leaf: tree.leaf.clone(),
+                leaf_siblings: tree.leaf_siblings.iter().map(stored_sibling).collect(),
+                path: steps,
+                internal_key: tree.internal_key,
+                asp_expiry_script: tree.asp_expiry_script.clone(),
+                extensions: StoredExtensions::new(extensions),
+            },
+        );
+        Ok(id)
+    }
+
+    /// Parses a complete V-PACK (header, checksum, payload) and inserts it with its extensions.
+    pub fn insert_vpack(&mut self, vpack_bytes: &[u8]) -> Result<VtxoId, VPackError> {
+        if vpack_bytes.len() < HEADER_SIZE {
+            return Err(VPackError::IncompleteData);
+        }
+        let header = Header::from_bytes(&vpack_bytes[..HEADER_SIZE])?;
+        let (payload, _) = header.split_body(&vpack_bytes[HEADER_SIZE..])?;
+        header.verify_checksum(payload)?;
+        let (tree, extensions) = BoundedReader::parse_with_extensions(&header, payload)?;
+        self.insert_with_extensions(&header, &tree, &extensions)
+    }
+
+    /// Removes a VTXO; path transactions no other VTXO references are dropped.
+    pub fn remove(&mut self, id: &VtxoId) -> bool {
+        let Some(vtxo) = self.vtxos.remove(&id.key()) else {
+            return false;
+        };
+        for step in &vtxo.path {
+            if let Some(tx) = self.txs.get_mut(&step.txid) {
+                tx.refs -= 1;
+                if tx.refs == 0 {
+                    self.txs.remove(&step.txid);
+                }
+            }
+        }
+        true
+    }
+
+    /// Header the VTXO was inserted with, as a packing template: `payload_len` and `checksum`
+    /// are zero ([`crate::pack::pack`] fills them in). `Ok(None)` if the ID is not stored.
+    pub fn header(&self, id: &VtxoId) -> Result<Option<Header>, VPackError> {
+        self.vtxos
+            .get(&id.key())
+            .map(|vtxo| vtxo.header.to_header())
+            .transpose()
+    }
+
+    /// Rebuilds the VTXO's tree from the shared transaction table. `Ok(None)` if the ID is not
+    /// stored; an entry that no longer rebuilds is an error.
+    pub fn tree(&self, id: &VtxoId) -> Result<Option<VPackTree>, VPackError> {
+        self.vtxos
+            .get(&id.key())
+            .map(|vtxo| rebuild_tree(&self.txs, vtxo))
+            .transpose()
+    }
+
+    /// Extension records the VTXO was inserted with (empty for version 1).
+    pub fn extensions(&self, id: &VtxoId) -> Option<Extensions> {
+        Some(self.vtxos.get(&id.key())?.extensions.to_extensions())
+    }
+
+    /// Rebuilds the VTXO and packs it, with its extensions, into a standalone V-PACK.
+    pub fn vpack(&self, id: &VtxoId) -> Result<Option<Vec<u8>>, VPackError> {
+        let Some(vtxo) = self.vtxos.get(&id.key()) else {
+            return Ok(None);
+        };
+        let header = vtxo.header.to_header()?;
+        let tree = rebuild_tree(&self.txs, vtxo)?;
+        pack_with_extensions(&header, &tree, &vtxo.extensions.to_extensions()).map(Some)
+    }
+
+    /// Packs every stored VTXO as a V-PACK into one [bundle](crate::bundle), each ID once.
+    ///
+    /// The store does not know anchor values; `anchor_value` supplies the one recorded in the
+    /// bundle's table of contents for each ID.
+    pub fn to_bundle(
+        &self,
+        mut anchor_value: impl FnMut(&VtxoId) -> u64,
+    ) -> Result<Vec<u8>, VPackError> {
+        let mut entries = Vec::with_capacity(self.vtxos.len());
+        for id in self.ids() {
+            entries.push(BundleEntry {
+                anchor_value: anchor_value(&id),
+                vpack: self.vpack(&id)?.ok_or(VPackError::EncodingError)?,
+                id,
+            });
+        }
+        pack_bundle(&entries)
+    }
+
+    /// Serializes the whole store (each path transaction once) as a backup blob:
+    /// `"VPS" || version || borsh(tables) || crc32`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, VPackError> {
+        let image = StoreImage {
+            txs: self.txs.clone(),
+            vtxos: self.vtxos.clone(),
+        };
+        let mut out = Vec::new();
+        out.extend_from_slice(&STORE_MAGIC);
+        out.push(STORE_VERSION);
+        image
+            .serialize(&mut out)
+            .map_err(|_| VPackError::EncodingError)?;
+        let checksum = crc32fast::hash(&out);
+        out.extend_from_slice(&checksum.to_le_bytes());
+        Ok(out)
+    }
+
+    /// Restores a store from [`Self::to_bytes`] output.
+    ///
+    /// Every VTXO is rebuilt and re-inserted, so txids and IDs are recomputed rather than trusted;
+    /// a recomputed ID that differs from its stored key fails with [`VPackError::IdMismatch`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VPackError> {
+        if bytes.len() < STORE_MAGIC.len() + 1 + 4 {
+            return Err(VPackError::IncompleteData);
+        }
+        let (body, crc) = bytes.split_at(bytes.len() - 4);
+        if body[0..3] != STORE_MAGIC {
+            return Err(VPackError::InvalidMagic);
+        }
+        if body[3] != STORE_VERSION {
+            return Err(VPackError::UnsupportedVersion(body[3]));
+        }
+        let expected = u32::from_le_bytes([crc[0], crc[1], crc[2], crc[3]]);
+        let found = crc32fast::hash(body);
+        if found != expected {
+            return Err(VPackError::ChecksumMismatch { expected, found });
+        }
+        let image =
+            StoreImage::try_from_slice(&body[4..]).map_err(|_| VPackError::EncodingError)?;
+
+        let mut store = Self::new();
+        for (key, vtxo) in &image.vtxos {
+            let expected_id = VtxoId::from_key(*key).ok_or(VPackError::EncodingError)?;
+            let header = vtxo.header.to_header()?;
+            let tree = rebuild_tree(&image.txs, vtxo)?;
+            let extensions = vtxo.extensions.to_extensions();
+            let id = store.insert_with_extensions(&header, &tree, &extensions)?;
+            if id != expected_id {
+                return Err(VPackError::IdMismatch {
+                    computed: crate::consensus::vtxo_id_mismatch_diagnostic_bytes(&id),
+                    expected: crate::consensus::vtxo_id_mismatch_diagnostic_bytes(&expected_id),
+                    computed_vout: crate::consensus::vtxo_id_mismatch_diagnostic_vout(&id),
+                    expected_vout: crate::consensus::vtxo_id_mismatch_diagnostic_vout(&expected_id),
+                });
+            }
+        }
+        Ok(store)
+    }
+}
+
+/// Runs the variant's engine (no conservation) and returns each path txid and the VTXO ID.
+fn chain_txids(
+    variant: TxVariant,
+    tree: &VPackTreeRef<'_>,
+) -> Result<(Vec<[u8; 32]>, VtxoId), VPackError> {
+    let output = builtin_engine(variant)?.compute_vtxo_id_ref(tree, None)?;
+    let path_txs = output
+        .signed_txs
+        .get(..tree.path.len())
+        .ok_or(VPackError::EncodingError)?;
+    let txids = path_txs
+        .iter()
+        .map(|raw| ParsedTx::parse(raw).map(|tx| tx.txid))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((txids, output.id))
+}
+
+fn stored_sibling(sibling: &SiblingNode) -> StoredOutput {
+    match sibling {
+        SiblingNode::Compact {
+            hash,
+            value,
+            script,
+        } => StoredOutput {
+            value: *value,
+            script: script.clone(),
+            sibling: Some(SiblingForm::Compact(*hash)),
+        },
+        SiblingNode::Full(txout) => StoredOutput {
+            value: txout.value.to_sat(),
+            script: txout.script_pubkey.as_bytes().to_vec(),
+            sibling: Some(SiblingForm::Full),
+        },
+    }
+}
+
+/// Splits a path step into the shared transaction (outputs in vout order) and its child slot,
+/// using the same child placement as the engine for `variant`.
+fn split_step(
+    variant: TxVariant,
+    item: &GenesisItem,
+    child_vout: u32,
+) -> Result<(StoredTx, ChildSlot), VPackError> {
+    let child = match variant.builtin()? {
+        BuiltinVariant::V3Anchored if item.child_script_pubkey.is_empty() => {
+            ChildSlot::Detached(item.child_amount)
+        }
+        BuiltinVariant::V3Anchored => ChildSlot::Output(child_vout),
+        BuiltinVariant::V3Plain => ChildSlot::Output(item.parent_index),
+    };
+    let child_pos = match child {
+        ChildSlot::Output(vout) => Some(vout as usize),
+        ChildSlot::Detached(_) => None,
+    };
+
+    let num_outputs = item.siblings.len() + usize::from(child_pos.is_some());
+    let mut siblings = item.siblings.iter();
+    let mut outputs = Vec::with_capacity(num_outputs);
+    for j in 0..num_outputs {
+        if Some(j) == child_pos {
+            outputs.push(StoredOutput {
+                value: item.child_amount,
+                script: item.child_script_pubkey.clone(),
+                sibling: None,
+            });
+        } else {
+            let sibling = siblings.next().ok_or(VPackError::EncodingError)?;
+            outputs.push(stored_sibling(sibling));
+        }
+    }
+    if child_pos.is_some_and(|pos| pos >= num_outputs) {
+        return Err(VPackError::InvalidVout(child_vout));
+    }
+
+    Ok((
+        StoredTx {
+            sequence: item.sequence,
+            signature: item.signature,
+            sighash_flag: item.sighash_flag,
+            outputs,
+            refs: 0,
+        },
+        child,
+    ))
+}
+
+/// Checks that a re-encountered transaction matches the stored copy.
+fn check_same_tx(existing: &StoredTx, new: &StoredTx, txid: &[u8; 32]) -> Result<(), VPackError> {
+    let conflict = VPackError::StoreConflict(*txid);
+    if existing.sequence != new.sequence
+        || existing.signature != new.signature
+        || existing.sighash_flag != new.sighash_flag
+        || existing.outputs.len() != new.outputs.len()
+    {
+        return Err(conflict);
+    }
+    for (stored, incoming) in existing.outputs.iter().zip(&new.outputs) {
+        if stored.value != incoming.value || stored.script != incoming.script {
+            return Err(conflict);
+        }
+        if let (Some(a), Some(b)) = (stored.sibling, incoming.sibling) {
+            if a != b {
+                return Err(conflict);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn sibling_node(output: &StoredOutput) -> Result<SiblingNode, VPackError> {
+    match output.sibling.ok_or(VPackError::EncodingError)? {
+        SiblingForm::Compact(hash) => Ok(SiblingNode::Compact {
+            hash,
+            value: output.value,
+            script: output.script.clone(),
+        }),
+        SiblingForm::Full => Ok(SiblingNode::Full(TxOut {
+            value: Amount::from_sat(output.value),
+            script_pubkey: ScriptBuf::from_bytes(output.script.clone()),
+        })),
+    }
+}
+
+fn rebuild_tree(
+    txs: &BTreeMap<[u8; 32], StoredTx>,
+    vtxo: &StoredVtxo,
+) -> Result<VPackTree, VPackError> {
+    let mut path = Vec::with_capacity(vtxo.path.len());
+    for step in &vtxo.path {
+        let tx = txs.get(&step.txid).ok_or(VPackError::EncodingError)?;
+        let (child_pos, child_amount, child_script_pubkey) = match step.child {
+            ChildSlot::Output(vout) => {
+                let output = tx
+                    .outputs
+                    .get(vout as usize)
+                    .ok_or(VPackError::InvalidVout(vout))?;
+                (Some(vout as usize), output.value, output.script.clone())
+            }
+            ChildSlot::Detached(amount) => (None, amount, Vec::new()),
+        };
+        let siblings = tx
+            .outputs
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| Some(*j) != child_pos)
+            .map(|(_, output)| sibling_node(output))
+            .collect::<Result<Vec<_>, _>>()?;
+        path.push(GenesisItem {
+            siblings,
+            parent_index: step.parent_index,
+            sequence: tx.sequence,
+            child_amount,
+            child_script_pubkey,
+            signature: tx.signature,
+            sighash_flag: tx.sighash_flag,
+        });
+    }
+
+    Ok(VPackTree {
+        leaf: vtxo.leaf.clone(),
+        leaf_siblings: vtxo
+            .leaf_siblings
+            .iter()
+            .map(sibling_node)
+            .collect::<Result<Vec<_>, _>>()?,
+        path,
+        anchor: OutPoint {
+            txid: Txid::from_byte_array(vtxo.anchor_txid),
+            vout: vtxo.anchor_vout,
+        },
+        asset_id: vtxo.asset_id,
+        fee_anchor_script: vtxo.fee_anchor_script.clone(),
+        internal_key: vtxo.internal_key,
+        asp_expiry_script: vtxo.asp_expiry_script.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::hash_sibling_birth_tx;
+    use crate::header::{FLAG_PROOF_COMPACT, VERSION_TLV};
+    use alloc::vec;
+
+    const FEE_ANCHOR: [u8; 4] = [0x51, 0x02, 0x4e, 0x73];
+
+    fn compact(value: u64, script: &[u8]) -> SiblingNode {
+        SiblingNode::Compact {
+            hash: hash_sibling_birth_tx(value, script),
+            value,
+            script: script.to_vec(),
+        }
+    }
+
+    fn step(
+        parent_index: u32,
+        child_amount: u64,
+        child_script: &[u8],
+        siblings: Vec<SiblingNode>,
+    ) -> GenesisItem {
+        GenesisItem {
+            siblings,
+            parent_index,
+            sequence: 0xFFFF_FFFF,
+            child_amount,
+            child_script_pubkey: child_script.to_vec(),
+            signature: None,
+            sighash_flag: 0,
+        }
+    }
+
+    /// Two Ark Labs VTXOs sharing the round's root transaction `[A 4000, B 5000, fee]`.
+    fn round_pair() -> (Header, VPackTree, VPackTree) {
+        let (script_a, script_b) = (vec![0x51, 0x20, 0xAA], vec![0x51, 0x20, 0xBB]);
+        let tree = |branch: u32, amount: u64, script: &[u8], other: (u64, &[u8])| {
+            let leaf_script = [script, &[0x01]].concat();
+            VPackTree {
+                leaf: VtxoLeaf {
+                    amount,
+                    vout: 0,
+                    sequence: 0xFFFF_FFFF,
+                    expiry: 0,
+                    exit_delta: 0,
+                    script_pubkey: leaf_script.clone(),
+                },
+                leaf_siblings: vec![compact(0, &FEE_ANCHOR)],
+                path: vec![
+                    step(
+                        0,
+                        amount,
+                        script,
+                        vec![compact(other.0, other.1), compact(0, &FEE_ANCHOR)],
+                    ),
+                    step(branch, amount, &leaf_script, vec![compact(0, &FEE_ANCHOR)]),
+                ],
+                anchor: OutPoint {
+                    txid: Txid::from_byte_array([0x11; 32]),
+                    vout: 0,
+                },
+                asset_id: None,
+                fee_anchor_script: FEE_ANCHOR.to_vec(),
+                internal_key: [0x22; 32],
+                asp_expiry_script: Vec::new(),
+            }
+        };
+        let tree_a = tree(0, 4000, &script_a, (5000, &script_b));
+        let tree_b = tree(1, 5000, &script_b, (4000, &script_a));
+        let header = Header {
+            flags: FLAG_PROOF_COMPACT,
+            version: 1,
+            tx_variant: TxVariant::V3Anchored,
+            tree_arity: 3,
+            tree_depth: 2,
+            node_count: 4,
+            asset_type: 0,
+            payload_len: 0,
+            checksum: 0,
+        };
+        (header, tree_a, tree_b)
+    }
+
+    #[test]
+    fn test_store_shares_round_prefix() {
+        let (header, tree_a, tree_b) = round_pair();
+        let mut store = VtxoStore::new();
+        let id_a = store.insert(&header, &tree_a).expect("insert A");
+        let id_b = store.insert(&header, &tree_b).expect("insert B");
+        assert_ne!(id_a, id_b);
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.tx_count(), 3);
+        assert_eq!(store.insert(&header, &tree_a), Ok(id_a));
+        assert_eq!(store.tx_count(), 3);
+
+        assert_eq!(store.tree(&id_a), Ok(Some(tree_a.clone())));
+        assert_eq!(store.tree(&id_b), Ok(Some(tree_b.clone())));
+        let vpack = store.vpack(&id_a).expect("pack").expect("stored");
+        assert_eq!(VtxoStore::new().insert_vpack(&vpack), Ok(id_a));
+
+        let restored =
+            VtxoStore::from_bytes(&store.to_bytes().expect("to_bytes")).expect("restore");
+        assert_eq!(restored, store);
+
+        assert!(store.remove(&id_a));
+        assert!(!store.remove(&id_a));
+        assert_eq!(store.tx_count(), 2);
+        assert_eq!(store.tree(&id_b), Ok(Some(tree_b)));
+        assert_eq!(store.tree(&id_a), Ok(None));
+    }
+
+    #[test]
+    fn test_store_reports_corrupted_entries() {
+        let (header, tree_a, _) = round_pair();
+        let mut store = VtxoStore::new();
+        let id = store.insert(&header, &tree_a).expect("insert");
+        // Drop a shared transaction the entry references.
+        let root = store.vtxos[&id.key()].path[0].txid;
+        store.txs.remove(&root);
+        assert_eq!(store.tree(&id), Err(VPackError::EncodingError));
+        assert_eq!(store.vpack(&id), Err(VPackError::EncodingError));
+
+        store.vtxos.get_mut(&id.key()).unwrap().header.tx_variant = 0x00;
+        assert_eq!(store.header(&id), Err(VPackError::InvalidTxVariant(0x00)));
+    }
+
+    #[test]
+    fn test_store_keeps_v2_extensions() {
+        let (header, tree_a, _) = round_pair();
+        let header = Header {
+            version: VERSION_TLV,
+            ..header
+        };
+        let extensions = Extensions {
+            round_txid: Some(Txid::from_byte_array([0x33; 32])),
+            asp_pubkey: Some([0x02; 33]),
+            created_at: Some(1_700_000_000),
+            label: Some(String::from("savings")),
+        };
+        let vpack = pack_with_extensions(&header, &tree_a, &extensions).expect("pack v2");
+
+        let mut store = VtxoStore::new();
+        let id = store.insert_vpack(&vpack).expect("insert v2");
+        assert_eq!(store.extensions(&id), Some(extensions));
+        assert_eq!(store.vpack(&id), Ok(Some(vpack.clone())));
+
+        let restored =
+            VtxoStore::from_bytes(&store.to_bytes().expect("to_bytes")).expect("restore");
+        assert_eq!(restored.vpack(&id), Ok(Some(vpack)));
+    }
+
+    #[test]
+    fn test_store_rejects_conflicting_shared_tx() {
+        let (header, tree_a, mut tree_b) = round_pair();
+        let mut store = VtxoStore::new();
+        store.insert(&header, &tree_a).expect("insert A");
+        let before = store.clone();
+
+        // Same root txid (the compact hash is not part of it), different commitment for the
+        // fee anchor sibling that A already recorded.
+        if let SiblingNode::Compact { hash, .. } = &mut tree_b.path[0].siblings[1] {
+            hash[0] ^= 0x01;
+        }
+        assert!(matches!(
+            store.insert(&header, &tree_b),
+            Err(VPackError::StoreConflict(_))
+        ));
+        assert_eq!(store, before);
+
+        let mut corrupted = store.to_bytes().expect("to_bytes");
+        let last = corrupted.len() - 5;
+        corrupted[last] ^= 0x01;
+        assert!(matches!(
+            VtxoStore::from_bytes(&corrupted),
+            Err(VPackError::ChecksumMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,252 @@
+//! The final transaction of a unilateral exit: sweeping the VTXO to the user's own address.
+//!
+//! Once the last exit transaction confirms and `exit_delta` blocks have passed, the VTXO output
+//! is spent through its exit leaf ([`ConsensusEngine::exit_leaf`]): the witness is the leaf's
+//! signatures, the tapscript and the BIP-341 control block. [`build_exit_sweep`] assembles
+//! everything but the signatures and returns the BIP-342 script-path sighash they commit to;
+//! [`ExitSweep::to_psbt`] exports the same spend for an external signer.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::consensus::taproot::{
+    balanced_merkle_sibling_path, compute_balanced_merkle_root,
+    compute_taproot_tweaked_key_x_and_parity, tap_branch_hash, tap_leaf_hash, TAPLEAF_VERSION,
+};
+use crate::consensus::taproot_sighash::{taproot_script_spend_sighash, NO_CODESEPARATOR};
+use crate::consensus::tx_decode::ParsedTx;
+use crate::consensus::{
+    builtin_engine, tx_preimage, tx_with_witnesses, ConsensusEngine, TxInPreimage, TxOutPreimage,
+    VtxoId,
+};
+use crate::cpfp::P2TR_DUST;
+use crate::error::VPackError;
+use crate::exit_cost::{signature_count, vbytes};
+use crate::header::TxVariant;
+use crate::payload::tree::VPackTree;
+use crate::psbt::{Psbt, PsbtInput, PsbtOutput, TapLeafScript, WitnessUtxo};
+use crate::types::hashes::{sha256d, Hash};
+
+/// Version 2: the lowest that enforces BIP-68 relative locktimes.
+const SWEEP_VERSION: u32 = 2;
+
+/// Unsigned script-path sweep of a VTXO.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExitSweep {
+    /// Spends the VTXO outpoint; nSequence is `exit_delta` blocks (BIP-68) for the CSV.
+    pub input: TxInPreimage,
+    /// Value of the VTXO.
+    pub amount: u64,
+    /// P2TR scriptPubKey of the VTXO output, committed to by the sighash.
+    pub prevout_script: Vec<u8>,
+    /// Value sent to `destination_script`: `amount - fee`.
+    pub value: u64,
+    pub destination_script: Vec<u8>,
+    pub fee: u64,
+    /// Exit tapscript being executed.
+    pub leaf_script: Vec<u8>,
+    /// `[control_byte || internal_key || merkle_path…]`.
+    pub control_block: Vec<u8>,
+    /// Number of signatures `leaf_script` checks.
+    pub signatures_required: usize,
+    /// BIP-342 SIGHASH_DEFAULT sighash every signature commits to.
+    pub sighash: [u8; 32],
+}
+
+impl ExitSweep {
+    /// Serialization without witness data.
+    pub fn unsigned_tx(&self) -> Vec<u8> {
+        tx_preimage(
+            SWEEP_VERSION,
+            core::slice::from_ref(&self.input),
+            &[self.output()],
+            0,
+        )
+    }
+
+    /// Txid (wire order); unchanged by signing.
+    pub fn txid(&self) -> [u8; 32] {
+        sha256d::Hash::hash(&self.unsigned_tx()).to_byte_array()
+    }
+
+    /// Broadcast-ready transaction. `signatures` fill the witness bottom-up, in the order the
+    /// script consumes them last-to-first (for the Ark Labs exit leaf: `[user, asp]`).
+    ///
+    /// # Errors
+    ///
+    /// [`VPackError::EncodingError`] if `signatures.len() != self.signatures_required`.
+    pub fn signed_tx(&self, signatures: &[[u8; 64]]) -> Result<Vec<u8>, VPackError> {
+        if signatures.len() != self.signatures_required {
+            return Err(VPackError::EncodingError);
+        }
+        let mut stack: Vec<&[u8]> = signatures.iter().map(|s| s.as_slice()).collect();
+        stack.push(&self.leaf_script);
+        stack.push(&self.control_block);
+        Ok(tx_with_witnesses(
+            SWEEP_VERSION,
+            core::slice::from_ref(&self.input),
+            &[self.output()],
+            &[&stack],
+            0,
+        ))
+    }
+
+    /// PSBT v2 of the sweep: the VTXO output as `witness_utxo`, the exit leaf with its control
+    /// block, the internal key and the Merkle root. Signers add one `tap_script_sigs` entry per
+    /// key of the leaf; [`Psbt::extract_tx`] then orders them as [`Self::signed_tx`] does.
+    ///
+    /// # Errors
+    ///
+    /// [`VPackError::InvalidPsbt`] if `control_block` is not `33 + 32 × depth` bytes.
+    pub fn to_psbt(&self) -> Result<Psbt, VPackError> {
+        let invalid = VPackError::InvalidPsbt("control block");
+        let (internal_key, path) = self
+            .control_block
+            .get(1..)
+            .and_then(|rest| rest.split_first_chunk::<32>())
+            .ok_or(invalid)?;
+        let (siblings, remainder) = path.as_chunks::<32>();
+        if !remainder.is_empty() {
+            return Err(invalid);
+        }
+        let merkle_root = siblings
+            .iter()
+            .fold(tap_leaf_hash(&self.leaf_script), |node, sibling| {
+                tap_branch_hash(node, *sibling)
+            });
+
+        let mut input = PsbtInput::new(
+            self.input.prev_out_txid,
+            self.input.prev_out_vout,
+            self.input.sequence,
+        );
+        input.witness_utxo = Some(WitnessUtxo {
+            value: self.amount,
+            script_pubkey: self.prevout_script.clone(),
+        });
+        input.tap_leaf_scripts.push(TapLeafScript {
+            control_block: self.control_block.clone(),
+            script: self.leaf_script.clone(),
+            leaf_version: TAPLEAF_VERSION,
+        });
+        input.tap_internal_key = Some(*internal_key);
+        input.tap_merkle_root = Some(merkle_root);
+        Ok(Psbt {
+            tx_version: SWEEP_VERSION,
+            fallback_locktime: Some(0),
+            inputs: vec![input],
+            outputs: vec![PsbtOutput::new(self.value, self.destination_script.clone())],
+            unknown: Vec::new(),
+        })
+    }
+
+    fn output(&self) -> TxOutPreimage<'_> {
+        TxOutPreimage {
+            value: self.value,
+            script_pubkey: &self.destination_script,
+        }
+    }
+}
+
+/// Builds the sweep of the VTXO of `tree` to `destination_script` at `feerate` (sat/vB), paid
+/// from the swept amount.
+///
+/// `variant` selects the engine as for [`crate::reconstruct_control_block`]; the exit leaf comes
+/// from [`ConsensusEngine::exit_leaf`].
+///
+/// # Errors
+///
+/// - [`VPackError::ControlBlockReconstructionFailed`] if the engine has no exit leaf or the
+///   leaf's scriptPubKey is not the P2TR output committing to it.
+/// - [`VPackError::InsufficientFunds`] if the fee leaves less than 330 sat.
+pub fn build_exit_sweep(
+    tree: &VPackTree,
+    variant: TxVariant,
+    destination_script: &[u8],
+    feerate: u64,
+) -> Result<ExitSweep, VPackError> {
+    build_exit_sweep_with(builtin_engine(variant)?, tree, destination_script, feerate)
+}
+
+/// [`build_exit_sweep`] with the reconstruction and Taproot leaf rules of `engine`.
+pub fn build_exit_sweep_with(
+    engine: &dyn ConsensusEngine,
+    tree: &VPackTree,
+    destination_script: &[u8],
+    feerate: u64,
+) -> Result<ExitSweep, VPackError> {
+    let output = engine.compute_vtxo_id(tree, None)?;
+    let (txid, vout) = match output.id {
+        VtxoId::Raw(txid) => (txid, tree.leaf.vout),
+        VtxoId::OutPoint(outpoint) => (outpoint.txid.to_byte_array(), outpoint.vout),
+    };
+
+    let exit = engine.exit_leaf(tree)?;
+    let (merkle_root, merkle_path) = compute_balanced_merkle_root(&exit.leaf_hashes)
+        .zip(balanced_merkle_sibling_path(&exit.leaf_hashes, exit.index))
+        .ok_or(VPackError::ControlBlockReconstructionFailed)?;
+    let (output_key, parity) =
+        compute_taproot_tweaked_key_x_and_parity(tree.internal_key, merkle_root)
+            .ok_or(VPackError::ControlBlockReconstructionFailed)?;
+    let mut prevout_script = vec![0x51, 0x20];
+    prevout_script.extend_from_slice(&output_key);
+    // The sighash commits to `prevout_script`; the leaf output must be exactly that key.
+    if tree.leaf.script_pubkey != prevout_script {
+        return Err(VPackError::ControlBlockReconstructionFailed);
+    }
+
+    let mut control_block = Vec::with_capacity(33 + 32 * merkle_path.len());
+    control_block.push(TAPLEAF_VERSION | parity);
+    control_block.extend_from_slice(&tree.internal_key);
+    for hash in &merkle_path {
+        control_block.extend_from_slice(hash);
+    }
+
+    let mut sweep = ExitSweep {
+        input: TxInPreimage {
+            prev_out_txid: txid,
+            prev_out_vout: vout,
+            sequence: u32::from(tree.leaf.exit_delta),
+        },
+        amount: tree.leaf.amount,
+        prevout_script,
+        value: 0,
+        destination_script: destination_script.to_vec(),
+        fee: 0,
+        signatures_required: signature_count(&exit.script)? as usize,
+        leaf_script: exit.script,
+        control_block,
+        sighash: [0u8; 32],
+    };
+
+    // Output values are fixed-size: weigh with placeholder signatures, then set the fee.
+    let placeholders = vec![[0u8; 64]; sweep.signatures_required];
+    let weight = ParsedTx::parse(&sweep.signed_tx(&placeholders)?)?.weight;
+    let fee = vbytes(weight).saturating_mul(feerate);
+    let required = fee.saturating_add(P2TR_DUST);
+    if sweep.amount < required {
+        return Err(VPackError::InsufficientFunds {
+            required,
+            available: sweep.amount,
+        });
+    }
+    sweep.fee = fee;
+    sweep.value = sweep.amount - fee;
+
+    let prevout = TxOutPreimage {
+        value: sweep.amount,
+        script_pubkey: &sweep.prevout_script,
+    };
+    sweep.sighash = taproot_script_spend_sighash(
+        SWEEP_VERSION,
+        0,
+        core::slice::from_ref(&sweep.input),
+        core::slice::from_ref(&prevout),
+        &[sweep.output()],
+        0,
+        &tap_leaf_hash(&sweep.leaf_script),
+        NO_CODESEPARATOR,
+        0x00,
+    )?;
+    Ok(sweep)
+}
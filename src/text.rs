@@ -0,0 +1,437 @@
+//! Bech32m text encoding of V-PACKs (`vpack1…`) for copy/paste and QR codes.
+//!
+//! A V-PACK is encoded as one or more bech32m strings (BIP-350 checksum, BIP-173 alphabet):
+//!
+//! ```text
+//! part := hrp "1" data checksum(6)
+//!   hrp:  "vpack" (mainnet) or "tvpack" (FLAG_TESTNET set in the header)
+//!   data: kind(1) [index(3) total(3)] bytes…   (5-bit symbols)
+//!     kind 0 — single part: bytes are the whole V-PACK
+//!     kind 1 — multi-part:  index / total are 15-bit big-endian, 0 <= index < total;
+//!                           bytes are the index-th chunk of MAX_PART_BYTES
+//! ```
+//!
+//! BIP-173 limits addresses to 90 characters; V-PACKs are longer than that, so parts use the
+//! 1023-character range over which the bech32 checksum is defined ([`MAX_PART_LEN`]). Encoders
+//! emit lowercase; decoders also accept all-uppercase parts (QR alphanumeric mode).
+//!
+//! Parts carry no integrity beyond their own checksum: [`from_text`] reassembles them in index
+//! order and then runs [`Header::from_bytes`] and the header's CRC32 over the result, so parts of
+//! different V-PACKs cannot be combined unnoticed. The returned bytes feed [`crate::verify`].
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::{TextErrorReason, VPackError};
+use crate::header::{Header, HEADER_SIZE};
+
+/// Human-readable part for mainnet V-PACKs.
+pub const HRP_MAINNET: &str = "vpack";
+/// Human-readable part for V-PACKs with `FLAG_TESTNET` set.
+pub const HRP_TESTNET: &str = "tvpack";
+/// Maximum length of one text part (the bech32 checksum's design length).
+pub const MAX_PART_LEN: usize = 1023;
+/// V-PACK bytes carried per part; larger V-PACKs are split into multi-part encodings.
+pub const MAX_PART_BYTES: usize = 620;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+const CHECKSUM_LEN: usize = 6;
+const KIND_SINGLE: u8 = 0;
+const KIND_MULTI: u8 = 1;
+/// Largest part count representable in three 5-bit symbols.
+const MAX_PARTS: usize = (1 << 15) - 1;
+
+/// Encodes a V-PACK as bech32m text: one part if it fits in [`MAX_PART_BYTES`], else several.
+///
+/// Only the header is inspected (for `FLAG_TESTNET`); the payload is encoded as-is.
+pub fn to_text(vpack_bytes: &[u8]) -> Result<Vec<String>, VPackError> {
+    if vpack_bytes.len() < HEADER_SIZE {
+        return Err(VPackError::IncompleteData);
+    }
+    let header = Header::from_bytes(&vpack_bytes[..HEADER_SIZE])?;
+    let hrp = if header.is_testnet() {
+        HRP_TESTNET
+    } else {
+        HRP_MAINNET
+    };
+
+    if vpack_bytes.len() <= MAX_PART_BYTES {
+        let mut data = Vec::with_capacity(1 + (vpack_bytes.len() * 8).div_ceil(5));
+        data.push(KIND_SINGLE);
+        push_bytes_as_symbols(&mut data, vpack_bytes);
+        return Ok(alloc::vec![encode_part(hrp, &data)]);
+    }
+
+    let chunks = vpack_bytes.chunks(MAX_PART_BYTES);
+    let total = chunks.len();
+    if total > MAX_PARTS {
+        return Err(VPackError::PayloadTooLarge(vpack_bytes.len() as u32));
+    }
+    Ok(chunks
+        .enumerate()
+        .map(|(index, chunk)| {
+            let mut data = Vec::with_capacity(7 + (chunk.len() * 8).div_ceil(5));
+            data.push(KIND_MULTI);
+            push_u15(&mut data, index);
+            push_u15(&mut data, total);
+            push_bytes_as_symbols(&mut data, chunk);
+            encode_part(hrp, &data)
+        })
+        .collect())
+}
+
+/// Decodes [`to_text`] output back into V-PACK bytes. Parts may be given in any order.
+///
+/// The reassembled bytes are checked with [`Header::from_bytes`], against the part prefix
+/// (`FLAG_TESTNET`), for exact length, and against the header CRC32.
+pub fn from_text<S: AsRef<str>>(parts: &[S]) -> Result<Vec<u8>, VPackError> {
+    let invalid = |reason| VPackError::InvalidText(reason);
+    if parts.is_empty() {
+        return Err(invalid(TextErrorReason::Parts));
+    }
+    let mut testnet = None;
+    let mut chunks: Vec<Option<Vec<u8>>> = Vec::new();
+
+    for part in parts {
+        let (is_testnet, data) = decode_part(part.as_ref())?;
+        if testnet.replace(is_testnet).is_some_and(|t| t != is_testnet) {
+            return Err(invalid(TextErrorReason::UnknownHrp));
+        }
+        let (kind, rest) = data.split_first().ok_or(invalid(TextErrorReason::Parts))?;
+        match *kind {
+            KIND_SINGLE if parts.len() == 1 => chunks.push(Some(symbols_to_bytes(rest)?)),
+            KIND_MULTI if rest.len() >= 6 => {
+                let index = read_u15(&rest[0..3]);
+                let total = read_u15(&rest[3..6]);
+                if chunks.is_empty() {
+                    if total < 2 || total != parts.len() {
+                        return Err(invalid(TextErrorReason::Parts));
+                    }
+                    chunks.resize(total, None);
+                }
+                if total != chunks.len() || index >= total || chunks[index].is_some() {
+                    return Err(invalid(TextErrorReason::Parts));
+                }
+                chunks[index] = Some(symbols_to_bytes(&rest[6..])?);
+            }
+            _ => return Err(invalid(TextErrorReason::Parts)),
+        }
+    }
+
+    let mut bytes = Vec::new();
+    for chunk in &chunks {
+        bytes.extend_from_slice(chunk.as_ref().ok_or(invalid(TextErrorReason::Parts))?);
+    }
+    if bytes.len() < HEADER_SIZE {
+        return Err(VPackError::IncompleteData);
+    }
+    let header = Header::from_bytes(&bytes[..HEADER_SIZE])?;
+    if testnet != Some(header.is_testnet()) {
+        return Err(invalid(TextErrorReason::NetworkMismatch));
+    }
+    let (payload, _) = header.split_body(&bytes[HEADER_SIZE..])?;
+    let payload_len = header.payload_len as usize;
+    if payload.len() < payload_len {
+        return Err(VPackError::IncompleteData);
+    }
+    if payload.len() > payload_len {
+        return Err(VPackError::TrailingData(payload.len() - payload_len));
+    }
+    header.verify_checksum(payload)?;
+    Ok(bytes)
+}
+
+fn polymod(values: impl Iterator<Item = u8>) -> u32 {
+    const GEN: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    let mut chk: u32 = 1;
+    for v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(v);
+        for (i, g) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    hrp.iter()
+        .map(|c| c >> 5)
+        .chain(core::iter::once(0))
+        .chain(hrp.iter().map(|c| c & 0x1f))
+}
+
+fn encode_part(hrp: &str, data: &[u8]) -> String {
+    let pm = polymod(
+        hrp_expand(hrp.as_bytes())
+            .chain(data.iter().copied())
+            .chain([0u8; CHECKSUM_LEN]),
+    ) ^ BECH32M_CONST;
+    let mut out = String::with_capacity(hrp.len() + 1 + data.len() + CHECKSUM_LEN);
+    out.push_str(hrp);
+    out.push('1');
+    for &d in data {
+        out.push(char::from(CHARSET[usize::from(d)]));
+    }
+    for i in 0..CHECKSUM_LEN {
+        let d = (pm >> (5 * (5 - i))) & 0x1f;
+        out.push(char::from(CHARSET[d as usize]));
+    }
+    out
+}
+
+/// Validates one part; returns whether it is testnet and its data symbols (checksum stripped).
+fn decode_part(part: &str) -> Result<(bool, Vec<u8>), VPackError> {
+    let invalid = |reason| VPackError::InvalidText(reason);
+    if part.len() > MAX_PART_LEN {
+        return Err(invalid(TextErrorReason::TooLong));
+    }
+    let bytes = part.as_bytes();
+    let has_lower = bytes.iter().any(u8::is_ascii_lowercase);
+    let has_upper = bytes.iter().any(u8::is_ascii_uppercase);
+    if has_lower && has_upper {
+        return Err(invalid(TextErrorReason::InvalidChar));
+    }
+    let sep = bytes
+        .iter()
+        .rposition(|&c| c == b'1')
+        .ok_or(invalid(TextErrorReason::InvalidChar))?;
+    let hrp: Vec<u8> = bytes[..sep].iter().map(u8::to_ascii_lowercase).collect();
+    let is_testnet = match hrp.as_slice() {
+        h if h == HRP_MAINNET.as_bytes() => false,
+        h if h == HRP_TESTNET.as_bytes() => true,
+        _ => return Err(invalid(TextErrorReason::UnknownHrp)),
+    };
+
+    let symbols = bytes[sep + 1..]
+        .iter()
+        .map(|c| {
+            CHARSET
+                .iter()
+                .position(|&x| x == c.to_ascii_lowercase())
+                .map(|p| p as u8)
+                .ok_or(invalid(TextErrorReason::InvalidChar))
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    if symbols.len() < CHECKSUM_LEN {
+        return Err(invalid(TextErrorReason::InvalidChar));
+    }
+    if polymod(hrp_expand(&hrp).chain(symbols.iter().copied())) != BECH32M_CONST {
+        return Err(invalid(TextErrorReason::Checksum));
+    }
+    let data_len = symbols.len() - CHECKSUM_LEN;
+    let mut data = symbols;
+    data.truncate(data_len);
+    Ok((is_testnet, data))
+}
+
+/// 8-bit to 5-bit regrouping with zero padding (BIP-173 `convertbits(.., 8, 5, true)`).
+fn push_bytes_as_symbols(out: &mut Vec<u8>, bytes: &[u8]) {
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for &b in bytes {
+        acc = (acc << 8) | u32::from(b);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(((acc >> bits) & 0x1f) as u8);
+        }
+    }
+    if bits > 0 {
+        out.push(((acc << (5 - bits)) & 0x1f) as u8);
+    }
+}
+
+/// 5-bit to 8-bit regrouping; padding must be under 5 bits and zero.
+fn symbols_to_bytes(symbols: &[u8]) -> Result<Vec<u8>, VPackError> {
+    let mut out = Vec::with_capacity(symbols.len() * 5 / 8);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for &s in symbols {
+        acc = (acc << 5) | u32::from(s);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push(((acc >> bits) & 0xff) as u8);
+        }
+    }
+    if bits >= 5 || (acc & ((1 << bits) - 1)) != 0 {
+        return Err(VPackError::InvalidText(TextErrorReason::Padding));
+    }
+    Ok(out)
+}
+
+fn push_u15(out: &mut Vec<u8>, value: usize) {
+    out.extend([
+        (value >> 10) as u8 & 0x1f,
+        (value >> 5) as u8 & 0x1f,
+        value as u8 & 0x1f,
+    ]);
+}
+
+fn read_u15(symbols: &[u8]) -> usize {
+    symbols
+        .iter()
+        .fold(0usize, |acc, &s| (acc << 5) | usize::from(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::{TxVariant, FLAG_TESTNET};
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    /// Header + opaque payload with a valid checksum (the payload is never parsed here).
+    fn fake_vpack(flags: u8, payload_len: usize) -> Vec<u8> {
+        let payload: Vec<u8> = (0..payload_len).map(|i| (i * 7) as u8).collect();
+        let mut header = Header {
+            flags,
+            version: 1,
+            tx_variant: TxVariant::V3Anchored,
+            tree_arity: 2,
+            tree_depth: 1,
+            node_count: 1,
+            asset_type: 0,
+            payload_len: payload_len as u32,
+            checksum: 0,
+        };
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&header.to_bytes()[..20]);
+        hasher.update(&payload);
+        header.checksum = hasher.finalize();
+        let mut out = header.to_bytes().to_vec();
+        out.extend_from_slice(&payload);
+        out
+    }
+
+    #[test]
+    fn test_bech32m_reference_checksums() {
+        // Valid bech32m strings from BIP-350.
+        for s in [
+            "a1lqfn3a",
+            "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx",
+            "split1checkupstagehandshakeupstreamerranterredcaperredlc445v",
+        ] {
+            let sep = s.rfind('1').unwrap();
+            let (hrp, data) = s.as_bytes().split_at(sep);
+            let data = &data[1..];
+            let symbols = data
+                .iter()
+                .map(|c| CHARSET.iter().position(|x| x == c).unwrap() as u8);
+            assert_eq!(
+                polymod(hrp_expand(hrp).chain(symbols)),
+                BECH32M_CONST,
+                "{s}"
+            );
+            let payload: Vec<u8> = data[..data.len() - CHECKSUM_LEN]
+                .iter()
+                .map(|c| CHARSET.iter().position(|x| x == c).unwrap() as u8)
+                .collect();
+            assert_eq!(encode_part(core::str::from_utf8(hrp).unwrap(), &payload), s);
+        }
+    }
+
+    #[test]
+    fn test_text_round_trip_single_and_multi_part() {
+        let small = fake_vpack(0, 100);
+        let parts = to_text(&small).expect("encode");
+        assert_eq!(parts.len(), 1);
+        assert!(parts[0].starts_with("vpack1"));
+        assert_eq!(from_text(&parts), Ok(small.clone()));
+        let upper = vec![parts[0].to_uppercase()];
+        assert_eq!(from_text(&upper), Ok(small));
+
+        let large = fake_vpack(FLAG_TESTNET, 3 * MAX_PART_BYTES);
+        let mut parts = to_text(&large).expect("encode");
+        assert_eq!(parts.len(), 4);
+        assert!(parts
+            .iter()
+            .all(|p| p.starts_with("tvpack1") && p.len() <= MAX_PART_LEN));
+        parts.reverse();
+        assert_eq!(from_text(&parts), Ok(large));
+
+        let missing = &parts[1..];
+        assert_eq!(
+            from_text(missing),
+            Err(VPackError::InvalidText(TextErrorReason::Parts))
+        );
+        let duplicated = vec![
+            parts[0].clone(),
+            parts[0].clone(),
+            parts[1].clone(),
+            parts[2].clone(),
+        ];
+        assert_eq!(
+            from_text(&duplicated),
+            Err(VPackError::InvalidText(TextErrorReason::Parts))
+        );
+    }
+
+    #[test]
+    fn test_text_part_count_boundary() {
+        // The largest count still fits the three-symbol field.
+        let mut symbols = Vec::new();
+        push_u15(&mut symbols, MAX_PARTS);
+        assert_eq!(read_u15(&symbols), MAX_PARTS);
+
+        // Only the header is inspected, so trailing bytes stand in for a large V-PACK.
+        let mut over = fake_vpack(0, 64);
+        over.resize(MAX_PARTS * MAX_PART_BYTES + 1, 0);
+        assert_eq!(
+            to_text(&over),
+            Err(VPackError::PayloadTooLarge(over.len() as u32))
+        );
+    }
+
+    #[test]
+    fn test_text_rejects_corruption_and_network_mismatch() {
+        let parts = to_text(&fake_vpack(0, 64)).expect("encode");
+        let part = &parts[0];
+
+        // Single-character substitution in the data section.
+        let pos = part.len() / 2;
+        let swapped = if part.as_bytes()[pos] == b'q' {
+            "p"
+        } else {
+            "q"
+        };
+        let typo = [part[..pos].to_string() + swapped + &part[pos + 1..]];
+        assert_eq!(
+            from_text(&typo),
+            Err(VPackError::InvalidText(TextErrorReason::Checksum))
+        );
+
+        let mixed = [part[..6].to_uppercase() + &part[6..]];
+        assert_eq!(
+            from_text(&mixed),
+            Err(VPackError::InvalidText(TextErrorReason::InvalidChar))
+        );
+
+        // Re-encode the mainnet bytes under the testnet prefix: checksum valid, network wrong.
+        let (_, data) = decode_part(part).expect("decode");
+        let relabelled = [encode_part(HRP_TESTNET, &data)];
+        assert_eq!(
+            from_text(&relabelled),
+            Err(VPackError::InvalidText(TextErrorReason::NetworkMismatch))
+        );
+
+        // A valid encoding of bytes whose CRC does not match.
+        let mut corrupted = fake_vpack(0, 64);
+        corrupted[HEADER_SIZE] ^= 0x01;
+        let parts = to_text(&corrupted).expect("encode");
+        assert!(matches!(
+            from_text(&parts),
+            Err(VPackError::ChecksumMismatch { .. })
+        ));
+    }
+}
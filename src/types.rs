@@ -32,6 +32,7 @@ mod wasm_shim {
 
     /// Re-export so `crate::types::hashes::Hash`, `sha256`, and `sha256d` match the bitcoin crate API.
     pub mod hashes {
+        pub use bitcoin_hashes::hash160;
         pub use bitcoin_hashes::sha256;
         pub use bitcoin_hashes::sha256d;
         pub use bitcoin_hashes::Hash;
@@ -19,6 +19,7 @@ use vpack::export::create_vpack_from_tree;
 use vpack::header::TxVariant;
 use vpack::payload::tree::{GenesisItem, SiblingNode, VPackTree, VtxoLeaf};
 use vpack::types::OutPoint;
+use vpack::Network;
 use vpack::{compute_ark_labs_merkle_root, verify, ArkLabsV3, ConsensusEngine, VtxoId};
 
 const FEE_ANCHOR_HEX: &str = "51024e73";
@@ -408,7 +409,7 @@ fn audit_vector(vector: &Value, idx: usize) {
         "{vector_id}: taproot output key mismatch"
     );
 
-    let bytes = create_vpack_from_tree(&tree, TxVariant::V3Anchored, false)
+    let bytes = create_vpack_from_tree(&tree, TxVariant::V3Anchored, Network::Mainnet)
         .unwrap_or_else(|e| panic!("{vector_id}: pack {e:?}"));
 
     if vector_id == "fire_escape_transition" {
@@ -427,7 +428,7 @@ fn audit_vector(vector: &Value, idx: usize) {
         );
     }
 
-    verify(&bytes, &expected_id, anchor_value)
+    verify(&bytes, &expected_id, anchor_value, Network::Mainnet)
         .unwrap_or_else(|e| panic!("{vector_id}: vpack::verify {e:?}"));
 }
 
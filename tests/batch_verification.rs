@@ -0,0 +1,237 @@
+//! `verify_batch` over a signed Ark Labs round: every VTXO of a binary round tree, each path step
+//! (below the root) cosigned by the leaf key, so the shared upper levels are what the batch
+//! memoizes.
+#![cfg(feature = "schnorr-verify")]
+
+mod common;
+
+use std::time::Instant;
+
+use common::exit_fixtures::compact;
+use k256::elliptic_curve::PrimeField;
+use k256::schnorr::signature::hazmat::PrehashSigner;
+use k256::schnorr::SigningKey;
+use vpack::consensus::ark_labs::compile_forfeit_script;
+use vpack::consensus::taproot::{compute_taproot_tweak, tagged_hash};
+use vpack::consensus::taproot_sighash::taproot_sighash;
+use vpack::consensus::{tx_preimage, TxInPreimage, TxOutPreimage};
+use vpack::cpfp::P2A_SCRIPT;
+use vpack::error::VPackError;
+use vpack::payload::tree::{GenesisItem, VPackTree, VtxoLeaf};
+use vpack::types::hashes::{sha256d, Hash};
+use vpack::types::{OutPoint, Txid};
+use vpack::{
+    compute_ark_labs_merkle_root, create_vpack_from_tree, verify, verify_batch, ArkLabsV3,
+    ConsensusEngine, Network, TxVariant, VtxoId,
+};
+
+const LEAF_AMOUNT: u64 = 1_000;
+const SEQUENCE: u32 = 0xFFFF_FFFF;
+
+/// One VTXO of the round: its tree, V-PACK, ID and anchor value.
+struct RoundVtxo {
+    tree: VPackTree,
+    vpack: Vec<u8>,
+    id: VtxoId,
+    anchor_value: u64,
+}
+
+/// Leaf P2TR script (internal key tweaked with the Ark script tree) and the matching signing key.
+fn leaf_key(template: &VPackTree) -> (Vec<u8>, SigningKey) {
+    let internal = SigningKey::from_bytes(&[0x11; 32]).expect("valid key");
+    let internal_key: [u8; 32] = internal.verifying_key().to_bytes().into();
+    let root = compute_ark_labs_merkle_root(template).expect("ark script tree");
+
+    let mut msg = internal_key.to_vec();
+    msg.extend_from_slice(&root);
+    let tweak = k256::Scalar::from_repr(tagged_hash(b"TapTweak", &msg).into()).unwrap();
+    let secret = **internal.as_nonzero_scalar() + tweak;
+    let tweaked = SigningKey::from_bytes(&secret.to_repr()).expect("tweaked key");
+
+    let output_key: [u8; 32] = tweaked.verifying_key().to_bytes().into();
+    assert_eq!(
+        Some(output_key),
+        compute_taproot_tweak(internal_key, root),
+        "tweaked signing key matches the P2TR output key"
+    );
+    let mut script = vec![0x51, 0x20];
+    script.extend_from_slice(&output_key);
+    (script, tweaked)
+}
+
+/// Binary round of `2^depth` VTXOs. Node `(level, k)` splits its value into two children and a
+/// zero-value fee anchor; every leaf exits through a final `[leaf, anchor]` transaction.
+fn build_round(depth: u32) -> Vec<RoundVtxo> {
+    let anchor = OutPoint {
+        txid: Txid::from_byte_array([0xAA; 32]),
+        vout: 0,
+    };
+    let anchor_value = LEAF_AMOUNT << depth;
+    let template = VPackTree {
+        leaf: VtxoLeaf {
+            amount: LEAF_AMOUNT,
+            vout: 0,
+            sequence: SEQUENCE,
+            expiry: 0,
+            exit_delta: 144,
+            script_pubkey: Vec::new(),
+        },
+        leaf_siblings: vec![compact(0, &P2A_SCRIPT)],
+        path: Vec::new(),
+        anchor,
+        asset_id: None,
+        fee_anchor_script: P2A_SCRIPT.to_vec(),
+        internal_key: SigningKey::from_bytes(&[0x11; 32])
+            .expect("valid key")
+            .verifying_key()
+            .to_bytes()
+            .into(),
+        asp_expiry_script: compile_forfeit_script(&[0x02; 32], &[0x03; 32]),
+    };
+    let (script, key) = leaf_key(&template);
+
+    // Path steps of every node, level by level: (txid, step with both children in `siblings`).
+    let mut levels: Vec<Vec<([u8; 32], [u8; 64])>> = Vec::new();
+    for level in 0..depth {
+        let child_value = LEAF_AMOUNT << (depth - level - 1);
+        let outputs = [
+            TxOutPreimage {
+                value: child_value,
+                script_pubkey: &script,
+            },
+            TxOutPreimage {
+                value: child_value,
+                script_pubkey: &script,
+            },
+            TxOutPreimage {
+                value: 0,
+                script_pubkey: &P2A_SCRIPT,
+            },
+        ];
+        let nodes = (0..1u32 << level)
+            .map(|k| {
+                let (prev_txid, prev_vout) = match level {
+                    0 => (anchor.txid.to_byte_array(), anchor.vout),
+                    _ => (levels[level as usize - 1][(k / 2) as usize].0, k % 2),
+                };
+                let input = TxInPreimage {
+                    prev_out_txid: prev_txid,
+                    prev_out_vout: prev_vout,
+                    sequence: SEQUENCE,
+                };
+                let preimage = tx_preimage(3, std::slice::from_ref(&input), &outputs, 0);
+                let txid = sha256d::Hash::hash(&preimage).to_byte_array();
+                let sighash =
+                    taproot_sighash(3, 0, &input, child_value * 2, &script, &outputs, 0x00)
+                        .expect("sighash");
+                let sig = key.sign_prehash(&sighash).expect("sign").to_bytes();
+                (txid, sig)
+            })
+            .collect();
+        levels.push(nodes);
+    }
+
+    (0..1u32 << depth)
+        .map(|i| {
+            let bit = |level: u32| (i >> (depth - 1 - level)) & 1;
+            let path = (0..depth)
+                .map(|level| {
+                    let child_value = LEAF_AMOUNT << (depth - level - 1);
+                    let node = i >> (depth - level);
+                    GenesisItem {
+                        siblings: vec![compact(child_value, &script), compact(0, &P2A_SCRIPT)],
+                        parent_index: if level == 0 { 0 } else { bit(level - 1) },
+                        sequence: SEQUENCE,
+                        child_amount: child_value,
+                        child_script_pubkey: script.clone(),
+                        signature: (level > 0).then(|| levels[level as usize][node as usize].1),
+                        sighash_flag: 0x00,
+                    }
+                })
+                .collect();
+            let tree = VPackTree {
+                leaf: VtxoLeaf {
+                    vout: bit(depth - 1),
+                    script_pubkey: script.clone(),
+                    ..template.leaf.clone()
+                },
+                path,
+                ..template.clone()
+            };
+            let id = ArkLabsV3
+                .compute_vtxo_id(&tree, Some(anchor_value))
+                .expect("fixture reconstructs")
+                .id;
+            let vpack = create_vpack_from_tree(&tree, TxVariant::V3Anchored, Network::Mainnet)
+                .expect("pack");
+            RoundVtxo {
+                tree,
+                vpack,
+                id,
+                anchor_value,
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn verify_batch_matches_verify_per_item() {
+    let round = build_round(4);
+    let mut vpacks: Vec<Vec<u8>> = round.iter().map(|v| v.vpack.clone()).collect();
+    let mut items: Vec<(VtxoId, u64)> = round.iter().map(|v| (v.id, v.anchor_value)).collect();
+
+    // A forged signature on a step whose valid signature is already cached.
+    let mut forged = round[5].tree.clone();
+    forged.path[2].signature = Some([0x42; 64]);
+    vpacks.push(
+        create_vpack_from_tree(&forged, TxVariant::V3Anchored, Network::Mainnet).expect("pack"),
+    );
+    items.push((round[5].id, round[5].anchor_value));
+    // Wrong anchor value: conservation fails at the root, which is cached as well.
+    vpacks.push(round[3].vpack.clone());
+    items.push((round[3].id, round[3].anchor_value + 1));
+
+    let batch_items: Vec<(&[u8], VtxoId, u64)> = vpacks
+        .iter()
+        .zip(&items)
+        .map(|(vpack, &(id, anchor_value))| (vpack.as_slice(), id, anchor_value))
+        .collect();
+    let batch = verify_batch(&batch_items, Network::Mainnet);
+    let sequential: Vec<_> = batch_items
+        .iter()
+        .map(|(vpack, id, anchor_value)| verify(vpack, id, *anchor_value, Network::Mainnet))
+        .collect();
+
+    assert_eq!(batch, sequential);
+    for (result, vtxo) in batch.iter().zip(&round) {
+        assert_eq!(result.as_ref(), Ok(&vtxo.tree));
+    }
+    assert_eq!(batch[16], Err(VPackError::InvalidSignature));
+    assert!(matches!(batch[17], Err(VPackError::ValueMismatch { .. })));
+}
+
+#[test]
+#[ignore = "timing; run with `cargo test --release -- --ignored`"]
+fn verify_batch_is_faster_than_verify_on_1024_leaf_round() {
+    let round = build_round(10);
+    let items: Vec<(&[u8], VtxoId, u64)> = round
+        .iter()
+        .map(|v| (v.vpack.as_slice(), v.id, v.anchor_value))
+        .collect();
+
+    let start = Instant::now();
+    for (vpack, id, anchor_value) in &items {
+        verify(vpack, id, *anchor_value, Network::Mainnet).expect("verify");
+    }
+    let sequential = start.elapsed();
+
+    let start = Instant::now();
+    let results = verify_batch(&items, Network::Mainnet);
+    let batched = start.elapsed();
+
+    assert!(results.iter().all(Result::is_ok));
+    assert!(
+        batched < sequential,
+        "1024 VTXOs: verify x N {sequential:?}, verify_batch {batched:?}"
+    );
+}
@@ -0,0 +1,79 @@
+//! Signing keys and Ark Labs trees for the exit-builder tests (sweeps, CPFP children, PSBTs,
+//! exit costs, batch verification).
+
+#![allow(dead_code)]
+
+use k256::schnorr::SigningKey;
+use vpack::compute_ark_labs_merkle_root;
+use vpack::consensus::ark_labs::compile_forfeit_script;
+use vpack::consensus::hash_sibling_birth_tx;
+use vpack::consensus::taproot::compute_taproot_tweak;
+use vpack::cpfp::P2A_SCRIPT;
+use vpack::payload::tree::{GenesisItem, SiblingNode, VPackTree, VtxoLeaf};
+use vpack::types::hashes::Hash;
+use vpack::types::{OutPoint, Txid};
+
+/// Deterministic BIP-340 key pair: the secret `[seed; 32]` and its x-only public key.
+pub fn key(seed: u8) -> (SigningKey, [u8; 32]) {
+    let sk = SigningKey::from_bytes(&[seed; 32]).expect("valid key");
+    let pk = sk.verifying_key().to_bytes().into();
+    (sk, pk)
+}
+
+/// Compact sibling of `value` sat paying `script`.
+pub fn compact(value: u64, script: &[u8]) -> SiblingNode {
+    SiblingNode::Compact {
+        hash: hash_sibling_birth_tx(value, script),
+        value,
+        script: script.to_vec(),
+    }
+}
+
+/// P2TR scriptPubKey with the output key `[byte; 32]`.
+pub fn p2tr(byte: u8) -> Vec<u8> {
+    let mut script = vec![0x51, 0x20];
+    script.extend_from_slice(&[byte; 32]);
+    script
+}
+
+/// Two-level Ark Labs branch into a 100k sat leaf whose P2TR key commits to the forfeit and exit
+/// leaves of `asp` and `user`. Each step splits its value between the child, an equal sibling
+/// and a pay-to-anchor output.
+pub fn ark_tree(asp_pk: &[u8; 32], user_pk: &[u8; 32]) -> VPackTree {
+    let mut tree = VPackTree {
+        leaf: VtxoLeaf {
+            amount: 100_000,
+            vout: 0,
+            sequence: 0xFFFF_FFFF,
+            expiry: 0,
+            exit_delta: 144,
+            script_pubkey: Vec::new(),
+        },
+        leaf_siblings: vec![compact(0, &P2A_SCRIPT)],
+        path: Vec::new(),
+        anchor: OutPoint {
+            txid: Txid::from_byte_array([0xAA; 32]),
+            vout: 0,
+        },
+        asset_id: None,
+        fee_anchor_script: P2A_SCRIPT.to_vec(),
+        internal_key: key(0x11).1,
+        asp_expiry_script: compile_forfeit_script(asp_pk, user_pk),
+    };
+    let root = compute_ark_labs_merkle_root(&tree).expect("script tree");
+    let output_key = compute_taproot_tweak(tree.internal_key, root).expect("tweak");
+    let mut script = vec![0x51, 0x20];
+    script.extend_from_slice(&output_key);
+    tree.leaf.script_pubkey = script.clone();
+    let step = |child_amount: u64| GenesisItem {
+        siblings: vec![compact(child_amount, &script), compact(0, &P2A_SCRIPT)],
+        parent_index: 0,
+        sequence: 0xFFFF_FFFF,
+        child_amount,
+        child_script_pubkey: script.clone(),
+        signature: None,
+        sighash_flag: 0x00,
+    };
+    tree.path = vec![step(200_000), step(100_000)];
+    tree
+}
@@ -6,7 +6,7 @@
 
 use core::str::FromStr;
 use vpack::error::VPackError;
-use vpack::header::TxVariant;
+use vpack::header::{BuiltinVariant, TxVariant};
 use vpack::payload::tree::{GenesisItem, SiblingNode, VPackTree, VtxoLeaf};
 
 /// Converts 32-byte hash (internal/wire order) to 64-char hex in Bitcoin display order (reversed).
@@ -317,8 +317,12 @@ pub fn tree_from_ingredients(
     variant: TxVariant,
     reconstruction_ingredients: &serde_json::Value,
 ) -> Option<Result<VPackTree, VPackError>> {
+    let variant = match variant.builtin() {
+        Ok(variant) => variant,
+        Err(e) => return Some(Err(e)),
+    };
     match variant {
-        TxVariant::V3Anchored => {
+        BuiltinVariant::V3Anchored => {
             if reconstruction_ingredients.get("parent_outpoint").is_some()
                 || reconstruction_ingredients.get("anchor_outpoint").is_some()
             {
@@ -327,7 +331,7 @@ pub fn tree_from_ingredients(
                 None
             }
         }
-        TxVariant::V3Plain => {
+        BuiltinVariant::V3Plain => {
             if reconstruction_ingredients.get("amount").is_some()
                 && (reconstruction_ingredients
                     .get("script_pubkey_hex")
@@ -1,5 +1,8 @@
-//! Shared test helpers: logic-mapping adapters and ingredient builders for export/conformance.
+//! Shared test helpers: logic-mapping adapters and ingredient builders for export/conformance,
+//! and the exit-builder fixtures.
 
+#[cfg(feature = "schnorr-verify")]
+pub mod exit_fixtures;
 mod ingredients_from_json;
 mod logic_adapters;
 
@@ -7,12 +7,20 @@ use core::str::FromStr;
 use serde::Deserialize;
 use std::fs;
 use std::path::{Path, PathBuf};
+use vpack::bundle::{
+    pack_bundle, unpack_bundle, verify_bundle, BundleEntry, BundleReader, BUNDLE_HEADER_SIZE,
+};
 use vpack::consensus::ConsensusEngine;
 use vpack::error::VPackError;
 use vpack::export::{create_vpack_ark_labs, create_vpack_from_tree, create_vpack_second_tech};
-use vpack::header::{Header, TxVariant, FLAG_PROOF_COMPACT};
-use vpack::pack::pack;
+use vpack::header::{Header, TxVariant, FLAG_PROOF_COMPACT, VERSION_TLV};
+use vpack::pack::{pack, pack_with_extensions};
+use vpack::payload::stream::{Progress, StreamRecord, StreamingReader};
 use vpack::payload::tree::{VPackTree, VtxoLeaf};
+use vpack::report::{Check, CheckStatus};
+use vpack::Network;
+use vpack::{VerificationPolicy, VerificationProfile};
+use vpack::{VtxoIdFold, VtxoStore};
 
 #[derive(Debug, Deserialize)]
 struct AuditVector {
@@ -65,11 +73,12 @@ fn make_header(tx_variant: TxVariant) -> Header {
     }
 }
 
-#[test]
-fn run_conformance_vectors() {
+/// All audit vector JSON files (ark_labs, then second).
+fn conformance_vector_paths() -> Vec<PathBuf> {
     let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     let vectors_root = manifest_dir.join("tests/conformance/vectors");
 
+    let mut paths = Vec::new();
     for subdir in ["ark_labs", "second"] {
         let dir = vectors_root.join(subdir);
         if !dir.is_dir() {
@@ -79,11 +88,603 @@ fn run_conformance_vectors() {
             let entry = entry.expect("dir entry");
             let path = entry.path();
             if path.extension().map(|e| e.to_str()) == Some(Some("json")) {
-                run_audit_vector(&path);
-                run_integrity_sabotage(&path);
+                paths.push(path);
             }
         }
     }
+    paths
+}
+
+#[test]
+fn run_conformance_vectors() {
+    for path in conformance_vector_paths() {
+        run_audit_vector(&path);
+        run_integrity_sabotage(&path);
+    }
+}
+
+/// All vectors packed into one bundle: every entry verifies, and one corrupted entry fails alone.
+#[test]
+fn bundle_of_conformance_vectors() {
+    let mut entries: Vec<BundleEntry> = Vec::new();
+    for path in conformance_vector_paths() {
+        let (id, _, anchor_value, vpack) = build_vector_vpack(&path);
+        // Some vectors share an expected ID; a bundle holds each ID once.
+        if entries.iter().all(|e| e.id != id) {
+            entries.push(BundleEntry {
+                id,
+                anchor_value,
+                vpack,
+            });
+        }
+    }
+    assert!(entries.len() > 1);
+    let mut with_duplicate = entries.clone();
+    with_duplicate.push(entries[0].clone());
+    assert_eq!(pack_bundle(&with_duplicate), Err(VPackError::EncodingError));
+    let bundle = pack_bundle(&entries).expect("pack_bundle");
+    assert_eq!(unpack_bundle(&bundle).expect("unpack").len(), entries.len());
+
+    let reader = BundleReader::parse(&bundle).expect("parse bundle");
+    for entry in &entries {
+        let (toc, vpack) = reader.get(&entry.id).expect("entry by id");
+        assert_eq!(toc.anchor_value, entry.anchor_value);
+        assert_eq!(vpack, entry.vpack.as_slice());
+    }
+    let results = verify_bundle(&bundle, Network::Mainnet).expect("verify_bundle");
+    assert_eq!(results.len(), entries.len());
+    assert!(results.iter().all(|r| r.result.is_ok()));
+
+    // Flip the last payload byte of the first entry: only that entry fails its own checksum.
+    let (first, _) = reader.entry(0).expect("first entry");
+    let (first_id, first_end) = (first.id, (first.offset + first.len) as usize);
+    let data_start = bundle.len() - reader.toc().iter().map(|e| e.len as usize).sum::<usize>();
+    let mut corrupted = bundle.clone();
+    corrupted[data_start + first_end - 1] ^= 0x01;
+    let results = verify_bundle(&corrupted, Network::Mainnet).expect("container still valid");
+    for r in &results {
+        if r.id == first_id {
+            assert!(matches!(r.result, Err(VPackError::ChecksumMismatch { .. })));
+        } else {
+            assert!(r.result.is_ok());
+        }
+    }
+
+    // A corrupted TOC is a container-level error.
+    let mut corrupted = bundle;
+    corrupted[BUNDLE_HEADER_SIZE + 1] ^= 0x01;
+    assert!(matches!(
+        verify_bundle(&corrupted, Network::Mainnet),
+        Err(VPackError::ChecksumMismatch { .. })
+    ));
+}
+
+#[test]
+fn store_round_trips_conformance_vectors() {
+    let mut store = VtxoStore::new();
+    let mut expected = Vec::new();
+    for path in conformance_vector_paths() {
+        let (id, _, anchor_value, vpack) = build_vector_vpack(&path);
+        let inserted = store.insert_vpack(&vpack).expect("insert_vpack");
+        assert_eq!(inserted, id, "{}", path.display());
+        expected.push((id, anchor_value));
+    }
+    assert!(store.len() > 1);
+
+    let restored = VtxoStore::from_bytes(&store.to_bytes().expect("to_bytes")).expect("from_bytes");
+    assert_eq!(restored, store);
+    for (id, anchor_value) in &expected {
+        let vpack = restored.vpack(id).expect("pack").expect("stored");
+        vpack::verify(&vpack, id, *anchor_value, Network::Mainnet)
+            .expect("rebuilt V-PACK verifies");
+    }
+
+    // Bulk export: one standalone V-PACK per stored VTXO.
+    let bundle = restored
+        .to_bundle(|id| expected.iter().find(|(e, _)| e == id).expect("known id").1)
+        .expect("to_bundle");
+    let results = verify_bundle(&bundle, Network::Mainnet).expect("bundle");
+    assert_eq!(results.len(), store.len());
+    for entry in results {
+        entry.result.expect("bundled V-PACK verifies");
+    }
+}
+
+#[test]
+fn text_encoding_of_conformance_vectors_verifies() {
+    for path in conformance_vector_paths() {
+        let (id, _, anchor_value, vpack) = build_vector_vpack(&path);
+        let parts = vpack::to_text(&vpack).expect("to_text");
+        assert!(parts.iter().all(|p| p.starts_with("vpack1")));
+        assert_eq!(vpack::from_text(&parts).expect("from_text"), vpack);
+        vpack::verify_text(&parts, &id, anchor_value, Network::Mainnet)
+            .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+    }
+}
+
+#[test]
+fn full_form_vectors_verify_after_to_compact() {
+    for path in conformance_vector_paths() {
+        let (id, _, anchor_value, vpack) = build_vector_vpack(&path);
+        let header = Header::from_bytes(&vpack[..24]).expect("header");
+        let tree =
+            vpack::payload::reader::BoundedReader::parse(&header, &vpack[24..]).expect("parse");
+
+        // Re-pack in Full form, as another tool might, and read it back.
+        let (full_header, full_tree) = tree.to_full(&header);
+        assert!(!full_header.is_compact());
+        assert_eq!(full_header.node_count, tree.node_count());
+        let full_bytes = pack(&full_header, &full_tree).expect("pack full");
+        let full_header = Header::from_bytes(&full_bytes[..24]).expect("full header");
+        let parsed = vpack::payload::reader::BoundedReader::parse(&full_header, &full_bytes[24..])
+            .expect("parse full");
+        assert_eq!(parsed, full_tree);
+        assert!(parsed
+            .path
+            .iter()
+            .flat_map(|item| &item.siblings)
+            .chain(&parsed.leaf_siblings)
+            .all(|s| !s.is_compact()));
+
+        let (compact_header, compact_tree) = parsed.to_compact(&full_header);
+        assert!(compact_header.is_compact());
+        let compact_bytes = pack(&compact_header, &compact_tree).expect("pack compact");
+        vpack::verify(&compact_bytes, &id, anchor_value, Network::Mainnet)
+            .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+    }
+}
+
+/// ASP-signed vectors pass `verify_authenticated` with the ASP key and plain `verify` without it;
+/// a different key or an unsigned V-PACK is rejected before reconstruction.
+#[test]
+fn asp_signed_vectors_verify_authenticated() {
+    let secret = [0x5au8; 32];
+    let asp_pubkey: [u8; 32] = k256::schnorr::SigningKey::from_bytes(&secret)
+        .expect("key")
+        .verifying_key()
+        .to_bytes()
+        .into();
+    for path in conformance_vector_paths() {
+        let (id, _, anchor_value, vpack) = build_vector_vpack(&path);
+        let signed = vpack::auth::sign_vpack(&vpack, &secret, &[0u8; 32]).expect("sign");
+
+        let tree =
+            vpack::verify_authenticated(&signed, &id, anchor_value, Network::Mainnet, &asp_pubkey)
+                .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+        assert_eq!(
+            vpack::verify(&signed, &id, anchor_value, Network::Mainnet),
+            Ok(tree)
+        );
+        assert_eq!(
+            vpack::from_text(&vpack::to_text(&signed).expect("to_text")),
+            Ok(signed.clone())
+        );
+
+        assert_eq!(
+            vpack::verify_authenticated(&signed, &id, anchor_value, Network::Mainnet, &[0x02; 32]),
+            Err(VPackError::InvalidAspSignature)
+        );
+        assert_eq!(
+            vpack::verify_authenticated(&vpack, &id, anchor_value, Network::Mainnet, &asp_pubkey),
+            Err(VPackError::MissingAspSignature)
+        );
+        assert_eq!(
+            vpack::verify_authenticated(
+                &signed[..23],
+                &id,
+                anchor_value,
+                Network::Mainnet,
+                &asp_pubkey
+            ),
+            Err(VPackError::IncompleteData)
+        );
+    }
+}
+
+/// `Permissive` is plain `verify`; the stricter profiles reject the vectors, which carry neither
+/// leaf expiries nor the ASP's cosignatures.
+#[test]
+fn verify_with_policy_profiles() {
+    for path in conformance_vector_paths() {
+        let (id, _, anchor_value, vpack) = build_vector_vpack(&path);
+        let permissive = VerificationPolicy::new(VerificationProfile::Permissive, Network::Mainnet);
+        assert_eq!(
+            vpack::verify_with(&vpack, &id, anchor_value, &permissive),
+            vpack::verify(&vpack, &id, anchor_value, Network::Mainnet),
+            "{}",
+            path.display()
+        );
+
+        // The SIGHASH audit starts from the anchor output; without its script nothing is audited,
+        // so the policy is refused rather than passed.
+        let audited = VerificationPolicy {
+            audit_sighash: true,
+            ..permissive.clone()
+        };
+        assert_eq!(
+            vpack::verify_with(&vpack, &id, anchor_value, &audited),
+            Err(VPackError::UnsupportedPolicy)
+        );
+        assert_eq!(
+            vpack::verify_with(
+                &vpack,
+                &id,
+                anchor_value,
+                &VerificationPolicy::new(VerificationProfile::Strict, Network::Mainnet)
+            ),
+            Err(VPackError::UnsupportedPolicy)
+        );
+
+        for profile in [
+            VerificationProfile::Strict,
+            VerificationProfile::ExitReady,
+            VerificationProfile::Boarding,
+        ] {
+            let result = vpack::verify_with(
+                &vpack,
+                &id,
+                anchor_value,
+                &VerificationPolicy::new(profile, Network::Mainnet).with_anchor_script(vec![0x51]),
+            );
+            assert!(
+                matches!(
+                    result,
+                    Err(VPackError::TimelockViolation { .. } | VPackError::TreeIncomplete { .. })
+                ),
+                "{} {profile:?}: {result:?}",
+                path.display()
+            );
+        }
+
+        assert_eq!(
+            vpack::verify_with(
+                &vpack,
+                &id,
+                anchor_value,
+                &VerificationPolicy::new(VerificationProfile::Permissive, Network::Testnet)
+            ),
+            Err(VPackError::NetworkMismatch {
+                expected: Network::Testnet,
+                found: Network::Mainnet,
+            })
+        );
+        assert_eq!(
+            vpack::verify_with(
+                &vpack,
+                &id,
+                anchor_value,
+                &permissive.clone().with_asp_pubkey([0x02; 32])
+            ),
+            Err(VPackError::MissingAspSignature)
+        );
+    }
+}
+
+/// The report agrees with `verify`, and keeps going past a conservation failure: every txid and
+/// the VTXO ID are still reported when the anchor value is wrong.
+#[test]
+fn verification_report_of_conformance_vectors() {
+    for path in conformance_vector_paths() {
+        let (id, _, anchor_value, vpack) = build_vector_vpack(&path);
+        let tree = vpack::verify(&vpack, &id, anchor_value, Network::Mainnet).expect("verify");
+
+        let report = vpack::verify_report(&vpack, &id, anchor_value, Network::Mainnet);
+        assert!(
+            report.is_valid(),
+            "{}: {:?}",
+            path.display(),
+            report.entries
+        );
+        assert_eq!(report.tree.as_ref(), Some(&tree));
+        for step in 0..tree.path.len() as u32 {
+            assert!(
+                report.txid(step).is_some(),
+                "{} step {step}",
+                path.display()
+            );
+        }
+        let id_entry = report
+            .entries
+            .iter()
+            .find(|e| e.check == Check::VtxoId)
+            .expect("vtxo id entry");
+        assert_eq!(id_entry.vtxo_id, Some(id));
+
+        let wrong = vpack::verify_report(&vpack, &id, anchor_value + 1, Network::Mainnet);
+        assert!(!wrong.is_valid());
+        let failure = wrong.failures().next().expect("failure");
+        assert_eq!(failure.check, Check::Conservation);
+        assert_eq!(failure.input_value, Some(anchor_value + 1));
+        assert_eq!(failure.output_sum, Some(anchor_value));
+        assert!(matches!(
+            vpack::verify(&vpack, &id, anchor_value + 1, Network::Mainnet),
+            Err(VPackError::ValueMismatch { .. })
+        ));
+        assert_eq!(
+            wrong
+                .entries
+                .iter()
+                .filter(|e| e.check == Check::Transaction)
+                .count(),
+            report
+                .entries
+                .iter()
+                .filter(|e| e.check == Check::Transaction)
+                .count()
+        );
+        assert!(wrong
+            .entries
+            .iter()
+            .any(|e| e.check == Check::VtxoId && e.status == CheckStatus::Pass));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&wrong.to_json().expect("json")).expect("parse json");
+        let entries = json["entries"].as_array().expect("entries");
+        assert_eq!(entries.len(), wrong.entries.len());
+        assert!(entries
+            .iter()
+            .any(|e| e["check"] == "conservation" && e["status"] == "fail"));
+
+        let mut corrupt = vpack.clone();
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 0xff;
+        let corrupt_report = vpack::verify_report(&corrupt, &id, anchor_value, Network::Mainnet);
+        assert_eq!(
+            corrupt_report.failures().next().map(|e| e.check),
+            Some(Check::Checksum)
+        );
+        assert!(corrupt_report.tree.is_none());
+    }
+}
+
+/// The engine's own signed transactions match the reconstruction; a changed `nSequence` on the
+/// last path step is pinned to that step with a field-level diff.
+#[test]
+fn localize_mismatch_finds_first_diverging_step() {
+    use vpack::consensus::{FieldDiff, KnownTx};
+
+    for path in conformance_vector_paths() {
+        let (id, variant, anchor_value, vpack) = build_vector_vpack(&path);
+        let tree = vpack::verify(&vpack, &id, anchor_value, Network::Mainnet).expect("verify");
+        let signed_txs = match variant {
+            TxVariant::V3Anchored => vpack::ArkLabsV3.compute_vtxo_id(&tree, None),
+            TxVariant::V3Plain => vpack::SecondTechV3.compute_vtxo_id(&tree, None),
+            TxVariant::Custom(_) => unreachable!("vectors use the built-in variants"),
+        }
+        .expect("compute id")
+        .signed_txs;
+        let known: Vec<KnownTx<'_>> = signed_txs.iter().map(|tx| KnownTx::Raw(tx)).collect();
+        assert_eq!(
+            vpack::localize_mismatch(&tree, variant, &known),
+            Ok(None),
+            "{}",
+            path.display()
+        );
+
+        let Some(last) = tree.path.len().checked_sub(1) else {
+            continue;
+        };
+        let mut changed = tree.clone();
+        changed.path[last].sequence ^= 1;
+        let divergence = vpack::localize_mismatch(&changed, variant, &known)
+            .expect("localize")
+            .expect("divergence");
+        assert_eq!(divergence.step, last as u32);
+        assert_eq!(
+            divergence.fields,
+            vec![FieldDiff::Sequence {
+                index: 0,
+                reconstructed: tree.path[last].sequence ^ 1,
+                known: tree.path[last].sequence,
+            }]
+        );
+
+        let txids_only: Vec<KnownTx<'_>> =
+            vpack::verify_report(&vpack, &id, anchor_value, Network::Mainnet)
+                .entries
+                .iter()
+                .filter_map(|e| e.txid.map(KnownTx::Txid))
+                .collect();
+        let divergence = vpack::localize_mismatch(&changed, variant, &txids_only)
+            .expect("localize")
+            .expect("divergence");
+        assert_eq!(divergence.step, last as u32);
+        assert!(divergence.fields.is_empty());
+    }
+}
+
+/// A delta from a strict path prefix (one step fewer, older leaf) rebuilds the vector's tree and
+/// re-verifies; a delta against a divergent path falls back to a zero-length base.
+#[test]
+fn delta_from_path_prefix_rebuilds_and_verifies() {
+    use vpack::delta::{apply, apply_vpack, diff};
+    use vpack::VPackDelta;
+
+    for path in conformance_vector_paths() {
+        let (id, _, anchor_value, vpack) = build_vector_vpack(&path);
+        let header = Header::from_bytes(&vpack[..24]).expect("header");
+        let tree =
+            vpack::payload::reader::BoundedReader::parse(&header, &vpack[24..]).expect("parse");
+        if tree.path.len() < 2 {
+            continue;
+        }
+
+        let mut old = tree.clone();
+        old.path.pop();
+        old.leaf.amount += 1;
+        let old_vpack = pack(&header, &old).expect("pack old");
+
+        let delta = diff(&old, &tree).expect("diff");
+        assert_eq!(delta.base_path_len as usize, tree.path.len() - 1);
+        let delta_bytes = delta.to_bytes().expect("to_bytes");
+        let decoded = VPackDelta::from_bytes(&delta_bytes).expect("from_bytes");
+        assert_eq!(decoded, delta);
+        assert_eq!(apply(&old, &decoded), Ok(tree.clone()));
+
+        let new_vpack = apply_vpack(&old_vpack, &decoded, &id, anchor_value, Network::Mainnet)
+            .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+        assert_eq!(
+            vpack::verify(&new_vpack, &id, anchor_value, Network::Mainnet),
+            Ok(tree.clone())
+        );
+
+        let mut divergent = old.clone();
+        divergent.path[0].child_amount += 1;
+        assert_eq!(
+            apply(&divergent, &decoded),
+            Err(VPackError::DeltaBaseMismatch)
+        );
+        let full = diff(&divergent, &tree).expect("diff divergent");
+        assert_eq!(full.base_path_len, 0);
+        assert_eq!(apply(&divergent, &full), Ok(tree.clone()));
+
+        let mut corrupt = delta_bytes.clone();
+        corrupt[10] ^= 0x01;
+        assert!(matches!(
+            VPackDelta::from_bytes(&corrupt),
+            Err(VPackError::ChecksumMismatch { .. })
+        ));
+    }
+}
+
+/// Every vector re-packed as version 2 with round metadata verifies to the same VTXO ID.
+#[test]
+fn v2_extension_vectors_verify_to_same_id() {
+    for path in conformance_vector_paths() {
+        let (id, _, anchor_value, vpack) = build_vector_vpack(&path);
+        let mut header = Header::from_bytes(&vpack[..24]).expect("header");
+        let tree =
+            vpack::payload::reader::BoundedReader::parse(&header, &vpack[24..]).expect("parse");
+
+        header.version = VERSION_TLV;
+        let ext = vpack::Extensions {
+            round_txid: Some(tree.anchor.txid),
+            asp_pubkey: None,
+            created_at: Some(1_767_225_600),
+            label: Some("conformance".into()),
+        };
+        let v2 = pack_with_extensions(&header, &tree, &ext).expect("pack v2");
+        let verified = vpack::verify(&v2, &id, anchor_value, Network::Mainnet)
+            .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+        assert_eq!(verified, tree);
+
+        let v2_header = Header::from_bytes(&v2[..24]).expect("v2 header");
+        let (_, parsed_ext) =
+            vpack::payload::reader::BoundedReader::parse_with_extensions(&v2_header, &v2[24..])
+                .expect("parse v2");
+        assert_eq!(parsed_ext, ext);
+    }
+}
+
+/// Private dialect for the registry test: Second Tech rules under another variant byte.
+struct PrivateDialect;
+
+impl ConsensusEngine for PrivateDialect {
+    fn compute_vtxo_id(
+        &self,
+        tree: &VPackTree,
+        anchor_value: Option<u64>,
+    ) -> Result<vpack::VerificationOutput, VPackError> {
+        vpack::SecondTechV3.compute_vtxo_id(tree, anchor_value)
+    }
+
+    fn exclusivity_merkle_root(
+        &self,
+        tree: &vpack::VPackTreeRef<'_>,
+    ) -> Result<[u8; 32], VPackError> {
+        vpack::SecondTechV3.exclusivity_merkle_root(tree)
+    }
+
+    fn tap_leaf_hashes(&self, tree: &VPackTree) -> Result<(Vec<[u8; 32]>, usize), VPackError> {
+        vpack::SecondTechV3.tap_leaf_hashes(tree)
+    }
+}
+
+/// Same ID rules as [`PrivateDialect`], but no script-tree rules.
+struct NoExclusivityRules;
+
+impl ConsensusEngine for NoExclusivityRules {
+    fn compute_vtxo_id(
+        &self,
+        tree: &VPackTree,
+        anchor_value: Option<u64>,
+    ) -> Result<vpack::VerificationOutput, VPackError> {
+        vpack::SecondTechV3.compute_vtxo_id(tree, anchor_value)
+    }
+}
+
+#[test]
+fn engine_registry_dispatches_private_variant() {
+    const PRIVATE: u8 = 0xA0;
+    let private = TxVariant::custom(PRIVATE).expect("not a built-in byte");
+    assert_eq!(private.as_u8(), PRIVATE);
+    assert_eq!(TxVariant::from_byte(PRIVATE), private);
+    assert_eq!(
+        private.builtin(),
+        Err(VPackError::InvalidTxVariant(PRIVATE))
+    );
+    // The built-in bytes never become a custom variant.
+    assert_eq!(TxVariant::custom(0x03), None);
+    assert_eq!(TxVariant::custom(0x04), None);
+    assert_eq!(TxVariant::from_byte(0x04), TxVariant::V3Anchored);
+
+    for path in conformance_vector_paths() {
+        let (id, variant, anchor_value, vpack) = build_vector_vpack(&path);
+        let registry = vpack::EngineRegistry::default();
+        let tree = registry
+            .verify(&vpack, &id, anchor_value, Network::Mainnet)
+            .expect("default registry verifies built-in variants");
+        if variant != TxVariant::V3Plain {
+            continue;
+        }
+
+        let custom =
+            create_vpack_from_tree(&tree, private, Network::Mainnet).expect("pack private variant");
+        assert_eq!(
+            vpack::verify(&custom, &id, anchor_value, Network::Mainnet),
+            Err(VPackError::InvalidTxVariant(PRIVATE))
+        );
+
+        let registry = registry.with_engine(PRIVATE, PrivateDialect);
+        let verified = registry
+            .verify(&custom, &id, anchor_value, Network::Mainnet)
+            .expect("registered private variant verifies");
+        assert_eq!(verified, tree, "{}", path.display());
+        let borrowed = registry
+            .verify_ref(&custom, &id, anchor_value, Network::Mainnet)
+            .expect("registered private variant verifies zero-copy");
+        assert_eq!(VPackTree::from(&borrowed), tree);
+
+        // Policy checks reach the private variant through the registry only.
+        let permissive = VerificationPolicy::new(VerificationProfile::Permissive, Network::Mainnet);
+        assert_eq!(
+            vpack::verify_with(&custom, &id, anchor_value, &permissive),
+            Err(VPackError::InvalidTxVariant(PRIVATE))
+        );
+        assert_eq!(
+            registry.verify_with(&custom, &id, anchor_value, &permissive),
+            Ok(tree.clone())
+        );
+        let boarding = VerificationPolicy::new(VerificationProfile::Boarding, Network::Mainnet);
+        assert_eq!(
+            registry.verify_with(&custom, &id, anchor_value, &boarding),
+            vpack::verify_with(&vpack, &id, anchor_value, &boarding)
+        );
+        assert_eq!(
+            registry.reconstruct_control_block(&tree, private),
+            vpack::reconstruct_control_block(&tree, TxVariant::V3Plain)
+        );
+
+        let bare = vpack::EngineRegistry::new().with_engine(PRIVATE, NoExclusivityRules);
+        assert_eq!(
+            bare.verify(&custom, &id, anchor_value, Network::Mainnet),
+            Err(VPackError::MissingExclusivityData)
+        );
+        assert_eq!(
+            bare.verify(&vpack, &id, anchor_value, Network::Mainnet),
+            Err(VPackError::InvalidTxVariant(TxVariant::V3Plain.as_u8()))
+        );
+    }
 }
 
 /// Hardcoded L1 anchor value for a vector (no derivation from ingredients).
@@ -108,12 +709,13 @@ fn anchor_value_for_vector(path: &Path, tx_variant: TxVariant) -> u64 {
                 10_000
             }
         }
+        TxVariant::Custom(_) => unreachable!("vectors use the built-in variants"),
     }
 }
 
-/// Strict pipeline: load ingredients → create_vpack_* (public API) → verify.
-fn run_audit_vector(path: &Path) {
-    println!("CHECKING: {:?}", path.file_name().unwrap());
+/// Loads a vector's ingredients and packs them via the public export API.
+/// Returns (expected_id, variant, anchor_value, V-PACK bytes).
+fn build_vector_vpack(path: &Path) -> (vpack::VtxoId, TxVariant, u64, Vec<u8>) {
     let contents = fs::read_to_string(path).expect("read JSON");
     let vector: AuditVector = serde_json::from_str(&contents).expect("parse audit JSON");
 
@@ -134,7 +736,7 @@ fn run_audit_vector(path: &Path) {
                     .unwrap_or_else(|e| {
                         panic!("ingredients_from_json failed for {}: {}", path.display(), e)
                     });
-            create_vpack_ark_labs(ingredients).expect("create_vpack_ark_labs")
+            create_vpack_ark_labs(ingredients, Network::Mainnet).expect("create_vpack_ark_labs")
         }
         TxVariant::V3Plain => {
             let ingredients = crate::common::second_tech_ingredients_from_json(
@@ -143,10 +745,110 @@ fn run_audit_vector(path: &Path) {
             .unwrap_or_else(|e| {
                 panic!("ingredients_from_json failed for {}: {}", path.display(), e)
             });
-            create_vpack_second_tech(ingredients).expect("create_vpack_second_tech")
+            create_vpack_second_tech(ingredients, Network::Mainnet)
+                .expect("create_vpack_second_tech")
         }
+        TxVariant::Custom(_) => unreachable!("vectors use the built-in variants"),
     };
-    vpack::verify(&full_bytes, &expected_id, anchor_value).expect("verify");
+    (expected_id, tx_variant, anchor_value, full_bytes)
+}
+
+/// Strict pipeline: load ingredients → create_vpack_* (public API) → verify.
+fn run_audit_vector(path: &Path) {
+    println!("CHECKING: {:?}", path.file_name().unwrap());
+    let (expected_id, tx_variant, anchor_value, full_bytes) = build_vector_vpack(path);
+    let tree =
+        vpack::verify(&full_bytes, &expected_id, anchor_value, Network::Mainnet).expect("verify");
+    // Zero-copy path must accept the same bytes and borrow an identical tree.
+    let tree_ref = vpack::verify_ref(&full_bytes, &expected_id, anchor_value, Network::Mainnet)
+        .expect("verify_ref");
+    assert_eq!(VPackTree::from(&tree_ref), tree);
+    // Streaming path must rebuild the same tree and fold to the same ID at any chunk size.
+    for chunk_len in [1, 7, 255, full_bytes.len()] {
+        let (streamed, id) = stream_vpack(&full_bytes, chunk_len, tx_variant, Some(anchor_value))
+            .expect("stream_vpack");
+        assert_eq!(streamed, tree, "chunk_len {}", chunk_len);
+        assert_eq!(id, expected_id, "chunk_len {}", chunk_len);
+    }
+}
+
+/// Feeds `bytes` to a `StreamingReader` in `chunk_len` pieces, folding every record into a
+/// `VtxoIdFold` and reassembling the owned tree from the records.
+fn stream_vpack(
+    bytes: &[u8],
+    chunk_len: usize,
+    tx_variant: TxVariant,
+    anchor_value: Option<u64>,
+) -> Result<(VPackTree, vpack::VtxoId), VPackError> {
+    let mut reader = StreamingReader::new();
+    let mut fold = VtxoIdFold::new(tx_variant, anchor_value);
+    let mut records = Vec::new();
+    for mut chunk in bytes.chunks(chunk_len) {
+        loop {
+            match reader.feed(chunk)? {
+                Progress::NeedMore | Progress::Done { .. } => break,
+                Progress::Record { consumed, record } => {
+                    chunk = &chunk[consumed..];
+                    fold.push(&record)?;
+                    records.push(record);
+                }
+            }
+        }
+    }
+    assert!(reader.is_done(), "stream ended before the trailer");
+
+    let mut tree = VPackTree {
+        leaf: VtxoLeaf {
+            amount: 0,
+            vout: 0,
+            sequence: 0,
+            expiry: 0,
+            exit_delta: 0,
+            script_pubkey: Vec::new(),
+        },
+        leaf_siblings: Vec::new(),
+        path: Vec::new(),
+        anchor: vpack::types::OutPoint {
+            txid: vpack::types::Txid::all_zeros(),
+            vout: 0,
+        },
+        asset_id: None,
+        fee_anchor_script: Vec::new(),
+        internal_key: [0u8; 32],
+        asp_expiry_script: Vec::new(),
+    };
+    for record in records {
+        match record {
+            StreamRecord::Header(_) => {}
+            StreamRecord::Prefix {
+                asset_id,
+                anchor,
+                fee_anchor_script,
+            } => {
+                tree.asset_id = asset_id;
+                tree.anchor = anchor;
+                tree.fee_anchor_script = fee_anchor_script;
+            }
+            StreamRecord::Leaf {
+                leaf,
+                leaf_siblings,
+                ..
+            } => {
+                tree.leaf = leaf;
+                tree.leaf_siblings = leaf_siblings;
+            }
+            StreamRecord::Step(item) => tree.path.push(item),
+            StreamRecord::Trailer {
+                internal_key,
+                asp_expiry_script,
+                ..
+            } => {
+                tree.internal_key = internal_key;
+                tree.asp_expiry_script = asp_expiry_script;
+            }
+        }
+    }
+    Ok((tree, fold.finish()?))
 }
 
 /// For the same vector, corrupt ingredients (amount+1 sat, sequence change) and assert verify returns IdMismatch or SequenceMismatch.
@@ -174,8 +876,8 @@ fn run_integrity_sabotage(path: &Path) {
             ingredients_json["amount"] = serde_json::json!(amt + 1);
             let ingredients =
                 crate::common::second_tech_ingredients_from_json(&ingredients_json).unwrap();
-            let bytes = create_vpack_second_tech(ingredients).expect("pack");
-            let result = vpack::verify(&bytes, &expected_id, anchor_value);
+            let bytes = create_vpack_second_tech(ingredients, Network::Mainnet).expect("pack");
+            let result = vpack::verify(&bytes, &expected_id, anchor_value, Network::Mainnet);
             assert!(
                 matches!(
                     result,
@@ -199,8 +901,8 @@ fn run_integrity_sabotage(path: &Path) {
             0xffff_ffffu32
         });
         let ingredients = crate::common::ark_labs_ingredients_from_json(&ingredients_json).unwrap();
-        let bytes = create_vpack_ark_labs(ingredients).expect("pack");
-        let result = vpack::verify(&bytes, &expected_id, anchor_value);
+        let bytes = create_vpack_ark_labs(ingredients, Network::Mainnet).expect("pack");
+        let result = vpack::verify(&bytes, &expected_id, anchor_value, Network::Mainnet);
         assert!(
             matches!(
                 result,
@@ -223,9 +925,9 @@ fn run_integrity_sabotage(path: &Path) {
         let ingredients =
             crate::common::ark_labs_ingredients_from_json(&vector.reconstruction_ingredients)
                 .unwrap();
-        let good_bytes = create_vpack_ark_labs(ingredients).expect("pack");
-        let mut tree =
-            vpack::verify(&good_bytes, &expected_id, anchor_value).expect("verify good bytes");
+        let good_bytes = create_vpack_ark_labs(ingredients, Network::Mainnet).expect("pack");
+        let mut tree = vpack::verify(&good_bytes, &expected_id, anchor_value, Network::Mainnet)
+            .expect("verify good bytes");
         if let Some(vpack::payload::tree::SiblingNode::Compact { ref mut script, .. }) =
             tree.path.first_mut().and_then(|p| p.siblings.first_mut())
         {
@@ -235,9 +937,9 @@ fn run_integrity_sabotage(path: &Path) {
                 script[0] = script[0].wrapping_add(1);
             }
         }
-        let bad_bytes =
-            create_vpack_from_tree(&tree, TxVariant::V3Anchored, false).expect("pack mutated tree");
-        let result = vpack::verify(&bad_bytes, &expected_id, anchor_value);
+        let bad_bytes = create_vpack_from_tree(&tree, TxVariant::V3Anchored, Network::Mainnet)
+            .expect("pack mutated tree");
+        let result = vpack::verify(&bad_bytes, &expected_id, anchor_value, Network::Mainnet);
         assert!(
             matches!(result, Err(VPackError::IdMismatch { .. })),
             "sabotaged sibling script should yield IdMismatch, got {:?}",
@@ -251,8 +953,8 @@ fn run_integrity_sabotage(path: &Path) {
         ingredients_json["vout"] = serde_json::json!(99u32);
         if let Ok(ingredients) = crate::common::second_tech_ingredients_from_json(&ingredients_json)
         {
-            let bytes = create_vpack_second_tech(ingredients).expect("pack");
-            let result = vpack::verify(&bytes, &expected_id, anchor_value);
+            let bytes = create_vpack_second_tech(ingredients, Network::Mainnet).expect("pack");
+            let result = vpack::verify(&bytes, &expected_id, anchor_value, Network::Mainnet);
             assert!(
                 matches!(result, Err(VPackError::InvalidVout(99))),
                 "vout 99 should yield InvalidVout(99), got {:?}",
@@ -273,9 +975,9 @@ fn run_integrity_sabotage(path: &Path) {
         let ingredients =
             crate::common::ark_labs_ingredients_from_json(&vector.reconstruction_ingredients)
                 .unwrap();
-        let good_bytes = create_vpack_ark_labs(ingredients).expect("pack");
-        let mut tree =
-            vpack::verify(&good_bytes, &expected_id, anchor_value).expect("verify good bytes");
+        let good_bytes = create_vpack_ark_labs(ingredients, Network::Mainnet).expect("pack");
+        let mut tree = vpack::verify(&good_bytes, &expected_id, anchor_value, Network::Mainnet)
+            .expect("verify good bytes");
         if let Some(item) = tree.path.first_mut() {
             item.sequence = if tree.leaf.sequence == 0xffff_ffff {
                 0xffff_fffe
@@ -283,9 +985,9 @@ fn run_integrity_sabotage(path: &Path) {
                 0xffff_ffff
             };
         }
-        let bad_bytes =
-            create_vpack_from_tree(&tree, TxVariant::V3Anchored, false).expect("pack mutated tree");
-        let result = vpack::verify(&bad_bytes, &expected_id, anchor_value);
+        let bad_bytes = create_vpack_from_tree(&tree, TxVariant::V3Anchored, Network::Mainnet)
+            .expect("pack mutated tree");
+        let result = vpack::verify(&bad_bytes, &expected_id, anchor_value, Network::Mainnet);
         assert!(
             matches!(result, Err(VPackError::PolicyMismatch)),
             "sequence mismatch in path should yield PolicyMismatch, got {:?}",
@@ -310,7 +1012,7 @@ fn print_second_computed_ids() {
                 continue;
             }
         };
-        let bytes = create_vpack_second_tech(ingredients).expect("pack");
+        let bytes = create_vpack_second_tech(ingredients, Network::Mainnet).expect("pack");
         let id = vpack::compute_vtxo_id_from_bytes(&bytes).expect("compute id");
         println!("{} expected_vtxo_id: {}", name, id);
     }
@@ -362,8 +1064,14 @@ fn test_sabotage_inflation() {
     let good_ingredients =
         crate::common::ark_labs_ingredients_from_json(&leaf_vector.reconstruction_ingredients)
             .expect("round_leaf ingredients");
-    let good_bytes = create_vpack_ark_labs(good_ingredients).expect("pack");
-    vpack::verify(&good_bytes, &expected_id, ANCHOR_ROUND_LEAF).expect("verify good round_leaf");
+    let good_bytes = create_vpack_ark_labs(good_ingredients, Network::Mainnet).expect("pack");
+    vpack::verify(
+        &good_bytes,
+        &expected_id,
+        ANCHOR_ROUND_LEAF,
+        Network::Mainnet,
+    )
+    .expect("verify good round_leaf");
 
     let mut ri_leaf = leaf_vector.reconstruction_ingredients.clone();
     if let Some(arr) = ri_leaf["outputs"].as_array_mut() {
@@ -374,8 +1082,14 @@ fn test_sabotage_inflation() {
     }
     let bad_ingredients_leaf =
         crate::common::ark_labs_ingredients_from_json(&ri_leaf).expect("sabotaged ingredients");
-    let bad_bytes_leaf = create_vpack_ark_labs(bad_ingredients_leaf).expect("pack");
-    let result_leaf = vpack::verify(&bad_bytes_leaf, &expected_id, ANCHOR_ROUND_LEAF);
+    let bad_bytes_leaf =
+        create_vpack_ark_labs(bad_ingredients_leaf, Network::Mainnet).expect("pack");
+    let result_leaf = vpack::verify(
+        &bad_bytes_leaf,
+        &expected_id,
+        ANCHOR_ROUND_LEAF,
+        Network::Mainnet,
+    );
     assert!(
         matches!(
             result_leaf,
@@ -401,9 +1115,15 @@ fn test_sabotage_inflation() {
     let good_branch_ingredients =
         crate::common::ark_labs_ingredients_from_json(&branch_vector.reconstruction_ingredients)
             .expect("round_branch ingredients");
-    let good_branch_bytes = create_vpack_ark_labs(good_branch_ingredients).expect("pack");
-    vpack::verify(&good_branch_bytes, &branch_expected_id, ANCHOR_ROUND_BRANCH)
-        .expect("verify good round_branch");
+    let good_branch_bytes =
+        create_vpack_ark_labs(good_branch_ingredients, Network::Mainnet).expect("pack");
+    vpack::verify(
+        &good_branch_bytes,
+        &branch_expected_id,
+        ANCHOR_ROUND_BRANCH,
+        Network::Mainnet,
+    )
+    .expect("verify good round_branch");
 
     let mut ri_branch = branch_vector.reconstruction_ingredients.clone();
     if let Some(arr) = ri_branch["siblings"].as_array_mut() {
@@ -414,8 +1134,14 @@ fn test_sabotage_inflation() {
     }
     let bad_ingredients_branch =
         crate::common::ark_labs_ingredients_from_json(&ri_branch).expect("sabotaged ingredients");
-    let bad_bytes_branch = create_vpack_ark_labs(bad_ingredients_branch).expect("pack");
-    let result_branch = vpack::verify(&bad_bytes_branch, &branch_expected_id, ANCHOR_ROUND_BRANCH);
+    let bad_bytes_branch =
+        create_vpack_ark_labs(bad_ingredients_branch, Network::Mainnet).expect("pack");
+    let result_branch = vpack::verify(
+        &bad_bytes_branch,
+        &branch_expected_id,
+        ANCHOR_ROUND_BRANCH,
+        Network::Mainnet,
+    );
     assert!(
         matches!(
             result_branch,
@@ -442,7 +1168,7 @@ fn print_computed_vtxo_id() {
         let ingredients =
             crate::common::ark_labs_ingredients_from_json(&vector.reconstruction_ingredients)
                 .expect("full reconstruction_ingredients");
-        let bytes = create_vpack_ark_labs(ingredients).expect("pack");
+        let bytes = create_vpack_ark_labs(ingredients, Network::Mainnet).expect("pack");
         let id = vpack::compute_vtxo_id_from_bytes(&bytes).expect("compute id");
         println!("expected_vtxo_id for {}: {}", name, id);
     }
@@ -480,7 +1206,7 @@ fn vpack_byte_size_summary() {
                         Ok(i) => i,
                         Err(_) => continue,
                     };
-                    match create_vpack_ark_labs(ingredients) {
+                    match create_vpack_ark_labs(ingredients, Network::Mainnet) {
                         Ok(b) => b,
                         Err(_) => continue,
                     }
@@ -492,11 +1218,12 @@ fn vpack_byte_size_summary() {
                         Ok(i) => i,
                         Err(_) => continue,
                     };
-                    match create_vpack_second_tech(ingredients) {
+                    match create_vpack_second_tech(ingredients, Network::Mainnet) {
                         Ok(b) => b,
                         Err(_) => continue,
                     }
                 }
+                TxVariant::Custom(_) => unreachable!("vectors use the built-in variants"),
             };
             let size = bytes.len();
             if subdir == "ark_labs" {
@@ -544,7 +1271,7 @@ fn print_oor_forfeit_expected_id() {
     let ingredients =
         crate::common::ark_labs_ingredients_from_json(&vector.reconstruction_ingredients)
             .expect("oor_forfeit_pset must have full reconstruction_ingredients");
-    let bytes = create_vpack_ark_labs(ingredients).expect("pack");
+    let bytes = create_vpack_ark_labs(ingredients, Network::Mainnet).expect("pack");
     let id = vpack::compute_vtxo_id_from_bytes(&bytes).expect("compute id");
     println!("expected_vtxo_id for oor_forfeit_pset.json: {}", id);
 }
@@ -1126,9 +1853,13 @@ fn test_vpack_internal_consistency_roundtrip() {
 
     let ark_packed_bytes = pack(&ark_header, &ark_tree).expect("pack Ark Labs V-PACK");
     const ARK_ROUND_LEAF_ANCHOR: u64 = 1100;
-    let ark_verified_tree =
-        vpack::verify(&ark_packed_bytes, &ark_expected_id, ARK_ROUND_LEAF_ANCHOR)
-            .expect("Ark Labs round-trip verification should succeed");
+    let ark_verified_tree = vpack::verify(
+        &ark_packed_bytes,
+        &ark_expected_id,
+        ARK_ROUND_LEAF_ANCHOR,
+        Network::Mainnet,
+    )
+    .expect("Ark Labs round-trip verification should succeed");
 
     assert_eq!(ark_verified_tree.leaf.amount, ark_tree.leaf.amount);
     assert_eq!(
@@ -1289,6 +2020,7 @@ fn test_vpack_internal_consistency_roundtrip() {
         &second_packed_bytes,
         &second_expected_id,
         ROUND_1_ANCHOR_SATS,
+        Network::Mainnet,
     )
     .expect("Second Tech round-trip verification should succeed");
 
@@ -1325,8 +2057,9 @@ fn test_reject_invalid_sequence() {
             .expect("valid ingredients");
     ingredients.n_sequence = 0x0000_0005u32;
 
-    let packed_bytes = create_vpack_ark_labs(ingredients).expect("pack invalid-sequence V-PACK");
-    let result = vpack::verify(&packed_bytes, &ark_expected_id, 1100);
+    let packed_bytes =
+        create_vpack_ark_labs(ingredients, Network::Mainnet).expect("pack invalid-sequence V-PACK");
+    let result = vpack::verify(&packed_bytes, &ark_expected_id, 1100, Network::Mainnet);
     assert!(
         result.is_err(),
         "V-PACK with invalid sequence should be rejected"
@@ -517,7 +517,8 @@ mod engine_schnorr {
             &p2tr,
             &step1_outputs,
             0x00,
-        );
+        )
+        .expect("sighash");
         let (sig, _) = sign_sighash_for_test(&sighash);
 
         let sibling0 = SiblingNode::Compact {
@@ -0,0 +1,136 @@
+//! CPFP children checked with rust-bitcoin: structure, chaining, fees and key-path sighashes.
+#![cfg(all(feature = "bitcoin", feature = "schnorr-verify"))]
+
+mod common;
+
+use bitcoin::consensus::deserialize;
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+use bitcoin::{Amount, ScriptBuf, Transaction, TxOut, Witness};
+use common::exit_fixtures::{ark_tree, key, p2tr};
+use vpack::cpfp::P2A_SCRIPT;
+use vpack::error::VPackError;
+use vpack::types::{OutPoint, Txid};
+use vpack::{build_cpfp_children, ArkLabsV3, ConsensusEngine, FundingUtxo, VerificationOutput};
+
+/// Exit of a two-level Ark Labs branch: two path transactions and the leaf transaction.
+fn exit_output() -> VerificationOutput {
+    ArkLabsV3
+        .compute_vtxo_id(&ark_tree(&key(0x01).1, &key(0x02).1), None)
+        .expect("reconstructs")
+}
+
+#[test]
+fn children_spend_each_anchor_and_chain_change() {
+    let output = exit_output();
+    let wallet = p2tr(0x66);
+    let funding = FundingUtxo {
+        outpoint: OutPoint {
+            txid: Txid::from_byte_array([0xBB; 32]),
+            vout: 1,
+        },
+        value: 50_000,
+        script_pubkey: &wallet,
+    };
+    let feerate = 4;
+    let children = build_cpfp_children(&output, &funding, &wallet, feerate).expect("children");
+    assert_eq!(children.len(), output.signed_txs.len());
+
+    let mut wallet_prevout = (
+        funding.outpoint.txid.to_byte_array(),
+        funding.outpoint.vout,
+        funding.value,
+    );
+    for (raw_parent, child) in output.signed_txs.iter().zip(&children) {
+        let mut parent: Transaction = deserialize(raw_parent).expect("parent");
+        let tx: Transaction = deserialize(&child.unsigned_tx).expect("child");
+        assert_eq!(tx.version.0, 3);
+        assert_eq!(tx.compute_txid().to_byte_array(), child.txid);
+        assert_eq!(parent.compute_txid().to_byte_array(), child.parent_txid);
+
+        // Input 0: the parent's anchor; input 1: the wallet UTXO or the previous change.
+        let anchor_vout = parent
+            .output
+            .iter()
+            .position(|o| o.script_pubkey.as_bytes() == P2A_SCRIPT)
+            .expect("anchor") as u32;
+        assert_eq!(tx.input[0].previous_output.txid, parent.compute_txid());
+        assert_eq!(tx.input[0].previous_output.vout, anchor_vout);
+        assert_eq!(
+            tx.input[1].previous_output.txid.to_byte_array(),
+            wallet_prevout.0
+        );
+        assert_eq!(tx.input[1].previous_output.vout, wallet_prevout.1);
+        assert_eq!(tx.output.len(), 1);
+        assert_eq!(tx.output[0].script_pubkey.as_bytes(), wallet.as_slice());
+        assert_eq!(tx.output[0].value.to_sat(), child.change_value);
+        assert_eq!(child.change_value + child.fee, wallet_prevout.2);
+
+        // Package feerate: both transactions once signed.
+        for input in &mut parent.input {
+            input.witness = Witness::from_slice(&[[0u8; 64]]);
+        }
+        let mut signed = tx.clone();
+        signed.input[1].witness = Witness::from_slice(&[[0u8; 64]]);
+        assert!(signed.vsize() <= 1_000);
+        assert_eq!(
+            child.fee,
+            feerate * (parent.vsize() + signed.vsize()) as u64
+        );
+
+        let prevouts = [
+            TxOut {
+                value: Amount::ZERO,
+                script_pubkey: ScriptBuf::from_bytes(P2A_SCRIPT.to_vec()),
+            },
+            TxOut {
+                value: Amount::from_sat(wallet_prevout.2),
+                script_pubkey: ScriptBuf::from_bytes(wallet.clone()),
+            },
+        ];
+        let sighash = SighashCache::new(&tx)
+            .taproot_key_spend_signature_hash(1, &Prevouts::All(&prevouts), TapSighashType::Default)
+            .expect("sighash");
+        assert_eq!(child.sighash, sighash.to_byte_array());
+
+        wallet_prevout = (child.txid, 0, child.change_value);
+    }
+}
+
+#[test]
+fn funding_must_cover_fees_and_dust() {
+    let output = exit_output();
+    let wallet = p2tr(0x66);
+    let funding = FundingUtxo {
+        outpoint: OutPoint {
+            txid: Txid::from_byte_array([0xBB; 32]),
+            vout: 0,
+        },
+        value: 1_000,
+        script_pubkey: &wallet,
+    };
+    assert!(matches!(
+        build_cpfp_children(&output, &funding, &wallet, 10),
+        Err(VPackError::InsufficientFunds {
+            available: 1_000,
+            ..
+        })
+    ));
+
+    let no_anchor = VerificationOutput {
+        signed_txs: vec![deserialize_strip_anchor(&output.signed_txs[0])],
+        ..output
+    };
+    assert_eq!(
+        build_cpfp_children(&no_anchor, &funding, &wallet, 1),
+        Err(VPackError::FeeAnchorMissing)
+    );
+}
+
+/// The transaction with its P2A output removed.
+fn deserialize_strip_anchor(raw: &[u8]) -> Vec<u8> {
+    let mut tx: Transaction = deserialize(raw).expect("tx");
+    tx.output
+        .retain(|o| o.script_pubkey.as_bytes() != P2A_SCRIPT);
+    bitcoin::consensus::serialize(&tx)
+}
@@ -15,6 +15,7 @@ use vpack::header::{Header, HEADER_SIZE};
 use vpack::payload::reader::BoundedReader;
 use vpack::payload::tree::SiblingNode;
 use vpack::taproot::compute_taproot_tweak;
+use vpack::Network;
 use vpack::{create_vpack_from_tree, validate_timelocks, ConsensusEngine, SecondTechV3, TxVariant};
 
 const DUST_THRESHOLD_SATS: u64 = 330;
@@ -351,22 +352,23 @@ fn audit_via_full_pipeline(
     file_name: String,
     tree_from_bark: &vpack::VPackTree,
 ) -> CryptoAuditEntry {
-    let vpack_bytes = match create_vpack_from_tree(tree_from_bark, TxVariant::V3Plain, false) {
-        Ok(b) => b,
-        Err(e) => {
-            return CryptoAuditEntry {
-                filename: file_name,
-                merkle_root_match: false,
-                tweak_match: false,
-                sig_valid: false,
-                timelock_match: false,
-                dust_leaf_count: 0,
-                is_spendable: false,
-                binding_mode: "full".into(),
-                error_trace: Some(format!("create_vpack: {e:?}")),
-            };
-        }
-    };
+    let vpack_bytes =
+        match create_vpack_from_tree(tree_from_bark, TxVariant::V3Plain, Network::Mainnet) {
+            Ok(b) => b,
+            Err(e) => {
+                return CryptoAuditEntry {
+                    filename: file_name,
+                    merkle_root_match: false,
+                    tweak_match: false,
+                    sig_valid: false,
+                    timelock_match: false,
+                    dust_leaf_count: 0,
+                    is_spendable: false,
+                    binding_mode: "full".into(),
+                    error_trace: Some(format!("create_vpack: {e:?}")),
+                };
+            }
+        };
 
     let tree = match parse_vpack_tree(&vpack_bytes) {
         Ok(t) => t,
@@ -0,0 +1,117 @@
+//! `ExitCostReport` weights against transactions assembled and weighed by rust-bitcoin.
+#![cfg(all(feature = "bitcoin", feature = "schnorr-verify"))]
+
+mod common;
+
+use bitcoin::consensus::deserialize;
+use bitcoin::{Amount, ScriptBuf, Transaction, TxIn, TxOut, Witness};
+use common::exit_fixtures::{ark_tree, key, p2tr};
+use vpack::consensus::ark_labs::compile_exit_script;
+use vpack::error::VPackError;
+use vpack::{exit_cost_report, ArkLabsV3, ConsensusEngine, TxVariant};
+
+/// Ark Labs test tree (two path transactions and the leaf transaction) and its exit leaf.
+fn ark_exit() -> (vpack::payload::tree::VPackTree, Vec<u8>) {
+    let (asp, user) = (key(0x01).1, key(0x02).1);
+    (
+        ark_tree(&asp, &user),
+        compile_exit_script(&asp, &user, &[0x90, 0x00]),
+    )
+}
+
+fn p2tr_output() -> TxOut {
+    TxOut {
+        value: Amount::from_sat(900),
+        script_pubkey: ScriptBuf::from_bytes(p2tr(0x55)),
+    }
+}
+
+fn spend(witness: Witness) -> TxIn {
+    TxIn {
+        witness,
+        ..TxIn::default()
+    }
+}
+
+#[test]
+fn weights_match_rust_bitcoin() {
+    let (tree, exit_script) = ark_exit();
+    let report = exit_cost_report(&tree, TxVariant::V3Anchored, &exit_script).expect("report");
+
+    // Unsigned exit transactions are charged one key-path signature per input.
+    let signed_txs = ArkLabsV3
+        .compute_vtxo_id(&tree, None)
+        .expect("reconstructs")
+        .signed_txs;
+    assert_eq!(report.tx_weights.len(), 3);
+    for (raw, &weight) in signed_txs.iter().zip(&report.tx_weights) {
+        let mut tx: Transaction = deserialize(raw).expect("consensus encoding");
+        for input in &mut tx.input {
+            input.witness = Witness::from_slice(&[[0u8; 64]]);
+        }
+        assert_eq!(weight, tx.weight().to_wu());
+    }
+
+    // Anchor input with an empty witness plus a key-path wallet input.
+    let child = Transaction {
+        version: bitcoin::transaction::Version(3),
+        lock_time: bitcoin::absolute::LockTime::ZERO,
+        input: vec![
+            spend(Witness::new()),
+            spend(Witness::from_slice(&[[0u8; 64]])),
+        ],
+        output: vec![p2tr_output()],
+    };
+    assert_eq!(report.cpfp_child_weight, child.weight().to_wu());
+
+    // Exit leaf beside the forfeit leaf: one sibling hash in the control block.
+    assert_eq!(report.control_block_len, 65);
+    let sweep = Transaction {
+        version: bitcoin::transaction::Version(2),
+        lock_time: bitcoin::absolute::LockTime::ZERO,
+        input: vec![spend(Witness::from_slice(&[
+            vec![0u8; 64],
+            vec![0u8; 64],
+            exit_script.clone(),
+            vec![0xc0; 65],
+        ]))],
+        output: vec![p2tr_output()],
+    };
+    assert_eq!(report.sweep_weight, sweep.weight().to_wu());
+
+    let vbytes: u64 = report
+        .tx_weights
+        .iter()
+        .map(|w| w.div_ceil(4) + child.vsize() as u64)
+        .sum();
+    assert_eq!(report.cpfp_fee(3), 3 * vbytes);
+    assert_eq!(report.sweep_fee(3), 3 * sweep.vsize() as u64);
+    assert_eq!(report.total_vbytes(), vbytes + sweep.vsize() as u64);
+    assert_eq!(
+        report.total_fee(3),
+        report.cpfp_fee(3) + report.sweep_fee(3)
+    );
+}
+
+#[test]
+fn break_even_feerate_is_the_last_feerate_the_vtxo_covers() {
+    let (tree, exit_script) = ark_exit();
+    let report = exit_cost_report(&tree, TxVariant::V3Anchored, &exit_script).expect("report");
+
+    let break_even = report.break_even_feerate();
+    assert!(break_even > 0);
+    assert!(report.total_fee(break_even) <= tree.leaf.amount);
+    assert!(report.total_fee(break_even + 1) > tree.leaf.amount);
+}
+
+#[test]
+fn sweep_script_must_be_a_tap_leaf() {
+    let (asp, user) = (key(0x01).1, key(0x02).1);
+    let tree = ark_tree(&asp, &user);
+    // CSV of 145 blocks is not the committed exit leaf.
+    let other = compile_exit_script(&asp, &user, &[0x91, 0x00]);
+    assert_eq!(
+        exit_cost_report(&tree, TxVariant::V3Anchored, &other),
+        Err(VPackError::ControlBlockReconstructionFailed)
+    );
+}
@@ -0,0 +1,133 @@
+//! `build_exit_sweep`: the Ark Labs exit leaf swept, signed and checked by rust-bitcoin and the
+//! tapscript evaluator.
+#![cfg(all(feature = "bitcoin", feature = "schnorr-verify"))]
+
+mod common;
+
+use bitcoin::consensus::deserialize;
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+use bitcoin::taproot::{LeafVersion, TapLeafHash};
+use bitcoin::{Amount, ScriptBuf, Transaction, TxOut};
+use common::exit_fixtures::{ark_tree, key};
+use k256::schnorr::signature::hazmat::PrehashSigner;
+use vpack::consensus::ark_labs::compile_exit_script;
+use vpack::consensus::second_tech::compile_bark_delayed_sign_script;
+use vpack::consensus::{eval_tapscript, SpendContext};
+use vpack::error::VPackError;
+use vpack::{build_exit_sweep, verify_control_block, ConsensusEngine, SecondTechV3, TxVariant};
+
+#[test]
+fn ark_exit_sweep_signs_and_spends() {
+    let (asp, asp_pk) = key(0x01);
+    let (user, user_pk) = key(0x02);
+    let tree = ark_tree(&asp_pk, &user_pk);
+    let mut destination = vec![0x51, 0x20];
+    destination.extend_from_slice(&[0x77; 32]);
+
+    let sweep = build_exit_sweep(&tree, TxVariant::V3Anchored, &destination, 5).expect("sweep");
+    let exit_script = compile_exit_script(&asp_pk, &user_pk, &[0x90, 0x00]);
+    assert_eq!(sweep.leaf_script, exit_script);
+    assert_eq!(sweep.input.sequence, 144);
+    assert_eq!(sweep.signatures_required, 2);
+    assert!(verify_control_block(
+        &sweep.control_block,
+        &exit_script,
+        &sweep.prevout_script[2..].try_into().unwrap()
+    ));
+    assert_eq!(sweep.prevout_script, tree.leaf.script_pubkey);
+
+    // The sighash matches rust-bitcoin's BIP-342 computation.
+    let unsigned: Transaction = deserialize(&sweep.unsigned_tx()).expect("unsigned");
+    let prevouts = [TxOut {
+        value: Amount::from_sat(sweep.amount),
+        script_pubkey: ScriptBuf::from_bytes(sweep.prevout_script.clone()),
+    }];
+    let leaf_hash = TapLeafHash::from_script(
+        &ScriptBuf::from_bytes(exit_script.clone()),
+        LeafVersion::TapScript,
+    );
+    let expected = SighashCache::new(&unsigned)
+        .taproot_script_spend_signature_hash(
+            0,
+            &Prevouts::All(&prevouts),
+            leaf_hash,
+            TapSighashType::Default,
+        )
+        .expect("sighash");
+    assert_eq!(sweep.sighash, expected.to_byte_array());
+
+    // Signed: the exit leaf evaluates, and the fee pays `feerate` on the final size.
+    let user_sig = user.sign_prehash(&sweep.sighash).expect("sign").to_bytes();
+    let asp_sig = asp.sign_prehash(&sweep.sighash).expect("sign").to_bytes();
+    let raw = sweep.signed_tx(&[user_sig, asp_sig]).expect("signed");
+    let signed: Transaction = deserialize(&raw).expect("signed");
+    assert_eq!(signed.compute_txid().to_byte_array(), sweep.txid());
+    assert_eq!(sweep.fee, 5 * signed.vsize() as u64);
+    assert_eq!(sweep.value + sweep.fee, tree.leaf.amount);
+    assert_eq!(
+        signed.output[0].script_pubkey.as_bytes(),
+        destination.as_slice()
+    );
+
+    let witness: Vec<&[u8]> = signed.input[0].witness.iter().collect();
+    assert_eq!(witness.len(), 4);
+    let ctx = SpendContext {
+        sighash: sweep.sighash,
+        tx_version: 2,
+        locktime: 0,
+        sequence: signed.input[0].sequence.0,
+    };
+    assert_eq!(eval_tapscript(witness[2], &witness[..2], &ctx), Ok(()));
+
+    assert_eq!(sweep.signed_tx(&[user_sig]), Err(VPackError::EncodingError));
+}
+
+#[test]
+fn sweep_fee_must_leave_dust() {
+    let tree = ark_tree(&key(0x01).1, &key(0x02).1);
+    assert!(matches!(
+        build_exit_sweep(&tree, TxVariant::V3Anchored, &[0x51, 0x20, 0x00], 1_000),
+        Err(VPackError::InsufficientFunds {
+            available: 100_000,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn leaf_key_must_commit_to_the_exit_leaf() {
+    let mut tree = ark_tree(&key(0x01).1, &key(0x02).1);
+    tree.internal_key = key(0x12).1;
+    assert_eq!(
+        build_exit_sweep(&tree, TxVariant::V3Anchored, &[0x51, 0x20, 0x00], 1),
+        Err(VPackError::ControlBlockReconstructionFailed)
+    );
+}
+
+#[test]
+fn leaf_output_must_be_the_swept_key() {
+    let mut tree = ark_tree(&key(0x01).1, &key(0x02).1);
+    // Not P2TR: the real output does not carry the key the sighash commits to.
+    tree.leaf.script_pubkey = vec![0x00, 0x14];
+    tree.leaf.script_pubkey.extend_from_slice(&[0x33; 20]);
+    tree.path[1].child_script_pubkey = tree.leaf.script_pubkey.clone();
+    assert_eq!(
+        build_exit_sweep(&tree, TxVariant::V3Anchored, &[0x51, 0x20, 0x00], 1),
+        Err(VPackError::ControlBlockReconstructionFailed)
+    );
+}
+
+#[test]
+fn bark_exit_leaf_is_the_delayed_sign_script() {
+    let user_pk = key(0x02).1;
+    let mut compressed = vec![0x02];
+    compressed.extend_from_slice(&user_pk);
+    let mut tree = ark_tree(&key(0x01).1, &user_pk);
+    tree.leaf.script_pubkey = compressed;
+
+    let exit = SecondTechV3.exit_leaf(&tree).expect("exit leaf");
+    assert_eq!(exit.script, compile_bark_delayed_sign_script(144, &user_pk));
+    assert_eq!(exit.index, 0);
+    assert_eq!(exit.leaf_hashes.len(), 1);
+}
@@ -6,6 +6,7 @@ use std::fs;
 use std::path::PathBuf;
 
 use vpack::export::{create_vpack_ark_labs, create_vpack_second_tech};
+use vpack::Network;
 
 #[test]
 fn export_ark_labs_parity() {
@@ -35,7 +36,8 @@ fn export_ark_labs_parity() {
             .unwrap_or(serde_json::Value::Null);
         let ingredients = crate::common::ark_labs_ingredients_from_json(&ingredients_json)
             .unwrap_or_else(|e| panic!("{}: ingredients_from_json: {}", path.display(), e));
-        let bytes = create_vpack_ark_labs(ingredients).expect("create_vpack_ark_labs");
+        let bytes =
+            create_vpack_ark_labs(ingredients, Network::Mainnet).expect("create_vpack_ark_labs");
         let name = path.file_name().and_then(|p| p.to_str()).unwrap_or("");
         let anchor_value = match name {
             "round_leaf_v3.json" => 1100,
@@ -43,7 +45,7 @@ fn export_ark_labs_parity() {
             "oor_forfeit_pset.json" => 1000,
             _ => 1100,
         };
-        vpack::verify(&bytes, &expected_id, anchor_value).expect("verify");
+        vpack::verify(&bytes, &expected_id, anchor_value, Network::Mainnet).expect("verify");
     }
 }
 
@@ -75,7 +77,8 @@ fn export_second_tech_parity() {
             .unwrap_or(serde_json::Value::Null);
         let ingredients = crate::common::second_tech_ingredients_from_json(&ingredients_json)
             .unwrap_or_else(|e| panic!("{}: ingredients_from_json: {}", path.display(), e));
-        let bytes = create_vpack_second_tech(ingredients).expect("create_vpack_second_tech");
+        let bytes = create_vpack_second_tech(ingredients, Network::Mainnet)
+            .expect("create_vpack_second_tech");
         let anchor_value = path
             .file_name()
             .and_then(|p| p.to_str())
@@ -87,6 +90,52 @@ fn export_second_tech_parity() {
                 }
             })
             .unwrap_or(10_000);
-        vpack::verify(&bytes, &expected_id, anchor_value).expect("verify");
+        vpack::verify(&bytes, &expected_id, anchor_value, Network::Mainnet).expect("verify");
+    }
+}
+
+#[test]
+fn export_network_is_checked_by_verify() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let path = manifest_dir.join("tests/conformance/vectors/ark_labs/round_leaf_v3.json");
+    if !path.is_file() {
+        return;
+    }
+    let contents = fs::read_to_string(&path).expect("read");
+    let value: serde_json::Value = serde_json::from_str(&contents).expect("parse");
+    let expected_id = value
+        .get("raw_evidence")
+        .and_then(|r| r.get("expected_vtxo_id"))
+        .and_then(|v| v.as_str())
+        .map(|s| vpack::VtxoId::from_str(s).expect("parse expected_vtxo_id"))
+        .expect("expected_vtxo_id");
+    let ingredients_json = value
+        .get("reconstruction_ingredients")
+        .cloned()
+        .unwrap_or(serde_json::Value::Null);
+
+    for network in [
+        Network::Mainnet,
+        Network::Testnet,
+        Network::Signet,
+        Network::Regtest,
+    ] {
+        let ingredients = crate::common::ark_labs_ingredients_from_json(&ingredients_json)
+            .expect("ingredients_from_json");
+        let bytes = create_vpack_ark_labs(ingredients, network).expect("create_vpack_ark_labs");
+        vpack::verify(&bytes, &expected_id, 1100, network).expect("verify");
+
+        let other = if network == Network::Signet {
+            Network::Regtest
+        } else {
+            Network::Signet
+        };
+        assert_eq!(
+            vpack::verify(&bytes, &expected_id, 1100, other),
+            Err(vpack::error::VPackError::NetworkMismatch {
+                expected: other,
+                found: network,
+            })
+        );
     }
 }
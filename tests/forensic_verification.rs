@@ -17,6 +17,7 @@ use std::io::Cursor;
 use vpack::header::{Header, TxVariant, FLAG_PROOF_COMPACT};
 use vpack::pack::pack;
 use vpack::payload::tree::{GenesisItem, SiblingNode, VPackTree, VtxoLeaf};
+use vpack::Network;
 
 // Naked hash tests use hex from audit fixtures (round_leaf, round_branch, oor).
 const ARK_LABS_OOR_FORFEIT_TX_HEX: &str = "0300000001411d0d848ab79c0f7ae5a73742c4addd4e5b5646c2bc4bea854d287107825c750000000000feffffff02e803000000000000150014a1b2c3d4e5f6789012345678901234567890ab00000000000000000451024e7300000000";
@@ -246,10 +247,15 @@ fn master_universal_verification() {
 
     const ARK_ANCHOR: u64 = 1100;
     const SECOND_ROUND_ANCHOR: u64 = 13_000;
-    let ark_tree_result = vpack::verify(&ark_bytes, &ark_expected_id, ARK_ANCHOR)
+    let ark_tree_result = vpack::verify(&ark_bytes, &ark_expected_id, ARK_ANCHOR, Network::Mainnet)
         .expect("Ark Labs verification should succeed");
-    let second_tree_result = vpack::verify(&second_bytes, &second_expected, SECOND_ROUND_ANCHOR)
-        .expect("Second Tech verification should succeed");
+    let second_tree_result = vpack::verify(
+        &second_bytes,
+        &second_expected,
+        SECOND_ROUND_ANCHOR,
+        Network::Mainnet,
+    )
+    .expect("Second Tech verification should succeed");
 
     assert!(!ark_tree_result.leaf.script_pubkey.is_empty() || ark_tree_result.leaf.amount > 0);
     assert!(
@@ -405,7 +411,12 @@ fn test_sabotage_invalid_signature() {
 
     let packed_tampered = pack(&header, &tree_tampered).expect("pack tampered V-PACK");
     const ROUND_1_ANCHOR: u64 = 45_000;
-    let result = vpack::verify(&packed_tampered, &expected_id, ROUND_1_ANCHOR);
+    let result = vpack::verify(
+        &packed_tampered,
+        &expected_id,
+        ROUND_1_ANCHOR,
+        Network::Mainnet,
+    );
     assert!(
         matches!(result, Err(vpack::error::VPackError::InvalidSignature)),
         "tampered or invalid signature must yield InvalidSignature, got {:?}",
@@ -0,0 +1,303 @@
+//! PSBT v2 export and import of exit transactions, checked with rust-bitcoin and k256 signatures.
+#![cfg(all(feature = "bitcoin", feature = "schnorr-verify"))]
+
+mod common;
+
+use bitcoin::consensus::deserialize;
+use bitcoin::hashes::Hash;
+use bitcoin::Transaction;
+use common::exit_fixtures::{ark_tree, key, p2tr};
+use k256::schnorr::signature::hazmat::PrehashSigner;
+use vpack::consensus::taproot::tap_leaf_hash;
+use vpack::cpfp::P2A_SCRIPT;
+use vpack::error::VPackError;
+use vpack::psbt::TapScriptSig;
+use vpack::types::{OutPoint, Txid};
+use vpack::{
+    build_cpfp_children, build_exit_sweep, compute_ark_labs_merkle_root, exit_psbts,
+    exit_psbts_with_keys, verify_control_block, ArkLabsV3, ConsensusEngine, FundingUtxo, Psbt,
+    TapKeySpend, TxVariant,
+};
+
+const ANCHOR_VALUE: u64 = 300_000;
+
+#[test]
+fn exit_psbts_carry_every_prevout_and_round_trip() {
+    let tree = ark_tree(&key(0x01).1, &key(0x02).1);
+    let output = ArkLabsV3
+        .compute_vtxo_id(&tree, None)
+        .expect("reconstructs");
+    let anchor_script = p2tr(0x55);
+    let psbts = exit_psbts(&output, ANCHOR_VALUE, &anchor_script).expect("psbts");
+    assert_eq!(psbts.len(), output.signed_txs.len());
+
+    let txs: Vec<Transaction> = output
+        .signed_txs
+        .iter()
+        .map(|raw| deserialize(raw).expect("exit tx"))
+        .collect();
+    for (i, (psbt, tx)) in psbts.iter().zip(&txs).enumerate() {
+        let bytes = psbt.serialize();
+        assert_eq!(hex::encode(&bytes[..5]), "70736274ff");
+        assert_eq!(Psbt::parse(&bytes).as_ref(), Ok(psbt));
+        assert_eq!(psbt.txid(), tx.compute_txid().to_byte_array());
+        assert_eq!(psbt.tx_version, tx.version.0 as u32);
+        assert_eq!(psbt.outputs.len(), tx.output.len());
+
+        // Each input spends the anchor or an output of the previous exit transaction.
+        let utxo = psbt.inputs[0].witness_utxo.as_ref().expect("witness_utxo");
+        if i == 0 {
+            assert_eq!(utxo.value, ANCHOR_VALUE);
+            assert_eq!(utxo.script_pubkey, anchor_script);
+        } else {
+            let spent = &txs[i - 1].output[psbt.inputs[0].output_index as usize];
+            assert_eq!(
+                psbt.inputs[0].previous_txid,
+                txs[i - 1].compute_txid().to_byte_array()
+            );
+            assert_eq!(utxo.value, spent.value.to_sat());
+            assert_eq!(utxo.script_pubkey, spent.script_pubkey.as_bytes());
+        }
+
+        // Unsigned P2TR inputs cannot be finalized; a key-path signature finalizes them.
+        assert_eq!(
+            psbt.extract_tx(),
+            Err(VPackError::InvalidPsbt("input is not signed"))
+        );
+        let mut signed = Psbt::parse(&bytes).expect("parse");
+        signed.inputs[0].tap_key_sig = Some(vec![0x5A; 64]);
+        let raw = Psbt::parse(&signed.serialize())
+            .expect("signed parse")
+            .extract_tx()
+            .expect("extract");
+        let extracted: Transaction = deserialize(&raw).expect("extracted");
+        assert_eq!(extracted.compute_txid(), tx.compute_txid());
+        assert_eq!(extracted.input[0].witness.to_vec(), vec![vec![0x5A; 64]]);
+
+        // Imported again, the signature comes back as the key-path signature.
+        let reimported = exit_psbts(
+            &vpack::VerificationOutput {
+                signed_txs: vec![raw],
+                ..output.clone()
+            },
+            utxo.value,
+            &utxo.script_pubkey,
+        )
+        .expect("reimport");
+        assert_eq!(reimported[0].inputs[0].tap_key_sig, Some(vec![0x5A; 64]));
+    }
+}
+
+#[test]
+fn exit_psbt_inputs_carry_the_taproot_key_fields_the_signer_supplies() {
+    let tree = ark_tree(&key(0x01).1, &key(0x02).1);
+    let output = ArkLabsV3
+        .compute_vtxo_id(&tree, None)
+        .expect("reconstructs");
+    let anchor_script = p2tr(0x55);
+
+    // The tree does not know the internal keys behind the spent outputs (round cosigners).
+    let psbts = exit_psbts(&output, ANCHOR_VALUE, &anchor_script).expect("psbts");
+    for input in psbts.iter().flat_map(|psbt| &psbt.inputs) {
+        let utxo = input.witness_utxo.as_ref().expect("witness_utxo");
+        assert_eq!(utxo.script_pubkey[..2], [0x51, 0x20]);
+        assert_eq!(input.tap_internal_key, None);
+        assert_eq!(input.tap_merkle_root, None);
+        assert!(input.tap_leaf_scripts.is_empty());
+    }
+
+    // The signer supplies them per input: with a script tree for the anchor spend, key-only for
+    // the next hop and nothing after that.
+    let supplied = |i: usize| match i {
+        0 => Some(TapKeySpend {
+            internal_key: [0xA0; 32],
+            merkle_root: Some([0xB0; 32]),
+        }),
+        1 => Some(TapKeySpend {
+            internal_key: [0xA1; 32],
+            merkle_root: None,
+        }),
+        _ => None,
+    };
+    let mut seen = Vec::new();
+    let keyed = exit_psbts_with_keys(&output, ANCHOR_VALUE, &anchor_script, |i, input| {
+        seen.push((i, input.previous_txid, input.witness_utxo.clone()));
+        supplied(i)
+    })
+    .expect("psbts");
+    assert!(keyed.len() > 2);
+    assert_eq!(seen.len(), psbts.len());
+
+    for (i, (psbt, plain)) in keyed.iter().zip(&psbts).enumerate() {
+        let input = &psbt.inputs[0];
+        assert_eq!(
+            seen[i],
+            (i, input.previous_txid, input.witness_utxo.clone())
+        );
+        assert_eq!(input.tap_internal_key, supplied(i).map(|k| k.internal_key));
+        assert_eq!(
+            input.tap_merkle_root,
+            supplied(i).and_then(|k| k.merkle_root)
+        );
+        assert_eq!(Psbt::parse(&psbt.serialize()).as_ref(), Ok(psbt));
+        assert_eq!(psbt.unsigned_tx(), plain.unsigned_tx());
+    }
+}
+
+#[test]
+fn sweep_psbt_describes_the_exit_leaf_and_finalizes() {
+    let (asp, asp_pk) = key(0x01);
+    let (user, user_pk) = key(0x02);
+    let tree = ark_tree(&asp_pk, &user_pk);
+    let sweep = build_exit_sweep(&tree, TxVariant::V3Anchored, &p2tr(0x77), 5).expect("exit sweep");
+    let mut psbt = sweep.to_psbt().expect("sweep psbt");
+    assert_eq!(psbt.txid(), sweep.txid());
+    assert_eq!(psbt.unsigned_tx(), sweep.unsigned_tx());
+
+    let input = &psbt.inputs[0];
+    let utxo = input.witness_utxo.as_ref().expect("witness_utxo");
+    assert_eq!(utxo.value, tree.leaf.amount);
+    assert_eq!(utxo.script_pubkey, tree.leaf.script_pubkey);
+    assert_eq!(input.tap_internal_key, Some(tree.internal_key));
+    assert_eq!(
+        input.tap_merkle_root,
+        Some(compute_ark_labs_merkle_root(&tree).expect("root"))
+    );
+    let leaf = &input.tap_leaf_scripts[0];
+    assert_eq!(leaf.script, sweep.leaf_script);
+    assert!(verify_control_block(
+        &leaf.control_block,
+        &leaf.script,
+        &utxo.script_pubkey[2..].try_into().unwrap()
+    ));
+    assert_eq!(
+        psbt.extract_tx(),
+        Err(VPackError::InvalidPsbt("input is not signed"))
+    );
+
+    // Signers add their script signatures in any order; extraction orders the witness.
+    let leaf_hash = tap_leaf_hash(&sweep.leaf_script);
+    let asp_sig = asp.sign_prehash(&sweep.sighash).expect("sign").to_bytes();
+    let user_sig = user.sign_prehash(&sweep.sighash).expect("sign").to_bytes();
+    for (pubkey, signature) in [(asp_pk, asp_sig), (user_pk, user_sig)] {
+        psbt.inputs[0].tap_script_sigs.push(TapScriptSig {
+            pubkey,
+            leaf_hash,
+            signature: signature.to_vec(),
+        });
+    }
+    let signed = Psbt::parse(&psbt.serialize()).expect("parse");
+    assert_eq!(signed, psbt);
+    assert_eq!(signed.extract_tx(), sweep.signed_tx(&[user_sig, asp_sig]));
+}
+
+#[test]
+fn cpfp_psbt_extracts_with_an_empty_anchor_witness() {
+    let tree = ark_tree(&key(0x01).1, &key(0x02).1);
+    let output = ArkLabsV3
+        .compute_vtxo_id(&tree, None)
+        .expect("reconstructs");
+    let wallet = p2tr(0x66);
+    let funding = FundingUtxo {
+        outpoint: OutPoint {
+            txid: Txid::from_byte_array([0xBB; 32]),
+            vout: 1,
+        },
+        value: 50_000,
+        script_pubkey: &wallet,
+    };
+    let children = build_cpfp_children(&output, &funding, &wallet, 2).expect("children");
+
+    let mut spent_value = funding.value;
+    for child in &children {
+        let mut psbt = child.to_psbt().expect("child psbt");
+        assert_eq!(psbt.txid(), child.txid);
+        assert_eq!(psbt.unsigned_tx(), child.unsigned_tx);
+        let anchor = psbt.inputs[0].witness_utxo.as_ref().expect("anchor");
+        assert_eq!(anchor.script_pubkey, P2A_SCRIPT);
+        let utxo = psbt.inputs[1].witness_utxo.as_ref().expect("wallet");
+        assert_eq!(
+            (utxo.value, utxo.script_pubkey.as_slice()),
+            (spent_value, &wallet[..])
+        );
+
+        psbt.inputs[1].tap_key_sig = Some(vec![0x5A; 64]);
+        let raw = Psbt::parse(&psbt.serialize())
+            .expect("parse")
+            .extract_tx()
+            .expect("extract");
+        let tx: Transaction = deserialize(&raw).expect("child");
+        assert_eq!(tx.compute_txid().to_byte_array(), child.txid);
+        assert!(tx.input[0].witness.is_empty());
+        assert_eq!(tx.input[1].witness.to_vec(), vec![vec![0x5A; 64]]);
+        spent_value = child.change_value;
+    }
+
+    // Fields are public: an edited child fails instead of panicking.
+    let mut edited = children[0].clone();
+    edited.unsigned_tx.truncate(10);
+    assert_eq!(edited.to_psbt(), Err(VPackError::IncompleteData));
+}
+
+#[test]
+fn sweep_psbt_rejects_a_malformed_control_block() {
+    let tree = ark_tree(&key(0x01).1, &key(0x02).1);
+    let mut sweep =
+        build_exit_sweep(&tree, TxVariant::V3Anchored, &p2tr(0x77), 5).expect("exit sweep");
+    sweep.control_block.push(0x00);
+    assert_eq!(
+        sweep.to_psbt(),
+        Err(VPackError::InvalidPsbt("control block"))
+    );
+    sweep.control_block.truncate(32);
+    assert_eq!(
+        sweep.to_psbt(),
+        Err(VPackError::InvalidPsbt("control block"))
+    );
+}
+
+#[test]
+fn parse_rejects_malformed_psbts() {
+    let tree = ark_tree(&key(0x01).1, &key(0x02).1);
+    let sweep = build_exit_sweep(&tree, TxVariant::V3Anchored, &p2tr(0x77), 5).expect("sweep");
+    let bytes = sweep.to_psbt().expect("sweep psbt").serialize();
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[4] = 0x00;
+    assert_eq!(
+        Psbt::parse(&bad_magic),
+        Err(VPackError::InvalidPsbt("magic"))
+    );
+    assert_eq!(
+        Psbt::parse(&bytes[..bytes.len() - 1]),
+        Err(VPackError::IncompleteData)
+    );
+    let mut trailing = bytes.clone();
+    trailing.push(0x00);
+    assert_eq!(Psbt::parse(&trailing), Err(VPackError::TrailingData(1)));
+
+    // A version 0 PSBT: the unsigned transaction as a global field, no PSBT_GLOBAL_VERSION.
+    let mut v0 = b"psbt\xff".to_vec();
+    v0.extend_from_slice(&[0x01, 0x00, 0x01, 0x00, 0x00]);
+    assert_eq!(
+        Psbt::parse(&v0),
+        Err(VPackError::InvalidPsbt("PSBT_GLOBAL_UNSIGNED_TX"))
+    );
+
+    // PSBT_GLOBAL_TX_VERSION written twice.
+    let mut duplicate = b"psbt\xff".to_vec();
+    duplicate.extend_from_slice(&[0x01, 0x02, 0x04, 0x02, 0x00, 0x00, 0x00]);
+    duplicate.extend_from_slice(&bytes[5..]);
+    assert_eq!(
+        Psbt::parse(&duplicate),
+        Err(VPackError::InvalidPsbt("duplicate key"))
+    );
+
+    // Unknown pairs survive a round trip.
+    let mut psbt = sweep.to_psbt().expect("sweep psbt");
+    psbt.inputs[0]
+        .unknown
+        .push((vec![0xfc, 0x01], vec![0xde, 0xad]));
+    psbt.unknown.push((vec![0x01, 0xaa], vec![0x00]));
+    assert_eq!(Psbt::parse(&psbt.serialize()), Ok(psbt));
+}
@@ -1,18 +1,20 @@
 //! Tree-wide SIGHASH policy audit tests.
 //!
 //! Exercises `audit_sighash_policy` with a manually constructed 2-level tree:
-//! valid signatures, rejected sighash flags, amount-binding, and forgery detection.
+//! valid signatures, rejected sighash flags, amount-binding, and forgery detection, and
+//! `audit_sighash_policy_with` with configured allow-lists.
 
 #![cfg(feature = "schnorr-verify")]
 
 use vpack::consensus::taproot_sighash::{
-    audit_sighash_policy, extract_verify_key, sign_sighash_for_test, taproot_sighash,
-    verify_schnorr_bip340,
+    audit_sighash_policy, audit_sighash_policy_with, extract_verify_key, sign_sighash_for_test,
+    taproot_sighash, verify_schnorr_bip340,
 };
 use vpack::consensus::{TxInPreimage, TxOutPreimage};
 use vpack::error::VPackError;
 use vpack::header::TxVariant;
 use vpack::payload::tree::{GenesisItem, SiblingNode, VPackTree, VtxoLeaf};
+use vpack::{sighash_type_name, SighashPolicy};
 
 /// Builds a P2TR scriptPubKey: OP_1 OP_PUSHBYTES_32 <32-byte-key>.
 fn p2tr_script(pubkey: &[u8; 32]) -> Vec<u8> {
@@ -93,7 +95,8 @@ fn build_signed_tree(
         &anchor_script,
         &depth1_outputs,
         depth1_sighash_flag,
-    );
+    )
+    .expect("sighash");
     let (depth1_sig, _) = sign_sighash_for_test(&depth1_sighash);
 
     let depth1_preimage = tx_preimage(3, &[depth1_input], &depth1_outputs, 0);
@@ -123,7 +126,8 @@ fn build_signed_tree(
         &script,
         &depth2_outputs,
         depth2_sighash_flag,
-    );
+    )
+    .expect("sighash");
     let (depth2_sig, _) = sign_sighash_for_test(&depth2_sighash);
 
     let sibling1 = SiblingNode::Compact {
@@ -291,7 +295,8 @@ fn build_signed_tree_3_steps() -> (VPackTree, u64, Vec<u8>) {
         &anchor_script,
         &depth1_outputs,
         0x00,
-    );
+    )
+    .expect("sighash");
     let (depth1_sig, _) = sign_sighash_for_test(&depth1_sighash);
 
     let depth1_preimage = tx_preimage(3, &[depth1_input], &depth1_outputs, 0);
@@ -321,7 +326,8 @@ fn build_signed_tree_3_steps() -> (VPackTree, u64, Vec<u8>) {
         &script,
         &depth2_outputs,
         0x00,
-    );
+    )
+    .expect("sighash");
     let (depth2_sig, _) = sign_sighash_for_test(&depth2_sighash);
 
     let depth2_preimage = tx_preimage(3, &[depth2_input], &depth2_outputs, 0);
@@ -351,7 +357,8 @@ fn build_signed_tree_3_steps() -> (VPackTree, u64, Vec<u8>) {
         &script,
         &depth3_outputs,
         0x00,
-    );
+    )
+    .expect("sighash");
     let (depth3_sig, _) = sign_sighash_for_test(&depth3_sighash);
 
     let sibling1 = SiblingNode::Compact {
@@ -570,7 +577,8 @@ fn build_signed_tree_v3plain() -> (VPackTree, u64, Vec<u8>) {
         &anchor_script,
         &step0_outputs,
         0x00,
-    );
+    )
+    .expect("sighash");
     let (sig_0, _) = sign_sighash_for_test(&step0_sighash);
 
     let step0_txid =
@@ -608,7 +616,8 @@ fn build_signed_tree_v3plain() -> (VPackTree, u64, Vec<u8>) {
         &script,
         &step1_outputs,
         0x00,
-    );
+    )
+    .expect("sighash");
     let (sig_1, _) = sign_sighash_for_test(&step1_sighash);
 
     let sibling_a = SiblingNode::Compact {
@@ -698,3 +707,92 @@ fn test_audit_v3plain_corrupted_sig_step1() {
         "V3Plain: corrupted sig at step 1 must be detected as forgery"
     );
 }
+
+// ---------------------------------------------------------------------------
+// Configurable allow-list: foreign types recognized, signed over and reported
+// ---------------------------------------------------------------------------
+
+#[test]
+fn audit_with_any_policy_verifies_none_and_single_signatures() {
+    for (depth1, depth2) in [(0x02, 0x03), (0x82, 0x83)] {
+        let (tree, anchor_value, anchor_script) = build_signed_tree(depth1, depth2);
+        let strict =
+            audit_sighash_policy(&tree, TxVariant::V3Anchored, anchor_value, &anchor_script);
+        assert_eq!(strict, Err(VPackError::InvalidSighashFlag(depth1)));
+
+        let any = audit_sighash_policy_with(
+            &tree,
+            TxVariant::V3Anchored,
+            anchor_value,
+            &anchor_script,
+            &SighashPolicy::ANY,
+        );
+        assert_eq!(any, Ok(()), "0x{depth1:02x}, 0x{depth2:02x}");
+    }
+
+    // A signature over SINGLE does not pass as NONE.
+    let (mut tree, anchor_value, anchor_script) = build_signed_tree(0x00, 0x03);
+    tree.path[1].sighash_flag = 0x02;
+    let result = audit_sighash_policy_with(
+        &tree,
+        TxVariant::V3Anchored,
+        anchor_value,
+        &anchor_script,
+        &SighashPolicy::ANY,
+    );
+    assert_eq!(result, Err(VPackError::InvalidSignature));
+}
+
+#[test]
+fn audit_reports_the_first_disallowed_type() {
+    let (tree, anchor_value, anchor_script) = build_signed_tree(0x81, 0x83);
+    let policy = SighashPolicy::STRICT.deny(0x81).allow(0x83);
+    let result = audit_sighash_policy_with(
+        &tree,
+        TxVariant::V3Anchored,
+        anchor_value,
+        &anchor_script,
+        &policy,
+    );
+    assert_eq!(result, Err(VPackError::InvalidSighashFlag(0x81)));
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Invalid sighash flag: 0x81 (SIGHASH_ALL|ANYONECANPAY not allowed by policy)"
+    );
+
+    let policy = SighashPolicy::STRICT.allow(0x83);
+    assert_eq!(
+        audit_sighash_policy_with(
+            &tree,
+            TxVariant::V3Anchored,
+            anchor_value,
+            &anchor_script,
+            &policy,
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn non_bip341_flags_are_never_allowed() {
+    let policy = SighashPolicy::ANY.allow(0x04).allow(0x80);
+    assert_eq!(policy, SighashPolicy::ANY);
+    assert!(!policy.allows(0x04) && !policy.allows(0x80));
+    assert_eq!(sighash_type_name(0x82), Some("SIGHASH_NONE|ANYONECANPAY"));
+    assert_eq!(sighash_type_name(0x04), None);
+
+    let (mut tree, anchor_value, anchor_script) = build_signed_tree(0x00, 0x00);
+    tree.path[0].sighash_flag = 0x04;
+    let result = audit_sighash_policy_with(
+        &tree,
+        TxVariant::V3Anchored,
+        anchor_value,
+        &anchor_script,
+        &policy,
+    );
+    assert_eq!(result, Err(VPackError::InvalidSighashFlag(0x04)));
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Invalid sighash flag: 0x04 (not a BIP-341 sighash type)"
+    );
+}
@@ -0,0 +1,415 @@
+//! BIP-341 / BIP-342 sighash reference vectors, key path and script path.
+//!
+//! The vectors are the Bitcoin Core `feature_taproot` cases that rust-bitcoin's sighash suite
+//! carries: raw transaction, spent outputs, input index and expected sighash, for every hash
+//! type and with and without an annex.
+#![cfg(all(feature = "bitcoin", feature = "schnorr-verify"))]
+
+use bitcoin::consensus::deserialize;
+use bitcoin::hashes::Hash;
+use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+use bitcoin::taproot::{LeafVersion, TapLeafHash};
+use bitcoin::{ScriptBuf, Transaction, TxOut};
+use k256::schnorr::signature::hazmat::PrehashSigner;
+use k256::schnorr::SigningKey;
+use vpack::consensus::ark_labs::compile_forfeit_script;
+use vpack::consensus::taproot::tap_leaf_hash;
+use vpack::consensus::taproot_sighash::{
+    taproot_key_spend_sighash, taproot_script_spend_sighash, taproot_sighash,
+    taproot_signature_hash, NO_CODESEPARATOR,
+};
+use vpack::consensus::{eval_tapscript, SpendContext, TxInPreimage, TxOutPreimage};
+use vpack::error::VPackError;
+
+struct Vector {
+    tx: &'static str,
+    prevouts: &'static str,
+    input_index: usize,
+    hash_type: u8,
+    /// Hex annex, `0x50` prefix included.
+    annex: Option<&'static str>,
+    /// Hex tapscript for a script-path spend; `None` for a key-path spend.
+    leaf_script: Option<&'static str>,
+    sighash: &'static str,
+}
+
+const VECTORS: &[Vector] = &[
+    Vector {
+        tx: "020000000164eb050a5e3da0c2a65e4786f26d753b7bc69691fabccafb11f7acef36641f1846010000003101b2b404392a22000000000017a9147f2bde86fe78bf68a0544a4f290e12f0b7e0a08c87580200000000000017a91425d11723074ecfb96a0a83c3956bfaf362ae0c908758020000000000001600147e20f938993641de67bb0cdd71682aa34c4d29ad5802000000000000160014c64984dc8761acfa99418bd6bedc79b9287d652d72000000",
+        prevouts: "01365724000000000023542156b39dab4f8f3508e0432cfb41fab110170acaa2d4c42539cb90a4dc7c093bc500",
+        input_index: 0,
+        hash_type: 0x00,
+        annex: None,
+        leaf_script: None,
+        sighash: "33ca0ebfb4a945eeee9569fc0f5040221275f88690b7f8592ada88ce3bdf6703",
+    },
+    // Second input of two.
+    Vector {
+        tx: "0200000002fff49be59befe7566050737910f6ccdc5e749c7f8860ddc140386463d88c5ad0f3000000002cf68eb4a3d67f9d4c079249f7e4f27b8854815cb1ed13842d4fbf395f9e217fd605ee24090100000065235d9203f458520000000000160014b6d48333bb13b4c644e57c43a9a26df3a44b785e58020000000000001976a914eea9461a9e1e3f765d3af3e726162e0229fe3eb688ac58020000000000001976a9143a8869c9f2b5ea1d4ff3aeeb6a8fb2fffb1ad5fe88ac0ad7125c",
+        prevouts: "02591f220000000000225120f25ad35583ea31998d968871d7de1abd2a52f6fe4178b54ea158274806ff4ece48fb310000000000225120f25ad35583ea31998d968871d7de1abd2a52f6fe4178b54ea158274806ff4ece",
+        input_index: 1,
+        hash_type: 0x01,
+        annex: None,
+        leaf_script: None,
+        sighash: "626ab955d58c9a8a600a0c580549d06dc7da4e802eb2a531f62a588e430967a8",
+    },
+    Vector {
+        tx: "0200000001350005f65aa830ced2079df348e2d8c2bdb4f10e2dde6a161d8a07b40d1ad87dae000000001611d0d603d9dc0e000000000017a914459b6d7d6bbb4d8837b4bf7e9a4556f952da2f5c8758020000000000001976a9141dd70e1299ffc2d5b51f6f87de9dfe9398c33cbb88ac58020000000000001976a9141dd70e1299ffc2d5b51f6f87de9dfe9398c33cbb88aca71c1f4f",
+        prevouts: "01c4811000000000002251201bf9297d0a2968ae6693aadd0fa514717afefd218087a239afb7418e2d22e65c",
+        input_index: 0,
+        hash_type: 0x81,
+        annex: None,
+        leaf_script: None,
+        sighash: "dfa9437f9c9a1d1f9af271f79f2f5482f287cdb0d2e03fa92c8a9b216cc6061c",
+    },
+    Vector {
+        tx: "020000000185bed1a6da2bffbd60ec681a1bfb71c5111d6395b99b3f8b2bf90167111bcb18f5010000007c83ace802ded24a00000000001600142c4698f9f7a773866879755aa78c516fb332af8e5802000000000000160014d38639dfbac4259323b98a472405db0c461b31fa61073747",
+        prevouts: "0144c84d0000000000225120e3f2107989c88e67296ab2faca930efa2e3a5bd3ff0904835a11c9e807458621",
+        input_index: 0,
+        hash_type: 0x02,
+        annex: None,
+        leaf_script: None,
+        sighash: "3129de36a5d05fff97ffca31eb75fcccbbbc27b3147a7a36a9e4b45d8b625067",
+    },
+    Vector {
+        tx: "eb93dbb901028c8515589dac980b6e7f8e4088b77ed866ca0d6d210a7218b6fd0f6b22dd6d7300000000eb4740a9047efc0e0000000000160014913da2128d8fcf292b3691db0e187414aa1783825802000000000000160014913da2128d8fcf292b3691db0e187414aa178382580200000000000017a9143dd27f01c6f7ef9bb9159937b17f17065ed01a0c875802000000000000160014d7630e19df70ada9905ede1722b800c0005f246641000000",
+        prevouts: "013fed110000000000225120eb536ae8c33580290630fc495046e998086a64f8f33b93b07967d9029b265c55",
+        input_index: 0,
+        hash_type: 0x82,
+        annex: None,
+        leaf_script: None,
+        sighash: "2441e8b0e063a2083ee790f14f2045022f07258ddde5ee01de543c9e789d80ae",
+    },
+    Vector {
+        tx: "02000000017836b409a5fed32211407e44b971591f2032053f14701fb5b3a30c0ff382f2cc9c0100000061ac55f60288fb5600000000001976a9144ea02f6f182b082fb6ce47e36bbde390b6a41b5088ac58020000000000001976a9144ea02f6f182b082fb6ce47e36bbde390b6a41b5088ace4000000",
+        prevouts: "01efa558000000000022512007071ea3dc7e331b0687d0193d1e6d6ed10e645ef36f10ef8831d5e522ac9e80",
+        input_index: 0,
+        hash_type: 0x03,
+        annex: None,
+        leaf_script: None,
+        sighash: "30239345177cadd0e3ea413d49803580abb6cb27971b481b7788a78d35117a88",
+    },
+    Vector {
+        tx: "0100000001aa6deae89d5e0aaca58714fc76ef6f3c8284224888089232d4e663843ed3ab3eae010000008b6657a60450cb4c0000000000160014a3d42b5413ef0c0701c4702f3cd7d4df222c147058020000000000001976a91430b4ed8723a4ee8992aa2c8814cfe5c3ad0ab9d988ac5802000000000000160014365b1166a6ed0a5e8e9dff17a6d00bbb43454bc758020000000000001976a914bc98c51a84fe7fad5dc380eb8b39586eff47241688ac4f313247",
+        prevouts: "0107af4e00000000002251202c36d243dfc06cb56a248e62df27ecba7417307511a81ae61aa41c597a929c69",
+        input_index: 0,
+        hash_type: 0x83,
+        annex: None,
+        leaf_script: None,
+        sighash: "bf9c83f26c6dd16449e4921f813f551c4218e86f2ec906ca8611175b41b566df",
+    },
+    // Annex-bearing witness.
+    Vector {
+        tx: "0200000001df8123752e8f37d132c4e9f1ff7e4f9b986ade9211267e9ebd5fd22a5e718dec6d01000000ce4023b903cb7b23000000000017a914a18b36ea7a094db2f4940fc09edf154e86de7bd787580200000000000017a914afd0d512a2c5c2b40e25669e9cc460303c325b8b87580200000000000017a914a18b36ea7a094db2f4940fc09edf154e86de7bd787f6020000",
+        prevouts: "01ea49260000000000225120ab5e9800806bf18cb246edcf5fe63441208fe955a4b5a35bbff65f5db622a010",
+        input_index: 0,
+        hash_type: 0x83,
+        annex: Some("507b979802e62d397acb29f56743a791894b99372872fc5af06a4f6e8d242d0615cda53062bb20e6ec79756fe39183f0c128adfe85559a8fa042b042c018aa8010143799e44f0893c40e1e"),
+        leaf_script: None,
+        sighash: "3b003000add359a364a156e73e02846782a59d0d95ca8c4638aaad99f2ef915c",
+    },
+    // Script path.
+    Vector {
+        tx: "020000000189fc651483f9296b906455dd939813bf086b1bbe7c77635e157c8e14ae29062195010000004445b5c7044561320000000000160014331414dbdada7fb578f700f38fb69995fc9b5ab958020000000000001976a914268db0a8104cc6d8afd91233cc8b3d1ace8ac3ef88ac580200000000000017a914ec00dcb368d6a693e11986d265f659d2f59e8be2875802000000000000160014c715799a49a0bae3956df9c17cb4440a673ac0df6f010000",
+        prevouts: "011bec34000000000022512028055142ea437db73382e991861446040b61dd2185c4891d7daf6893d79f7182",
+        input_index: 0,
+        hash_type: 0x01,
+        annex: None,
+        leaf_script: Some("20cc4e1107aea1d170c5ff5b6817e1303010049724fb3caa7941792ea9d29b3e2bacab"),
+        sighash: "d66de5274a60400c7b08c86ba6b7f198f40660079edf53aca89d2a9501317f2e",
+    },
+    // Script path with an annex.
+    Vector {
+        tx: "020000000132fb72cb8fba496755f027a9743e2d698c831fdb8304e4d1a346ac92cbf51acba50100000026bdc7df044aad34000000000017a9144fa2554ed6174586854fa3bc01de58dcf33567d0875802000000000000160014950367e1e62cdf240b35b883fc2f5e39f0eb9ab95802000000000000160014950367e1e62cdf240b35b883fc2f5e39f0eb9ab958020000000000001600141b31217d48ccc8760dcc0710fade5866d628e733a02d5122",
+        prevouts: "011458360000000000225120a7baec3fb9f84614e3899fcc010c638f80f13539344120e1f4d8b68a9a011a13",
+        input_index: 0,
+        hash_type: 0x01,
+        annex: Some("50a6272b470e1460e3332ade7bb14b81671c564fb6245761bd5bd531394b28860e0b3808ab229fb51791fb6ae6fa82d915b2efb8f6df83ae1f5ab3db13e30928875e2a22b749d89358de481f19286cd4caa792ce27f9559082d227a731c5486882cc707f83da361c51b7aadd9a0cf68fe7480c410fa137b454482d9a1ebf0f96d760b4d61426fc109c6e8e99a508372c45caa7b000a41f8251305da3f206c1849985ba03f3d9592832b4053afbd23ab25d0465df0bc25a36c223aacf8e04ec736a418c72dc319e4da3e972e349713ca600965e7c665f2090d5a70e241ac164115a1f5639f28b1773327715ca307ace64a2de7f0e3df70a2ffee3857689f909c0dad46d8a20fa373a4cc6eed6d4c9806bf146f0d76baae1"),
+        leaf_script: Some("7520ab9160dd8299dc1367659be3e8f66781fe440d52940c7f8d314a89b9f2698d406ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6eadac"),
+        sighash: "a0042aa434f9a75904b64043f2a283f8b4c143c7f4f7f49a6cbe5b9f745f4c15",
+    },
+];
+
+/// `(value, scriptPubKey)` pairs backing [`TxOutPreimage`]s.
+type Outputs = Vec<(u64, Vec<u8>)>;
+
+/// Inputs, spent outputs and outputs of `tx` in preimage form.
+fn preimages(tx: &Transaction, prevouts: &[TxOut]) -> (Vec<TxInPreimage>, Outputs, Outputs) {
+    let inputs = tx
+        .input
+        .iter()
+        .map(|i| TxInPreimage {
+            prev_out_txid: i.previous_output.txid.to_byte_array(),
+            prev_out_vout: i.previous_output.vout,
+            sequence: i.sequence.0,
+        })
+        .collect();
+    let owned = |outs: &[TxOut]| {
+        outs.iter()
+            .map(|o| (o.value.to_sat(), o.script_pubkey.to_bytes()))
+            .collect()
+    };
+    (inputs, owned(prevouts), owned(&tx.output))
+}
+
+fn borrowed(outs: &[(u64, Vec<u8>)]) -> Vec<TxOutPreimage<'_>> {
+    outs.iter()
+        .map(|(value, script)| TxOutPreimage {
+            value: *value,
+            script_pubkey: script,
+        })
+        .collect()
+}
+
+#[test]
+fn reference_vectors() {
+    for (n, v) in VECTORS.iter().enumerate() {
+        let tx: Transaction = deserialize(&hex::decode(v.tx).unwrap()).expect("tx");
+        let prevouts: Vec<TxOut> =
+            deserialize(&hex::decode(v.prevouts).unwrap()).expect("prevouts");
+        let (inputs, spent, outputs) = preimages(&tx, &prevouts);
+        let (spent, outputs) = (borrowed(&spent), borrowed(&outputs));
+        let version = tx.version.0 as u32;
+        let locktime = tx.lock_time.to_consensus_u32();
+
+        let annex = v.annex.map(|a| hex::decode(a).unwrap());
+        let leaf_hash = v
+            .leaf_script
+            .map(|l| tap_leaf_hash(&hex::decode(l).unwrap()));
+        let leaf = leaf_hash.as_ref().map(|h| (h, NO_CODESEPARATOR));
+        let sighash = taproot_signature_hash(
+            version,
+            locktime,
+            &inputs,
+            &spent,
+            &outputs,
+            v.input_index,
+            v.hash_type,
+            annex.as_deref(),
+            leaf,
+        )
+        .expect("valid vector");
+        assert_eq!(hex::encode(sighash), v.sighash, "vector {n}");
+
+        // The annex-free entry points agree where they apply.
+        let plain = match (&annex, leaf_hash) {
+            (Some(_), _) => continue,
+            (None, None) => taproot_key_spend_sighash(
+                version,
+                locktime,
+                &inputs,
+                &spent,
+                &outputs,
+                v.input_index,
+                v.hash_type,
+            ),
+            (None, Some(leaf_hash)) => taproot_script_spend_sighash(
+                version,
+                locktime,
+                &inputs,
+                &spent,
+                &outputs,
+                v.input_index,
+                &leaf_hash,
+                NO_CODESEPARATOR,
+                v.hash_type,
+            ),
+        };
+        assert_eq!(plain, Ok(sighash), "vector {n}");
+    }
+}
+
+#[test]
+fn invalid_hash_type_single_and_annex_are_errors() {
+    let inputs = [TxInPreimage {
+        prev_out_txid: [0xAA; 32],
+        prev_out_vout: 0,
+        sequence: 0xFFFF_FFFF,
+    }];
+    let script = [0x51, 0x20, 0x44];
+    let spent = [TxOutPreimage {
+        value: 1_000,
+        script_pubkey: &script,
+    }];
+    let sighash = |outputs: &[TxOutPreimage<'_>], hash_type, annex| {
+        taproot_signature_hash(3, 0, &inputs, &spent, outputs, 0, hash_type, annex, None)
+    };
+    assert_eq!(
+        sighash(&spent, 0x04, None),
+        Err(VPackError::InvalidSighashFlag(0x04))
+    );
+    assert_eq!(
+        sighash(&spent, 0x80, None),
+        Err(VPackError::InvalidSighashFlag(0x80))
+    );
+    assert_eq!(
+        sighash(&[], 0x83, None),
+        Err(VPackError::SighashSingleWithoutOutput(0))
+    );
+    assert!(sighash(&[], 0x02, None).is_ok());
+    assert_eq!(
+        sighash(&spent, 0x01, Some(&[0x51, 0x00])),
+        Err(VPackError::InvalidAnnex)
+    );
+    assert_eq!(
+        sighash(&spent, 0x01, Some(&[])),
+        Err(VPackError::InvalidAnnex)
+    );
+
+    // The annex-free entry points report the same errors instead of panicking.
+    assert_eq!(
+        taproot_sighash(3, 0, &inputs[0], 1_000, &script, &spent, 0x04),
+        Err(VPackError::InvalidSighashFlag(0x04))
+    );
+    assert_eq!(
+        taproot_key_spend_sighash(3, 0, &inputs, &spent, &[], 0, 0x03),
+        Err(VPackError::SighashSingleWithoutOutput(0))
+    );
+    assert_eq!(
+        taproot_script_spend_sighash(
+            3,
+            0,
+            &inputs,
+            &spent,
+            &spent,
+            0,
+            &[0x11; 32],
+            NO_CODESEPARATOR,
+            0x84
+        ),
+        Err(VPackError::InvalidSighashFlag(0x84))
+    );
+}
+
+/// `codesep_pos` is committed to; no reference vector executes `OP_CODESEPARATOR`, so the
+/// positions are checked against rust-bitcoin.
+#[test]
+fn codeseparator_position_matches_rust_bitcoin() {
+    let v = VECTORS
+        .iter()
+        .find(|v| v.leaf_script.is_some() && v.annex.is_none())
+        .expect("script-path vector");
+    let tx: Transaction = deserialize(&hex::decode(v.tx).unwrap()).expect("tx");
+    let prevouts: Vec<TxOut> = deserialize(&hex::decode(v.prevouts).unwrap()).expect("prevouts");
+    let script = hex::decode(v.leaf_script.unwrap()).unwrap();
+    let (inputs, spent, outputs) = preimages(&tx, &prevouts);
+    let (spent, outputs) = (borrowed(&spent), borrowed(&outputs));
+    let leaf_hash = TapLeafHash::from_script(
+        &ScriptBuf::from_bytes(script.clone()),
+        LeafVersion::TapScript,
+    );
+    assert_eq!(leaf_hash.to_byte_array(), tap_leaf_hash(&script));
+
+    let mut cache = SighashCache::new(&tx);
+    for (codesep_pos, hash_type) in [(0, TapSighashType::Default), (2, TapSighashType::All)] {
+        let ours = taproot_script_spend_sighash(
+            tx.version.0 as u32,
+            tx.lock_time.to_consensus_u32(),
+            &inputs,
+            &spent,
+            &outputs,
+            0,
+            &tap_leaf_hash(&script),
+            codesep_pos,
+            hash_type as u8,
+        )
+        .expect("sighash");
+        let expected = cache
+            .taproot_signature_hash(
+                0,
+                &Prevouts::All(&prevouts),
+                None,
+                Some((leaf_hash, codesep_pos)),
+                hash_type,
+            )
+            .expect("sighash");
+        assert_eq!(ours, expected.to_byte_array());
+    }
+}
+
+/// A forfeit leaf spent as the second input next to a connector: both signatures commit to the
+/// same multi-input script-path sighash, and the leaf evaluates with them.
+#[test]
+fn forfeit_leaf_spend_signs_and_evaluates() {
+    let asp = SigningKey::from_bytes(&[0x01; 32]).expect("key");
+    let user = SigningKey::from_bytes(&[0x02; 32]).expect("key");
+    let asp_pk: [u8; 32] = asp.verifying_key().to_bytes().into();
+    let user_pk: [u8; 32] = user.verifying_key().to_bytes().into();
+    let forfeit = compile_forfeit_script(&asp_pk, &user_pk);
+
+    let inputs = [
+        TxInPreimage {
+            prev_out_txid: [0xC0; 32],
+            prev_out_vout: 1,
+            sequence: 0xFFFF_FFFF,
+        },
+        TxInPreimage {
+            prev_out_txid: [0xAA; 32],
+            prev_out_vout: 0,
+            sequence: 0xFFFF_FFFF,
+        },
+    ];
+    let mut connector = vec![0x51, 0x20];
+    connector.extend_from_slice(&[0x33; 32]);
+    let mut vtxo = vec![0x51, 0x20];
+    vtxo.extend_from_slice(&[0x44; 32]);
+    let spent = [
+        TxOutPreimage {
+            value: 330,
+            script_pubkey: &connector,
+        },
+        TxOutPreimage {
+            value: 100_000,
+            script_pubkey: &vtxo,
+        },
+    ];
+    let mut asp_script = vec![0x51, 0x20];
+    asp_script.extend_from_slice(&asp_pk);
+    let outputs = [TxOutPreimage {
+        value: 100_330,
+        script_pubkey: &asp_script,
+    }];
+
+    let sighash = taproot_script_spend_sighash(
+        3,
+        0,
+        &inputs,
+        &spent,
+        &outputs,
+        1,
+        &tap_leaf_hash(&forfeit),
+        NO_CODESEPARATOR,
+        0x00,
+    )
+    .expect("sighash");
+    let key_spend =
+        taproot_key_spend_sighash(3, 0, &inputs, &spent, &outputs, 1, 0x00).expect("sighash");
+    assert_ne!(sighash, key_spend);
+
+    let user_sig = user.sign_prehash(&sighash).expect("sign").to_bytes();
+    let asp_sig = asp.sign_prehash(&sighash).expect("sign").to_bytes();
+    let ctx = SpendContext {
+        sighash,
+        tx_version: 3,
+        locktime: 0,
+        sequence: 0xFFFF_FFFF,
+    };
+    let witness: [&[u8]; 2] = [&user_sig, &asp_sig];
+    assert_eq!(eval_tapscript(&forfeit, &witness, &ctx), Ok(()));
+    // Signed for the first input instead: rejected.
+    let other = taproot_script_spend_sighash(
+        3,
+        0,
+        &inputs,
+        &spent,
+        &outputs,
+        0,
+        &tap_leaf_hash(&forfeit),
+        NO_CODESEPARATOR,
+        0x00,
+    )
+    .expect("sighash");
+    let stale = user.sign_prehash(&other).expect("sign").to_bytes();
+    let witness: [&[u8]; 2] = [&stale, &asp_sig];
+    assert!(eval_tapscript(&forfeit, &witness, &ctx).is_err());
+}
@@ -0,0 +1,184 @@
+//! Tapscript dry runs: the Ark Labs and Bark leaf templates evaluated with real witnesses.
+
+#![cfg(feature = "schnorr-verify")]
+
+use k256::schnorr::signature::hazmat::PrehashSigner;
+use k256::schnorr::SigningKey;
+use vpack::consensus::ark_labs::{compile_exit_script, compile_forfeit_script};
+use vpack::consensus::second_tech::{compile_bark_expiry_script, compile_bark_unlock_script};
+use vpack::consensus::{eval_tapscript, SpendContext};
+use vpack::error::{ScriptErrorReason, VPackError};
+use vpack::types::hashes::{hash160, Hash};
+
+const SIGHASH: [u8; 32] = [0x5a; 32];
+
+fn key(seed: u8) -> (SigningKey, [u8; 32]) {
+    let sk = SigningKey::from_bytes(&[seed; 32]).expect("valid key");
+    let pk = sk.verifying_key().to_bytes().into();
+    (sk, pk)
+}
+
+fn sign(sk: &SigningKey) -> Vec<u8> {
+    sk.sign_prehash(&SIGHASH).expect("sign").to_bytes().to_vec()
+}
+
+fn ctx(sequence: u32, locktime: u32) -> SpendContext {
+    SpendContext {
+        sighash: SIGHASH,
+        tx_version: 3,
+        locktime,
+        sequence,
+    }
+}
+
+fn failure(offset: u32, reason: ScriptErrorReason) -> Result<(), VPackError> {
+    Err(VPackError::ScriptFailed { offset, reason })
+}
+
+#[test]
+fn ark_forfeit_needs_both_signatures() {
+    let (asp, asp_pk) = key(0x01);
+    let (user, user_pk) = key(0x02);
+    let script = compile_forfeit_script(&asp_pk, &user_pk);
+    let (user_sig, asp_sig) = (sign(&user), sign(&asp));
+
+    assert_eq!(
+        eval_tapscript(&script, &[&user_sig, &asp_sig], &ctx(0xFFFF_FFFF, 0)),
+        Ok(())
+    );
+    // No ASP signature: OP_CHECKSIGVERIFY (after OP_1 OP_VERIFY <asp_pk>) fails.
+    assert_eq!(
+        eval_tapscript(&script, &[&user_sig, &[]], &ctx(0xFFFF_FFFF, 0)),
+        failure(35, ScriptErrorReason::Verify)
+    );
+    // Signatures swapped: the ASP key rejects the user's signature.
+    assert_eq!(
+        eval_tapscript(&script, &[&asp_sig, &user_sig], &ctx(0xFFFF_FFFF, 0)),
+        failure(35, ScriptErrorReason::Signature)
+    );
+    // No user signature: OP_CHECKSIG leaves false.
+    assert_eq!(
+        eval_tapscript(&script, &[&[], &asp_sig], &ctx(0xFFFF_FFFF, 0)),
+        failure(script.len() as u32, ScriptErrorReason::EvalFalse)
+    );
+}
+
+#[test]
+fn ark_exit_enforces_csv() {
+    let (asp, asp_pk) = key(0x01);
+    let (user, user_pk) = key(0x02);
+    // 144 blocks: 0x90 needs a sign byte.
+    let script = compile_exit_script(&asp_pk, &user_pk, &[0x90, 0x00]);
+    let witness: [&[u8]; 2] = [&sign(&user), &sign(&asp)];
+    let csv_offset = 2 + 3;
+
+    assert_eq!(eval_tapscript(&script, &witness, &ctx(144, 0)), Ok(()));
+    assert_eq!(
+        eval_tapscript(&script, &witness, &ctx(143, 0)),
+        failure(csv_offset, ScriptErrorReason::UnsatisfiedLocktime)
+    );
+    // BIP-68 disable bit on the input.
+    assert_eq!(
+        eval_tapscript(&script, &witness, &ctx(144 | 1 << 31, 0)),
+        failure(csv_offset, ScriptErrorReason::UnsatisfiedLocktime)
+    );
+    // Version 1 transactions carry no relative locktime.
+    let v1 = SpendContext {
+        tx_version: 1,
+        ..ctx(144, 0)
+    };
+    assert_eq!(
+        eval_tapscript(&script, &witness, &v1),
+        failure(csv_offset, ScriptErrorReason::UnsatisfiedLocktime)
+    );
+}
+
+#[test]
+fn bark_expiry_enforces_cltv() {
+    let (server, server_pk) = key(0x03);
+    let script = compile_bark_expiry_script(800_000, &server_pk);
+    let sig = sign(&server);
+
+    assert_eq!(
+        eval_tapscript(&script, &[&sig], &ctx(0xFFFF_FFFE, 800_000)),
+        Ok(())
+    );
+    assert_eq!(
+        eval_tapscript(&script, &[&sig], &ctx(0xFFFF_FFFE, 799_999)),
+        failure(4, ScriptErrorReason::UnsatisfiedLocktime)
+    );
+    // A final input disables nLockTime.
+    assert_eq!(
+        eval_tapscript(&script, &[&sig], &ctx(0xFFFF_FFFF, 800_000)),
+        failure(4, ScriptErrorReason::UnsatisfiedLocktime)
+    );
+    // Timestamp locktime against a height operand.
+    assert_eq!(
+        eval_tapscript(&script, &[&sig], &ctx(0xFFFF_FFFE, 1_700_000_000)),
+        failure(4, ScriptErrorReason::UnsatisfiedLocktime)
+    );
+}
+
+#[test]
+fn bark_unlock_needs_the_preimage() {
+    let (musig, musig_pk) = key(0x04);
+    let preimage = [0x77u8; 32];
+    let hash = hash160::Hash::hash(&preimage).to_byte_array();
+    let script = compile_bark_unlock_script(&hash, &musig_pk);
+    let sig = sign(&musig);
+
+    assert_eq!(
+        eval_tapscript(&script, &[&sig, &preimage], &ctx(0xFFFF_FFFF, 0)),
+        Ok(())
+    );
+    assert_eq!(
+        eval_tapscript(&script, &[&sig, &[0x78; 32]], &ctx(0xFFFF_FFFF, 0)),
+        failure(22, ScriptErrorReason::Verify)
+    );
+    assert_eq!(
+        eval_tapscript(&script, &[], &ctx(0xFFFF_FFFF, 0)),
+        failure(0, ScriptErrorReason::StackUnderflow)
+    );
+}
+
+#[test]
+fn checksigadd_threshold() {
+    let (a, a_pk) = key(0x05);
+    let (b, b_pk) = key(0x06);
+    // <a> OP_CHECKSIG <b> OP_CHECKSIGADD OP_2 OP_NUMEQUAL
+    let mut script = vec![0x20];
+    script.extend_from_slice(&a_pk);
+    script.push(0xac);
+    script.push(0x20);
+    script.extend_from_slice(&b_pk);
+    script.extend_from_slice(&[0xba, 0x52, 0x9c]);
+
+    let (a_sig, b_sig) = (sign(&a), sign(&b));
+    assert_eq!(
+        eval_tapscript(&script, &[&b_sig, &a_sig], &ctx(0xFFFF_FFFF, 0)),
+        Ok(())
+    );
+    assert_eq!(
+        eval_tapscript(&script, &[&[], &a_sig], &ctx(0xFFFF_FFFF, 0)),
+        failure(script.len() as u32, ScriptErrorReason::EvalFalse)
+    );
+}
+
+#[test]
+fn unsupported_and_malformed_scripts_fail() {
+    // OP_SUCCESS80 would make consensus accept anything; the dry run refuses it.
+    assert_eq!(
+        eval_tapscript(&[0x50], &[], &ctx(0, 0)),
+        failure(0, ScriptErrorReason::UnsupportedOpcode(0x50))
+    );
+    // Push of 32 bytes with only 3 available.
+    assert_eq!(
+        eval_tapscript(&[0x51, 0x20, 1, 2, 3], &[], &ctx(0, 0)),
+        failure(1, ScriptErrorReason::MalformedPush)
+    );
+    // Two elements left: not a clean stack.
+    assert_eq!(
+        eval_tapscript(&[0x51, 0x51], &[], &ctx(0, 0)),
+        failure(2, ScriptErrorReason::CleanStack)
+    );
+}
@@ -1,6 +1,7 @@
 //! `VpackState` JSON envelope: schema version, strict fields, flat `ingredients` by `implementation`.
 
 use vpack::export::create_vpack_ark_labs;
+use vpack::Network;
 use vpack::{VpackImplementation, VpackIngredients, VpackState};
 
 const VALID_ARK_LABS_ENVELOPE: &str = r#"{
@@ -33,7 +34,8 @@ fn vpack_state_deserializes_schema_1_0_ark_labs() {
     let VpackIngredients::ArkLabs(ingredients) = state.ingredients else {
         panic!("expected ArkLabs ingredients");
     };
-    let bytes = create_vpack_ark_labs(ingredients).expect("pack from deserialized ingredients");
+    let bytes = create_vpack_ark_labs(ingredients, Network::Mainnet)
+        .expect("pack from deserialized ingredients");
     assert!(!bytes.is_empty());
 }
 
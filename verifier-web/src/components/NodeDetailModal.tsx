@@ -177,8 +177,8 @@ function NodeDetailModal({ node, variant, onClose, network = 'bitcoin', blockHei
   const exitWeightLabel = persona === 'anchor' ? 'Cost to Open' : 'Cost to Exit this level';
   const exitWeightText =
     persona === 'anchor'
-      ? `~${node.exit_weight_vb} vB. This is the on-chain transaction that opens the vault.`
-      : `~${node.exit_weight_vb} vB. Each level requires one Bitcoin transaction to exit.`;
+      ? 'Already confirmed. This is the on-chain transaction that opens the vault.'
+      : `${node.exit_weight_vb} vB. Each level requires one Bitcoin transaction to exit.`;
 
   const personaConfig = {
     anchor: {
@@ -67,7 +67,7 @@ function SovereigntyMap({ pathDetails, anchorTxid, finalVtxoId, variant, sibling
                   <div className="flex items-center gap-4 text-xs text-gray-600 dark:text-gray-400">
                     <span>{anchorNode.amount.toLocaleString()} sats</span>
                     <span className="px-2 py-1 bg-orange-100 dark:bg-orange-900/30 text-orange-700 dark:text-orange-300 rounded">
-                      Exit: ~{anchorNode.exit_weight_vb} vB
+                      On-chain
                     </span>
                   </div>
                 </div>
@@ -119,7 +119,7 @@ function SovereigntyMap({ pathDetails, anchorTxid, finalVtxoId, variant, sibling
                     <div className="flex items-center gap-4 text-xs text-gray-600 dark:text-gray-400">
                       <span>{node.amount.toLocaleString()} sats</span>
                       <span className="px-2 py-1 bg-orange-100 dark:bg-orange-900/30 text-orange-700 dark:text-orange-300 rounded">
-                        Exit: ~{node.exit_weight_vb} vB
+                        Exit: {node.exit_weight_vb} vB
                       </span>
                     </div>
                   </div>
@@ -163,7 +163,7 @@ function SovereigntyMap({ pathDetails, anchorTxid, finalVtxoId, variant, sibling
                   <div className="flex items-center gap-4 text-xs text-gray-600 dark:text-gray-400">
                     <span>{leafNode.amount.toLocaleString()} sats</span>
                     <span className="px-2 py-1 bg-orange-100 dark:bg-orange-900/30 text-orange-700 dark:text-orange-300 rounded">
-                      Exit: ~{leafNode.exit_weight_vb} vB
+                      Exit: {leafNode.exit_weight_vb} vB
                     </span>
                   </div>
                 </div>
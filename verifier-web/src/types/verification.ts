@@ -141,7 +141,8 @@ export interface PathDetail {
   vout: number;
   has_signature: boolean;
   has_fee_anchor: boolean;
-  exit_weight_vb: number; // Estimated vbytes for exit transaction
+  /** Virtual size of the signed exit transaction. 0 for anchor (already on chain) and a leaf without its own tx. */
+  exit_weight_vb: number;
   /** Relative timelock in blocks (user must wait before exit). Leaf only; 0 for anchor/branches. */
   exit_delta?: number;
   /** CamelCase alias from some serializers; prefer exit_delta (WASM snake_case). */
@@ -154,6 +155,10 @@ export interface PathDetail {
   sequence?: number;
   /** Number of sibling outputs at this level (excluding fee anchor). Branch scaling factor = sibling_count + 1. */
   sibling_count?: number;
+  /** Value of the spent output (from the verification report). Null for anchor and a leaf without its own tx. */
+  input_value?: number | null;
+  /** Sum of the transaction's outputs (from the verification report). Null where input_value is null. */
+  output_sum?: number | null;
 }
 
 export interface VerifyResult {
@@ -163,4 +168,6 @@ export interface VerifyResult {
   path_details?: PathDetail[];
   /** Fully signed transaction hex strings (lowercase). One per path step plus leaf. */
   signed_txs?: string[];
+  /** Virtual size of all signed exit transactions together (sum of the path rows). */
+  exit_weight_vb?: number;
 }
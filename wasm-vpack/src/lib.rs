@@ -5,16 +5,19 @@ use core::str::FromStr;
 use serde::Serialize;
 use wasm_bindgen::prelude::*;
 
-use vpack::consensus::{tx_preimage, TxInPreimage, TxOutPreimage};
-use vpack::header::{Header, HEADER_SIZE, MAGIC_BYTES};
+use vpack::consensus::chain::reconstruct_chain;
+use vpack::exit_cost::{exit_tx_weight, vbytes};
+use vpack::header::{BuiltinVariant, Header, HEADER_SIZE, MAGIC_BYTES};
 use vpack::payload::reader::BoundedReader;
-use vpack::payload::tree::{GenesisItem, SiblingNode};
+use vpack::payload::tree::{SiblingNode, VPackTreeRef};
 use vpack::payload::validate_invariants;
-use vpack::types::hashes::{sha256d, Hash};
-use vpack::types::{OutPoint, Txid};
+use vpack::report::{report_tree, Check};
+use vpack::types::hashes::Hash;
+use vpack::types::Txid;
 use vpack::{
-    create_vpack_from_tree, verify, ArkLabsAdapter, ArkLabsV3, ConsensusEngine, LogicAdapter,
-    SecondTechAdapter, SecondTechV3, TxVariant, VPackTree, VtxoId,
+    create_vpack_from_tree, verify, ArkLabsAdapter, ArkLabsV3, ConsensusEngine, EngineRegistry,
+    LogicAdapter, Network, SecondTechAdapter, SecondTechV3, TxVariant, VPackTree,
+    VerificationReport, VtxoId,
 };
 
 /// Set the panic hook so Rust panics show up as readable errors in the browser console.
@@ -32,6 +35,8 @@ struct PathDetail {
     vout: u32,
     has_signature: bool,
     has_fee_anchor: bool,
+    /// Virtual size of the signed exit transaction. 0 for the anchor (already on chain) and a
+    /// leaf without its own tx.
     exit_weight_vb: u32,
     /// Relative timelock in blocks (user must wait before exit). Leaf only; 0 for anchor/branches.
     exit_delta: u16,
@@ -43,6 +48,10 @@ struct PathDetail {
     sequence: u32,
     /// Number of sibling outputs at this level (excluding fee anchor). Branch scaling factor = sibling_count + 1.
     sibling_count: u32,
+    /// Value of the spent output (from the report). None for anchor and a leaf without its own tx.
+    input_value: Option<u64>,
+    /// Sum of the transaction's outputs (from the report). None where input_value is None.
+    output_sum: Option<u64>,
 }
 
 #[derive(Serialize)]
@@ -53,6 +62,10 @@ struct WasmVerifyResult {
     path_details: Vec<PathDetail>,
     /// Fully signed transaction hex strings (lowercase). One per path step plus leaf.
     signed_txs: Vec<String>,
+    /// Virtual size of all signed exit transactions together (sum of the path rows).
+    exit_weight_vb: u32,
+    /// Every verification check and its outcome (see `vpack::report`).
+    report: VerificationReport,
 }
 
 /// Parses anchor_value from JSON. Accepts string (decimal) or u64 for small values.
@@ -71,16 +84,18 @@ fn parse_anchor_value(value: &serde_json::Value) -> Result<u64, JsValue> {
     ))
 }
 
-/// Estimates Bitcoin transaction size in vbytes for a V3/TRUC transaction.
-/// Base: ~10 vB, Input: ~57 vB (Taproot with witness), Output: ~43 vB each.
-fn estimate_exit_weight_vb(num_outputs: usize) -> u32 {
-    // Base transaction overhead: version (4) + vin count (1) + vout count (1) + locktime (4) ≈ 10 vB
-    let base = 10u32;
-    // 1 Taproot input: prevout (36) + sequence (4) + witness (17 vB) ≈ 57 vB
-    let input = 57u32;
-    // Each output: value (8) + scriptPubKey with compact size ≈ 43 vB
-    let outputs = (num_outputs as u32) * 43u32;
-    base + input + outputs
+/// Virtual size of a signed exit transaction (see `vpack::exit_cost::exit_tx_weight`).
+fn exit_weight_vb(raw: &[u8]) -> Result<u32, JsValue> {
+    let weight = exit_tx_weight(raw).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(vbytes(weight) as u32)
+}
+
+/// Signed transaction `step` of the exit and its virtual size.
+fn signed_exit_tx(signed_txs: &[Vec<u8>], step: usize) -> Result<(&[u8], u32), JsValue> {
+    let raw = signed_txs
+        .get(step)
+        .ok_or_else(|| JsValue::from_str("path step has no signed transaction"))?;
+    Ok((raw, exit_weight_vb(raw)?))
 }
 
 /// Converts a Txid to its display string (reversed byte order for human readability).
@@ -116,20 +131,62 @@ fn tree_output_sum(tree: &VPackTree) -> u64 {
     child.saturating_add(siblings_sum)
 }
 
+/// Display txid of transaction `step`, as recorded in the verification report.
+fn report_txid(report: &VerificationReport, step: u32) -> Result<String, JsValue> {
+    let txid = report
+        .txid(step)
+        .ok_or_else(|| JsValue::from_str("verification report has no txid for path step"))?;
+    Ok(txid_to_string(&Txid::from_byte_array(txid)))
+}
+
+/// Spent value and output sum of transaction `step`, from its conservation entry.
+fn report_conservation(report: &VerificationReport, step: u32) -> (Option<u64>, Option<u64>) {
+    report
+        .step(step)
+        .find(|e| e.check == Check::Conservation)
+        .map_or((None, None), |e| (e.input_value, e.output_sum))
+}
+
+/// Whether `siblings` include the fee anchor, and how many other siblings there are.
+fn sibling_stats(
+    siblings: &[SiblingNode],
+    fee_anchor_script: &[u8],
+) -> Result<(bool, u32), JsValue> {
+    let mut has_fee_anchor = false;
+    let mut sibling_count: u32 = 0;
+    for sibling in siblings {
+        match sibling {
+            SiblingNode::Compact { script, .. } if script == fee_anchor_script => {
+                has_fee_anchor = true
+            }
+            SiblingNode::Compact { .. } => sibling_count += 1,
+            SiblingNode::Full(_) => {
+                return Err(JsValue::from_str("Full sibling nodes not supported"))
+            }
+        }
+    }
+    Ok((has_fee_anchor, sibling_count))
+}
+
 /// Extracts path details from a VPackTree (works for both ArkLabs and SecondTech variants).
 /// Returns a vector of PathDetail structs representing the sovereignty path.
 /// signed_txs: hex-encoded signed transactions; index i maps to path[i], index path.len() maps to leaf.
+/// Txids, input values and output sums come from the verification report, preimages from the
+/// chain walk the report runs. When the engine builds no separate leaf transaction, the leaf is an
+/// output of the last path transaction: its row carries that txid and no preimage of its own.
 fn extract_path_details(
     tree: &VPackTree,
     anchor_value: u64,
     variant: TxVariant,
     signed_txs: &[Vec<u8>],
+    report: &VerificationReport,
 ) -> Result<Vec<PathDetail>, JsValue> {
-    let mut path_details = Vec::new();
+    let tree_ref = VPackTreeRef::from(tree);
+    let walk = reconstruct_chain(&tree_ref, variant);
+    let mut path_details = Vec::with_capacity(tree.path.len() + 2);
 
     // Add anchor node (L1 transaction)
     let anchor_txid = txid_to_string(&tree.anchor.txid);
-    let anchor_outputs = 1; // Anchor typically has 1 output
     path_details.push(PathDetail {
         txid: anchor_txid,
         amount: anchor_value,
@@ -138,193 +195,103 @@ fn extract_path_details(
         vout: tree.anchor.vout,
         has_signature: false,
         has_fee_anchor: false,
-        exit_weight_vb: estimate_exit_weight_vb(anchor_outputs),
+        exit_weight_vb: 0,
         exit_delta: 0,
         tx_preimage_hex: String::new(), // L1 tx; no virtual preimage
         signed_tx_hex: String::new(),   // L1 tx; no signed virtual tx
         sequence: 0,
         sibling_count: 0,
+        input_value: None,
+        output_sum: None,
     });
 
-    let mut signed_idx: usize = 0;
-
-    // Traverse path (similar to consensus engine compute_vtxo_id)
-    let mut current_prevout = tree.anchor;
-    let mut input_amount: Option<u64> = Some(anchor_value);
-
     for (idx, genesis_item) in tree.path.iter().enumerate() {
-        let mut outputs = Vec::new();
-
-        // Add child output if present
-        if !genesis_item.child_script_pubkey.is_empty() {
-            outputs.push(TxOutPreimage {
-                value: genesis_item.child_amount,
-                script_pubkey: genesis_item.child_script_pubkey.as_slice(),
-            });
-        }
-
-        // Add sibling outputs
-        let mut has_fee_anchor = false;
-        let mut sibling_count: u32 = 0;
-        for sibling in &genesis_item.siblings {
-            match sibling {
-                SiblingNode::Compact { value, script, .. } => {
-                    outputs.push(TxOutPreimage {
-                        value: *value,
-                        script_pubkey: script.as_slice(),
-                    });
-                    if script == &tree.fee_anchor_script {
-                        has_fee_anchor = true;
-                    } else {
-                        sibling_count += 1;
-                    }
-                }
-                SiblingNode::Full(_) => {
-                    return Err(JsValue::from_str("Full sibling nodes not supported"))
-                }
-            }
-        }
-
-        // Build input
-        let input = TxInPreimage {
-            prev_out_txid: current_prevout.txid.to_byte_array(),
-            prev_out_vout: current_prevout.vout,
-            sequence: genesis_item.sequence,
-        };
-
-        // Compute transaction ID
-        let preimage_bytes = tx_preimage(3, &[input], &outputs, 0);
-        let hash = sha256d::Hash::hash(&preimage_bytes);
-        let txid_bytes = hash.to_byte_array();
-        let txid = Txid::from_byte_array(txid_bytes);
-        let txid_str = txid_to_string(&txid);
-
-        // Get amount (child amount or first output value)
-        let amount = genesis_item.child_amount;
-
-        // Calculate exit weight
-        let exit_weight = estimate_exit_weight_vb(outputs.len());
-
-        // Determine vout based on variant
-        // For display purposes, we show the vout that would be used for the next transaction
-        let vout = match variant {
-            TxVariant::V3Anchored => 0, // Ark Labs always uses vout 0
-            TxVariant::V3Plain => {
-                // SecondTech uses parent_index from next item in path, or leaf.vout if last
-                if idx + 1 < tree.path.len() {
-                    tree.path[idx + 1].parent_index
-                } else {
-                    tree.leaf.vout
-                }
-            }
-        };
-
-        let signed_hex = signed_txs
-            .get(signed_idx)
-            .map(|b| hex::encode(b))
-            .unwrap_or_default();
-        if signed_idx < signed_txs.len() {
-            signed_idx += 1;
-        }
+        let step = idx as u32;
+        let tx = walk
+            .txs
+            .get(idx)
+            .ok_or_else(|| JsValue::from_str("path step could not be reconstructed"))?;
+        let (signed_tx, exit_weight_vb) = signed_exit_tx(signed_txs, idx)?;
+        let (input_value, output_sum) = report_conservation(report, step);
+        let (has_fee_anchor, sibling_count) =
+            sibling_stats(&genesis_item.siblings, &tree.fee_anchor_script)?;
+        // The output the next transaction spends
+        let vout = walk
+            .txs
+            .get(idx + 1)
+            .map_or(tree.leaf.vout, |next| next.input.prev_out_vout);
 
         path_details.push(PathDetail {
-            txid: txid_str,
-            amount,
+            txid: report_txid(report, step)?,
+            amount: genesis_item.child_amount,
             is_leaf: false,
             is_anchor: false,
             vout,
             has_signature: genesis_item.signature.is_some(),
             has_fee_anchor,
-            exit_weight_vb: exit_weight,
+            exit_weight_vb,
             exit_delta: 0,
-            tx_preimage_hex: hex::encode(&preimage_bytes),
-            signed_tx_hex: signed_hex,
+            tx_preimage_hex: hex::encode(tx.preimage()),
+            signed_tx_hex: hex::encode(signed_tx),
             sequence: genesis_item.sequence,
             sibling_count,
+            input_value,
+            output_sum,
         });
-
-        // Update for next iteration - determine which output index to use
-        let next_vout = match variant {
-            TxVariant::V3Anchored => 0,
-            TxVariant::V3Plain => {
-                // Use parent_index from next path item, or leaf.vout if last
-                if idx + 1 < tree.path.len() {
-                    tree.path[idx + 1].parent_index
-                } else {
-                    tree.leaf.vout
-                }
-            }
-        };
-        current_prevout = OutPoint {
-            txid,
-            vout: next_vout,
-        };
-        // Get the amount from the output at the next_vout index
-        input_amount = outputs.get(next_vout as usize).map(|o| o.value);
     }
 
     // Add leaf node
-    let num_leaf_outputs = 1 + tree.leaf_siblings.len();
-    let mut leaf_has_fee_anchor = false;
-    let mut leaf_sibling_count: u32 = 0;
-    let mut leaf_outputs = Vec::new();
-    leaf_outputs.push(TxOutPreimage {
-        value: tree.leaf.amount,
-        script_pubkey: tree.leaf.script_pubkey.as_slice(),
-    });
-    for sibling in &tree.leaf_siblings {
-        match sibling {
-            SiblingNode::Compact { value, script, .. } => {
-                leaf_outputs.push(TxOutPreimage {
-                    value: *value,
-                    script_pubkey: script.as_slice(),
-                });
-                if script == &tree.fee_anchor_script {
-                    leaf_has_fee_anchor = true;
-                } else {
-                    leaf_sibling_count += 1;
-                }
+    let leaf_step = tree.path.len() as u32;
+    let (has_fee_anchor, sibling_count) =
+        sibling_stats(&tree.leaf_siblings, &tree.fee_anchor_script)?;
+    let leaf = match walk.txs.get(tree.path.len()) {
+        Some(tx) => {
+            let (signed_tx, exit_weight_vb) = signed_exit_tx(signed_txs, tree.path.len())?;
+            let (input_value, output_sum) = report_conservation(report, leaf_step);
+            PathDetail {
+                txid: report_txid(report, leaf_step)?,
+                exit_weight_vb,
+                tx_preimage_hex: hex::encode(tx.preimage()),
+                signed_tx_hex: hex::encode(signed_tx),
+                input_value,
+                output_sum,
+                ..leaf_detail(tree, has_fee_anchor, sibling_count)
             }
-            SiblingNode::Full(_) => {
-                return Err(JsValue::from_str("Full sibling nodes not supported"))
+        }
+        None => {
+            let last_step = leaf_step
+                .checked_sub(1)
+                .ok_or_else(|| JsValue::from_str("leaf transaction could not be reconstructed"))?;
+            PathDetail {
+                txid: report_txid(report, last_step)?,
+                ..leaf_detail(tree, has_fee_anchor, sibling_count)
             }
         }
-    }
-
-    // Compute leaf transaction ID
-    let leaf_input = TxInPreimage {
-        prev_out_txid: current_prevout.txid.to_byte_array(),
-        prev_out_vout: current_prevout.vout,
-        sequence: tree.leaf.sequence,
     };
-    let leaf_preimage = tx_preimage(3, &[leaf_input], &leaf_outputs, 0);
-    let leaf_hash = sha256d::Hash::hash(&leaf_preimage);
-    let leaf_txid_bytes = leaf_hash.to_byte_array();
-    let leaf_txid = Txid::from_byte_array(leaf_txid_bytes);
-    let leaf_txid_str = txid_to_string(&leaf_txid);
+    path_details.push(leaf);
 
-    let leaf_signed_hex = signed_txs
-        .get(signed_idx)
-        .map(|b| hex::encode(b))
-        .unwrap_or_default();
+    Ok(path_details)
+}
 
-    path_details.push(PathDetail {
-        txid: leaf_txid_str,
+/// Leaf row without transaction data.
+fn leaf_detail(tree: &VPackTree, has_fee_anchor: bool, sibling_count: u32) -> PathDetail {
+    PathDetail {
+        txid: String::new(),
         amount: tree.leaf.amount,
         is_leaf: true,
         is_anchor: false,
         vout: tree.leaf.vout,
         has_signature: false,
-        has_fee_anchor: leaf_has_fee_anchor,
-        exit_weight_vb: estimate_exit_weight_vb(leaf_outputs.len()),
+        has_fee_anchor,
+        exit_weight_vb: 0,
         exit_delta: tree.leaf.exit_delta,
-        tx_preimage_hex: hex::encode(&leaf_preimage),
-        signed_tx_hex: leaf_signed_hex,
+        tx_preimage_hex: String::new(),
+        signed_tx_hex: String::new(),
         sequence: tree.leaf.sequence,
-        sibling_count: leaf_sibling_count,
-    });
-
-    Ok(path_details)
+        sibling_count,
+        input_value: None,
+        output_sum: None,
+    }
 }
 
 /// Verifies reconstruction_ingredients JSON against expected_vtxo_id.
@@ -355,55 +322,65 @@ pub fn wasm_verify(json_input: &str) -> Result<JsValue, JsValue> {
 
     // Try ArkLabs (V3Anchored) first
     if let Ok(tree) = ArkLabsAdapter::map_ingredients(ri) {
-        let bytes = create_vpack_from_tree(&tree, TxVariant::V3Anchored, false)
+        let bytes = create_vpack_from_tree(&tree, TxVariant::V3Anchored, Network::Mainnet)
             .map_err(|e: vpack::error::VPackError| JsValue::from_str(&e.to_string()))?;
         // Use master verify() function
-        verify(&bytes, &expected_id, anchor_value)
+        verify(&bytes, &expected_id, anchor_value, Network::Mainnet)
             .map_err(|e: vpack::error::VPackError| JsValue::from_str(&e.to_string()))?;
         let engine = ArkLabsV3;
         let output = engine
             .compute_vtxo_id(&tree, None)
             .map_err(|e: vpack::error::VPackError| JsValue::from_str(&e.to_string()))?;
+        let report = report_tree(&tree, TxVariant::V3Anchored, &expected_id, anchor_value);
         let path_details = extract_path_details(
             &tree,
             anchor_value,
             TxVariant::V3Anchored,
             &output.signed_txs,
-        )
-        .map_err(|e| e)?;
+            &report,
+        )?;
         let signed_txs_hex: Vec<String> =
             output.signed_txs.iter().map(|b| hex::encode(b)).collect();
         return Ok(serde_wasm_bindgen::to_value(&WasmVerifyResult {
             variant: "0x04".to_string(),
             status: "Success".to_string(),
             reconstructed_tx_id: output.id.to_string(),
+            exit_weight_vb: path_details.iter().map(|p| p.exit_weight_vb).sum(),
             path_details,
             signed_txs: signed_txs_hex,
+            report,
         })?);
     }
 
     // Try SecondTech (V3Plain)
     if let Ok(tree) = SecondTechAdapter::map_ingredients(ri) {
-        let bytes = create_vpack_from_tree(&tree, TxVariant::V3Plain, false)
+        let bytes = create_vpack_from_tree(&tree, TxVariant::V3Plain, Network::Mainnet)
             .map_err(|e: vpack::error::VPackError| JsValue::from_str(&e.to_string()))?;
         // Use master verify() function
-        verify(&bytes, &expected_id, anchor_value)
+        verify(&bytes, &expected_id, anchor_value, Network::Mainnet)
             .map_err(|e: vpack::error::VPackError| JsValue::from_str(&e.to_string()))?;
         let engine = SecondTechV3;
         let output = engine
             .compute_vtxo_id(&tree, None)
             .map_err(|e: vpack::error::VPackError| JsValue::from_str(&e.to_string()))?;
-        let path_details =
-            extract_path_details(&tree, anchor_value, TxVariant::V3Plain, &output.signed_txs)
-                .map_err(|e| e)?;
+        let report = report_tree(&tree, TxVariant::V3Plain, &expected_id, anchor_value);
+        let path_details = extract_path_details(
+            &tree,
+            anchor_value,
+            TxVariant::V3Plain,
+            &output.signed_txs,
+            &report,
+        )?;
         let signed_txs_hex: Vec<String> =
             output.signed_txs.iter().map(|b| hex::encode(b)).collect();
         return Ok(serde_wasm_bindgen::to_value(&WasmVerifyResult {
             variant: "0x03".to_string(),
             status: "Success".to_string(),
             reconstructed_tx_id: output.id.to_string(),
+            exit_weight_vb: path_details.iter().map(|p| p.exit_weight_vb).sum(),
             path_details,
             signed_txs: signed_txs_hex,
+            report,
         })?);
     }
 
@@ -471,14 +448,19 @@ pub fn wasm_export_to_vpack(json_input: &str, is_testnet: bool) -> Result<Vec<u8
     let ri = value
         .get("reconstruction_ingredients")
         .ok_or_else(|| JsValue::from_str("missing reconstruction_ingredients"))?;
+    let network = if is_testnet {
+        Network::Testnet
+    } else {
+        Network::Mainnet
+    };
 
     if let Ok(tree) = ArkLabsAdapter::map_ingredients(ri) {
-        return create_vpack_from_tree(&tree, TxVariant::V3Anchored, is_testnet)
+        return create_vpack_from_tree(&tree, TxVariant::V3Anchored, network)
             .map_err(|e: vpack::error::VPackError| JsValue::from_str(&e.to_string()));
     }
 
     if let Ok(tree) = SecondTechAdapter::map_ingredients(ri) {
-        return create_vpack_from_tree(&tree, TxVariant::V3Plain, is_testnet)
+        return create_vpack_from_tree(&tree, TxVariant::V3Plain, network)
             .map_err(|e: vpack::error::VPackError| JsValue::from_str(&e.to_string()));
     }
 
@@ -487,16 +469,43 @@ pub fn wasm_export_to_vpack(json_input: &str, is_testnet: bool) -> Result<Vec<u8
     ))
 }
 
+/// Encodes a binary V-PACK as bech32m text ("vpack1…", or "tvpack1…" when FLAG_TESTNET is set).
+/// Returns an array of strings: one for small V-PACKs, several parts otherwise.
+#[wasm_bindgen]
+pub fn wasm_vpack_to_text(vpack_bytes: Vec<u8>) -> Result<JsValue, JsValue> {
+    let parts =
+        vpack::to_text(&vpack_bytes).map_err(|e| JsValue::from_str(&format!("Error: {}", e)))?;
+    Ok(serde_wasm_bindgen::to_value(&parts)?)
+}
+
+/// Decodes bech32m text parts (array of strings, any order) back into binary V-PACK bytes.
+/// The header, network prefix and checksum are validated; pass the result to wasm_verify_binary.
+#[wasm_bindgen]
+pub fn wasm_vpack_from_text(parts: JsValue) -> Result<Vec<u8>, JsValue> {
+    let parts: Vec<String> = serde_wasm_bindgen::from_value(parts)?;
+    vpack::from_text(&parts).map_err(|e| JsValue::from_str(&format!("Error: {}", e)))
+}
+
+fn network_to_str(network: Network) -> &'static str {
+    match network {
+        Network::Mainnet => "mainnet",
+        Network::Testnet => "testnet",
+        Network::Signet => "signet",
+        Network::Regtest => "regtest",
+    }
+}
+
 #[derive(Serialize)]
 struct WasmParseHeaderResult {
     anchor_txid: String,
     anchor_vout: u32,
     tx_variant: String,
     is_testnet: bool,
+    network: String,
 }
 
 /// Parses the V-PACK header and minimal payload prefix to extract anchor outpoint.
-/// Validates magic bytes first. Returns { anchor_txid, anchor_vout, tx_variant, is_testnet }.
+/// Validates magic bytes first. Returns { anchor_txid, anchor_vout, tx_variant, is_testnet, network }.
 /// Use anchor_txid (display hex) with mempool.space for L1 fetch.
 #[wasm_bindgen]
 pub fn wasm_parse_vpack_header(vpack_bytes: Vec<u8>) -> Result<JsValue, JsValue> {
@@ -524,6 +533,14 @@ pub fn wasm_parse_vpack_header(vpack_bytes: Vec<u8>) -> Result<JsValue, JsValue>
         return Err(JsValue::from_str("Error: Incomplete V-PACK data."));
     }
     let payload = &payload[..payload_len];
+    let decompressed;
+    let payload = if header.is_compressed() {
+        decompressed = vpack::payload::compression::decompress_payload(payload)
+            .map_err(|e| JsValue::from_str(&format!("Error: {}.", e)))?;
+        decompressed.as_slice()
+    } else {
+        payload
+    };
 
     let anchor_offset = if header.has_asset_id() { 32 } else { 0 };
     if payload.len() < anchor_offset + 36 {
@@ -536,16 +553,19 @@ pub fn wasm_parse_vpack_header(vpack_bytes: Vec<u8>) -> Result<JsValue, JsValue>
         .map_err(|_| JsValue::from_str("Error: Failed to parse anchor outpoint."))?;
 
     let anchor_txid = txid_to_string(&anchor.txid);
-    let tx_variant = match header.tx_variant {
-        TxVariant::V3Plain => "0x03",
-        TxVariant::V3Anchored => "0x04",
-    };
+    let tx_variant = format!("0x{:02x}", header.tx_variant.as_u8());
 
     Ok(serde_wasm_bindgen::to_value(&WasmParseHeaderResult {
         anchor_txid,
         anchor_vout: anchor.vout,
-        tx_variant: tx_variant.to_string(),
+        tx_variant,
         is_testnet: header.is_testnet(),
+        network: network_to_str(
+            header
+                .network()
+                .map_err(|e| JsValue::from_str(&format!("Error: {}", e)))?,
+        )
+        .to_string(),
     })?)
 }
 
@@ -587,29 +607,26 @@ pub fn wasm_unpack_to_json(vpack_bytes: Vec<u8>) -> Result<String, JsValue> {
 
     validate_invariants(&header, &tree).map_err(|e| JsValue::from_str(&format!("Error: {}", e)))?;
 
-    let output = match header.tx_variant {
-        TxVariant::V3Anchored => ArkLabsV3
-            .compute_vtxo_id(&tree, None)
-            .map_err(|e| JsValue::from_str(&format!("Error: {}", e)))?,
-        TxVariant::V3Plain => SecondTechV3
-            .compute_vtxo_id(&tree, None)
-            .map_err(|e| JsValue::from_str(&format!("Error: {}", e)))?,
-    };
+    let output = EngineRegistry::default()
+        .engine_for(header.tx_variant)
+        .and_then(|engine| engine.compute_vtxo_id(&tree, None))
+        .map_err(|e| JsValue::from_str(&format!("Error: {}", e)))?;
     let expected_id = output.id;
 
     let fee_hex = hex::encode(&tree.fee_anchor_script);
 
-    let reconstruction_ingredients = match header.tx_variant {
-        TxVariant::V3Anchored => tree_to_ark_labs_json(&tree, &fee_hex),
-        TxVariant::V3Plain => tree_to_second_tech_json(&tree, &fee_hex),
+    let reconstruction_ingredients = match header
+        .tx_variant
+        .builtin()
+        .map_err(|e| JsValue::from_str(&format!("Error: {}", e)))?
+    {
+        BuiltinVariant::V3Anchored => tree_to_ark_labs_json(&tree, &fee_hex),
+        BuiltinVariant::V3Plain => tree_to_second_tech_json(&tree, &fee_hex),
     };
 
     let output = serde_json::json!({
         "meta": {
-            "variant": match header.tx_variant {
-                TxVariant::V3Anchored => "0x04",
-                TxVariant::V3Plain => "0x03",
-            },
+            "variant": format!("0x{:02x}", header.tx_variant.as_u8()),
             "description": "Unpacked from binary V-PACK"
         },
         "raw_evidence": {
@@ -775,34 +792,39 @@ pub fn wasm_verify_binary(
 
     validate_invariants(&header, &tree).map_err(|e| JsValue::from_str(&format!("Error: {}", e)))?;
 
-    let output = match header.tx_variant {
-        TxVariant::V3Anchored => ArkLabsV3
-            .compute_vtxo_id(&tree, None)
-            .map_err(|e| JsValue::from_str(&format!("Error: {}", e)))?,
-        TxVariant::V3Plain => SecondTechV3
-            .compute_vtxo_id(&tree, None)
-            .map_err(|e| JsValue::from_str(&format!("Error: {}", e)))?,
-    };
+    let output = EngineRegistry::default()
+        .engine_for(header.tx_variant)
+        .and_then(|engine| engine.compute_vtxo_id(&tree, None))
+        .map_err(|e| JsValue::from_str(&format!("Error: {}", e)))?;
     let expected_id = output.id;
 
     let anchor_val = anchor_value.unwrap_or_else(|| tree_output_sum(&tree));
 
-    verify(vpack_bytes.as_slice(), &expected_id, anchor_val)
+    // The header's own network is trusted here: this entry point has no caller-supplied expectation.
+    let network = header
+        .network()
+        .map_err(|e| JsValue::from_str(&format!("Error: {}", e)))?;
+    verify(vpack_bytes.as_slice(), &expected_id, anchor_val, network)
         .map_err(|e| JsValue::from_str(&format!("Error: {}", e)))?;
 
-    let variant_str = match header.tx_variant {
-        TxVariant::V3Anchored => "0x04",
-        TxVariant::V3Plain => "0x03",
-    };
-    let path_details =
-        extract_path_details(&tree, anchor_val, header.tx_variant, &output.signed_txs)?;
+    let variant_str = format!("0x{:02x}", header.tx_variant.as_u8());
+    let report = report_tree(&tree, header.tx_variant, &expected_id, anchor_val);
+    let path_details = extract_path_details(
+        &tree,
+        anchor_val,
+        header.tx_variant,
+        &output.signed_txs,
+        &report,
+    )?;
 
     let signed_txs_hex: Vec<String> = output.signed_txs.iter().map(|b| hex::encode(b)).collect();
     Ok(serde_wasm_bindgen::to_value(&WasmVerifyResult {
-        variant: variant_str.to_string(),
+        variant: variant_str,
         status: "Success".to_string(),
         reconstructed_tx_id: expected_id.to_string(),
+        exit_weight_vb: path_details.iter().map(|p| p.exit_weight_vb).sum(),
         path_details,
         signed_txs: signed_txs_hex,
+        report,
     })?)
 }